@@ -1,111 +1,138 @@
 use std::time::Instant;
-use std::env;
 use serde_json::json;
-use chrono;
+use benchmark_metrics::{
+    extra_fields, BenchmarkMetrics, Features, Operation, PerformanceMetrics,
+    ResourceMetrics, ScalabilityMetrics, SecurityMetrics, SetupMetrics,
+    SystemRequirements, TemplateArgs, TimeMetrics,
+};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let circuit_size = args.get(1).map(String::as_str).unwrap_or("small");
-    
-    let mut metrics = json!({
-        "operation": "operation_name",
-        "system": "aleo-snarkos",
-        "circuit_size": circuit_size,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        
-        "time_metrics": {
-            "setup_time_ms": 0,
-            "proving_time_ms": 0,
-            "verification_time_ms": 0,
-            "total_execution_time_ms": 0,
-            "block_production_time_ms": 0,  // snarkOS specific
-            "consensus_time_ms": 0          // snarkOS specific
+    // Circuit size, --format and --out; see benchmark_metrics::TemplateArgs
+    let args = TemplateArgs::from_env();
+    let circuit_size = args.circuit_size.as_str();
+
+    // resolved_parameters: what circuit_size expanded to (transactions, iterations)
+    let mut metrics = BenchmarkMetrics {
+        time_metrics: TimeMetrics {
+            extra: extra_fields(json!({
+                "block_production_time_ms": 0,  // snarkOS specific
+                "consensus_time_ms": 0          // snarkOS specific
+            })),
+            ..Default::default()
         },
-        
-        "resource_metrics": {
-            "peak_memory_usage_kb": 0,
-            "proof_size_bytes": 0,
-            "cpu_utilization_percent": 0,
-            "gpu_utilization_percent": 0,
-            "network_bandwidth_usage": 0     // snarkOS specific
+
+        resource_metrics: ResourceMetrics {
+            extra: extra_fields(json!({
+                "network_bandwidth_usage": 0     // snarkOS specific
+            })),
+            ..Default::default()
         },
-        
-        "setup_metrics": {
-            "setup_type": "universal_srs",
-            "setup_size_bytes": 0,
-            "setup_reusable": true
+
+        setup_metrics: SetupMetrics {
+            setup_type: "universal_srs".to_string(),
+            setup_reusable: true,
+            ..Default::default()
         },
-        
-        "features": {
-            "recursive_proofs": true,
-            "universal_circuits": true,
-            "parallel_proving": true,
-            "parallel_verification": true,
-            "custom_gates": true,
-            "consensus_mechanism": "PoSW",   // snarkOS specific
-            "network_protocol": "P2P"        // snarkOS specific
+
+        features: Features {
+            recursive_proofs: true,
+            universal_circuits: true,
+            parallel_proving: true,
+            parallel_verification: true,
+            custom_gates: true,
+            extra: extra_fields(json!({
+                "consensus_mechanism": "PoSW",   // snarkOS specific
+                "network_protocol": "P2P"        // snarkOS specific
+            })),
         },
-        
-        "security_metrics": {
-            "post_quantum_resistant": false,
-            "security_level_bits": 128,
-            "assumptions": ["discrete_log", "collision_resistant_hash"]
+
+        security_metrics: SecurityMetrics {
+            post_quantum_resistant: false,
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string(), "collision_resistant_hash".to_string()],
+            ..Default::default()
         },
-        
-        "scalability_metrics": {
-            "constraints_count": 0,
-            "variables_count": 0,
-            "degree": 0,
-            "proving_complexity_class": "O(n log n)",
-            "verification_complexity_class": "O(1)",
-            "network_tps": 0,               // snarkOS specific
-            "block_capacity": 0             // snarkOS specific
+
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: 253,  // BLS12-377 scalar field
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(1)".to_string(),
+            extra: extra_fields(json!({
+                "network_tps": 0,               // snarkOS specific
+                "block_capacity": 0             // snarkOS specific
+            })),
+            ..Default::default()
         },
-        
-        "performance_metrics": {
-            "throughput_proofs_per_second": 0.0,
-            "latency_ms": 0,
-            "batch_proving_supported": true,
-            "batch_verification_supported": true,
-            "block_time_ms": 0,             // snarkOS specific
-            "network_latency_ms": 0         // snarkOS specific
+
+        performance_metrics: PerformanceMetrics {
+            batch_proving_supported: true,
+            batch_verification_supported: true,
+            extra: extra_fields(json!({
+                "block_time_ms": 0,             // snarkOS specific
+                "network_latency_ms": 0         // snarkOS specific
+            })),
+            ..Default::default()
         },
-        
-        "system_requirements": {
-            "minimum_memory_gb": 0,
-            "recommended_cpu_cores": 0,
-            "gpu_required": false,
-            "disk_space_gb": 0,
-            "network_bandwidth_required": 0  // snarkOS specific
+
+        system_requirements: SystemRequirements {
+            extra: extra_fields(json!({
+                "network_bandwidth_required": 0  // snarkOS specific
+            })),
+            ..Default::default()
         },
-        
+
         // snarkOS specific metrics
-        "network_metrics": {
-            "block_height": 0,
-            "network_difficulty": 0,
-            "connected_peers": 0,
-            "sync_status": "",
-            "mempool_size": 0
-        }
-    });
+        extra: extra_fields(json!({
+            "network_metrics": {
+                "block_height": 0,
+                "network_difficulty": 0,
+                "connected_peers": 0,
+                "sync_status": "",
+                "mempool_size": 0
+            }
+        })),
+
+        // The operation this benchmark proves, from benchmark_metrics::Operation
+        ..BenchmarkMetrics::new("aleo-snarkos", Operation::BlockProduction.as_str(), circuit_size)
+    };
 
     // Implementation placeholder
     /*
-    // 1. Setup phase
-    let setup_start = Instant::now();
-    let node = your_implementation::setup_node(circuit_size);
-    metrics["time_metrics"]["setup_time_ms"] = setup_start.elapsed().as_millis();
-    
-    // 2. Block production phase
-    let proving_start = Instant::now();
-    let block = your_implementation::produce_block(&node);
-    metrics["time_metrics"]["block_production_time_ms"] = proving_start.elapsed().as_millis();
-    
-    // 3. Consensus phase
-    let verify_start = Instant::now();
-    let verified = your_implementation::verify_block(&block);
-    metrics["time_metrics"]["consensus_time_ms"] = verify_start.elapsed().as_millis();
+    // --repeat <n> runs the measured phases n times; time_metrics ends up
+    // with the median run and time_statistics with the distribution
+    let mut aggregator = benchmark_metrics::Aggregator::new();
+    for _ in 0..args.repeat {
+        // 1. Setup phase
+        let setup_start = Instant::now();
+        let node = your_implementation::setup_node(circuit_size);
+        metrics.time_metrics.setup_time_ms = setup_start.elapsed().as_millis() as u64;
+
+        // 2. Block production phase
+        let proving_start = Instant::now();
+        let block = {
+            // Writes cpu_utilization_percent when it goes out of scope
+            let _cpu = benchmark_metrics::CpuSampler::start_into(&mut metrics.resource_metrics);
+            your_implementation::produce_block(&node)
+        };
+        metrics.time_metrics.extra["block_production_time_ms"] = json!(proving_start.elapsed().as_millis());
+
+        // Peak memory so far, which block production usually dominates
+        metrics.resource_metrics.peak_memory_usage_kb = benchmark_metrics::peak_rss_kb().unwrap_or(0);
+
+        // 3. Consensus phase
+        let verify_start = Instant::now();
+        let verified = your_implementation::verify_block(&block);
+        metrics.time_metrics.extra["consensus_time_ms"] = json!(verify_start.elapsed().as_millis());
+
+        aggregator.record(&metrics.time_metrics);
+    }
+    aggregator.apply(&mut metrics);
     */
 
-    println!("{}", serde_json::to_string_pretty(&metrics).unwrap());
+    // Machine, toolchain and revision, so results can be compared across runs
+    benchmark_metrics::collect_environment(circuit_size).apply(&mut metrics);
+
+    // `--format json-compact` prints a single line for log ingestion;
+    // `--out <path>` writes the metrics there instead of stdout
+    metrics.emit(&args).expect("failed to write metrics");
 }
\ No newline at end of file
@@ -1,115 +1,143 @@
 use std::time::Instant;
-use std::env;
 use serde_json::json;
-use chrono;
+use benchmark_metrics::{
+    extra_fields, BenchmarkMetrics, Features, Operation, PerformanceMetrics,
+    ResourceMetrics, ScalabilityMetrics, SecurityMetrics, SetupMetrics, TemplateArgs,
+    TimeMetrics,
+};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let circuit_size = args.get(1).map(String::as_str).unwrap_or("small");
-    
-    let mut metrics = json!({
-        "operation": "operation_name",
-        "system": "aleo-snarkvm",
-        "circuit_size": circuit_size,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        
-        "time_metrics": {
-            "setup_time_ms": 0,
-            "proving_time_ms": 0,
-            "verification_time_ms": 0,
-            "total_execution_time_ms": 0,
-            "compilation_time_ms": 0,        // snarkVM specific (Leo compilation)
-            "execution_time_ms": 0           // snarkVM specific
-        },
-        
-        "resource_metrics": {
-            "peak_memory_usage_kb": 0,
-            "proof_size_bytes": 0,
-            "cpu_utilization_percent": 0,
-            "gpu_utilization_percent": 0,
-            "circuit_size_bytes": 0          // snarkVM specific
+    // Circuit size, --format and --out; see benchmark_metrics::TemplateArgs
+    let args = TemplateArgs::from_env();
+    let circuit_size = args.circuit_size.as_str();
+
+    // resolved_parameters: what circuit_size expanded to (program inputs, iterations)
+    let mut metrics = BenchmarkMetrics {
+        time_metrics: TimeMetrics {
+            extra: extra_fields(json!({
+                "compilation_time_ms": 0,        // snarkVM specific (Leo compilation)
+                "execution_time_ms": 0           // snarkVM specific
+            })),
+            ..Default::default()
         },
-        
-        "setup_metrics": {
-            "setup_type": "universal_srs",
-            "setup_size_bytes": 0,
-            "setup_reusable": true
+
+        resource_metrics: ResourceMetrics {
+            extra: extra_fields(json!({
+                "circuit_size_bytes": 0          // snarkVM specific
+            })),
+            ..Default::default()
         },
-        
-        "features": {
-            "recursive_proofs": true,
-            "universal_circuits": true,
-            "parallel_proving": true,
-            "parallel_verification": true,
-            "custom_gates": true,
-            "private_execution": true,       // snarkVM specific
-            "record_types": true            // snarkVM specific
+
+        setup_metrics: SetupMetrics {
+            setup_type: "universal_srs".to_string(),
+            setup_reusable: true,
+            ..Default::default()
         },
-        
-        "security_metrics": {
-            "post_quantum_resistant": false,
-            "security_level_bits": 128,
-            "assumptions": ["discrete_log", "collision_resistant_hash"]
+
+        features: Features {
+            recursive_proofs: true,
+            universal_circuits: true,
+            parallel_proving: true,
+            parallel_verification: true,
+            custom_gates: true,
+            extra: extra_fields(json!({
+                "private_execution": true,       // snarkVM specific
+                "record_types": true            // snarkVM specific
+            })),
         },
-        
-        "scalability_metrics": {
-            "constraints_count": 0,
-            "variables_count": 0,
-            "degree": 0,
-            "proving_complexity_class": "O(n log n)",
-            "verification_complexity_class": "O(1)",
-            "program_size": 0               // snarkVM specific
+
+        security_metrics: SecurityMetrics {
+            post_quantum_resistant: false,
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string(), "collision_resistant_hash".to_string()],
+            ..Default::default()
         },
-        
-        "performance_metrics": {
-            "throughput_proofs_per_second": 0.0,
-            "latency_ms": 0,
-            "batch_proving_supported": true,
-            "batch_verification_supported": true,
-            "instruction_count": 0,         // snarkVM specific
-            "memory_usage_per_instruction": 0 // snarkVM specific
+
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: 253,  // BLS12-377 scalar field
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(1)".to_string(),
+            extra: extra_fields(json!({
+                "program_size": 0               // snarkVM specific
+            })),
+            ..Default::default()
         },
-        
-        "system_requirements": {
-            "minimum_memory_gb": 0,
-            "recommended_cpu_cores": 0,
-            "gpu_required": false,
-            "disk_space_gb": 0
+
+        performance_metrics: PerformanceMetrics {
+            batch_proving_supported: true,
+            batch_verification_supported: true,
+            extra: extra_fields(json!({
+                "instruction_count": 0,         // snarkVM specific
+                "memory_usage_per_instruction": 0 // snarkVM specific
+            })),
+            ..Default::default()
         },
-        
+
         // snarkVM specific metrics
-        "execution_metrics": {
-            "program_id": "",
-            "function_id": "",
-            "input_size": 0,
-            "output_size": 0,
-            "stack_size": 0,
-            "register_usage": 0
-        }
-    });
+        extra: extra_fields(json!({
+            "execution_metrics": {
+                "program_id": "",
+                "function_id": "",
+                "input_size": 0,
+                "output_size": 0,
+                "stack_size": 0,
+                "register_usage": 0
+            }
+        })),
+
+        // The operation this benchmark proves, from benchmark_metrics::Operation
+        ..BenchmarkMetrics::new("aleo-snarkvm", Operation::EcdsaVerify.as_str(), circuit_size)
+    };
 
     // Implementation placeholder
     /*
-    // 1. Compilation phase
-    let compile_start = Instant::now();
-    let program = your_implementation::compile(circuit_size);
-    metrics["time_metrics"]["compilation_time_ms"] = compile_start.elapsed().as_millis();
-    
-    // 2. Setup phase
-    let setup_start = Instant::now();
-    let circuit = your_implementation::setup(&program);
-    metrics["time_metrics"]["setup_time_ms"] = setup_start.elapsed().as_millis();
-    
-    // 3. Execution and proving phase
-    let proving_start = Instant::now();
-    let (proof, output) = your_implementation::execute_and_prove(&circuit);
-    metrics["time_metrics"]["proving_time_ms"] = proving_start.elapsed().as_millis();
-    
+    // --repeat <n> runs the measured phases n times; time_metrics ends up
+    // with the median run and time_statistics with the distribution
+    let mut aggregator = benchmark_metrics::Aggregator::new();
+    let mut proving_times = Vec::new();
+    for _ in 0..args.repeat {
+        // 1. Compilation phase
+        let compile_start = Instant::now();
+        let program = your_implementation::compile(circuit_size);
+        metrics.time_metrics.extra["compilation_time_ms"] = json!(compile_start.elapsed().as_millis());
+
+        // 2. Setup phase
+        let setup_start = Instant::now();
+        let circuit = your_implementation::setup(&program);
+        metrics.time_metrics.setup_time_ms = setup_start.elapsed().as_millis() as u64;
+
+        // 3. Execution and proving phase
+        let proving_start = Instant::now();
+        let (proof, output) = {
+            // Writes cpu_utilization_percent when it goes out of scope
+            let _cpu = benchmark_metrics::CpuSampler::start_into(&mut metrics.resource_metrics);
+            your_implementation::execute_and_prove(&circuit)
+        };
+        let proving_time = proving_start.elapsed();
+        proving_times.push(proving_time);
+        metrics.time_metrics.proving_time_ms = proving_time.as_millis() as u64;
+
+        // Peak memory so far, which proving usually dominates
+        metrics.resource_metrics.peak_memory_usage_kb = benchmark_metrics::peak_rss_kb().unwrap_or(0);
+
+        aggregator.record(&metrics.time_metrics);
+    }
+    aggregator.apply(&mut metrics);
+    // Throughput over the runs and the median single-proof latency
+    if let Some(throughput) = benchmark_metrics::Throughput::of(&proving_times) {
+        throughput.apply(&mut metrics);
+    }
+
     // 4. Verification phase
     let verify_start = Instant::now();
     let verified = your_implementation::verify(&proof);
-    metrics["time_metrics"]["verification_time_ms"] = verify_start.elapsed().as_millis();
+    metrics.time_metrics.verification_time_ms = verify_start.elapsed().as_millis() as u64;
     */
 
-    println!("{}", serde_json::to_string_pretty(&metrics).unwrap());
+    // Machine, toolchain and revision, so results can be compared across runs
+    benchmark_metrics::collect_environment(circuit_size).apply(&mut metrics);
+
+    // `--format json-compact` prints a single line for log ingestion;
+    // `--out <path>` writes the metrics there instead of stdout
+    metrics.emit(&args).expect("failed to write metrics");
 }
\ No newline at end of file
@@ -0,0 +1,214 @@
+//! Repeated-Run Aggregation
+//!
+//! One timing is noisy. With `--repeat <n>` a template runs its measured
+//! phases n times and hands each run's `TimeMetrics` to an `Aggregator`.
+//! `Aggregator::apply` then writes the distribution of every phase to
+//! `time_statistics` and sets the single-value `time_metrics` fields to the
+//! median, so readers that only know the old fields still get a sensible
+//! number. `Throughput` turns the same runs' proving times into
+//! `performance_metrics`.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BenchmarkMetrics, TimeMetrics};
+
+// Phases of `TimeMetrics` that are aggregated
+const PHASES: [&str; 3] = ["proving_time_ms", "setup_time_ms", "verification_time_ms"];
+
+/// Distribution of one phase's timings over the repeated runs, in ms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub min: f64,
+    pub p95: f64,
+    pub runs: usize,
+    pub stddev: f64, // sample standard deviation; 0 for a single run
+}
+
+impl Summary {
+    /// Summarizes `samples`, or `None` if there are none.
+    pub fn of(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let stddev = if n > 1 {
+            (sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            max: sorted[n - 1],
+            mean,
+            median: percentile(&sorted, 50.0),
+            min: sorted[0],
+            p95: percentile(&sorted, 95.0),
+            runs: n,
+            stddev,
+        })
+    }
+}
+
+// The p-th percentile of ascending `sorted`, interpolating linearly between
+// the two nearest ranks (NumPy's default method)
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+/// Collects the phase timings of repeated runs.
+#[derive(Debug, Clone, Default)]
+pub struct Aggregator {
+    samples: BTreeMap<&'static str, Vec<f64>>,
+}
+
+impl Aggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the setup, proving and verification times of one run.
+    pub fn record(&mut self, time: &TimeMetrics) {
+        let values = [time.proving_time_ms, time.setup_time_ms, time.verification_time_ms];
+        for (phase, value) in PHASES.into_iter().zip(values) {
+            self.samples.entry(phase).or_default().push(value as f64);
+        }
+    }
+
+    pub fn summary(&self, phase: &str) -> Option<Summary> {
+        self.samples.get(phase).and_then(|samples| Summary::of(samples))
+    }
+
+    /// Fills `time_statistics` and sets each phase's `time_metrics` field to
+    /// its median. Does nothing if no run was recorded.
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        let summaries: BTreeMap<String, Summary> = PHASES
+            .iter()
+            .filter_map(|phase| self.summary(phase).map(|summary| (phase.to_string(), summary)))
+            .collect();
+        if summaries.is_empty() {
+            return;
+        }
+
+        let median = |phase: &str| summaries[phase].median.round() as u64;
+        metrics.time_metrics.proving_time_ms = median("proving_time_ms");
+        metrics.time_metrics.setup_time_ms = median("setup_time_ms");
+        metrics.time_metrics.verification_time_ms = median("verification_time_ms");
+        metrics.time_statistics = Some(summaries);
+    }
+}
+
+/// Proving throughput over repeated runs, each proving one proof.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    // Runs over their total proving time, in seconds
+    pub proofs_per_second: f64,
+    // Median proving time of a single proof, rounded to the millisecond
+    pub latency_ms: u64,
+}
+
+impl Throughput {
+    /// Throughput of `proving_times`, one per proof, or `None` if there are
+    /// none or they sum to zero.
+    pub fn of(proving_times: &[Duration]) -> Option<Self> {
+        let total: Duration = proving_times.iter().sum();
+        if total.is_zero() {
+            return None;
+        }
+
+        let mut millis: Vec<f64> = proving_times.iter().map(|time| time.as_secs_f64() * 1000.0).collect();
+        millis.sort_by(f64::total_cmp);
+
+        Some(Self {
+            proofs_per_second: proving_times.len() as f64 / total.as_secs_f64(),
+            latency_ms: percentile(&millis, 50.0).round() as u64,
+        })
+    }
+
+    /// Sets `throughput_proofs_per_second` and `latency_ms`.
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        metrics.performance_metrics.throughput_proofs_per_second = self.proofs_per_second;
+        metrics.performance_metrics.latency_ms = self.latency_ms;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_of_fixed_sample() {
+        let summary = Summary::of(&[15.0, 20.0, 35.0, 40.0, 50.0]).unwrap();
+
+        assert_eq!(summary.min, 15.0);
+        assert_eq!(summary.max, 50.0);
+        assert_eq!(summary.mean, 32.0);
+        assert_eq!(summary.median, 35.0);
+        // rank 0.95 * 4 = 3.8, between 40 and 50
+        assert!((summary.p95 - 48.0).abs() < 1e-9);
+        // squared deviations 289 + 144 + 9 + 64 + 324 = 830, over n - 1 = 4
+        assert!((summary.stddev - 207.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(summary.runs, 5);
+    }
+
+    #[test]
+    fn test_percentile_interpolates() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 50.0), 2.5);
+        assert_eq!(percentile(&sorted, 100.0), 4.0);
+        assert_eq!(Summary::of(&[7.0]).unwrap().p95, 7.0);
+        assert_eq!(Summary::of(&[]), None);
+    }
+
+    #[test]
+    fn test_apply_sets_medians() {
+        let mut aggregator = Aggregator::new();
+        for proving in [300, 100, 200] {
+            aggregator.record(&TimeMetrics { proving_time_ms: proving, setup_time_ms: 10, ..Default::default() });
+        }
+
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        aggregator.apply(&mut metrics);
+
+        assert_eq!(metrics.time_metrics.proving_time_ms, 200);
+        assert_eq!(metrics.time_metrics.setup_time_ms, 10);
+        let statistics = metrics.time_statistics.as_ref().unwrap();
+        assert_eq!(statistics["proving_time_ms"].max, 300.0);
+        assert_eq!(statistics["verification_time_ms"].runs, 3);
+    }
+    #[test]
+    fn test_throughput_of_synthetic_runs() {
+        let times = [250, 500, 1250, 2000].map(Duration::from_millis);
+        let throughput = Throughput::of(&times).unwrap();
+
+        // 4 proofs in 4 s
+        assert!((throughput.proofs_per_second - 1.0).abs() < 1e-12);
+        // Median of 500 and 1250
+        assert_eq!(throughput.latency_ms, 875);
+
+        let throughput = Throughput::of(&[Duration::from_micros(400), Duration::from_micros(600)]).unwrap();
+        assert!((throughput.proofs_per_second - 2000.0).abs() < 1e-9);
+        assert_eq!(throughput.latency_ms, 1);
+
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        Throughput::of(&[Duration::from_millis(200)]).unwrap().apply(&mut metrics);
+        assert_eq!(metrics.performance_metrics.throughput_proofs_per_second, 5.0);
+        assert_eq!(metrics.performance_metrics.latency_ms, 200);
+
+        assert_eq!(Throughput::of(&[]), None);
+        assert_eq!(Throughput::of(&[Duration::ZERO]), None);
+    }
+}
@@ -0,0 +1,126 @@
+//! Allocated Memory
+//!
+//! The resident set counts the pages the allocator holds from the OS, not
+//! the bytes the program has live: freed blocks the allocator keeps for
+//! reuse, and holes between live ones, are resident too. `CountingAllocator`,
+//! once installed as the `#[global_allocator]`, tracks the bytes currently
+//! allocated and their peak. `AllocationGap` sets that peak against
+//! `peak_rss_kb`; a large gap means the allocator, not the prover, holds
+//! the memory, and a different one (e.g. jemalloc) may help.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::memory::peak_rss_kb;
+use crate::BenchmarkMetrics;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting live bytes and their peak.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn added(size: usize) {
+        let now = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_ALLOCATED.fetch_max(now, Ordering::Relaxed);
+    }
+
+    fn removed(size: usize) {
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::added(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::added(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::removed(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::removed(layout.size());
+            Self::added(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Peak bytes allocated at once through `CountingAllocator`; 0 if it is
+/// not the global allocator.
+pub fn peak_allocated_bytes() -> usize {
+    PEAK_ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// Peak resident set against peak live allocation, in kilobytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationGap {
+    pub peak_rss_kb: u64,
+    pub peak_allocated_kb: u64,
+    // Resident but not allocated at the peak; 0 if allocation ran higher
+    pub gap_kb: u64,
+}
+
+impl AllocationGap {
+    /// Both peaks so far, or `None` if the platform cannot report the
+    /// resident set or `CountingAllocator` is not installed.
+    pub fn measure() -> Option<Self> {
+        let peak_allocated_kb = (peak_allocated_bytes() / 1024) as u64;
+        if peak_allocated_kb == 0 {
+            return None;
+        }
+        let peak_rss_kb = peak_rss_kb()?;
+        Some(Self { peak_rss_kb, peak_allocated_kb, gap_kb: peak_rss_kb.saturating_sub(peak_allocated_kb) })
+    }
+
+    /// Sets `peak_allocated_kb` and `rss_allocated_gap_kb` in
+    /// `resource_metrics.extra`.
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        let extra = &mut metrics.resource_metrics.extra;
+        extra.insert("peak_allocated_kb".to_string(), self.peak_allocated_kb.into());
+        extra.insert("rss_allocated_gap_kb".to_string(), self.gap_kb.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_allocation_gap_captures_both_peaks() {
+        const SIZE: usize = 16 * 1024 * 1024;
+
+        let buffer = vec![1u8; SIZE];
+        let gap = AllocationGap::measure().expect("the counting allocator is installed");
+        assert_eq!(buffer.iter().map(|&b| b as usize).sum::<usize>(), SIZE);
+
+        assert!(gap.peak_allocated_kb >= (SIZE / 1024) as u64);
+        assert!(gap.peak_rss_kb > 0);
+        assert_eq!(gap.gap_kb, gap.peak_rss_kb.saturating_sub(gap.peak_allocated_kb));
+
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        gap.apply(&mut metrics);
+        assert_eq!(metrics.resource_metrics.extra["peak_allocated_kb"], gap.peak_allocated_kb);
+        assert!(metrics.resource_metrics.extra["rss_allocated_gap_kb"].as_u64().is_some());
+    }
+}
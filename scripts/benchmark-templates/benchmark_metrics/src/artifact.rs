@@ -0,0 +1,97 @@
+//! Temporary Artifacts
+//!
+//! Benchmarks write files a run only needs while it lasts, such as setup
+//! parameters written out to measure their size. Over a long sweep, files
+//! left behind by a run that panicked fill the disk. `TempArtifact` owns
+//! such a file and removes it when dropped, unwinding included, unless the
+//! template was given `--keep-artifacts`. A process killed outright still
+//! leaves its files; artifacts are named by what they hold rather than by
+//! run, so the next run overwrites them instead of adding more.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static KEEP_ARTIFACTS: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether artifacts created by `TempArtifact::new` from now on are
+/// kept; `TemplateArgs::from_env` sets it from `--keep-artifacts`.
+pub fn set_keep_artifacts(keep: bool) {
+    KEEP_ARTIFACTS.store(keep, Ordering::Relaxed);
+}
+
+/// A file removed when this guard is dropped, unless it is kept.
+#[derive(Debug)]
+#[must_use = "the file is removed as soon as the guard is dropped"]
+pub struct TempArtifact {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempArtifact {
+    /// Guards `path`, keeping it if `--keep-artifacts` was passed.
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_keep(path, KEEP_ARTIFACTS.load(Ordering::Relaxed))
+    }
+
+    /// Guards `path`, keeping it if `keep` is set.
+    pub fn with_keep(path: PathBuf, keep: bool) -> Self {
+        Self { path, keep }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempArtifact {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TemplateArgs;
+
+    fn artifact_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("benchmark_metrics_{}_{}", name, std::process::id()));
+        fs::write(&path, b"params").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_artifact_removed_on_drop() {
+        let args = TemplateArgs::parse(["bench", "small"].map(String::from)).unwrap();
+        let path = artifact_path("removed");
+
+        drop(TempArtifact::with_keep(path.clone(), args.keep_artifacts));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_artifact_removed_when_unwinding() {
+        let path = artifact_path("unwound");
+
+        let guarded = path.clone();
+        let result = std::panic::catch_unwind(move || {
+            let _artifact = TempArtifact::with_keep(guarded, false);
+            panic!("prover failed");
+        });
+        assert!(result.is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_artifact_kept_with_flag() {
+        let args = TemplateArgs::parse(["bench", "small", "--keep-artifacts"].map(String::from)).unwrap();
+        let path = artifact_path("kept");
+
+        drop(TempArtifact::with_keep(path.clone(), args.keep_artifacts));
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+    }
+}
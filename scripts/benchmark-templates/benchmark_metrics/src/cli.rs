@@ -0,0 +1,407 @@
+//! Template Command Line
+//!
+//! Every template takes the circuit size as its first argument plus
+//! `--format json|json-compact`, `--out <path>` and `--repeat <n>`, and
+//! templates that draw random inputs take `--seed <u64>`. `TemplateArgs`
+//! parses those once, and `BenchmarkMetrics::emit` honours them: with `--out` the
+//! metrics go to the file, written atomically, and a one-line summary goes
+//! to stderr, so an orchestrator can run many templates in parallel
+//! without their stdout interleaving. An argument that cannot be parsed is
+//! an error rather than being ignored, so a typo does not silently fall
+//! back to the default. `--keep-artifacts` keeps the temporary files a
+//! run writes, such as setup parameters. `--abort-on-lint` makes a lint warning fail `emit`,
+//! for CI runs that must not ship placeholder values. Knobs only one
+//! operation has, such as a tree depth, go through
+//! `--operation-args key=val,...`, checked against the keys that operation
+//! accepts.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{lint_metrics, BenchmarkMetrics, CircuitSize, ParseCircuitSizeError};
+
+/// The values `--format` accepts.
+const FORMATS: [&str; 2] = ["json", "json-compact"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateArgs {
+    pub circuit_size: String,
+    pub format: String, // one of FORMATS
+    pub out: Option<PathBuf>,
+    pub repeat: usize, // runs of the measured phases, at least 1
+    pub seed: Option<u64>, // seed for random inputs; None draws a fresh one
+    pub keep_artifacts: bool, // keep files `TempArtifact` would remove
+    args: Vec<String>,
+}
+
+/// A shared template argument with a value that cannot be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseArgsError {
+    // `--repeat` was not followed by a positive integer
+    InvalidRepeat(String),
+    // `--seed` was not followed by a u64
+    InvalidSeed(String),
+    // `--format` named something other than one of `FORMATS`
+    UnknownFormat(String),
+    // `--out` was not followed by a path
+    MissingOut,
+    // An `--operation-args` entry that is not KEY=VAL
+    MalformedOperationArg(String),
+    // An `--operation-args` key the operation does not take
+    UnknownOperationArg { key: String, accepted: Vec<String> },
+}
+
+impl fmt::Display for ParseArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseArgsError::InvalidRepeat(value) => {
+                write!(f, "--repeat expects a positive integer, got {:?}", value)
+            }
+            ParseArgsError::InvalidSeed(value) => write!(f, "--seed expects a u64, got {:?}", value),
+            ParseArgsError::UnknownFormat(format) => {
+                write!(f, "unknown --format {:?}, expected {}", format, FORMATS.join(" or "))
+            }
+            ParseArgsError::MissingOut => write!(f, "--out expects a path"),
+            ParseArgsError::MalformedOperationArg(arg) => {
+                write!(f, "--operation-args expects KEY=VAL pairs, got {:?}", arg)
+            }
+            ParseArgsError::UnknownOperationArg { key, accepted } if accepted.is_empty() => {
+                write!(f, "unknown operation argument {:?}, this operation takes none", key)
+            }
+            ParseArgsError::UnknownOperationArg { key, accepted } => {
+                write!(f, "unknown operation argument {:?}, expected one of {}", key, accepted.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseArgsError {}
+
+impl TemplateArgs {
+    /// Parses `args` as passed to `main`, program name first.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, ParseArgsError> {
+        let args: Vec<String> = args.into_iter().collect();
+        let mut parsed = Self {
+            circuit_size: "small".to_string(),
+            format: "json".to_string(),
+            out: None,
+            repeat: 1,
+            seed: None,
+            keep_artifacts: false,
+            args,
+        };
+
+        if let Some(size) = parsed.args.get(1).filter(|arg| !arg.starts_with("--")) {
+            parsed.circuit_size = size.clone();
+        }
+        if parsed.has_flag("--format") {
+            let format = parsed.value_of("--format").unwrap_or_default();
+            if !FORMATS.contains(&format) {
+                return Err(ParseArgsError::UnknownFormat(format.to_string()));
+            }
+            parsed.format = format.to_string();
+        }
+        if parsed.has_flag("--out") {
+            let out = parsed.value_of("--out").filter(|path| !path.starts_with("--"));
+            parsed.out = Some(PathBuf::from(out.ok_or(ParseArgsError::MissingOut)?));
+        }
+        if parsed.has_flag("--repeat") {
+            let value = parsed.value_of("--repeat").unwrap_or_default();
+            let repeat = value
+                .parse::<usize>()
+                .map_err(|_| ParseArgsError::InvalidRepeat(value.to_string()))?;
+            if repeat == 0 {
+                return Err(ParseArgsError::InvalidRepeat(value.to_string()));
+            }
+            parsed.repeat = repeat;
+        }
+        if parsed.has_flag("--seed") {
+            let value = parsed.value_of("--seed").unwrap_or_default();
+            let seed = value.parse::<u64>().map_err(|_| ParseArgsError::InvalidSeed(value.to_string()))?;
+            parsed.seed = Some(seed);
+        }
+        parsed.keep_artifacts = parsed.has_flag("--keep-artifacts");
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, exiting with status 2 after printing
+    /// the error if they cannot be parsed, and applies `--keep-artifacts`
+    /// with `set_keep_artifacts`.
+    pub fn from_env() -> Self {
+        let args = Self::parse(std::env::args()).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            std::process::exit(2);
+        });
+        crate::set_keep_artifacts(args.keep_artifacts);
+        args
+    }
+
+    /// The circuit size argument as a named `CircuitSize`.
+    pub fn size(&self) -> Result<CircuitSize, ParseCircuitSizeError> {
+        self.circuit_size.parse()
+    }
+
+    /// The circuit size argument as a named `CircuitSize`, exiting with
+    /// status 2 after printing the error if it names none, as `from_env`
+    /// does for the other arguments.
+    pub fn size_or_exit(&self) -> CircuitSize {
+        self.size().unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            std::process::exit(2);
+        })
+    }
+
+    /// The value following `flag`, for template-specific options such as
+    /// `--security-bits`.
+    pub fn value_of(&self, flag: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| self.args.get(i + 1))
+            .map(String::as_str)
+    }
+
+    /// The `--operation-args key=val,key=val` map for an operation that
+    /// takes the keys in `accepted`; empty without the flag. Each operation
+    /// documents its keys and parses the values itself.
+    pub fn operation_args(&self, accepted: &[&str]) -> Result<BTreeMap<String, String>, ParseArgsError> {
+        let mut parsed = BTreeMap::new();
+        let Some(list) = self.value_of("--operation-args") else {
+            return Ok(parsed);
+        };
+        for pair in list.split(',').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| ParseArgsError::MalformedOperationArg(pair.to_string()))?;
+            if !accepted.contains(&key) {
+                return Err(ParseArgsError::UnknownOperationArg {
+                    key: key.to_string(),
+                    accepted: accepted.iter().map(|key| key.to_string()).collect(),
+                });
+            }
+            parsed.insert(key.to_string(), value.to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Whether `flag` was passed at all.
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.args.iter().any(|arg| arg == flag)
+    }
+}
+
+impl BenchmarkMetrics {
+    fn to_json_in(&self, format: &str) -> String {
+        match format {
+            "json-compact" => self.to_json_compact(),
+            _ => self.to_json_pretty(),
+        }
+    }
+
+    /// Prints the metrics in the requested format, or writes them to
+    /// `--out` and prints a summary line to stderr instead. Anything
+    /// `lint_metrics` flags is printed to stderr as a warning first; with
+    /// `--abort-on-lint` a warning is an error and nothing is emitted.
+    pub fn emit(&self, args: &TemplateArgs) -> io::Result<()> {
+        let warnings = lint_metrics(self);
+        for warning in &warnings {
+            eprintln!("warning: {}", warning);
+        }
+        if !warnings.is_empty() && args.has_flag("--abort-on-lint") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} lint warning(s) with --abort-on-lint", warnings.len()),
+            ));
+        }
+
+        let json = self.to_json_in(&args.format);
+        match &args.out {
+            Some(path) => {
+                write_atomically(path, &json)?;
+                eprintln!(
+                    "{} {} ({}): proving {} ms, verification {} ms -> {}",
+                    self.system,
+                    self.operation,
+                    self.circuit_size,
+                    self.time_metrics.proving_time_ms,
+                    self.time_metrics.verification_time_ms,
+                    path.display(),
+                );
+            }
+            None => println!("{}", json),
+        }
+        Ok(())
+    }
+}
+
+// Writes `contents` next to `path` and renames it into place, so a reader
+// never sees a partial file. Missing parent directories are created.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "--out needs a file name"))?;
+    let tmp = dir.join(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+
+    fs::write(&tmp, format!("{}\n", contents))?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> TemplateArgs {
+        TemplateArgs::parse(list.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    fn parse_err(list: &[&str]) -> ParseArgsError {
+        TemplateArgs::parse(list.iter().map(|arg| arg.to_string())).unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_defaults_and_flags() {
+        let defaults = args(&["bench"]);
+        assert_eq!(defaults.circuit_size, "small");
+        assert_eq!(defaults.format, "json");
+        assert_eq!(defaults.out, None);
+
+        let parsed = args(&["bench", "large", "--format", "json-compact", "--out", "r/x.json", "--security-bits", "100"]);
+        assert_eq!(parsed.circuit_size, "large");
+        assert_eq!(parsed.size(), Ok(CircuitSize::Large));
+        assert_eq!(parsed.format, "json-compact");
+        assert_eq!(parsed.out, Some(PathBuf::from("r/x.json")));
+        assert_eq!(parsed.value_of("--security-bits"), Some("100"));
+        assert_eq!(parsed.repeat, 1);
+        assert_eq!(args(&["bench", "small", "--repeat", "5"]).repeat, 5);
+        assert_eq!(parsed.seed, None);
+        assert_eq!(args(&["bench", "small", "--seed", "42"]).seed, Some(42));
+
+        // A flag in first position is not a circuit size
+        assert_eq!(args(&["bench", "--out", "x.json"]).circuit_size, "small");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_repeat() {
+        let err = parse_err(&["bench", "small", "--repeat", "abc"]);
+        assert_eq!(err, ParseArgsError::InvalidRepeat("abc".to_string()));
+        assert_eq!(err.to_string(), "--repeat expects a positive integer, got \"abc\"");
+
+        // Zero runs would leave nothing to measure
+        assert_eq!(parse_err(&["bench", "small", "--repeat", "0"]), ParseArgsError::InvalidRepeat("0".to_string()));
+
+        assert_eq!(parse_err(&["bench", "small", "--repeat", "-3"]), ParseArgsError::InvalidRepeat("-3".to_string()));
+        assert_eq!(parse_err(&["bench", "small", "--repeat"]), ParseArgsError::InvalidRepeat(String::new()));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_seed() {
+        let err = parse_err(&["bench", "small", "--seed", "-1"]);
+        assert_eq!(err, ParseArgsError::InvalidSeed("-1".to_string()));
+        assert_eq!(err.to_string(), "--seed expects a u64, got \"-1\"");
+        assert_eq!(parse_err(&["bench", "--seed"]), ParseArgsError::InvalidSeed(String::new()));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_format() {
+        let err = parse_err(&["bench", "small", "--format", "yaml"]);
+        assert_eq!(err, ParseArgsError::UnknownFormat("yaml".to_string()));
+        assert_eq!(err.to_string(), "unknown --format \"yaml\", expected json or json-compact");
+
+        assert_eq!(parse_err(&["bench", "--format", "JSON"]), ParseArgsError::UnknownFormat("JSON".to_string()));
+        assert_eq!(parse_err(&["bench", "--format"]), ParseArgsError::UnknownFormat(String::new()));
+        assert_eq!(args(&["bench", "--format", "json"]).format, "json");
+    }
+
+    #[test]
+    fn test_out_without_path() {
+        let err = parse_err(&["bench", "small", "--out"]);
+        assert_eq!(err, ParseArgsError::MissingOut);
+        assert_eq!(err.to_string(), "--out expects a path");
+
+        // The next flag is not a path
+        assert_eq!(parse_err(&["bench", "--out", "--repeat", "2"]), ParseArgsError::MissingOut);
+        assert_eq!(args(&["bench"]).out, None);
+    }
+
+    // An operation with two knobs, built the way a template would build it
+    #[derive(Debug, PartialEq)]
+    struct DummyOperation {
+        depth: usize,
+        batch: usize,
+    }
+
+    impl DummyOperation {
+        const ARGS: [&'static str; 2] = ["depth", "batch"];
+
+        fn from_args(args: &TemplateArgs) -> Result<Self, ParseArgsError> {
+            let parsed = args.operation_args(&Self::ARGS)?;
+            let get = |key: &str, default: usize| parsed.get(key).map_or(default, |value| value.parse().unwrap());
+            Ok(Self { depth: get("depth", 8), batch: get("batch", 1) })
+        }
+    }
+
+    #[test]
+    fn test_operation_args_reach_operation() {
+        let received = DummyOperation::from_args(&args(&["bench", "small", "--operation-args", "depth=20,batch=4"]));
+        assert_eq!(received, Ok(DummyOperation { depth: 20, batch: 4 }));
+        assert_eq!(DummyOperation::from_args(&args(&["bench"])), Ok(DummyOperation { depth: 8, batch: 1 }));
+
+        let unknown = DummyOperation::from_args(&args(&["bench", "--operation-args", "depth=20,width=3"])).unwrap_err();
+        assert_eq!(unknown.to_string(), "unknown operation argument \"width\", expected one of depth, batch");
+        let malformed = DummyOperation::from_args(&args(&["bench", "--operation-args", "depth"])).unwrap_err();
+        assert_eq!(malformed, ParseArgsError::MalformedOperationArg("depth".to_string()));
+
+        let none = args(&["bench", "--operation-args", "depth=2"]).operation_args(&[]).unwrap_err();
+        assert_eq!(none.to_string(), "unknown operation argument \"depth\", this operation takes none");
+    }
+
+    #[test]
+    fn test_emit_writes_out_file() {
+        let dir = std::env::temp_dir().join(format!("benchmark_metrics_emit_{}", std::process::id()));
+        let path = dir.join("nested").join("metrics.json");
+        let metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+
+        metrics.emit(&args(&["bench", "small", "--out", path.to_str().unwrap()])).unwrap();
+
+        let written: BenchmarkMetrics = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, metrics);
+
+        // Only the renamed file is left behind
+        let entries: Vec<_> = fs::read_dir(path.parent().unwrap()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_abort_on_lint_fails_emit() {
+        let dir = std::env::temp_dir().join(format!("benchmark_metrics_lint_{}", std::process::id()));
+        let path = dir.join("metrics.json");
+        let out = path.to_str().unwrap();
+
+        // Security level 0 is a placeholder, so this lints
+        let metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        let err = metrics.emit(&args(&["bench", "small", "--out", out, "--abort-on-lint"])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "1 lint warning(s) with --abort-on-lint");
+        assert!(!path.exists());
+
+        // Off by default: the same metrics are written with a warning
+        metrics.emit(&args(&["bench", "small", "--out", out])).unwrap();
+        assert!(path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
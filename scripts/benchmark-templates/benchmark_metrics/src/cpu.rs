@@ -0,0 +1,221 @@
+//! CPU Utilization
+//!
+//! resource_metrics.cpu_utilization_percent is the share of the machine a
+//! phase kept busy: the CPU time the process spent over it, user and system
+//! across all threads, divided by its wall time times the number of cores.
+//! A `CpuSampler` reads the process's CPU time when it starts and again
+//! when it stops, so there is no thread to run alongside the phase and
+//! nothing is missed between samples. Started with `CpuSampler::start_into`
+//! it is a scoped guard that writes the result when it goes out of scope.
+
+use std::time::{Duration, Instant};
+
+use crate::{BenchmarkMetrics, ResourceMetrics};
+
+/// Measures CPU utilization from `start` until `finish` or drop.
+#[must_use = "a sampler records nothing until it is finished or dropped"]
+pub struct CpuSampler<'a> {
+    wall_start: Instant,
+    // None where the platform does not report process CPU time
+    cpu_start: Option<Duration>,
+    // Written on drop, unless `finish` was called
+    target: Option<&'a mut ResourceMetrics>,
+}
+
+impl CpuSampler<'static> {
+    /// Starts measuring; the result comes from `finish`.
+    pub fn start() -> Self {
+        Self { wall_start: Instant::now(), cpu_start: imp::process_cpu_time(), target: None }
+    }
+}
+
+impl<'a> CpuSampler<'a> {
+    /// Starts measuring into `resource_metrics`, written when the sampler is
+    /// dropped.
+    pub fn start_into(resource_metrics: &'a mut ResourceMetrics) -> Self {
+        Self { wall_start: Instant::now(), cpu_start: imp::process_cpu_time(), target: Some(resource_metrics) }
+    }
+
+    /// Stops measuring; `None` if the platform does not report CPU time.
+    pub fn finish(mut self) -> Option<CpuUsage> {
+        self.target = None;
+        self.usage()
+    }
+
+    fn usage(&self) -> Option<CpuUsage> {
+        let cpu = imp::process_cpu_time()?.saturating_sub(self.cpu_start?);
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        Some(CpuUsage { utilization_percent: utilization_percent(cpu, self.wall_start.elapsed(), cores) })
+    }
+}
+
+impl Drop for CpuSampler<'_> {
+    fn drop(&mut self) {
+        if let Some(resource_metrics) = self.target.take() {
+            if let Some(usage) = self.usage() {
+                resource_metrics.cpu_utilization_percent = usage.utilization_percent;
+            }
+        }
+    }
+}
+
+/// The utilization a `CpuSampler` measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuUsage {
+    // Of all cores, so at most 100
+    pub utilization_percent: u32,
+}
+
+impl CpuUsage {
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        metrics.resource_metrics.cpu_utilization_percent = self.utilization_percent;
+    }
+}
+
+// CPU time over the capacity of `cores` cores for `wall`, rounded; clock
+// granularity can put the CPU time slightly above that, hence the cap
+fn utilization_percent(cpu: Duration, wall: Duration, cores: usize) -> u32 {
+    let capacity = wall.as_secs_f64() * cores as f64;
+    if capacity <= 0.0 {
+        return 0;
+    }
+    (cpu.as_secs_f64() / capacity * 100.0).round().min(100.0) as u32
+}
+
+// utime and stime, the 14th and 15th fields of a /proc/<pid>/stat line, in
+// clock ticks. The second field is the command name in parentheses and may
+// itself contain spaces, so fields are counted from the last ')'.
+#[cfg(any(target_os = "linux", test))]
+fn parse_stat_ticks(stat: &str) -> Option<u64> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+// /proc/self/stat, converted from clock ticks
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+    use std::time::Duration;
+
+    use super::parse_stat_ticks;
+
+    pub(super) fn process_cpu_time() -> Option<Duration> {
+        let ticks = parse_stat_ticks(&fs::read_to_string("/proc/self/stat").ok()?)?;
+        let ticks_per_second = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).ok().filter(|&t| t > 0)?;
+        Some(Duration::from_secs_f64(ticks as f64 / ticks_per_second as f64))
+    }
+}
+
+// getrusage's user and system time
+#[cfg(all(unix, not(target_os = "linux")))]
+mod imp {
+    use std::time::Duration;
+
+    fn to_duration(time: libc::timeval) -> Duration {
+        Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+    }
+
+    pub(super) fn process_cpu_time() -> Option<Duration> {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+            return None;
+        }
+        Some(to_duration(usage.ru_utime) + to_duration(usage.ru_stime))
+    }
+}
+
+// GetProcessTimes' kernel and user time, in 100 ns units
+#[cfg(windows)]
+mod imp {
+    use std::time::Duration;
+
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    fn to_duration(time: FILETIME) -> Duration {
+        let units = (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+        Duration::from_nanos(units * 100)
+    }
+
+    pub(super) fn process_cpu_time() -> Option<Duration> {
+        let zero = FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+        let (mut creation, mut exit, mut kernel, mut user) = (zero, zero, zero, zero);
+        if unsafe { GetProcessTimes(GetCurrentProcess(), &mut creation, &mut exit, &mut kernel, &mut user) } == 0 {
+            return None;
+        }
+        Some(to_duration(kernel) + to_duration(user))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use std::time::Duration;
+
+    pub(super) fn process_cpu_time() -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hint::black_box;
+    use std::thread;
+
+    // Keeps every core busy for `duration`
+    fn spin_all_cores(duration: Duration) {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        thread::scope(|scope| {
+            for _ in 0..cores {
+                scope.spawn(|| {
+                    let start = Instant::now();
+                    let mut x = 0u64;
+                    while start.elapsed() < duration {
+                        x = black_box(x.wrapping_mul(6364136223846793005).wrapping_add(1));
+                    }
+                });
+            }
+        });
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_cpu_bound_work_reports_utilization() {
+        let sampler = CpuSampler::start();
+        spin_all_cores(Duration::from_millis(300));
+        let usage = sampler.finish().expect("CPU time is readable on this platform");
+
+        assert!(usage.utilization_percent >= 10, "only {}% while spinning", usage.utilization_percent);
+        assert!(usage.utilization_percent <= 100);
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_guard_writes_on_drop() {
+        let mut resource_metrics = ResourceMetrics::default();
+        {
+            let _cpu = CpuSampler::start_into(&mut resource_metrics);
+            spin_all_cores(Duration::from_millis(300));
+        }
+        assert!(resource_metrics.cpu_utilization_percent >= 10);
+    }
+
+    #[test]
+    fn test_parse_stat_ticks() {
+        let stat = "4242 (cargo (test) x) R 1 4242 4242 0 -1 4194304 1520 0 0 0 250 37 0 0 20 0 9 0";
+        assert_eq!(parse_stat_ticks(stat), Some(287));
+        assert_eq!(parse_stat_ticks("4242 (short) R 1"), None);
+    }
+
+    #[test]
+    fn test_utilization_percent() {
+        let second = Duration::from_secs(1);
+        assert_eq!(utilization_percent(second, second, 4), 25);
+        assert_eq!(utilization_percent(second * 4, second, 4), 100);
+        assert_eq!(utilization_percent(second * 5, second, 4), 100);
+        assert_eq!(utilization_percent(second, Duration::ZERO, 4), 0);
+    }
+}
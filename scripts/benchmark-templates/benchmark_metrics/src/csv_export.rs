@@ -0,0 +1,200 @@
+//! CSV Export
+//!
+//! Spreadsheets want one flat row per run. `to_csv` flattens each section
+//! into dotted column names (`time_metrics.proving_time_ms`), with
+//! operation, system and circuit_size first and every other column in
+//! alphabetical order. The columns are the union over all runs, so a system
+//! without some `extra` field gets an empty cell. Arrays such as
+//! `assumptions` are kept as JSON text in a single cell. `to_csv_columns`
+//! writes only the columns asked for, in the order asked for, and rejects
+//! a column no run has rather than leaving it empty.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+
+use serde_json::{Map, Value};
+
+use crate::BenchmarkMetrics;
+
+const LEADING: [&str; 3] = ["operation", "system", "circuit_size"];
+
+/// A requested CSV column that is not a flattened path of any run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownColumnError(String);
+
+impl fmt::Display for UnknownColumnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown CSV column {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownColumnError {}
+
+/// Collections of results that can be written as CSV.
+pub trait ToCsv {
+    fn to_csv(&self) -> String;
+
+    /// Only `columns`, in that order, each a dotted path as in `to_csv`'s
+    /// header.
+    fn to_csv_columns(&self, columns: &[&str]) -> Result<String, UnknownColumnError>;
+}
+
+impl ToCsv for [BenchmarkMetrics] {
+    fn to_csv(&self) -> String {
+        let rows: Vec<BTreeMap<String, String>> = self.iter().map(flatten_metrics).collect();
+
+        let keys: BTreeSet<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+        let columns: Vec<&str> = LEADING
+            .into_iter()
+            .chain(keys.into_iter().map(String::as_str).filter(|key| !LEADING.contains(key)))
+            .collect();
+        write_rows(&columns, &rows)
+    }
+
+    fn to_csv_columns(&self, columns: &[&str]) -> Result<String, UnknownColumnError> {
+        let rows: Vec<BTreeMap<String, String>> = self.iter().map(flatten_metrics).collect();
+
+        if let Some(unknown) = columns.iter().find(|column| !rows.iter().any(|row| row.contains_key(**column))) {
+            return Err(UnknownColumnError(unknown.to_string()));
+        }
+        Ok(write_rows(columns, &rows))
+    }
+}
+
+fn write_rows(columns: &[&str], rows: &[BTreeMap<String, String>]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns).expect("writing to memory cannot fail");
+    for row in rows {
+        let cells = columns.iter().map(|column| row.get(*column).map(String::as_str).unwrap_or(""));
+        writer.write_record(cells).expect("writing to memory cannot fail");
+    }
+    String::from_utf8(writer.into_inner().expect("writing to memory cannot fail")).expect("CSV cells are UTF-8")
+}
+
+impl BenchmarkMetrics {
+    /// Reads rows written by `to_csv` back into metrics. Empty cells take
+    /// the field's default; cells of fields the schema types as strings stay
+    /// strings, and other cells are parsed as JSON where they can be.
+    pub fn from_csv(csv: &str) -> io::Result<Vec<Self>> {
+        let template = serde_json::to_value(BenchmarkMetrics::default()).expect("metrics serialize to JSON");
+
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let columns = reader.headers()?.clone();
+        let mut runs = Vec::new();
+        for record in reader.records() {
+            let mut value = template.clone();
+            for (column, cell) in columns.iter().zip(record?.iter()) {
+                if !cell.is_empty() {
+                    insert_dotted(&mut value, column, cell);
+                }
+            }
+            runs.push(serde_json::from_value(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+        }
+        Ok(runs)
+    }
+}
+
+fn flatten_metrics(metrics: &BenchmarkMetrics) -> BTreeMap<String, String> {
+    let mut row = BTreeMap::new();
+    let value = serde_json::to_value(metrics).expect("metrics serialize to JSON");
+    flatten("", &value, &mut row);
+    row
+}
+
+fn flatten(prefix: &str, value: &Value, row: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let column = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&column, field, row);
+            }
+        }
+        Value::Null => {}
+        Value::String(text) => {
+            row.insert(prefix.to_string(), text.clone());
+        }
+        other => {
+            row.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+// Sets the field at dotted `path`, creating objects on the way
+fn insert_dotted(value: &mut Value, path: &str, cell: &str) {
+    let mut target = value;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        let fields = target.as_object_mut().expect("just made an object");
+        if keys.peek().is_none() {
+            let parsed = match fields.get(key) {
+                Some(Value::String(_)) => Value::String(cell.to_string()),
+                _ => serde_json::from_str(cell).unwrap_or_else(|_| Value::String(cell.to_string())),
+            };
+            fields.insert(key.to_string(), parsed);
+            return;
+        }
+        target = fields.entry(key).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::{extra_fields, Summary};
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut halo2 = BenchmarkMetrics::new("halo2", "ecdsa", "1024");
+        halo2.time_metrics.proving_time_ms = 1500;
+        halo2.security_metrics.assumptions = vec!["discrete_log".to_string(), "random_oracle, ROM".to_string()];
+        halo2.performance_metrics.throughput_proofs_per_second = 0.5;
+        halo2.resolved_parameters = extra_fields(json!({ "k": 11 }));
+        halo2.time_statistics = Some([("proving_time_ms".to_string(), Summary::of(&[1400.0, 1600.0]).unwrap())].into());
+
+        let mut miden = BenchmarkMetrics::new("miden", "ecdsa", "small");
+        miden.time_metrics.extra = extra_fields(json!({ "execution_time_ms": 40 }));
+        miden.extra = extra_fields(json!({ "vm_metrics": { "cycles": 65536, "trace_width": "wide" } }));
+
+        let runs = vec![halo2, miden];
+        let csv = runs.to_csv();
+
+        let header = csv.lines().next().unwrap();
+        assert!(header.starts_with("operation,system,circuit_size,"));
+        assert!(header.contains(",time_metrics.proving_time_ms,"));
+        assert!(header.contains(",time_metrics.execution_time_ms,"));
+        assert_eq!(csv.lines().count(), 3);
+
+        // halo2 has no execution_time_ms: its cell is empty, not 0
+        let columns: Vec<&str> = header.split(',').collect();
+        let index = columns.iter().position(|column| *column == "time_metrics.execution_time_ms").unwrap();
+        let halo2_row = csv::Reader::from_reader(csv.as_bytes()).records().next().unwrap().unwrap();
+        assert_eq!(&halo2_row[index], "");
+
+        assert_eq!(BenchmarkMetrics::from_csv(&csv).unwrap(), runs);
+    }
+
+    #[test]
+    fn test_csv_columns_in_requested_order() {
+        let mut halo2 = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        halo2.time_metrics.proving_time_ms = 1500;
+        halo2.resource_metrics.proof_size_bytes = 4096;
+        let runs = [halo2, BenchmarkMetrics::new("miden", "ecdsa", "small")];
+
+        let csv = runs
+            .to_csv_columns(&["resource_metrics.proof_size_bytes", "system", "time_metrics.proving_time_ms"])
+            .unwrap();
+        assert_eq!(
+            csv,
+            "resource_metrics.proof_size_bytes,system,time_metrics.proving_time_ms\n4096,halo2,1500\n0,miden,0\n",
+        );
+
+        let err = runs.to_csv_columns(&["system", "time_metrics.proving_time"]).unwrap_err();
+        assert_eq!(err.to_string(), "unknown CSV column \"time_metrics.proving_time\"");
+    }
+}
@@ -0,0 +1,206 @@
+//! Run Environment
+//!
+//! A proving time only means something next to the machine that produced
+//! it. `collect_environment` gathers the CPU, memory, OS, toolchain and
+//! source revision once per run (`source_commit`, which the benchmark
+//! suite's shell script also uses, through `benchmark-runner`), and `Environment::apply` adds them to the
+//! `run_metadata` section so results from different machines can be told
+//! apart. Anything a platform does not expose is left out rather than
+//! guessed.
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::BenchmarkMetrics;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Environment {
+    pub arch: String,
+    // The circuit size exactly as passed, before any parsing
+    pub circuit_size_arg: String,
+    pub cpu_cores: usize, // logical cores available to the process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_model: Option<String>,
+    // HEAD of the checkout the benchmark crates live in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<SourceCommit>,
+    pub os: String,
+    // The compiler that built this binary
+    pub rustc_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_memory_kb: Option<u64>,
+}
+
+/// Describes the machine and build running the benchmark, for a run with
+/// `circuit_size_arg` as its circuit size argument.
+pub fn collect_environment(circuit_size_arg: &str) -> Environment {
+    Environment {
+        arch: std::env::consts::ARCH.to_string(),
+        circuit_size_arg: circuit_size_arg.to_string(),
+        cpu_cores: std::thread::available_parallelism().map_or(1, |cores| cores.get()),
+        cpu_model: imp::cpu_model(),
+        source_commit: source_commit(Path::new(env!("CARGO_MANIFEST_DIR"))),
+        os: std::env::consts::OS.to_string(),
+        rustc_version: env!("BENCHMARK_RUSTC_VERSION").to_string(),
+        total_memory_kb: imp::total_memory_kb(),
+    }
+}
+
+/// A git commit and whether the working tree's tracked files differ from
+/// it. Untracked files do not count, since results and logs are written
+/// inside the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceCommit {
+    pub commit: String,
+    pub dirty: bool,
+}
+
+/// HEAD of the git working tree containing `dir`, or `None` if there is
+/// none or git cannot be run.
+pub fn source_commit(dir: &Path) -> Option<SourceCommit> {
+    let commit = git(dir, &["rev-parse", "HEAD"])?;
+    let changes = git(dir, &["status", "--porcelain", "--untracked-files=no"])?;
+    Some(SourceCommit { commit, dirty: !changes.is_empty() })
+}
+
+impl Environment {
+    /// Adds the environment to `run_metadata`, keeping what a template
+    /// already put there (such as its seed).
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        let Value::Object(fields) = serde_json::to_value(self).expect("environment serializes to JSON") else {
+            unreachable!("a struct serializes to an object");
+        };
+
+        let run_metadata = metrics.extra.entry("run_metadata").or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(run_metadata) = run_metadata {
+            run_metadata.extend(fields);
+        }
+    }
+}
+
+// Trimmed stdout of `git args` run in `dir`, if it succeeds
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).current_dir(dir).output().ok()?;
+    let stdout = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    output.status.success().then_some(stdout)
+}
+
+// /proc/cpuinfo and /proc/meminfo
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+
+    pub(super) fn cpu_model() -> Option<String> {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+        cpuinfo
+            .lines()
+            .find(|line| line.starts_with("model name"))
+            .and_then(|line| line.split_once(':'))
+            .map(|(_, model)| model.trim().to_string())
+    }
+
+    pub(super) fn total_memory_kb() -> Option<u64> {
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix("MemTotal:"))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+    }
+}
+
+// sysctl, which reports memory in bytes
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::path::Path;
+use std::process::Command;
+
+    fn sysctl(name: &str) -> Option<String> {
+        let output = Command::new("sysctl").args(["-n", name]).output().ok()?;
+        let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        (output.status.success() && !value.is_empty()).then_some(value)
+    }
+
+    pub(super) fn cpu_model() -> Option<String> {
+        sysctl("machdep.cpu.brand_string")
+    }
+
+    pub(super) fn total_memory_kb() -> Option<u64> {
+        sysctl("hw.memsize")?.parse::<u64>().ok().map(|bytes| bytes / 1024)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod imp {
+    pub(super) fn cpu_model() -> Option<String> {
+        None
+    }
+
+    pub(super) fn total_memory_kb() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_cores_and_memory_detected() {
+        let environment = collect_environment("small");
+
+        assert!(environment.cpu_cores > 0);
+        assert!(environment.total_memory_kb.is_some_and(|kb| kb > 0));
+        assert!(environment.rustc_version.starts_with("rustc "));
+        assert_eq!(environment.circuit_size_arg, "small");
+    }
+
+    #[test]
+    fn test_apply_keeps_existing_run_metadata() {
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "Large");
+        metrics.extra.insert("run_metadata".to_string(), json!({ "seed": 7 }));
+
+        collect_environment("Large").apply(&mut metrics);
+
+        let run_metadata = &metrics.extra["run_metadata"];
+        assert_eq!(run_metadata["seed"], 7);
+        assert_eq!(run_metadata["circuit_size_arg"], "Large");
+        assert_eq!(run_metadata["os"], std::env::consts::OS);
+        assert!(run_metadata["cpu_cores"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_source_commit_in_git_fixture() {
+        let dir = std::env::temp_dir().join(format!("benchmark_metrics_git_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(source_commit(&dir), None);
+
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=bench", "-c", "user.email=bench@example.com"])
+                .args(args)
+                .current_dir(&dir)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        std::fs::write(dir.join("tracked"), "a").unwrap();
+        git(&["add", "tracked"]);
+        git(&["commit", "--quiet", "-m", "fixture"]);
+        let head = self::git(&dir, &["rev-parse", "HEAD"]).unwrap();
+
+        std::fs::write(dir.join("untracked"), "b").unwrap();
+        assert_eq!(source_commit(&dir), Some(SourceCommit { commit: head.clone(), dirty: false }));
+
+        std::fs::write(dir.join("tracked"), "c").unwrap();
+        assert_eq!(source_commit(&dir), Some(SourceCommit { commit: head, dirty: true }));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+//! GPU Detection
+//!
+//! With the `gpu` feature, `gpu_available` asks nvidia-smi whether a CUDA
+//! device is present and `GpuSampler` polls its utilization on a background
+//! thread while a phase runs. The `GpuUsage` it finishes with sets
+//! system_requirements.gpu_required and
+//! resource_metrics.gpu_utilization_percent. Without the feature nothing is
+//! queried and both fields keep their defaults, so CPU-only builds report
+//! exactly what they did before.
+
+use crate::BenchmarkMetrics;
+
+/// Whether a CUDA device is available. Always false without the `gpu`
+/// feature.
+pub fn gpu_available() -> bool {
+    imp::gpu_available()
+}
+
+/// Samples GPU utilization from `start` until `finish`.
+#[must_use = "a sampler records nothing until it is finished"]
+pub struct GpuSampler {
+    inner: imp::Sampler,
+}
+
+impl GpuSampler {
+    pub fn start() -> Self {
+        Self { inner: imp::Sampler::start() }
+    }
+
+    /// Stops sampling; `None` if no device was found.
+    pub fn finish(self) -> Option<GpuUsage> {
+        self.inner.finish().map(|utilization_percent| GpuUsage { utilization_percent })
+    }
+}
+
+/// What a `GpuSampler` saw on a machine with a CUDA device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuUsage {
+    // Mean over the samples of the busiest device
+    pub utilization_percent: u32,
+}
+
+impl GpuUsage {
+    /// Marks the GPU as required and writes the utilization.
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        metrics.system_requirements.gpu_required = true;
+        metrics.resource_metrics.gpu_utilization_percent = self.utilization_percent;
+    }
+}
+
+// Utilization percentages from `nvidia-smi --query-gpu=utilization.gpu
+// --format=csv,noheader,nounits`, one line per device; the busiest device
+// counts
+#[cfg(any(feature = "gpu", test))]
+fn parse_utilization(output: &str) -> Option<u32> {
+    output.lines().filter_map(|line| line.trim().parse::<u32>().ok()).max()
+}
+
+#[cfg(feature = "gpu")]
+mod imp {
+    use std::process::Command;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    use super::parse_utilization;
+
+    const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+    fn nvidia_smi(query: &str) -> Option<String> {
+        let output = Command::new("nvidia-smi")
+            .args([&format!("--query-gpu={}", query), "--format=csv,noheader,nounits"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok()
+    }
+
+    pub(super) fn gpu_available() -> bool {
+        nvidia_smi("name").is_some_and(|names| names.lines().any(|name| !name.trim().is_empty()))
+    }
+
+    pub(super) struct Sampler {
+        // None when no device was found
+        running: Option<(Arc<AtomicBool>, JoinHandle<Vec<u32>>)>,
+    }
+
+    impl Sampler {
+        pub(super) fn start() -> Self {
+            if !gpu_available() {
+                return Self { running: None };
+            }
+
+            let stop = Arc::new(AtomicBool::new(false));
+            let handle = thread::spawn({
+                let stop = Arc::clone(&stop);
+                move || {
+                    let mut samples = Vec::new();
+                    while !stop.load(Ordering::Relaxed) {
+                        samples.extend(nvidia_smi("utilization.gpu").as_deref().and_then(parse_utilization));
+                        thread::sleep(SAMPLE_INTERVAL);
+                    }
+                    samples
+                }
+            });
+            Self { running: Some((stop, handle)) }
+        }
+
+        // Mean utilization, or None without a device. A phase shorter than
+        // one interval still has the sample taken as it started.
+        pub(super) fn finish(self) -> Option<u32> {
+            let (stop, handle) = self.running?;
+            stop.store(true, Ordering::Relaxed);
+            let samples = handle.join().unwrap_or_default();
+            if samples.is_empty() {
+                return Some(0);
+            }
+            let total: u64 = samples.iter().map(|&sample| u64::from(sample)).sum();
+            Some((total as f64 / samples.len() as f64).round() as u32)
+        }
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+mod imp {
+    pub(super) fn gpu_available() -> bool {
+        false
+    }
+
+    pub(super) struct Sampler;
+
+    impl Sampler {
+        pub(super) fn start() -> Self {
+            Sampler
+        }
+
+        pub(super) fn finish(self) -> Option<u32> {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_marks_gpu_required() {
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        GpuUsage { utilization_percent: 64 }.apply(&mut metrics);
+
+        assert!(metrics.system_requirements.gpu_required);
+        assert_eq!(metrics.resource_metrics.gpu_utilization_percent, 64);
+    }
+
+    #[test]
+    fn test_parse_utilization_takes_busiest_device() {
+        assert_eq!(parse_utilization("37\n"), Some(37));
+        assert_eq!(parse_utilization("12\n 85 \n40\n"), Some(85));
+        assert_eq!(parse_utilization("[N/A]\n"), None);
+        assert_eq!(parse_utilization(""), None);
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    #[test]
+    fn test_without_gpu_feature_nothing_is_required() {
+        assert!(!gpu_available());
+
+        let usage = GpuSampler::start().finish();
+        assert_eq!(usage, None);
+
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        if let Some(usage) = usage {
+            usage.apply(&mut metrics);
+        }
+        assert!(!metrics.system_requirements.gpu_required);
+        assert_eq!(metrics.resource_metrics.gpu_utilization_percent, 0);
+    }
+}
@@ -0,0 +1,325 @@
+//! Benchmark Metrics Schema
+//!
+//! Every operation template reports the same JSON document. `BenchmarkMetrics`
+//! is that document as a typed struct, so templates fill in fields the
+//! compiler checks and downstream tools can deserialize results instead of
+//! guessing field names.
+//!
+//! Each section keeps a flattened `extra` map for system-specific fields
+//! (snarkOS' `block_production_time_ms`, Miden's `vm_cycles`, ...), and the
+//! top-level `extra` holds whole system-specific sections such as snarkOS'
+//! `network_metrics`. Fields are declared in alphabetical order: the
+//! templates used to build a `json!` literal, which serde_json prints with
+//! sorted keys, and keeping that order keeps the output byte-compatible.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+mod aggregate;
+mod alloc;
+mod artifact;
+mod cli;
+mod cpu;
+mod csv_export;
+mod environment;
+mod gpu;
+mod memory;
+mod operation;
+mod size;
+mod timer;
+mod validate;
+
+pub use aggregate::{Aggregator, Summary, Throughput};
+pub use alloc::{peak_allocated_bytes, AllocationGap, CountingAllocator};
+pub use artifact::{set_keep_artifacts, TempArtifact};
+pub use cli::{ParseArgsError, TemplateArgs};
+pub use cpu::{CpuSampler, CpuUsage};
+pub use csv_export::{ToCsv, UnknownColumnError};
+pub use environment::{collect_environment, source_commit, Environment, SourceCommit};
+pub use gpu::{gpu_available, GpuSampler, GpuUsage};
+pub use memory::{peak_rss_kb, MemoryProbe};
+pub use operation::{Operation, ParseOperationError};
+pub use size::{CircuitSize, ParseCircuitSizeError};
+pub use timer::Timer;
+pub use validate::{lint_metrics, validate_metrics};
+
+// For `register_benchmark!` in other crates
+#[doc(hidden)]
+pub use inventory;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeMetrics {
+    pub proving_time_ms: u64,
+    pub setup_time_ms: u64,
+    pub total_execution_time_ms: u64,
+    pub verification_time_ms: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceMetrics {
+    pub cpu_utilization_percent: u32,
+    pub gpu_utilization_percent: u32,
+    pub peak_memory_usage_kb: u64,
+    pub proof_size_bytes: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SetupMetrics {
+    pub setup_reusable: bool,
+    pub setup_size_bytes: u64,
+    pub setup_type: String, // "transparent", "universal_srs", ...
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Features {
+    pub custom_gates: bool,
+    pub parallel_proving: bool,
+    pub parallel_verification: bool,
+    pub recursive_proofs: bool,
+    pub universal_circuits: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecurityMetrics {
+    pub assumptions: Vec<String>,
+    pub post_quantum_resistant: bool,
+    pub security_level_bits: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScalabilityMetrics {
+    pub constraints_count: u64,
+    pub degree: u32,
+    pub field_bits: u32,
+    pub proving_complexity_class: String,
+    pub variables_count: u64,
+    pub verification_complexity_class: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceMetrics {
+    pub batch_proving_supported: bool,
+    pub batch_verification_supported: bool,
+    pub latency_ms: u64,
+    pub throughput_proofs_per_second: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemRequirements {
+    pub disk_space_gb: u64,
+    pub gpu_required: bool,
+    pub minimum_memory_gb: u64,
+    pub recommended_cpu_cores: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkMetrics {
+    pub circuit_size: String,
+    pub features: Features,
+    pub operation: String,
+    pub performance_metrics: PerformanceMetrics,
+    // What circuit_size expanded to for this system (k, trace rows, ...)
+    pub resolved_parameters: Map<String, Value>,
+    pub resource_metrics: ResourceMetrics,
+    pub scalability_metrics: ScalabilityMetrics,
+    pub security_metrics: SecurityMetrics,
+    pub setup_metrics: SetupMetrics,
+    pub system: String,
+    pub system_requirements: SystemRequirements,
+    pub time_metrics: TimeMetrics,
+    // Per-phase distribution over --repeat runs; absent for a single run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_statistics: Option<BTreeMap<String, Summary>>,
+    pub timestamp: String, // RFC 3339
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Turns a `json!({...})` object literal into an `extra` map, so templates
+/// can list their system-specific fields the way they appear in the output.
+pub fn extra_fields(object: Value) -> Map<String, Value> {
+    match object {
+        Value::Object(fields) => fields,
+        other => panic!("extra fields must be a JSON object, got {}", other),
+    }
+}
+
+impl BenchmarkMetrics {
+    /// Empty metrics for one run, timestamped now.
+    pub fn new(system: &str, operation: &str, circuit_size: &str) -> Self {
+        Self {
+            system: system.to_string(),
+            operation: operation.to_string(),
+            circuit_size: circuit_size.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            ..Self::default()
+        }
+    }
+
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("metrics serialize to JSON")
+    }
+
+    pub fn to_json_compact(&self) -> String {
+        serde_json::to_string(self).expect("metrics serialize to JSON")
+    }
+
+    /// Prints the metrics as pretty JSON, the format run_all_benchmarks.sh reads.
+    pub fn print_json(&self) {
+        println!("{}", self.to_json_pretty());
+    }
+
+    /// Prints the metrics on a single line, for log ingestion.
+    pub fn print_json_compact(&self) {
+        println!("{}", self.to_json_compact());
+    }
+
+    /// Derives the metrics computed from others, once a template has filled
+    /// those in. For a benchmark that reports `resolved_parameters.input_bytes`
+    /// (the hash benchmarks) that is `input_bytes_per_proving_second` in
+    /// `performance_metrics`: input bytes hashed per second of proving, the
+    /// figure hash functions are compared by. Left out when either the input
+    /// size or the proving time is zero or missing.
+    pub fn finalize(&mut self) {
+        let input_bytes = self.resolved_parameters.get("input_bytes").and_then(Value::as_u64).unwrap_or(0);
+        let proving_ms = self.time_metrics.proving_time_ms;
+        if input_bytes > 0 && proving_ms > 0 {
+            let throughput = input_bytes as f64 / (proving_ms as f64 / 1000.0);
+            self.performance_metrics.extra.insert("input_bytes_per_proving_second".to_string(), throughput.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> BenchmarkMetrics {
+        let mut metrics = BenchmarkMetrics::new("plonky3", "ecdsa", "small");
+        metrics.timestamp = "2024-01-01T00:00:00+00:00".to_string();
+        metrics.setup_metrics.setup_type = "transparent".to_string();
+        metrics.setup_metrics.setup_reusable = true;
+        metrics.security_metrics.assumptions = vec!["collision_resistant_hash".to_string()];
+        metrics.security_metrics.security_level_bits = 128;
+        metrics.scalability_metrics.field_bits = 31;
+        metrics.scalability_metrics.proving_complexity_class = "O(n log n)".to_string();
+        metrics.scalability_metrics.verification_complexity_class = "O(1)".to_string();
+        metrics.time_metrics.proving_time_ms = 1500;
+        metrics
+    }
+
+    #[test]
+    fn test_output_matches_json_literal() {
+        // The literal the templates built before this struct existed
+        let literal = json!({
+            "operation": "ecdsa",
+            "system": "plonky3",
+            "circuit_size": "small",
+            "resolved_parameters": {},
+            "timestamp": "2024-01-01T00:00:00+00:00",
+            "time_metrics": {
+                "setup_time_ms": 0,
+                "proving_time_ms": 1500,
+                "verification_time_ms": 0,
+                "total_execution_time_ms": 0
+            },
+            "resource_metrics": {
+                "peak_memory_usage_kb": 0,
+                "proof_size_bytes": 0,
+                "cpu_utilization_percent": 0,
+                "gpu_utilization_percent": 0
+            },
+            "setup_metrics": {
+                "setup_type": "transparent",
+                "setup_size_bytes": 0,
+                "setup_reusable": true
+            },
+            "features": {
+                "recursive_proofs": false,
+                "universal_circuits": false,
+                "parallel_proving": false,
+                "parallel_verification": false,
+                "custom_gates": false
+            },
+            "security_metrics": {
+                "post_quantum_resistant": false,
+                "security_level_bits": 128,
+                "assumptions": ["collision_resistant_hash"]
+            },
+            "scalability_metrics": {
+                "constraints_count": 0,
+                "field_bits": 31,
+                "variables_count": 0,
+                "degree": 0,
+                "proving_complexity_class": "O(n log n)",
+                "verification_complexity_class": "O(1)"
+            },
+            "performance_metrics": {
+                "throughput_proofs_per_second": 0.0,
+                "latency_ms": 0,
+                "batch_proving_supported": false,
+                "batch_verification_supported": false
+            },
+            "system_requirements": {
+                "minimum_memory_gb": 0,
+                "recommended_cpu_cores": 0,
+                "gpu_required": false,
+                "disk_space_gb": 0
+            }
+        });
+
+        assert_eq!(sample().to_json_pretty(), serde_json::to_string_pretty(&literal).unwrap());
+        assert_eq!(sample().to_json_compact(), serde_json::to_string(&literal).unwrap());
+    }
+
+    #[test]
+    fn test_extra_fields_round_trip() {
+        let mut metrics = sample();
+        metrics.performance_metrics.extra = extra_fields(json!({ "vm_cycles": 4096 }));
+        metrics.extra = extra_fields(json!({ "network_metrics": { "connected_peers": 8 } }));
+
+        let json: Value = serde_json::from_str(&metrics.to_json_pretty()).unwrap();
+        assert_eq!(json["performance_metrics"]["vm_cycles"], 4096);
+        assert_eq!(json["network_metrics"]["connected_peers"], 8);
+
+        let parsed: BenchmarkMetrics = serde_json::from_str(&metrics.to_json_pretty()).unwrap();
+        assert_eq!(parsed, metrics);
+    }
+
+    #[test]
+    fn test_finalize_computes_input_throughput() {
+        let mut metrics = sample();
+        metrics.resolved_parameters.insert("input_bytes".to_string(), 3000.into());
+
+        metrics.finalize();
+
+        // 3000 bytes in 1500 ms
+        let throughput = metrics.performance_metrics.extra["input_bytes_per_proving_second"].as_f64().unwrap();
+        assert!(throughput > 0.0);
+        assert_eq!(throughput, 2000.0);
+
+        // Nothing to derive without an input size
+        let mut unsized_input = sample();
+        unsized_input.finalize();
+        assert!(!unsized_input.performance_metrics.extra.contains_key("input_bytes_per_proving_second"));
+    }
+}
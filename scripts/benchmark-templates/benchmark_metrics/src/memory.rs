@@ -0,0 +1,164 @@
+//! Peak Memory
+//!
+//! `peak_rss_kb` reports the high-water mark of the process's resident set,
+//! the number resource_metrics.peak_memory_usage_kb is meant to hold. It is
+//! a lifetime peak, so templates sample it once the proving phase, usually
+//! the most memory-hungry, has finished.
+//!
+//! A later, lighter phase such as verification never moves that peak, so
+//! `MemoryProbe` measures one phase on its own: it resets the high-water
+//! mark when it starts and reports how far the resident set rose above its
+//! starting size by the time it finishes. Only Linux can reset the mark;
+//! elsewhere the probe reports nothing rather than the lifetime peak.
+
+/// Peak resident set size of the current process in kilobytes, or `None`
+/// where the platform offers no way to read it.
+pub fn peak_rss_kb() -> Option<u64> {
+    imp::peak_rss_kb()
+}
+
+/// Peak memory of a single phase, from `start` to `finish`.
+#[must_use = "a probe measures nothing until it is finished"]
+pub struct MemoryProbe {
+    start_kb: u64,
+}
+
+impl MemoryProbe {
+    /// Resets the high-water mark and starts measuring; `None` where the
+    /// platform cannot reset it.
+    pub fn start() -> Option<Self> {
+        if !imp::reset_peak_rss() {
+            return None;
+        }
+        Some(Self { start_kb: imp::current_rss_kb()? })
+    }
+
+    /// Stops measuring: the highest resident set since `start`, less the
+    /// resident set at `start`, in kilobytes.
+    pub fn finish(self) -> Option<u64> {
+        peak_rss_kb().map(|peak| peak.saturating_sub(self.start_kb))
+    }
+}
+
+// VmHWM ("high water mark") and VmRSS in /proc/self/status, already in kB.
+// Writing 5 to /proc/self/clear_refs lowers VmHWM to the current VmRSS.
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+
+    fn status_kb(field: &str) -> Option<u64> {
+        let status = fs::read_to_string("/proc/self/status").ok()?;
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(field))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+    }
+
+    pub(super) fn peak_rss_kb() -> Option<u64> {
+        status_kb("VmHWM:")
+    }
+
+    pub(super) fn current_rss_kb() -> Option<u64> {
+        status_kb("VmRSS:")
+    }
+
+    pub(super) fn reset_peak_rss() -> bool {
+        fs::write("/proc/self/clear_refs", "5").is_ok()
+    }
+}
+
+// getrusage's ru_maxrss, which is bytes on Apple platforms and kB elsewhere
+#[cfg(all(unix, not(target_os = "linux")))]
+mod imp {
+    pub(super) fn peak_rss_kb() -> Option<u64> {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+            return None;
+        }
+        let max_rss = u64::try_from(usage.ru_maxrss).ok()?;
+        if cfg!(any(target_os = "macos", target_os = "ios")) {
+            Some(max_rss / 1024)
+        } else {
+            Some(max_rss)
+        }
+    }
+    pub(super) fn current_rss_kb() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn reset_peak_rss() -> bool {
+        false
+    }
+}
+
+// PeakWorkingSetSize from GetProcessMemoryInfo, in bytes
+#[cfg(windows)]
+mod imp {
+    use windows_sys::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    pub(super) fn peak_rss_kb() -> Option<u64> {
+        let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        if unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) } == 0 {
+            return None;
+        }
+        Some(counters.PeakWorkingSetSize as u64 / 1024)
+    }
+    pub(super) fn current_rss_kb() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn reset_peak_rss() -> bool {
+        false
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    pub(super) fn peak_rss_kb() -> Option<u64> {
+        None
+    }
+    pub(super) fn current_rss_kb() -> Option<u64> {
+        None
+    }
+
+    pub(super) fn reset_peak_rss() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_rss_covers_large_allocation() {
+        const SIZE: usize = 64 * 1024 * 1024;
+
+        // Write every page so the allocation is actually resident
+        let buffer = vec![1u8; SIZE];
+        let peak = peak_rss_kb().expect("peak RSS is readable on this platform");
+
+        assert!(peak > 0);
+        assert!(peak >= (SIZE / 1024) as u64, "peak {} kB below the {} kB allocated", peak, SIZE / 1024);
+        assert_eq!(buffer.iter().map(|&b| b as usize).sum::<usize>(), SIZE);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_memory_probe_measures_one_phase() {
+        const SIZE: usize = 64 * 1024 * 1024;
+
+        // An earlier, bigger phase does not count towards a later one
+        drop(std::hint::black_box(vec![1u8; 2 * SIZE]));
+
+        let probe = MemoryProbe::start().expect("the high-water mark can be reset on Linux");
+        let buffer = vec![1u8; SIZE];
+        let phase = probe.finish().unwrap();
+        assert_eq!(buffer.iter().map(|&b| b as usize).sum::<usize>(), SIZE);
+
+        assert!(phase >= (SIZE / 1024) as u64, "phase {} kB below the {} kB allocated", phase, SIZE / 1024);
+        assert!(phase < (2 * SIZE / 1024) as u64, "phase {} kB includes the earlier allocation", phase);
+    }
+}
@@ -0,0 +1,261 @@
+//! Operations
+//!
+//! The top-level "operation" field is what reports are grouped and compared
+//! by, so two benchmarks of the same operation must spell it the same way
+//! and a template copied without filling it in must not pass as one more
+//! operation. `Operation` is the registry of operations the templates
+//! benchmark; each one names its operation with `Operation::as_str` rather
+//! than a string literal, and `validate_metrics` rejects anything else. The
+//! names are the ones earlier reports already used, so baselines from before
+//! the registry still line up.
+//!
+//! Each operation is registered on its own with `register_benchmark!`, and
+//! `Operation::all` collects the registrations linked into the binary, so
+//! there is no list or match to extend. A registration is only seen by the
+//! binaries that link the crate it is in: the templates and
+//! `benchmark-runner` are separate executables that share only this crate,
+//! so an operation reports use belongs here, where the runner sees it too.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// One registered operation: its name in reports, what is proven and the
+/// systems with a template for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Operation {
+    name: &'static str,
+    description: &'static str,
+    systems: &'static [&'static str],
+}
+
+inventory::collect!(Operation);
+
+/// Registers an operation with name, description and the systems with a
+/// template for it, so `Operation::all`, `--list-operations` and
+/// `validate_metrics` pick it up. `Operation::Name => ...` declares it as an
+/// associated constant, which only this crate can do; elsewhere
+/// `NAME => ...` declares a constant in the calling module.
+#[macro_export]
+macro_rules! register_benchmark {
+    (Operation::$variant:ident => $operation:literal, $description:literal, [$($system:literal),*]) => {
+        impl $crate::Operation {
+            #[allow(non_upper_case_globals)]
+            pub const $variant: $crate::Operation =
+                $crate::Operation::new($operation, $description, &[$($system),*]);
+        }
+        $crate::inventory::submit! { $crate::Operation::$variant }
+    };
+    ($vis:vis $name:ident => $operation:literal, $description:literal, [$($system:literal),*]) => {
+        $vis const $name: $crate::Operation = $crate::Operation::new($operation, $description, &[$($system),*]);
+        $crate::inventory::submit! { $name }
+    };
+}
+
+register_benchmark!(Operation::BatchVerify => "batch_verify",
+    "verifying a batch of proofs under one verifying key", ["halo2"]);
+register_benchmark!(Operation::BatchedRange => "batched_range",
+    "that a batch of values fit in 8 bits, through one lookup table", ["halo2"]);
+register_benchmark!(Operation::BlockProduction => "block_production",
+    "producing and verifying a block", ["aleo-snarkos"]);
+register_benchmark!(Operation::ConditionalTree => "conditional_tree",
+    "selecting one leaf of a tree of nested conditionals", ["halo2"]);
+register_benchmark!(Operation::EcdsaVerify => "ecdsa",
+    "ECDSA signature verification over Pallas, committed with Vesta IPA",
+    ["aleo-snarkvm", "halo2", "miden", "nexus", "plonky3"]);
+register_benchmark!(Operation::EddsaVerify => "eddsa",
+    "EdDSA signature verification over Baby Jubjub", ["halo2"]);
+register_benchmark!(Operation::Fibonacci => "fibonacci",
+    "computing a Fibonacci number", ["plonky2"]);
+register_benchmark!(Operation::FieldArithmetic => "field_arithmetic",
+    "chains of native field multiplications and additions", ["halo2"]);
+register_benchmark!(Operation::HashToCurve => "hash_to_curve",
+    "mapping field elements to Pallas points with simplified SWU", ["halo2"]);
+register_benchmark!(Operation::InstanceCost => "instance_cost",
+    "the same witnesses with more or fewer of them public", ["halo2"]);
+register_benchmark!(Operation::MerkleInclusion => "merkle_inclusion",
+    "Merkle tree membership of a leaf", ["halo2"]);
+register_benchmark!(Operation::Msm => "msm",
+    "a multi-scalar multiplication, without a proof", ["halo2"]);
+register_benchmark!(Operation::Mux => "mux",
+    "multiplexer selections between two values", ["halo2"]);
+register_benchmark!(Operation::Permutation => "permutation",
+    "that one column is a permutation of another", ["halo2"]);
+register_benchmark!(Operation::PoseidonSponge => "poseidon_sponge",
+    "streaming a message into a Poseidon sponge", ["halo2"]);
+register_benchmark!(Operation::RangeCheck => "range_check",
+    "that values fit in a number of bits", ["halo2"]);
+register_benchmark!(Operation::Sha256 => "sha256",
+    "SHA-256 hashing", ["halo2", "risc0"]);
+
+impl Operation {
+    #[doc(hidden)]
+    pub const fn new(name: &'static str, description: &'static str, systems: &'static [&'static str]) -> Self {
+        Operation { name, description, systems }
+    }
+
+    /// Every operation registered in the binary, sorted by name.
+    pub fn all() -> Vec<Operation> {
+        let mut operations: Vec<Operation> = inventory::iter::<Operation>.into_iter().copied().collect();
+        operations.sort();
+        operations
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.name
+    }
+
+    /// One line on what is proven, for `benchmark-runner --list-operations`.
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    /// The systems with a template for this operation, as they name
+    /// themselves in the `system` field.
+    pub fn systems(&self) -> &'static [&'static str] {
+        self.systems
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An operation name that is not one of `Operation::all`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOperationError(String);
+
+impl fmt::Display for ParseOperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Operation::all().iter().map(Operation::as_str).collect();
+        write!(f, "unknown operation {:?}, expected one of {}", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for ParseOperationError {}
+
+impl FromStr for Operation {
+    type Err = ParseOperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Operation::all()
+            .into_iter()
+            .find(|operation| operation.as_str() == s)
+            .ok_or_else(|| ParseOperationError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    // Rust sources under `dir`, skipping build output
+    fn rust_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                if path.file_name().is_some_and(|name| name != "target") {
+                    rust_sources(&path, sources);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                sources.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_operation_names() {
+        assert_eq!("ecdsa".parse(), Ok(Operation::EcdsaVerify));
+        assert_eq!("merkle_inclusion".parse(), Ok(Operation::MerkleInclusion));
+        for operation in Operation::all() {
+            assert_eq!(operation.to_string().parse(), Ok(operation));
+        }
+
+        let err = "operation_name".parse::<Operation>().unwrap_err();
+        assert!(err.to_string().starts_with("unknown operation \"operation_name\", expected one of batch_verify"));
+    }
+
+    // Registered here, away from the others, as a new template would
+    register_benchmark!(DUMMY => "dummy_op", "a newly registered operation", ["dummy-system"]);
+
+    #[test]
+    fn test_registered_operation_is_discoverable() {
+        let listed: Vec<(&str, &str)> =
+            Operation::all().iter().map(|operation| (operation.as_str(), operation.description())).collect();
+        assert!(listed.contains(&("dummy_op", "a newly registered operation")));
+        assert!(listed.contains(&("ecdsa", Operation::EcdsaVerify.description())));
+        assert_eq!("dummy_op".parse(), Ok(DUMMY));
+        assert_eq!(DUMMY.systems(), ["dummy-system"]);
+    }
+
+    #[test]
+    fn test_all_is_sorted_and_unique() {
+        assert!(Operation::all().windows(2).all(|pair| pair[0].as_str() < pair[1].as_str()));
+    }
+
+    // Rust sources of every template and benchmark beside this crate
+    fn template_sources() -> Vec<PathBuf> {
+        let templates = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let mut sources = Vec::new();
+        for entry in fs::read_dir(templates).unwrap() {
+            let path = entry.unwrap().path();
+            let shared = ["benchmark_metrics", "benchmark_runner"].iter().any(|name| path.ends_with(name));
+            if path.is_dir() && !shared {
+                rust_sources(&path, &mut sources);
+            }
+        }
+        sources
+    }
+
+    // Every template and benchmark beside this crate names a registered
+    // operation, so none of them still emits the placeholder
+    #[test]
+    fn test_no_template_emits_placeholder_operation() {
+        let sources = template_sources();
+        assert!(sources.iter().any(|path| path.ends_with("halo2/operation_template.rs")));
+        for path in sources {
+            let source = fs::read_to_string(&path).unwrap();
+            assert!(!source.contains("\"operation_name\""), "{} still uses the placeholder operation", path.display());
+        }
+    }
+
+    // The registered systems are exactly the (system, operation) pairs the
+    // templates construct their metrics with
+    #[test]
+    fn test_registered_systems_match_templates() {
+        let mut templates = Vec::new();
+        for path in template_sources() {
+            for line in fs::read_to_string(&path).unwrap().lines() {
+                let Some((_, args)) = line.split_once("BenchmarkMetrics::new(\"") else { continue };
+                let Some((system, rest)) = args.split_once('"') else { continue };
+                let Some((_, rest)) = rest.split_once("Operation::") else { continue };
+                let variant: String = rest.chars().take_while(char::is_ascii_alphanumeric).collect();
+                templates.push((variant, system.to_string()));
+            }
+        }
+        templates.sort();
+        templates.dedup();
+
+        // Constant names as `register_benchmark!` declares them in this file
+        let mut variants = Vec::new();
+        for line in include_str!("operation.rs").lines() {
+            let Some((_, rest)) = line.split_once("register_benchmark!(Operation::") else { continue };
+            let Some((variant, rest)) = rest.split_once(" => \"") else { continue };
+            let Some((name, _)) = rest.split_once('"') else { continue };
+            variants.push((name, variant.to_string()));
+        }
+        let mut registered: Vec<(String, String)> = Operation::all()
+            .iter()
+            .filter(|operation| operation.as_str() != DUMMY.as_str())
+            .flat_map(|operation| {
+                let variant = &variants.iter().find(|(name, _)| *name == operation.as_str()).unwrap().1;
+                operation.systems().iter().map(move |system| (variant.clone(), system.to_string()))
+            })
+            .collect();
+        registered.sort();
+        assert_eq!(registered, templates);
+    }
+}
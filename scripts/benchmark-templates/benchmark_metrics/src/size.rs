@@ -0,0 +1,105 @@
+//! Circuit Sizes
+//!
+//! Templates take "small", "medium", "large" or "xlarge" as their circuit
+//! size. `CircuitSize` turns that name into the two numbers a benchmark
+//! actually scales: the halo2-style k (the circuit has 2^k rows) and a
+//! workload, the number of times the operation is repeated. An unknown name
+//! is an error rather than a silent fall back to small, so a typo does not
+//! produce results filed under the wrong size.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CircuitSize {
+    Small,
+    Medium,
+    Large,
+    XLarge,
+}
+
+impl CircuitSize {
+    pub const ALL: [CircuitSize; 4] = [CircuitSize::Small, CircuitSize::Medium, CircuitSize::Large, CircuitSize::XLarge];
+
+    /// log2 of the circuit's row count.
+    pub fn k(&self) -> u32 {
+        match self {
+            CircuitSize::Small => 11,
+            CircuitSize::Medium => 14,
+            CircuitSize::Large => 17,
+            CircuitSize::XLarge => 20,
+        }
+    }
+
+    /// How many times the operation is performed per run.
+    pub fn workload(&self) -> usize {
+        match self {
+            CircuitSize::Small => 1,
+            CircuitSize::Medium => 4,
+            CircuitSize::Large => 16,
+            CircuitSize::XLarge => 64,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitSize::Small => "small",
+            CircuitSize::Medium => "medium",
+            CircuitSize::Large => "large",
+            CircuitSize::XLarge => "xlarge",
+        }
+    }
+}
+
+impl fmt::Display for CircuitSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A circuit size name that is not one of `CircuitSize::ALL`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCircuitSizeError(String);
+
+impl fmt::Display for ParseCircuitSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown circuit size {:?}, expected small, medium, large or xlarge", self.0)
+    }
+}
+
+impl std::error::Error for ParseCircuitSizeError {}
+
+impl FromStr for CircuitSize {
+    type Err = ParseCircuitSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CircuitSize::ALL
+            .into_iter()
+            .find(|size| size.as_str() == s)
+            .ok_or_else(|| ParseCircuitSizeError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size_names() {
+        assert_eq!("small".parse(), Ok(CircuitSize::Small));
+        assert_eq!("xlarge".parse(), Ok(CircuitSize::XLarge));
+        for size in CircuitSize::ALL {
+            assert_eq!(size.to_string().parse(), Ok(size));
+        }
+
+        let err = "Large".parse::<CircuitSize>().unwrap_err();
+        assert_eq!(err.to_string(), "unknown circuit size \"Large\", expected small, medium, large or xlarge");
+        assert!("".parse::<CircuitSize>().is_err());
+    }
+
+    #[test]
+    fn test_k_and_workload_grow_with_size() {
+        assert_eq!(CircuitSize::ALL.map(|size| size.k()), [11, 14, 17, 20]);
+        assert_eq!(CircuitSize::ALL.map(|size| size.workload()), [1, 4, 16, 64]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Wall-Clock Timer
+//!
+//! `total_execution_time_ms` is the whole run: witness generation, checks
+//! and anything else between the measured phases, not just their sum. A
+//! template starts a `Timer` at the top of `main` and calls `finish` once its
+//! metrics exist. A timer started with `Timer::start_into` already knows
+//! where to write and does so when it goes out of scope, for code that has
+//! its `TimeMetrics` up front.
+
+use std::time::{Duration, Instant};
+
+use crate::TimeMetrics;
+
+#[must_use = "a timer records nothing until it is finished or dropped"]
+pub struct Timer<'a> {
+    start: Instant,
+    // Written on drop, unless `finish` wrote elsewhere first
+    target: Option<&'a mut TimeMetrics>,
+}
+
+impl Timer<'static> {
+    /// Starts timing; the elapsed time is recorded by `finish`.
+    pub fn start() -> Self {
+        Self { start: Instant::now(), target: None }
+    }
+}
+
+impl<'a> Timer<'a> {
+    /// Starts timing into `time_metrics`, written when the timer is dropped
+    /// or finished.
+    pub fn start_into(time_metrics: &'a mut TimeMetrics) -> Self {
+        Self { start: Instant::now(), target: Some(time_metrics) }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Writes the elapsed milliseconds to `time_metrics` and returns them.
+    pub fn finish(mut self, time_metrics: &mut TimeMetrics) -> u64 {
+        self.target = None;
+        let elapsed_ms = self.elapsed().as_millis() as u64;
+        time_metrics.total_execution_time_ms = elapsed_ms;
+        elapsed_ms
+    }
+}
+
+impl Drop for Timer<'_> {
+    fn drop(&mut self) {
+        let elapsed_ms = self.elapsed().as_millis() as u64;
+        if let Some(time_metrics) = self.target.take() {
+            time_metrics.total_execution_time_ms = elapsed_ms;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_finish_records_elapsed() {
+        let timer = Timer::start();
+        sleep(Duration::from_millis(5));
+
+        let mut time_metrics = TimeMetrics::default();
+        let elapsed_ms = timer.finish(&mut time_metrics);
+
+        assert!(elapsed_ms >= 5);
+        assert_eq!(time_metrics.total_execution_time_ms, elapsed_ms);
+    }
+
+    #[test]
+    fn test_drop_records_elapsed() {
+        let mut time_metrics = TimeMetrics::default();
+        {
+            let _timer = Timer::start_into(&mut time_metrics);
+            sleep(Duration::from_millis(5));
+        }
+        assert!(time_metrics.total_execution_time_ms >= 5);
+
+        // Finishing elsewhere leaves the original target alone
+        let mut other = TimeMetrics::default();
+        let timer = Timer::start_into(&mut time_metrics);
+        let elapsed_ms = timer.finish(&mut other);
+        assert!(time_metrics.total_execution_time_ms >= 5);
+        assert_eq!(other.total_execution_time_ms, elapsed_ms);
+    }
+}
@@ -0,0 +1,318 @@
+//! Metrics Validation
+//!
+//! Dashboards read template output as plain JSON, so a template that drops
+//! a field or writes one with the wrong type breaks them without any error
+//! at the source. `validate_metrics` checks a document against the fields
+//! `BenchmarkMetrics` declares and reports every problem at once, as dotted
+//! paths, rather than stopping at the first like deserialization does.
+//! "operation" must name an `Operation`, so the "operation_name"
+//! placeholder of an unfilled template is caught. Fields in the `extra` maps
+//! are not checked.
+//!
+//! `lint_metrics` looks for values that are well-formed but unlikely to be
+//! real: a zero proof size after a proof was timed, a security level that is
+//! not one of the usual targets, or a feature claimed without the metrics
+//! that would show it in use. `emit` prints these as warnings.
+
+use serde_json::Value;
+
+use crate::BenchmarkMetrics;
+
+// Security levels a scheme is normally parameterized for
+const SECURITY_LEVELS: [u32; 4] = [80, 100, 128, 256];
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Bool,
+    Number,
+    Object,
+    Operation, // a name from the `Operation` registry
+    String,
+    StringArray,
+    Timestamp, // an RFC 3339 string
+    Unsigned,
+}
+
+impl Kind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Kind::Bool => value.is_boolean(),
+            Kind::Number => value.is_number(),
+            Kind::Object => value.is_object(),
+            Kind::Operation => value.as_str().is_some_and(|s| s.parse::<crate::Operation>().is_ok()),
+            Kind::String => value.is_string(),
+            Kind::StringArray => value.as_array().is_some_and(|items| items.iter().all(Value::is_string)),
+            Kind::Timestamp => value.as_str().is_some_and(|s| chrono::DateTime::parse_from_rfc3339(s).is_ok()),
+            Kind::Unsigned => value.is_u64(),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Kind::Bool => "a boolean",
+            Kind::Number => "a number",
+            Kind::Object => "an object",
+            Kind::Operation => "a registered operation",
+            Kind::String => "a string",
+            Kind::StringArray => "an array of strings",
+            Kind::Timestamp => "an RFC 3339 timestamp",
+            Kind::Unsigned => "an unsigned integer",
+        }
+    }
+}
+
+const TOP_LEVEL: &[(&str, Kind)] = &[
+    ("circuit_size", Kind::String),
+    ("operation", Kind::Operation),
+    ("resolved_parameters", Kind::Object),
+    ("system", Kind::String),
+    ("timestamp", Kind::Timestamp),
+];
+
+const SECTIONS: &[(&str, &[(&str, Kind)])] = &[
+    ("features", &[
+        ("custom_gates", Kind::Bool),
+        ("parallel_proving", Kind::Bool),
+        ("parallel_verification", Kind::Bool),
+        ("recursive_proofs", Kind::Bool),
+        ("universal_circuits", Kind::Bool),
+    ]),
+    ("performance_metrics", &[
+        ("batch_proving_supported", Kind::Bool),
+        ("batch_verification_supported", Kind::Bool),
+        ("latency_ms", Kind::Unsigned),
+        ("throughput_proofs_per_second", Kind::Number),
+    ]),
+    ("resource_metrics", &[
+        ("cpu_utilization_percent", Kind::Unsigned),
+        ("gpu_utilization_percent", Kind::Unsigned),
+        ("peak_memory_usage_kb", Kind::Unsigned),
+        ("proof_size_bytes", Kind::Unsigned),
+    ]),
+    ("scalability_metrics", &[
+        ("constraints_count", Kind::Unsigned),
+        ("degree", Kind::Unsigned),
+        ("field_bits", Kind::Unsigned),
+        ("proving_complexity_class", Kind::String),
+        ("variables_count", Kind::Unsigned),
+        ("verification_complexity_class", Kind::String),
+    ]),
+    ("security_metrics", &[
+        ("assumptions", Kind::StringArray),
+        ("post_quantum_resistant", Kind::Bool),
+        ("security_level_bits", Kind::Unsigned),
+    ]),
+    ("setup_metrics", &[
+        ("setup_reusable", Kind::Bool),
+        ("setup_size_bytes", Kind::Unsigned),
+        ("setup_type", Kind::String),
+    ]),
+    ("system_requirements", &[
+        ("disk_space_gb", Kind::Unsigned),
+        ("gpu_required", Kind::Bool),
+        ("minimum_memory_gb", Kind::Unsigned),
+        ("recommended_cpu_cores", Kind::Unsigned),
+    ]),
+    ("time_metrics", &[
+        ("proving_time_ms", Kind::Unsigned),
+        ("setup_time_ms", Kind::Unsigned),
+        ("total_execution_time_ms", Kind::Unsigned),
+        ("verification_time_ms", Kind::Unsigned),
+    ]),
+];
+
+// Checks each of `fields` in `object`, whose path is `prefix`
+fn check_fields(object: &serde_json::Map<String, Value>, prefix: &str, fields: &[(&str, Kind)], errors: &mut Vec<String>) {
+    for &(name, kind) in fields {
+        let path = if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+        match object.get(name) {
+            None => errors.push(format!("{}: missing", path)),
+            Some(value) if !kind.matches(value) => {
+                errors.push(format!("{}: expected {}, got {}", path, kind.describe(), value))
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Checks that `value` has every section and field of `BenchmarkMetrics`
+/// with the right type. On failure, returns one message per missing or
+/// mis-typed field, each starting with its dotted path.
+pub fn validate_metrics(value: &Value) -> Result<(), Vec<String>> {
+    let Some(root) = value.as_object() else {
+        return Err(vec![format!("metrics must be a JSON object, got {}", value)]);
+    };
+
+    let mut errors = Vec::new();
+    check_fields(root, "", TOP_LEVEL, &mut errors);
+    for &(section, fields) in SECTIONS {
+        match root.get(section) {
+            None => errors.push(format!("{}: missing", section)),
+            Some(Value::Object(object)) => check_fields(object, section, fields, &mut errors),
+            Some(other) => errors.push(format!("{}: expected an object, got {}", section, other)),
+        }
+    }
+    if let Some(statistics) = root.get("time_statistics").filter(|stats| !stats.is_object()) {
+        errors.push(format!("time_statistics: expected an object, got {}", statistics));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Flags suspicious but valid values in `metrics`, mostly placeholders a
+/// template left unfilled. Each message starts with the dotted path of the
+/// field it is about.
+pub fn lint_metrics(metrics: &BenchmarkMetrics) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if metrics.time_metrics.proving_time_ms > 0 && metrics.resource_metrics.proof_size_bytes == 0 {
+        warnings.push("resource_metrics.proof_size_bytes: 0 although a proof was timed".to_string());
+    }
+    let bits = metrics.security_metrics.security_level_bits;
+    if !SECURITY_LEVELS.contains(&bits) {
+        warnings.push(format!(
+            "security_metrics.security_level_bits: {} is not one of {:?}",
+            bits, SECURITY_LEVELS,
+        ));
+    }
+    if metrics.system_requirements.gpu_required && metrics.resource_metrics.gpu_utilization_percent == 0 {
+        warnings.push("system_requirements.gpu_required: true but no GPU utilization was recorded".to_string());
+    }
+    if metrics.performance_metrics.batch_proving_supported && !metrics.extra.contains_key("batch_metrics") {
+        warnings.push("performance_metrics.batch_proving_supported: true but no batch_metrics were recorded".to_string());
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn valid() -> Value {
+        serde_json::to_value(BenchmarkMetrics::new("halo2", "ecdsa", "small")).unwrap()
+    }
+
+    #[test]
+    fn test_emitted_metrics_are_valid() {
+        assert_eq!(validate_metrics(&valid()), Ok(()));
+
+        let mut with_extras = valid();
+        with_extras["time_metrics"]["witness_time_ms"] = json!(3);
+        with_extras["network_metrics"] = json!({ "connected_peers": 8 });
+        with_extras["time_statistics"] = json!({});
+        assert_eq!(validate_metrics(&with_extras), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_fields_reported_by_path() {
+        let mut metrics = valid();
+        metrics.as_object_mut().unwrap().remove("system");
+        metrics.as_object_mut().unwrap().remove("features");
+        metrics["time_metrics"].as_object_mut().unwrap().remove("proving_time_ms");
+
+        assert_eq!(
+            validate_metrics(&metrics),
+            Err(vec![
+                "system: missing".to_string(),
+                "features: missing".to_string(),
+                "time_metrics.proving_time_ms: missing".to_string(),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_mistyped_fields_reported_by_path() {
+        let mut metrics = valid();
+        metrics["resource_metrics"]["proof_size_bytes"] = json!("1024");
+        metrics["time_metrics"]["setup_time_ms"] = json!(-1);
+        metrics["security_metrics"]["assumptions"] = json!(["discrete_log", 7]);
+        metrics["setup_metrics"] = json!([]);
+        metrics["timestamp"] = json!("yesterday");
+
+        assert_eq!(
+            validate_metrics(&metrics),
+            Err(vec![
+                "timestamp: expected an RFC 3339 timestamp, got \"yesterday\"".to_string(),
+                "resource_metrics.proof_size_bytes: expected an unsigned integer, got \"1024\"".to_string(),
+                "security_metrics.assumptions: expected an array of strings, got [\"discrete_log\",7]".to_string(),
+                "setup_metrics: expected an object, got []".to_string(),
+                "time_metrics.setup_time_ms: expected an unsigned integer, got -1".to_string(),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_placeholder_operation_rejected() {
+        let mut metrics = valid();
+        metrics["operation"] = json!("operation_name");
+
+        assert_eq!(
+            validate_metrics(&metrics),
+            Err(vec!["operation: expected a registered operation, got \"operation_name\"".to_string()]),
+        );
+    }
+
+    #[test]
+    fn test_non_object_document_rejected() {
+        let errors = validate_metrics(&json!([1, 2])).unwrap_err();
+        assert_eq!(errors, vec!["metrics must be a JSON object, got [1,2]".to_string()]);
+
+        let errors = validate_metrics(&json!({})).unwrap_err();
+        assert_eq!(errors.len(), TOP_LEVEL.len() + SECTIONS.len());
+    }
+
+    // Metrics a template that filled everything in would emit
+    fn filled() -> BenchmarkMetrics {
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        metrics.time_metrics.proving_time_ms = 1200;
+        metrics.resource_metrics.proof_size_bytes = 4096;
+        metrics.security_metrics.security_level_bits = 128;
+        metrics
+    }
+
+    #[test]
+    fn test_lint_passes_filled_metrics() {
+        assert_eq!(lint_metrics(&filled()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_lint_flags_zero_proof_size_after_proving() {
+        let mut metrics = filled();
+        metrics.resource_metrics.proof_size_bytes = 0;
+        assert_eq!(
+            lint_metrics(&metrics),
+            vec!["resource_metrics.proof_size_bytes: 0 although a proof was timed".to_string()],
+        );
+
+        // Nothing was proved, so there is no proof to have a size
+        metrics.time_metrics.proving_time_ms = 0;
+        assert!(lint_metrics(&metrics).is_empty());
+    }
+
+    #[test]
+    fn test_lint_flags_placeholders_and_unexercised_features() {
+        let mut metrics = filled();
+        metrics.security_metrics.security_level_bits = 0;
+        metrics.system_requirements.gpu_required = true;
+        metrics.performance_metrics.batch_proving_supported = true;
+
+        assert_eq!(
+            lint_metrics(&metrics),
+            vec![
+                "security_metrics.security_level_bits: 0 is not one of [80, 100, 128, 256]".to_string(),
+                "system_requirements.gpu_required: true but no GPU utilization was recorded".to_string(),
+                "performance_metrics.batch_proving_supported: true but no batch_metrics were recorded".to_string(),
+            ],
+        );
+
+        metrics.security_metrics.security_level_bits = 100;
+        metrics.resource_metrics.gpu_utilization_percent = 40;
+        metrics.extra.insert("batch_metrics".to_string(), json!({ "batch_size": 4 }));
+        assert!(lint_metrics(&metrics).is_empty());
+    }
+}
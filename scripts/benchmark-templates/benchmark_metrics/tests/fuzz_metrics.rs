@@ -0,0 +1,117 @@
+// Result files can come from untrusted CI artifacts, so reading one must
+// never panic, however it is damaged. These property tests feed the
+// deserializer, `validate_metrics` and `from_csv` arbitrary bytes and
+// mutations of real template output from `tests/corpus`, and only check
+// that every input is answered with a value or an error.
+
+use std::fs;
+use std::path::Path;
+
+use benchmark_metrics::{lint_metrics, validate_metrics, BenchmarkMetrics, ToCsv};
+
+const ITERATIONS: usize = 2000;
+
+// xorshift64*, so a failing input can be regenerated from the seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn corpus() -> Vec<Vec<u8>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut seeds: Vec<Vec<u8>> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    seeds.push(BenchmarkMetrics::new("halo2", "ecdsa", "small").to_json_compact().into_bytes());
+    seeds
+}
+
+// One random edit: flip, insert or delete a byte, splice in JSON
+// punctuation, or truncate
+fn mutate(rng: &mut Rng, input: &mut Vec<u8>) {
+    const TOKENS: [&[u8]; 8] = [b"{", b"}", b"[", b"]", b"\"", b",", b"-1", b"1e999"];
+    let at = rng.below(input.len() + 1);
+    match rng.below(5) {
+        0 if at < input.len() => input[at] ^= 1 << rng.below(8),
+        1 => input.insert(at, rng.next() as u8),
+        2 if at < input.len() => {
+            input.remove(at);
+        }
+        3 => {
+            let token = TOKENS[rng.below(TOKENS.len())];
+            input.splice(at..at, token.iter().copied());
+        }
+        _ => input.truncate(at),
+    }
+}
+
+// Everything a reader of a result file might do with it
+fn read_all_ways(input: &[u8]) {
+    let text = String::from_utf8_lossy(input);
+    if let Ok(metrics) = serde_json::from_str::<BenchmarkMetrics>(&text) {
+        let _ = lint_metrics(&metrics);
+        let _ = [metrics].to_csv();
+    }
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) {
+        let _ = validate_metrics(&value);
+    }
+    let _ = BenchmarkMetrics::from_csv(&text);
+}
+
+#[test]
+fn test_corpus_is_valid() {
+    for seed in corpus() {
+        let value: serde_json::Value = serde_json::from_slice(&seed).unwrap();
+        assert_eq!(validate_metrics(&value), Ok(()));
+        serde_json::from_value::<BenchmarkMetrics>(value).unwrap();
+    }
+}
+
+#[test]
+fn test_arbitrary_bytes_never_panic() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..ITERATIONS {
+        let len = rng.below(256);
+        let input: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+        read_all_ways(&input);
+    }
+}
+
+#[test]
+fn test_mutated_corpus_never_panics() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+    let seeds = corpus();
+    for _ in 0..ITERATIONS {
+        let mut input = seeds[rng.below(seeds.len())].clone();
+        for _ in 0..1 + rng.below(8) {
+            mutate(&mut rng, &mut input);
+        }
+        read_all_ways(&input);
+    }
+}
+
+#[test]
+fn test_mutated_csv_never_panics() {
+    let mut rng = Rng(0x94d0_49bb_1331_11eb);
+    let runs: Vec<BenchmarkMetrics> =
+        corpus().iter().map(|seed| serde_json::from_slice(seed).unwrap()).collect();
+    let csv = runs.to_csv().into_bytes();
+    for _ in 0..ITERATIONS {
+        let mut input = csv.clone();
+        for _ in 0..1 + rng.below(8) {
+            mutate(&mut rng, &mut input);
+        }
+        read_all_ways(&input);
+    }
+}
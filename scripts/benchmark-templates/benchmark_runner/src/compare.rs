@@ -0,0 +1,427 @@
+//! Report Comparison
+//!
+//! `benchmark-runner compare` lines up a baseline report against a newer one
+//! by operation, system and circuit size and shows how each headline metric
+//! moved. All of
+//! them are lower-is-better, so an increase beyond the threshold percentage
+//! is flagged as a regression. When both results carry a
+//! `setup_metrics.vk_fingerprint` and the two differ, the circuit itself
+//! changed between the runs, which the comparison notes: the numbers then
+//! describe different circuits, and old proofs no longer verify.
+//!
+//! A metric that shrank by more than the threshold is an improvement.
+//! `BaselinePolicy` decides whether improvements are printed alongside
+//! regressions and whether a regression fails the comparison.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use benchmark_metrics::BenchmarkMetrics;
+use serde::Serialize;
+
+use crate::{render_table, ReportEntry};
+
+// Reads one compared metric from a result
+type Read = fn(&BenchmarkMetrics) -> u64;
+
+// Compared metrics, by name
+const METRICS: [(&str, Read); 4] = [
+    ("proving_time_ms", |m| m.time_metrics.proving_time_ms),
+    ("verification_time_ms", |m| m.time_metrics.verification_time_ms),
+    ("proof_size_bytes", |m| m.resource_metrics.proof_size_bytes),
+    ("peak_memory_usage_kb", |m| m.resource_metrics.peak_memory_usage_kb),
+];
+
+/// Which of the two reports an operation, system and size appear in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Presence {
+    Both,
+    OnlyBaseline,
+    OnlyCurrent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricDelta {
+    pub metric: String,
+    pub baseline: u64,
+    pub current: u64,
+    pub delta: i64,
+    pub percent_change: Option<f64>, // None when the baseline is 0
+    pub regression: bool,
+    pub improvement: bool,
+}
+
+/// How one operation on one system at one circuit size changed between the
+/// reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub operation: String,
+    pub system: String,
+    pub circuit_size: String,
+    pub presence: Presence,
+    pub deltas: Vec<MetricDelta>, // empty unless present in both
+    pub vk_fingerprint_changed: bool,
+}
+
+impl Comparison {
+    pub fn has_regression(&self) -> bool {
+        self.deltas.iter().any(|delta| delta.regression)
+    }
+}
+
+/// What `compare` prints and what makes it fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BaselinePolicy {
+    /// Print every metric, improvements included, and never fail.
+    ReportAll,
+    /// Print only the regressions, and fail if there are any.
+    RegressionsOnly,
+    /// Print every metric, and fail on a regression. The default: printing
+    /// as `ReportAll` does, failing as `RegressionsOnly` does.
+    #[default]
+    FailOnRegression,
+}
+
+impl BaselinePolicy {
+    /// The comparisons worth printing. Under `RegressionsOnly` that is the
+    /// regressed metrics, plus operations missing from one report or whose
+    /// verifying key changed, since those make the deltas meaningless.
+    pub fn select(&self, mut comparisons: Vec<Comparison>) -> Vec<Comparison> {
+        if *self == BaselinePolicy::RegressionsOnly {
+            for comparison in &mut comparisons {
+                comparison.deltas.retain(|delta| delta.regression);
+            }
+            comparisons.retain(|comparison| {
+                comparison.presence != Presence::Both || comparison.vk_fingerprint_changed || comparison.has_regression()
+            });
+        }
+        comparisons
+    }
+
+    pub fn fails(&self, comparisons: &[Comparison]) -> bool {
+        *self != BaselinePolicy::ReportAll && comparisons.iter().any(Comparison::has_regression)
+    }
+}
+
+/// A policy name other than `report-all`, `regressions-only` or
+/// `fail-on-regression`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBaselinePolicyError(String);
+
+impl fmt::Display for ParseBaselinePolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown baseline policy {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseBaselinePolicyError {}
+
+impl FromStr for BaselinePolicy {
+    type Err = ParseBaselinePolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "report-all" => Ok(BaselinePolicy::ReportAll),
+            "regressions-only" => Ok(BaselinePolicy::RegressionsOnly),
+            "fail-on-regression" => Ok(BaselinePolicy::FailOnRegression),
+            _ => Err(ParseBaselinePolicyError(s.to_string())),
+        }
+    }
+}
+
+/// Reads a `report.json` written by the runner.
+pub fn load_report(path: &Path) -> io::Result<Vec<ReportEntry>> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Compares the successful entries of two reports, sorted by operation,
+/// system and circuit size. A metric regresses when it grew by more than
+/// `threshold_percent` and improves when it shrank by more than that; a
+/// metric whose baseline is 0 (usually "not measured") does neither.
+pub fn compare_reports(baseline: &[ReportEntry], current: &[ReportEntry], threshold_percent: f64) -> Vec<Comparison> {
+    let baseline: Vec<&BenchmarkMetrics> = baseline.iter().filter_map(ReportEntry::metrics).collect();
+    let current: Vec<&BenchmarkMetrics> = current.iter().filter_map(ReportEntry::metrics).collect();
+
+    let mut keys: Vec<(String, String, String)> = baseline
+        .iter()
+        .chain(&current)
+        .map(|m| (m.operation.clone(), m.system.clone(), m.circuit_size.clone()))
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .map(|(operation, system, circuit_size)| {
+            let matches = |m: &&BenchmarkMetrics| {
+                m.operation == operation && m.system == system && m.circuit_size == circuit_size
+            };
+            let old = baseline.iter().copied().find(matches);
+            let new = current.iter().copied().find(matches);
+            let (presence, deltas, vk_fingerprint_changed) = match (old, new) {
+                (Some(old), Some(new)) => {
+                    let changed = match (vk_fingerprint(old), vk_fingerprint(new)) {
+                        (Some(old), Some(new)) => old != new,
+                        _ => false,
+                    };
+                    (Presence::Both, metric_deltas(old, new, threshold_percent), changed)
+                }
+                (Some(_), None) => (Presence::OnlyBaseline, Vec::new(), false),
+                _ => (Presence::OnlyCurrent, Vec::new(), false),
+            };
+            Comparison { operation, system, circuit_size, presence, deltas, vk_fingerprint_changed }
+        })
+        .collect()
+}
+
+// The verifying key fingerprint a halo2 template records, if any
+fn vk_fingerprint(metrics: &BenchmarkMetrics) -> Option<&str> {
+    metrics.setup_metrics.extra.get("vk_fingerprint").and_then(|value| value.as_str())
+}
+
+fn metric_deltas(old: &BenchmarkMetrics, new: &BenchmarkMetrics, threshold_percent: f64) -> Vec<MetricDelta> {
+    METRICS
+        .iter()
+        .map(|(metric, read)| {
+            let (baseline, current) = (read(old), read(new));
+            let delta = current as i64 - baseline as i64;
+            let percent_change = (baseline != 0).then(|| delta as f64 / baseline as f64 * 100.0);
+            MetricDelta {
+                metric: metric.to_string(),
+                baseline,
+                current,
+                delta,
+                percent_change,
+                regression: percent_change.is_some_and(|percent| percent > threshold_percent),
+                improvement: percent_change.is_some_and(|percent| percent < -threshold_percent),
+            }
+        })
+        .collect()
+}
+
+/// One row per compared metric, with regressions and improvements marked.
+pub fn delta_table(comparisons: &[Comparison]) -> String {
+    const HEADER: [&str; 9] =
+        ["operation", "system", "size", "metric", "baseline", "current", "delta", "change", ""];
+
+    let mut rows = Vec::new();
+    for comparison in comparisons {
+        let names = vec![comparison.operation.clone(), comparison.system.clone(), comparison.circuit_size.clone()];
+        match comparison.presence {
+            Presence::OnlyBaseline => rows.push([names, vec!["missing from current report".to_string()]].concat()),
+            Presence::OnlyCurrent => rows.push([names, vec!["missing from baseline report".to_string()]].concat()),
+            Presence::Both => {
+                if comparison.vk_fingerprint_changed {
+                    rows.push([names.clone(), vec!["verifying key changed".to_string()]].concat());
+                }
+                for delta in &comparison.deltas {
+                    let change = match delta.percent_change {
+                        Some(percent) => format!("{:+.1}%", percent),
+                        None => "n/a".to_string(),
+                    };
+                    rows.push(
+                        [
+                            names.clone(),
+                            vec![
+                                delta.metric.clone(),
+                                delta.baseline.to_string(),
+                                delta.current.to_string(),
+                                format!("{:+}", delta.delta),
+                                change,
+                                match (delta.regression, delta.improvement) {
+                                    (true, _) => "REGRESSION",
+                                    (_, true) => "IMPROVEMENT",
+                                    _ => "",
+                                }
+                                .to_string(),
+                            ],
+                        ]
+                        .concat(),
+                    );
+                }
+            }
+        }
+    }
+    render_table(&HEADER, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(operation: &str, system: &str, proving_time_ms: u64, proof_size_bytes: u64) -> ReportEntry {
+        sized_entry(operation, system, "small", proving_time_ms, proof_size_bytes)
+    }
+
+    fn sized_entry(operation: &str, system: &str, size: &str, proving_time_ms: u64, proof_size: u64) -> ReportEntry {
+        let mut metrics = BenchmarkMetrics::new(system, operation, size);
+        metrics.time_metrics.proving_time_ms = proving_time_ms;
+        metrics.resource_metrics.proof_size_bytes = proof_size;
+        ReportEntry::Metrics(Box::new(metrics))
+    }
+
+    fn with_vk_fingerprint(entry: ReportEntry, fingerprint: &str) -> ReportEntry {
+        let ReportEntry::Metrics(mut metrics) = entry else { unreachable!() };
+        metrics.setup_metrics.extra.insert("vk_fingerprint".to_string(), fingerprint.into());
+        ReportEntry::Metrics(metrics)
+    }
+
+    fn delta<'a>(comparison: &'a Comparison, metric: &str) -> &'a MetricDelta {
+        comparison.deltas.iter().find(|delta| delta.metric == metric).unwrap()
+    }
+
+    #[test]
+    fn test_improvement_is_not_flagged() {
+        let baseline = [entry("ecdsa", "halo2", 2000, 4096)];
+        let comparisons = compare_reports(&baseline, &[entry("ecdsa", "halo2", 1500, 4096)], 5.0);
+
+        assert_eq!(comparisons.len(), 1);
+        let proving = delta(&comparisons[0], "proving_time_ms");
+        assert_eq!(proving.delta, -500);
+        assert_eq!(proving.percent_change, Some(-25.0));
+        assert!(proving.improvement);
+        assert!(!comparisons[0].has_regression());
+        // Unmeasured in both runs: no percentage, no regression
+        assert_eq!(delta(&comparisons[0], "peak_memory_usage_kb").percent_change, None);
+    }
+
+    #[test]
+    fn test_regression_above_threshold() {
+        let baseline = [entry("ecdsa", "halo2", 1000, 4096)];
+
+        let slower = compare_reports(&baseline, &[entry("ecdsa", "halo2", 1100, 4096)], 5.0);
+        assert!(delta(&slower[0], "proving_time_ms").regression);
+        assert!(!delta(&slower[0], "proof_size_bytes").regression);
+        assert!(delta_table(&slower).lines().any(|line| line.contains("+10.0%") && line.ends_with("REGRESSION")));
+
+        // The same slowdown within a looser threshold
+        let tolerated = compare_reports(&baseline, &[entry("ecdsa", "halo2", 1100, 4096)], 15.0);
+        assert!(!tolerated[0].has_regression());
+    }
+
+    #[test]
+    fn test_missing_operation() {
+        let baseline = [entry("ecdsa", "halo2", 1000, 4096), entry("sha256", "halo2", 300, 2048)];
+        let current = [
+            entry("ecdsa", "halo2", 1000, 4096),
+            entry("ecdsa", "plonky3", 800, 90000),
+            ReportEntry::Error { template: "miden".to_string(), error: "exit status: 1".to_string() },
+        ];
+
+        let comparisons = compare_reports(&baseline, &current, 5.0);
+        let presence: Vec<(&str, &str, Presence)> = comparisons
+            .iter()
+            .map(|c| (c.operation.as_str(), c.system.as_str(), c.presence))
+            .collect();
+
+        assert_eq!(
+            presence,
+            vec![
+                ("ecdsa", "halo2", Presence::Both),
+                ("ecdsa", "plonky3", Presence::OnlyCurrent),
+                ("sha256", "halo2", Presence::OnlyBaseline),
+            ]
+        );
+        assert!(comparisons[2].deltas.is_empty());
+
+        let json = serde_json::to_value(&comparisons).unwrap();
+        assert_eq!(json[2]["presence"], "only_baseline");
+        assert!(delta_table(&comparisons).contains("missing from current report"));
+    }
+
+    // Each size is compared against the same size, whatever order the
+    // reports list them in
+    #[test]
+    fn test_sizes_compared_separately() {
+        let small = |proving_time_ms| sized_entry("ecdsa", "halo2", "small", proving_time_ms, 4096);
+        let large = |proving_time_ms| sized_entry("ecdsa", "halo2", "large", proving_time_ms, 4096);
+        let baseline = [small(1000), large(8000)];
+        let current = [large(8000), small(1500)];
+
+        let comparisons = compare_reports(&baseline, &current, 5.0);
+
+        let sizes: Vec<(&str, Presence)> =
+            comparisons.iter().map(|c| (c.circuit_size.as_str(), c.presence)).collect();
+        assert_eq!(sizes, vec![("large", Presence::Both), ("small", Presence::Both)]);
+        assert_eq!(delta(&comparisons[0], "proving_time_ms").delta, 0);
+        assert!(!comparisons[0].has_regression());
+        assert_eq!(delta(&comparisons[1], "proving_time_ms").delta, 500);
+        assert!(comparisons[1].has_regression());
+        assert!(delta_table(&comparisons).lines().any(|line| line.contains("small") && line.ends_with("REGRESSION")));
+        assert_eq!(serde_json::to_value(&comparisons).unwrap()[1]["circuit_size"], "small");
+    }
+
+    #[test]
+    fn test_changed_vk_fingerprint_is_noted() {
+        let baseline = [with_vk_fingerprint(entry("ecdsa", "halo2", 1000, 4096), "aa11")];
+
+        let same = compare_reports(&baseline, &[with_vk_fingerprint(entry("ecdsa", "halo2", 1000, 4096), "aa11")], 5.0);
+        assert!(!same[0].vk_fingerprint_changed);
+        assert!(!delta_table(&same).contains("verifying key changed"));
+
+        let changed = compare_reports(&baseline, &[with_vk_fingerprint(entry("ecdsa", "halo2", 1000, 4096), "bb22")], 5.0);
+        assert!(changed[0].vk_fingerprint_changed);
+        assert!(delta_table(&changed).contains("verifying key changed"));
+        assert_eq!(serde_json::to_value(&changed).unwrap()[0]["vk_fingerprint_changed"], true);
+        // A changed circuit is worth knowing about, not a regression
+        assert!(!changed[0].has_regression());
+
+        // Without a fingerprint on both sides there is nothing to compare
+        let unknown = compare_reports(&baseline, &[entry("ecdsa", "halo2", 1000, 4096)], 5.0);
+        assert!(!unknown[0].vk_fingerprint_changed);
+    }
+
+    #[test]
+    fn test_improvement_under_each_policy() {
+        let baseline = [entry("ecdsa", "halo2", 2000, 4096), entry("sha256", "halo2", 300, 2048)];
+        let current = [entry("ecdsa", "halo2", 1500, 4096), entry("sha256", "halo2", 300, 2048)];
+        let comparisons = compare_reports(&baseline, &current, 5.0);
+        let printed = |policy: BaselinePolicy| delta_table(&policy.select(comparisons.clone()));
+
+        for policy in [BaselinePolicy::ReportAll, BaselinePolicy::FailOnRegression] {
+            let table = printed(policy);
+            assert!(table.lines().any(|line| line.contains("-25.0%") && line.ends_with("IMPROVEMENT")));
+            assert!(table.contains("sha256"));
+            assert!(!policy.fails(&comparisons));
+        }
+
+        // Nothing regressed, so there is nothing to print and nothing to fail
+        let policy = BaselinePolicy::RegressionsOnly;
+        assert!(policy.select(comparisons.clone()).is_empty());
+        assert_eq!(printed(policy).lines().count(), 1);
+        assert!(!policy.fails(&comparisons));
+    }
+
+    #[test]
+    fn test_regression_under_each_policy() {
+        let baseline = [entry("ecdsa", "halo2", 2000, 4096), entry("sha256", "halo2", 300, 2048)];
+        let current = [entry("ecdsa", "halo2", 1500, 4096), entry("sha256", "halo2", 600, 2048)];
+        let comparisons = compare_reports(&baseline, &current, 5.0);
+
+        assert!(!BaselinePolicy::ReportAll.fails(&comparisons));
+        assert!(BaselinePolicy::FailOnRegression.fails(&comparisons));
+        assert!(BaselinePolicy::RegressionsOnly.fails(&comparisons));
+
+        // Only the regressed metric of the regressed operation is printed
+        let selected = BaselinePolicy::RegressionsOnly.select(comparisons);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].operation, "sha256");
+        assert_eq!(selected[0].deltas.len(), 1);
+        assert_eq!(selected[0].deltas[0].metric, "proving_time_ms");
+    }
+
+    #[test]
+    fn test_parse_baseline_policy() {
+        assert_eq!("report-all".parse(), Ok(BaselinePolicy::ReportAll));
+        assert_eq!("regressions-only".parse(), Ok(BaselinePolicy::RegressionsOnly));
+        assert_eq!("fail-on-regression".parse(), Ok(BaselinePolicy::FailOnRegression));
+        assert_eq!(BaselinePolicy::default(), BaselinePolicy::FailOnRegression);
+
+        let err = "quiet".parse::<BaselinePolicy>().unwrap_err();
+        assert_eq!(err.to_string(), "unknown baseline policy \"quiet\"");
+    }
+}
@@ -0,0 +1,249 @@
+//! Benchmark Runner
+//!
+//! Each system's template is a standalone executable that prints one
+//! `BenchmarkMetrics` document. The runner finds the compiled templates in a
+//! directory, runs each with the same circuit size, and merges their output
+//! into a single report. A template that fails or prints something that is
+//! not metrics JSON becomes an error entry; the other templates still run.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use benchmark_metrics::{validate_metrics, BenchmarkMetrics, Operation};
+use serde::{Deserialize, Serialize};
+
+mod compare;
+
+pub use compare::{
+    compare_reports, delta_table, load_report, BaselinePolicy, Comparison, MetricDelta, ParseBaselinePolicyError,
+    Presence,
+};
+
+/// One element of `report.json`: either a template's metrics, unchanged, or
+/// the reason the template produced none.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ReportEntry {
+    // Listed first so metrics, which have no `error` field, never match it
+    Error { template: String, error: String },
+    Metrics(Box<BenchmarkMetrics>),
+}
+
+impl ReportEntry {
+    pub fn metrics(&self) -> Option<&BenchmarkMetrics> {
+        match self {
+            ReportEntry::Metrics(metrics) => Some(metrics),
+            ReportEntry::Error { .. } => None,
+        }
+    }
+}
+
+/// The template executables in `dir`, sorted by file name. Hidden files,
+/// non-executables and the runner's own binary are skipped, so `dir` can be
+/// a cargo `target/release` directory.
+pub fn discover_templates(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let runner = std::env::current_exe().ok().and_then(|exe| exe.canonicalize().ok());
+
+    let mut templates = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden || !is_executable(&path) {
+            continue;
+        }
+        if runner.is_some() && path.canonicalize().ok() == runner {
+            continue;
+        }
+        templates.push(path);
+    }
+    templates.sort();
+    Ok(templates)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+}
+
+/// Runs `template` with `circuit_size` and parses the metrics it prints.
+/// With `validate`, output that `validate_metrics` rejects is an error too,
+/// even where the missing fields would not stop it deserializing.
+pub fn run_template(template: &Path, circuit_size: &str, validate: bool) -> ReportEntry {
+    let name = template
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| template.display().to_string());
+    let error = |error: String| ReportEntry::Error { template: name.clone(), error };
+
+    let output = match Command::new(template).args([circuit_size, "--format", "json"]).output() {
+        Ok(output) => output,
+        Err(e) => return error(format!("failed to start: {}", e)),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last_line = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("");
+        return error(format!("{}: {}", output.status, last_line.trim()));
+    }
+
+    match parse_metrics(&output.stdout, validate) {
+        Ok(metrics) => ReportEntry::Metrics(Box::new(metrics)),
+        Err(e) => error(e),
+    }
+}
+
+// A template's stdout as metrics, checked first with `validate_metrics`
+// when `validate` is set
+fn parse_metrics(stdout: &[u8], validate: bool) -> Result<BenchmarkMetrics, String> {
+    let value: serde_json::Value =
+        serde_json::from_slice(stdout).map_err(|e| format!("malformed metrics JSON: {}", e))?;
+    if validate {
+        validate_metrics(&value).map_err(|errors| format!("non-conforming metrics: {}", errors.join("; ")))?;
+    }
+    serde_json::from_value(value).map_err(|e| format!("malformed metrics JSON: {}", e))
+}
+
+/// Runs every template in order, one at a time so their timings don't
+/// compete for the machine.
+pub fn run_all(templates: &[PathBuf], circuit_size: &str, validate: bool) -> Vec<ReportEntry> {
+    templates
+        .iter()
+        .map(|template| {
+            eprintln!("running {} ({})", template.display(), circuit_size);
+            run_template(template, circuit_size, validate)
+        })
+        .collect()
+}
+
+pub fn write_report(path: &Path, entries: &[ReportEntry]) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(entries).expect("report serializes to JSON");
+    fs::write(path, format!("{}\n", json))
+}
+
+/// A plain-text table with one row per entry, for comparing systems at a
+/// glance.
+pub fn comparison_table(entries: &[ReportEntry]) -> String {
+    const HEADER: [&str; 7] =
+        ["system", "operation", "circuit_size", "proving_ms", "verification_ms", "proof_bytes", "peak_memory_kb"];
+
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| match entry {
+            ReportEntry::Metrics(m) => vec![
+                m.system.clone(),
+                m.operation.clone(),
+                m.circuit_size.clone(),
+                m.time_metrics.proving_time_ms.to_string(),
+                m.time_metrics.verification_time_ms.to_string(),
+                m.resource_metrics.proof_size_bytes.to_string(),
+                m.resource_metrics.peak_memory_usage_kb.to_string(),
+            ],
+            ReportEntry::Error { template, error } => vec![template.clone(), format!("error: {}", error)],
+        })
+        .collect();
+
+    render_table(&HEADER, &rows)
+}
+
+/// Every system that has a template for some operation, sorted.
+pub fn known_systems() -> Vec<&'static str> {
+    let mut systems: Vec<&str> = Operation::all().iter().flat_map(|operation| operation.systems()).copied().collect();
+    systems.sort();
+    systems.dedup();
+    systems
+}
+
+/// Operations against systems, each cell `Implemented` or `Unsupported`,
+/// from the systems registered with each `Operation`.
+pub fn support_table() -> String {
+    let systems = known_systems();
+    let header: Vec<&str> = ["operation"].into_iter().chain(systems.iter().copied()).collect();
+
+    let rows: Vec<Vec<String>> = Operation::all()
+        .iter()
+        .map(|operation| {
+            let cells = systems.iter().map(|system| {
+                if operation.systems().contains(system) { "Implemented" } else { "Unsupported" }.to_string()
+            });
+            std::iter::once(operation.as_str().to_string()).chain(cells).collect()
+        })
+        .collect();
+
+    render_table(&header, &rows)
+}
+
+// Left-aligned columns two spaces apart. A row shorter than the header, such
+// as an error row, runs its last cell on and doesn't widen the columns.
+pub(crate) fn render_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|title| title.len()).collect();
+    for row in rows.iter().filter(|row| row.len() == header.len()) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_row = |cells: &[String]| {
+        let line: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| match widths.get(i) {
+                Some(&width) if i + 1 < cells.len() => format!("{:width$}", cell, width = width),
+                _ => cell.clone(),
+            })
+            .collect();
+        format!("{}\n", line.join("  ").trim_end())
+    };
+
+    let header: Vec<String> = header.iter().map(|title| title.to_string()).collect();
+    let mut table = format_row(&header);
+    for row in rows {
+        table.push_str(&format_row(row));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_entry_round_trip() {
+        let entries = vec![
+            ReportEntry::Metrics(Box::new(BenchmarkMetrics::new("halo2", "ecdsa", "small"))),
+            ReportEntry::Error { template: "miden".to_string(), error: "exit status: 1".to_string() },
+        ];
+
+        let json = serde_json::to_string(&entries).unwrap();
+        let parsed: Vec<ReportEntry> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, entries);
+        assert_eq!(parsed[0].metrics().unwrap().system, "halo2");
+        assert!(parsed[1].metrics().is_none());
+    }
+
+    #[test]
+    fn test_comparison_table_columns() {
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        metrics.time_metrics.proving_time_ms = 1500;
+        let entries = vec![
+            ReportEntry::Metrics(Box::new(metrics)),
+            ReportEntry::Error { template: "miden".to_string(), error: "exit status: 1".to_string() },
+        ];
+
+        let table = comparison_table(&entries);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("system  operation  circuit_size  proving_ms"));
+        assert_eq!(lines[1].find("1500"), lines[0].find("proving_ms"));
+        assert_eq!(lines[2], "miden   error: exit status: 1");
+    }
+}
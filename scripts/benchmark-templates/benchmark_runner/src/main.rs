@@ -0,0 +1,267 @@
+//! benchmark-runner <templates-dir> [circuit_size] [--out-dir <dir>] [--validate]
+//!
+//! Runs every template executable in <templates-dir> with circuit_size
+//! (default "small"), writes the combined `report.json` to --out-dir
+//! (default the current directory) and prints a comparison table. With
+//! --validate, output that does not pass `validate_metrics` counts as a
+//! failure. Exits with 1 if any template failed, after the report has been
+//! written.
+//!
+//! benchmark-runner compare <baseline.json> <current.json> [--threshold <percent>] [--format table|json]
+//!                          [--compare-baseline-policy report-all|regressions-only|fail-on-regression]
+//!
+//! Prints how each operation and system changed between two reports.
+//! Exits with 1 if any metric grew by more than --threshold percent
+//! (default 5). report-all prints improvements too and never fails,
+//! regressions-only prints only the regressions, and fail-on-regression,
+//! the default, prints everything and fails on a regression.
+//!
+//! benchmark-runner csv <report.json> [--csv-columns <a,b,c>]
+//!
+//! Prints the successful results of a report as CSV, every column or only
+//! the dotted paths given to --csv-columns, in that order. A path no result
+//! has is an error.
+//!
+//! benchmark-runner validate <metrics.json>...
+//!
+//! Checks each file with `validate_metrics` and prints a JSON object
+//! mapping every path to its problems, empty for a valid file. A file that
+//! cannot be read or parsed is a problem, not an error, so one bad file
+//! does not stop the rest from being checked. Exits with 1 if any file is
+//! invalid.
+//!
+//! benchmark-runner source-commit [dir]
+//!
+//! Prints the git commit of the working tree containing dir (default the
+//! current directory) and whether its tracked files are modified, as JSON,
+//! or null outside a git tree. The benchmark suite's shell script records
+//! this as each result's `source_commit`.
+//!
+//! benchmark-runner --list-operations
+//!
+//! Prints the operations a report can name, from `benchmark_metrics::Operation`.
+//!
+//! benchmark-runner --list-supported
+//!
+//! Prints which systems have a template for which operation.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use benchmark_metrics::{source_commit, validate_metrics, BenchmarkMetrics, Operation, ToCsv};
+use benchmark_runner::{
+    compare_reports, comparison_table, delta_table, discover_templates, load_report, run_all, support_table,
+    write_report, BaselinePolicy, ReportEntry,
+};
+
+const USAGE: &str = "usage: benchmark-runner <templates-dir> [circuit_size] [--out-dir <dir>] [--validate]
+       benchmark-runner compare <baseline.json> <current.json> [--threshold <percent>] [--format table|json]
+                                [--compare-baseline-policy report-all|regressions-only|fail-on-regression]
+       benchmark-runner csv <report.json> [--csv-columns <a,b,c>]
+       benchmark-runner validate <metrics.json>...
+       benchmark-runner source-commit [dir]
+       benchmark-runner --list-operations
+       benchmark-runner --list-supported";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    if args.iter().any(|arg| arg == "--list-operations") {
+        for operation in Operation::all() {
+            println!("{:<18} {}", operation.as_str(), operation.description());
+        }
+        return ExitCode::SUCCESS;
+    }
+    if args.iter().any(|arg| arg == "--list-supported") {
+        print!("{}", support_table());
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "compare" => {
+            parse(rest, &["--threshold", "--format", "--compare-baseline-policy"], &[]).and_then(compare)
+        }
+        Some((command, rest)) if command == "csv" => parse(rest, &["--csv-columns"], &[]).and_then(csv),
+        Some((command, rest)) if command == "validate" => parse(rest, &[], &[]).and_then(validate),
+        Some((command, rest)) if command == "source-commit" => parse(rest, &[], &[]).and_then(print_source_commit),
+        _ => parse(&args, &["--out-dir"], &["--validate"]).and_then(run),
+    };
+    result.unwrap_or_else(|message| {
+        eprintln!("error: {}\n{}", message, USAGE);
+        ExitCode::from(2)
+    })
+}
+
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<(String, String)>,
+    switches: Vec<String>,
+}
+
+impl Args {
+    fn flag(&self, name: &str) -> Option<&str> {
+        self.flags.iter().find(|(flag, _)| flag == name).map(|(_, value)| value.as_str())
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|switch| switch == name)
+    }
+}
+
+// Splits `args` into positionals, `--flag value` pairs from `known` and
+// value-less flags from `switches`
+fn parse(args: &[String], known: &[&str], switches: &[&str]) -> Result<Args, String> {
+    let mut parsed = Args { positional: Vec::new(), flags: Vec::new(), switches: Vec::new() };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            parsed.positional.push(arg.clone());
+        } else if switches.contains(&arg.as_str()) {
+            parsed.switches.push(arg.clone());
+        } else if known.contains(&arg.as_str()) {
+            let value = iter.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            parsed.flags.push((arg.clone(), value.clone()));
+        } else {
+            return Err(format!("unknown flag {}", arg));
+        }
+    }
+    Ok(parsed)
+}
+
+fn run(args: Args) -> Result<ExitCode, String> {
+    let (templates_dir, circuit_size) = match args.positional.as_slice() {
+        [dir] => (PathBuf::from(dir), "small"),
+        [dir, size] => (PathBuf::from(dir), size.as_str()),
+        _ => return Err("expected a templates directory and an optional circuit size".to_string()),
+    };
+    let out_dir = PathBuf::from(args.flag("--out-dir").unwrap_or("."));
+
+    let templates = match discover_templates(&templates_dir) {
+        Ok(templates) if !templates.is_empty() => templates,
+        Ok(_) => {
+            eprintln!("error: no template executables in {}", templates_dir.display());
+            return Ok(ExitCode::FAILURE);
+        }
+        Err(e) => {
+            eprintln!("error: cannot read {}: {}", templates_dir.display(), e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    let entries = run_all(&templates, circuit_size, args.switch("--validate"));
+
+    let report = out_dir.join("report.json");
+    if let Err(e) = std::fs::create_dir_all(&out_dir).and_then(|_| write_report(&report, &entries)) {
+        eprintln!("error: cannot write {}: {}", report.display(), e);
+        return Ok(ExitCode::FAILURE);
+    }
+    print!("{}", comparison_table(&entries));
+    eprintln!("report written to {}", report.display());
+
+    let failed = entries.iter().filter(|entry| matches!(entry, ReportEntry::Error { .. })).count();
+    if failed > 0 {
+        eprintln!("{} of {} templates failed", failed, entries.len());
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn compare(args: Args) -> Result<ExitCode, String> {
+    let [baseline, current] = args.positional.as_slice() else {
+        return Err("compare expects a baseline and a current report".to_string());
+    };
+    let threshold = match args.flag("--threshold") {
+        Some(value) => value.parse::<f64>().map_err(|_| format!("invalid --threshold {}", value))?,
+        None => 5.0,
+    };
+    let format = args.flag("--format").unwrap_or("table");
+    if format != "table" && format != "json" {
+        return Err(format!("unknown --format {}", format));
+    }
+    let policy = match args.flag("--compare-baseline-policy") {
+        Some(value) => value.parse::<BaselinePolicy>().map_err(|e| e.to_string())?,
+        None => BaselinePolicy::default(),
+    };
+
+    let load = |path: &str| {
+        load_report(Path::new(path)).map_err(|e| {
+            eprintln!("error: cannot read {}: {}", path, e);
+        })
+    };
+    let (Ok(baseline), Ok(current)) = (load(baseline), load(current)) else {
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let comparisons = compare_reports(&baseline, &current, threshold);
+    let printed = policy.select(comparisons.clone());
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&printed).expect("comparison serializes to JSON"));
+    } else {
+        print!("{}", delta_table(&printed));
+    }
+
+    if policy.fails(&comparisons) {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn csv(args: Args) -> Result<ExitCode, String> {
+    let [report] = args.positional.as_slice() else {
+        return Err("csv expects a report".to_string());
+    };
+    let entries = match load_report(Path::new(report)) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("error: cannot read {}: {}", report, e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    let runs: Vec<BenchmarkMetrics> = entries.iter().filter_map(ReportEntry::metrics).cloned().collect();
+    match args.flag("--csv-columns") {
+        Some(columns) => {
+            let columns: Vec<&str> = columns.split(',').map(str::trim).collect();
+            print!("{}", runs.to_csv_columns(&columns).map_err(|e| e.to_string())?);
+        }
+        None => print!("{}", runs.to_csv()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: Args) -> Result<ExitCode, String> {
+    if args.positional.is_empty() {
+        return Err("validate expects one or more metrics files".to_string());
+    }
+
+    let mut problems = serde_json::Map::new();
+    for path in &args.positional {
+        let value = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()));
+        let errors = match value {
+            Ok(value) => validate_metrics(&value).err().unwrap_or_default(),
+            Err(e) => vec![format!("unreadable: {}", e)],
+        };
+        problems.insert(path.clone(), errors.into());
+    }
+    println!("{}", serde_json::to_string_pretty(&problems).expect("problems serialize to JSON"));
+
+    if problems.values().any(|errors| errors.as_array().is_some_and(|errors| !errors.is_empty())) {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_source_commit(args: Args) -> Result<ExitCode, String> {
+    let dir = match args.positional.as_slice() {
+        [] => Path::new("."),
+        [dir] => Path::new(dir),
+        _ => return Err("source-commit expects at most one directory".to_string()),
+    };
+    println!("{}", serde_json::to_string(&source_commit(dir)).expect("source commit serializes to JSON"));
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,135 @@
+// End-to-end matrix: every benchmark built, run at `small` the way the
+// runner runs it, and its output validated. The other systems' directories
+// only hold operation templates to copy, so the halo2 benchmarks are the
+// whole matrix. Slow benchmarks are ignored; run them with
+// `cargo test --test matrix -- --ignored`. The benchmarks build against the
+// halo2 checkout the Docker image puts in /zk-repos, so without it the
+// matrix is skipped, saying so, rather than failed.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use benchmark_metrics::{lint_metrics, Operation};
+use benchmark_runner::{run_template, ReportEntry};
+use serde_json::json;
+
+// Builds the benchmark crate `name` under halo2/ and returns its binary
+fn build_benchmark(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().join("halo2").join(name);
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .args(["build", "--release", "--quiet", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .status()
+        .unwrap();
+    assert!(status.success(), "{} failed to build", name);
+    dir.join("target").join("release").join(name)
+}
+
+// The halo2 path every benchmark's Cargo.toml depends on
+const HALO2_PROOFS: &str = "/zk-repos/halo2/halo2_proofs";
+
+// Runs the benchmark `name` at small through the runner with validation and
+// checks it reported `operation` and, if it proves anything, a verified proof
+fn assert_runs(name: &str, operation: Operation, proves: bool) {
+    if !Path::new(HALO2_PROOFS).exists() {
+        eprintln!("skipping {}: no halo2 checkout at {}", name, HALO2_PROOFS);
+        return;
+    }
+    let metrics = match run_template(&build_benchmark(name), "small", true) {
+        ReportEntry::Metrics(metrics) => metrics,
+        ReportEntry::Error { error, .. } => panic!("runner rejected {}: {}", name, error),
+    };
+    assert_eq!(metrics.system, "halo2");
+    assert_eq!(metrics.operation, operation.as_str());
+    assert_eq!(metrics.circuit_size, "small");
+    assert_eq!(lint_metrics(&metrics), Vec::<String>::new(), "{} emitted placeholder values", name);
+    if proves {
+        assert_eq!(metrics.extra.get("verification_passed"), Some(&json!(true)), "{} did not verify", name);
+        assert!(metrics.resource_metrics.proof_size_bytes > 0);
+    }
+}
+
+#[test]
+fn test_matrix_batched_range() {
+    assert_runs("batched_range_benchmark", Operation::BatchedRange, true);
+}
+
+#[test]
+fn test_matrix_field_arithmetic() {
+    assert_runs("field_arithmetic_benchmark", Operation::FieldArithmetic, true);
+}
+
+#[test]
+fn test_matrix_instance_cost() {
+    assert_runs("instance_cost_benchmark", Operation::InstanceCost, true);
+}
+
+// The one benchmark that runs natively, outside any circuit
+#[test]
+fn test_matrix_msm() {
+    assert_runs("msm_benchmark", Operation::Msm, false);
+}
+
+#[test]
+fn test_matrix_mux() {
+    assert_runs("mux_benchmark", Operation::Mux, true);
+}
+
+#[test]
+fn test_matrix_permutation() {
+    assert_runs("permutation_benchmark", Operation::Permutation, true);
+}
+
+#[test]
+fn test_matrix_range_check() {
+    assert_runs("range_check_benchmark", Operation::RangeCheck, true);
+}
+
+#[test]
+#[ignore = "proves a batch at small; slow"]
+fn test_matrix_batch_verify() {
+    assert_runs("batch_verify_benchmark", Operation::BatchVerify, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_conditional_tree() {
+    assert_runs("conditional_tree_benchmark", Operation::ConditionalTree, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_ecdsa() {
+    assert_runs("ecdsa_benchmark", Operation::EcdsaVerify, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_eddsa() {
+    assert_runs("eddsa_benchmark", Operation::EddsaVerify, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_hash_to_curve() {
+    assert_runs("hash_to_curve_benchmark", Operation::HashToCurve, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_merkle() {
+    assert_runs("merkle_benchmark", Operation::MerkleInclusion, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_poseidon_sponge() {
+    assert_runs("poseidon_sponge_benchmark", Operation::PoseidonSponge, true);
+}
+
+#[test]
+#[ignore = "slow"]
+fn test_matrix_sha256() {
+    assert_runs("sha256_benchmark", Operation::Sha256, true);
+}
@@ -0,0 +1,291 @@
+// The stub templates are shell scripts, so these tests need a Unix shell
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use benchmark_metrics::BenchmarkMetrics;
+use benchmark_runner::{run_template, ReportEntry};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("benchmark_runner_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_stub(dir: &Path, name: &str, script: &str) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+// A stub that prints metrics for `system`, echoing back the circuit size it
+// was given the way a real template does
+fn write_metrics_stub(dir: &Path, name: &str, system: &str, proving_time_ms: u64) -> PathBuf {
+    let mut metrics = BenchmarkMetrics::new(system, "ecdsa", "CIRCUIT_SIZE");
+    metrics.time_metrics.proving_time_ms = proving_time_ms;
+    let json = dir.join(format!("{}.json", name));
+    fs::write(&json, metrics.to_json_pretty()).unwrap();
+    write_stub(dir, name, &format!("sed \"s/CIRCUIT_SIZE/$1/\" '{}'", json.display()))
+}
+
+#[test]
+fn test_runner_records_failed_template() {
+    let dir = scratch_dir("run");
+    let templates = dir.join("templates");
+    fs::create_dir_all(&templates).unwrap();
+    write_metrics_stub(&templates, "halo2", "halo2", 1500);
+    write_stub(&templates, "miden", "echo 'prover crashed' >&2\nexit 3");
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_benchmark-runner"))
+        .arg(&templates)
+        .arg("medium")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+
+    // The failure is reported in the exit code but didn't stop the run
+    assert_eq!(output.status.code(), Some(1));
+    let report = benchmark_runner::load_report(&out_dir.join("report.json")).unwrap();
+    assert_eq!(report.len(), 2);
+
+    let metrics = report[0].metrics().expect("halo2 stub produced metrics");
+    assert_eq!(metrics.system, "halo2");
+    assert_eq!(metrics.circuit_size, "medium");
+    assert_eq!(metrics.time_metrics.proving_time_ms, 1500);
+
+    match &report[1] {
+        ReportEntry::Error { template, error } => {
+            assert_eq!(template, "miden");
+            assert!(error.contains("prover crashed"), "unexpected error: {}", error);
+        }
+        other => panic!("expected an error entry, got {:?}", other),
+    }
+
+    let table = String::from_utf8(output.stdout).unwrap();
+    assert!(table.lines().any(|line| line.starts_with("halo2") && line.contains("1500")));
+    assert!(table.lines().any(|line| line.starts_with("miden") && line.contains("error:")));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_malformed_json_becomes_error_entry() {
+    let dir = scratch_dir("malformed");
+    let stub = write_stub(&dir, "plonky3", "echo 'proving... done'");
+
+    match run_template(&stub, "small", false) {
+        ReportEntry::Error { template, error } => {
+            assert_eq!(template, "plonky3");
+            assert!(error.starts_with("malformed metrics JSON"), "unexpected error: {}", error);
+        }
+        other => panic!("expected an error entry, got {:?}", other),
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_fails_nonconforming_template() {
+    let dir = scratch_dir("validate");
+    let templates = dir.join("templates");
+    fs::create_dir_all(&templates).unwrap();
+    // Deserializes fine, but the timestamp is not RFC 3339
+    let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+    metrics.timestamp = "yesterday".to_string();
+    let json = dir.join("halo2.json");
+    fs::write(&json, metrics.to_json_pretty()).unwrap();
+    let stub = write_stub(&templates, "halo2", &format!("cat '{}'", json.display()));
+
+    assert!(run_template(&stub, "small", false).metrics().is_some());
+    match run_template(&stub, "small", true) {
+        ReportEntry::Error { error, .. } => {
+            assert!(error.starts_with("non-conforming metrics: timestamp:"), "unexpected error: {}", error);
+        }
+        other => panic!("expected an error entry, got {:?}", other),
+    }
+
+    let run = |validate: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_benchmark-runner"));
+        command.arg(&templates).arg("--out-dir").arg(dir.join("out"));
+        if validate {
+            command.arg("--validate");
+        }
+        command.output().unwrap().status.code()
+    };
+    assert_eq!(run(false), Some(0));
+    assert_eq!(run(true), Some(1));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compare_flags_regression() {
+    let dir = scratch_dir("compare");
+    let report = |name: &str, proving_time_ms: u64| {
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        metrics.time_metrics.proving_time_ms = proving_time_ms;
+        let path = dir.join(name);
+        benchmark_runner::write_report(&path, &[ReportEntry::Metrics(Box::new(metrics))]).unwrap();
+        path
+    };
+    let baseline = report("baseline.json", 1000);
+    let current = report("current.json", 1200);
+
+    let compare = |threshold: &str| {
+        Command::new(env!("CARGO_BIN_EXE_benchmark-runner"))
+            .arg("compare")
+            .arg(&baseline)
+            .arg(&current)
+            .args(["--threshold", threshold, "--format", "json"])
+            .output()
+            .unwrap()
+    };
+
+    let flagged = compare("10");
+    assert_eq!(flagged.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&flagged.stdout).unwrap();
+    assert_eq!(json[0]["deltas"][0]["metric"], "proving_time_ms");
+    assert_eq!(json[0]["deltas"][0]["percent_change"], 20.0);
+    assert_eq!(json[0]["deltas"][0]["regression"], true);
+
+    assert_eq!(compare("25").status.code(), Some(0));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compare_baseline_policy() {
+    let dir = scratch_dir("policy");
+    let report = |name: &str, proving_time_ms: u64| {
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        metrics.time_metrics.proving_time_ms = proving_time_ms;
+        let path = dir.join(name);
+        benchmark_runner::write_report(&path, &[ReportEntry::Metrics(Box::new(metrics))]).unwrap();
+        path
+    };
+    let baseline = report("baseline.json", 1000);
+    let faster = report("faster.json", 800);
+    let slower = report("slower.json", 1200);
+
+    let compare = |current: &Path, policy: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_benchmark-runner"));
+        command.arg("compare").arg(&baseline).arg(current);
+        if let Some(policy) = policy {
+            command.args(["--compare-baseline-policy", policy]);
+        }
+        let output = command.output().unwrap();
+        (output.status.code(), String::from_utf8(output.stdout).unwrap())
+    };
+
+    // An improvement is printed unless only regressions are wanted, and
+    // never fails
+    for policy in [None, Some("report-all"), Some("fail-on-regression")] {
+        let (code, table) = compare(&faster, policy);
+        assert_eq!(code, Some(0));
+        assert!(table.lines().any(|line| line.contains("-20.0%") && line.ends_with("IMPROVEMENT")), "{:?}", policy);
+    }
+    let (code, table) = compare(&faster, Some("regressions-only"));
+    assert_eq!(code, Some(0));
+    assert_eq!(table.lines().count(), 1);
+
+    assert_eq!(compare(&slower, None).0, Some(1));
+    assert_eq!(compare(&slower, Some("regressions-only")).0, Some(1));
+    assert_eq!(compare(&slower, Some("report-all")).0, Some(0));
+
+    let (code, _) = compare(&slower, Some("quiet"));
+    assert_eq!(code, Some(2));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_csv_columns_select_and_order() {
+    let dir = scratch_dir("csv");
+    let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+    metrics.time_metrics.proving_time_ms = 1500;
+    let report = dir.join("report.json");
+    benchmark_runner::write_report(&report, &[ReportEntry::Metrics(Box::new(metrics))]).unwrap();
+
+    let csv = |columns: &str| {
+        Command::new(env!("CARGO_BIN_EXE_benchmark-runner"))
+            .arg("csv")
+            .arg(&report)
+            .args(["--csv-columns", columns])
+            .output()
+            .unwrap()
+    };
+
+    let output = csv("time_metrics.proving_time_ms,system,operation");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "time_metrics.proving_time_ms,system,operation\n1500,halo2,ecdsa\n");
+
+    let unknown = csv("system,no_such.column");
+    assert_eq!(unknown.status.code(), Some(2));
+    assert!(String::from_utf8(unknown.stderr).unwrap().starts_with("error: unknown CSV column \"no_such.column\""));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_reports_every_file() {
+    let dir = scratch_dir("validate");
+    let valid = dir.join("valid.json");
+    fs::write(&valid, BenchmarkMetrics::new("halo2", "ecdsa", "small").to_json_pretty()).unwrap();
+    let placeholder = dir.join("placeholder.json");
+    fs::write(&placeholder, BenchmarkMetrics::new("halo2", "operation_name", "small").to_json_pretty()).unwrap();
+    let truncated = dir.join("truncated.json");
+    fs::write(&truncated, "{\"system\": ").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_benchmark-runner"))
+        .arg("validate")
+        .args([&valid, &placeholder, &truncated])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let problems: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let problems_of = |path: &Path| problems[path.to_str().unwrap()].as_array().unwrap().clone();
+    assert!(problems_of(&valid).is_empty());
+    assert_eq!(problems_of(&placeholder).len(), 1);
+    assert!(problems_of(&placeholder)[0].as_str().unwrap().starts_with("operation:"));
+    assert!(problems_of(&truncated)[0].as_str().unwrap().starts_with("unreadable:"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_list_operations_prints_registry() {
+    let output = Command::new(env!("CARGO_BIN_EXE_benchmark-runner")).arg("--list-operations").output().unwrap();
+    assert!(output.status.success());
+
+    let listing = String::from_utf8(output.stdout).unwrap();
+    let names: Vec<&str> = listing.lines().filter_map(|line| line.split_whitespace().next()).collect();
+    let registered: Vec<&str> = benchmark_metrics::Operation::all().iter().map(|operation| operation.as_str()).collect();
+    assert_eq!(names, registered);
+}
+
+#[test]
+fn test_list_supported_marks_unsupported_pairs() {
+    let output = Command::new(env!("CARGO_BIN_EXE_benchmark-runner")).arg("--list-supported").output().unwrap();
+    assert!(output.status.success());
+
+    let table = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<Vec<&str>> = table.lines().map(|line| line.split_whitespace().collect()).collect();
+    let cell = |operation: &str, system: &str| {
+        let column = rows[0].iter().position(|title| *title == system).unwrap();
+        rows.iter().find(|row| row[0] == operation).unwrap()[column]
+    };
+
+    assert_eq!(cell("sha256", "risc0"), "Implemented");
+    assert_eq!(cell("fibonacci", "halo2"), "Unsupported");
+    assert_eq!(cell("poseidon_sponge", "halo2"), "Implemented");
+    assert_eq!(cell("poseidon_sponge", "risc0"), "Unsupported");
+    assert_eq!(rows.len(), benchmark_metrics::Operation::all().len() + 1);
+}
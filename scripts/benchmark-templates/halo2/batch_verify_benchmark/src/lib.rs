@@ -0,0 +1,558 @@
+//! Batch Proof Verification with a Shared Verifying Key
+//!
+//! Rollups verify many proofs of the same circuit at once. With IPA every
+//! proof ends in an MSM check, and those checks can be folded into a single
+//! MSM with random coefficients instead of being run one by one. This
+//! benchmark proves N instances of a small circuit (y = x²) under one
+//! verifying key and compares:
+//!
+//! - Single: each proof verified on its own (SingleStrategy)
+//! - Batch:  all proofs accumulated, then one final check (AccumulatorStrategy)
+//!
+//! The number of proofs N is chosen by the circuit_size argument:
+//!
+//! - small:  4
+//! - medium: 16
+//! - large:  64
+//! - xlarge: 256
+//!
+//! proving_time_ms covers all N proofs and verification_time_ms the batch
+//! check; both strategies' per-proof times go in `extra.batch_metrics`.
+//!
+//! With `--verification-breakdown` a single verification is also split into
+//! its transcript phase (reading commitments, deriving the Fiat-Shamir
+//! challenges and checking the opened evaluations) and the final MSM check,
+//! recorded in `time_metrics.extra.verification_breakdown`.
+//!
+//! `--transcript-hash blake2b|keccak256` also proves and verifies one
+//! instance with the chosen Fiat-Shamir hash and records its proof size and
+//! verification time in `extra.transcript_hash`. The proof holds the same
+//! points and scalars either way, so the size only changes if the encoding
+//! does; the verifier's hashing cost does change.
+
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Error, ErrorFront, Instance, ProvingKey, Selector, VerifyingKey,
+    },
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::{AccumulatorStrategy, SingleStrategy},
+        },
+        Rotation, VerificationStrategy,
+    },
+    transcript::{
+        Challenge255, Keccak256Read, Keccak256Write, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+
+use benchmark_metrics::{
+    collect_environment, Aggregator, BenchmarkMetrics, CircuitSize, Features, Operation,
+    PerformanceMetrics, ScalabilityMetrics, SecurityMetrics, SetupMetrics, Throughput,
+};
+use ff::{Field, PrimeField};
+use halo2_common::{collect_circuit_stats, deserialize_proof};
+use rand::rngs::OsRng;
+use pasta_curves::{vesta, Fp};
+use serde_json::json;
+
+// Large enough for the one-row circuit plus blinding rows
+const K: u32 = 4;
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct SquareConfig {
+    q_square: Selector,
+    x: Column<Advice>,
+    y: Column<Advice>,
+    public: Column<Instance>,
+}
+
+// Proves knowledge of x such that x² equals the public input
+#[derive(Default, Clone)]
+struct SquareCircuit {
+    x: Value<Fp>,
+}
+
+impl Circuit<Fp> for SquareCircuit {
+    type Config = SquareConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let q_square = meta.selector();
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let public = meta.instance_column();
+
+        meta.enable_equality(y);
+        meta.enable_equality(public);
+
+        meta.create_gate("square", |meta| {
+            let q_square = meta.query_selector(q_square);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+
+            vec![q_square * (x.clone() * x - y)]
+        });
+
+        SquareConfig { q_square, x, y, public }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), ErrorFront> {
+        let y = layouter.assign_region(
+            || "square",
+            |mut region| {
+                config.q_square.enable(&mut region, 0)?;
+                region.assign_advice(|| "x", config.x, 0, || self.x)?;
+                region.assign_advice(|| "y", config.y, 0, || self.x.map(|x| x.square()))
+            },
+        )?;
+
+        layouter.constrain_instance(y.cell(), config.public, 0)
+    }
+}
+
+// A proof together with the public input it was made for
+struct ProofInstance {
+    proof: Vec<u8>,
+    public: Fp,
+}
+
+fn batch_size(size: CircuitSize) -> usize {
+    match size {
+        CircuitSize::Small => 4,
+        CircuitSize::Medium => 16,
+        CircuitSize::Large => 64,
+        CircuitSize::XLarge => 256,
+    }
+}
+
+fn setup() -> Result<(ParamsIPA<vesta::Affine>, ProvingKey<vesta::Affine>), Error> {
+    let params = ParamsIPA::<vesta::Affine>::new(K);
+    let circuit = SquareCircuit::default();
+
+    let vk = keygen_vk(&params, &circuit)?;
+    let pk = keygen_pk(&params, vk, &circuit)?;
+
+    Ok((params, pk))
+}
+
+/// Fiat-Shamir hash of the proof transcript, as named by `--transcript-hash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptHash {
+    Blake2b,
+    Keccak256,
+}
+
+impl TranscriptHash {
+    fn as_str(self) -> &'static str {
+        match self {
+            TranscriptHash::Blake2b => "blake2b",
+            TranscriptHash::Keccak256 => "keccak256",
+        }
+    }
+}
+
+impl FromStr for TranscriptHash {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "blake2b" => Ok(TranscriptHash::Blake2b),
+            "keccak256" => Ok(TranscriptHash::Keccak256),
+            _ => Err(format!("unknown transcript hash {:?} (expected blake2b or keccak256)", name)),
+        }
+    }
+}
+
+fn prove(
+    params: &ParamsIPA<vesta::Affine>,
+    pk: &ProvingKey<vesta::Affine>,
+    x: Fp,
+) -> Result<ProofInstance, Error> {
+    let public = x.square();
+    let circuit = SquareCircuit { x: Value::known(x) };
+    let proof = halo2_common::prove(params, pk, &[circuit], &[&[&[public]]])?;
+
+    Ok(ProofInstance { proof, public })
+}
+
+fn prove_with<W>(
+    params: &ParamsIPA<vesta::Affine>,
+    pk: &ProvingKey<vesta::Affine>,
+    x: Fp,
+) -> Result<ProofInstance, Error>
+where
+    W: TranscriptWriterBuffer<Vec<u8>, vesta::Affine, Challenge255<vesta::Affine>>,
+{
+    let public = x.square();
+    let circuit = SquareCircuit { x: Value::known(x) };
+
+    let mut transcript = W::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[&[public]]],
+        OsRng,
+        &mut transcript,
+    )?;
+
+    Ok(ProofInstance {
+        proof: transcript.finalize(),
+        public,
+    })
+}
+
+fn verify_single(
+    params: &ParamsIPA<vesta::Affine>,
+    vk: &VerifyingKey<vesta::Affine>,
+    instance: &ProofInstance,
+) -> bool {
+    halo2_common::verify(params, vk, &instance.proof, &[&[&[instance.public]]])
+}
+
+fn verify_with<'a, R>(
+    params: &ParamsIPA<vesta::Affine>,
+    vk: &VerifyingKey<vesta::Affine>,
+    instance: &'a ProofInstance,
+) -> bool
+where
+    R: TranscriptReadBuffer<&'a [u8], vesta::Affine, Challenge255<vesta::Affine>>,
+{
+    let verifier_params = params.verifier_params();
+    let strategy = SingleStrategy::new(verifier_params);
+    let mut transcript = R::init(&instance.proof[..]);
+
+    verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+        verifier_params,
+        vk,
+        strategy,
+        &[&[&[instance.public]]],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+// Proves one instance with the given transcript hash and verifies it with
+// the matching reader, returning the proof and the verification time
+fn transcript_round_trip(
+    params: &ParamsIPA<vesta::Affine>,
+    pk: &ProvingKey<vesta::Affine>,
+    hash: TranscriptHash,
+    x: Fp,
+) -> Result<(ProofInstance, bool, Duration), Error> {
+    let instance = match hash {
+        TranscriptHash::Blake2b => prove(params, pk, x)?,
+        TranscriptHash::Keccak256 => prove_with::<Keccak256Write<_, _, _>>(params, pk, x)?,
+    };
+
+    let start = Instant::now();
+    let ok = match hash {
+        TranscriptHash::Blake2b => verify_single(params, pk.get_vk(), &instance),
+        TranscriptHash::Keccak256 => verify_with::<Keccak256Read<_, _, _>>(params, pk.get_vk(), &instance),
+    };
+    let elapsed = start.elapsed();
+
+    Ok((instance, ok, elapsed))
+}
+
+// Accumulates every proof's final MSM and checks them together once. A
+// proof that fails before the final check (e.g. a malformed transcript)
+// fails the whole batch.
+fn verify_batch(
+    params: &ParamsIPA<vesta::Affine>,
+    vk: &VerifyingKey<vesta::Affine>,
+    instances: &[ProofInstance],
+) -> bool {
+    let verifier_params = params.verifier_params();
+    let mut strategy = AccumulatorStrategy::new(verifier_params);
+
+    for instance in instances {
+        let mut transcript = deserialize_proof(&instance.proof);
+
+        strategy = match verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+            verifier_params,
+            vk,
+            strategy,
+            &[&[&[instance.public]]],
+            &mut transcript,
+        ) {
+            Ok(strategy) => strategy,
+            Err(_) => return false,
+        };
+    }
+
+    strategy.finalize()
+}
+
+// One verification split at the point where the IPA verifier has built its
+// final MSM. The two phases are timed back to back, so they sum to `total`.
+#[derive(Debug, Clone, Copy)]
+struct VerificationBreakdown {
+    transcript: Duration,
+    final_check: Duration,
+    total: Duration,
+}
+
+// Runs verify_proof with an AccumulatorStrategy, which stops short of the
+// final MSM, and then finalizes it. None if the proof does not verify.
+fn verification_breakdown(
+    params: &ParamsIPA<vesta::Affine>,
+    vk: &VerifyingKey<vesta::Affine>,
+    instance: &ProofInstance,
+) -> Option<VerificationBreakdown> {
+    let verifier_params = params.verifier_params();
+    let strategy = AccumulatorStrategy::new(verifier_params);
+    let mut transcript = deserialize_proof(&instance.proof);
+
+    let start = Instant::now();
+    let strategy = verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+        verifier_params,
+        vk,
+        strategy,
+        &[&[&[instance.public]]],
+        &mut transcript,
+    )
+    .ok()?;
+    let transcript_done = Instant::now();
+    let ok = strategy.finalize();
+    let end = Instant::now();
+
+    ok.then(|| VerificationBreakdown {
+        transcript: transcript_done - start,
+        final_check: end - transcript_done,
+        total: end - start,
+    })
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Benchmarks one batch at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks proving a batch of N proofs at `size` and verifying them
+/// both one by one and as a batch, `repeat` times: time_metrics holds the
+/// median run and time_statistics the distribution. A batch that fails to
+/// verify is reported on stderr; only setup and proving errors are
+/// returned as `Err`.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let n = batch_size(size);
+    let circuit_stats = collect_circuit_stats::<Fp, SquareCircuit>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        performance_metrics: PerformanceMetrics {
+            batch_verification_supported: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            // Constraints of the one circuit every proof in the batch is for
+            constraints_count: circuit_stats.constraints as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::BatchVerify.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("batch_size".to_string(), n.into());
+    metrics.resolved_parameters.insert("k".to_string(), K.into());
+
+    let mut aggregator = Aggregator::new();
+    let mut proving_times = Vec::new();
+    let mut verified = true;
+    for _ in 0..repeat {
+        let setup_start = Instant::now();
+        let (params, pk) = setup()?;
+        let setup_time = setup_start.elapsed();
+
+        let proving_start = Instant::now();
+        let instances = (0..n)
+            .map(|i| prove(&params, &pk, Fp::from(i as u64 + 2)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let proving_time = proving_start.elapsed();
+
+        let single_start = Instant::now();
+        let single_ok = instances.iter().all(|instance| verify_single(&params, pk.get_vk(), instance));
+        let single_time = single_start.elapsed();
+
+        let batch_start = Instant::now();
+        let batch_ok = verify_batch(&params, pk.get_vk(), &instances);
+        let batch_time = batch_start.elapsed();
+
+        if !(single_ok && batch_ok) {
+            eprintln!("warning: valid proofs failed to verify");
+        }
+        verified &= single_ok && batch_ok;
+
+        metrics.time_metrics.setup_time_ms = setup_time.as_millis() as u64;
+        metrics.time_metrics.proving_time_ms = proving_time.as_millis() as u64;
+        metrics.time_metrics.verification_time_ms = batch_time.as_millis() as u64;
+        metrics.resource_metrics.proof_size_bytes = instances[0].proof.len() as u64;
+        metrics.extra.insert(
+            "batch_metrics".to_string(),
+            json!({
+                "batch_size": n,
+                "single_verification_ms_per_proof": ms(single_time) / n as f64,
+                "batch_verification_ms_per_proof": ms(batch_time) / n as f64,
+                "batch_speedup": single_time.as_secs_f64() / batch_time.as_secs_f64(),
+            }),
+        );
+        aggregator.record(&metrics.time_metrics);
+        proving_times.push(proving_time / n as u32);
+    }
+    aggregator.apply(&mut metrics);
+    metrics.extra.insert("verification_passed".to_string(), verified.into());
+    if let Some(throughput) = Throughput::of(&proving_times) {
+        throughput.apply(&mut metrics);
+    }
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+/// Splits one verification into its transcript phase and final MSM check
+/// and records them in `time_metrics.extra.verification_breakdown`.
+pub fn record_verification_breakdown(metrics: &mut BenchmarkMetrics) -> Result<(), Error> {
+    let (params, pk) = setup()?;
+    let instance = prove(&params, &pk, Fp::random(OsRng))?;
+
+    match verification_breakdown(&params, pk.get_vk(), &instance) {
+        Some(breakdown) => {
+            metrics.time_metrics.extra.insert(
+                "verification_breakdown".to_string(),
+                json!({
+                    "transcript_ms": ms(breakdown.transcript),
+                    "final_check_ms": ms(breakdown.final_check),
+                    "total_ms": ms(breakdown.total),
+                }),
+            );
+        }
+        None => eprintln!("warning: valid proof failed to verify"),
+    }
+    Ok(())
+}
+
+/// Proves and verifies one instance with `hash` as the Fiat-Shamir hash
+/// and records its proof size and verification time in
+/// `extra.transcript_hash`.
+pub fn record_transcript_hash(metrics: &mut BenchmarkMetrics, hash: TranscriptHash) -> Result<(), Error> {
+    let (params, pk) = setup()?;
+    let (instance, ok, verify_time) = transcript_round_trip(&params, &pk, hash, Fp::random(OsRng))?;
+    if !ok {
+        eprintln!("warning: valid proof failed to verify");
+    }
+
+    metrics.extra.insert(
+        "transcript_hash".to_string(),
+        json!({
+            "hash": hash.as_str(),
+            "proof_size_bytes": instance.proof.len(),
+            "verification_time_ms": ms(verify_time),
+        }),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_instances(n: usize) -> (ParamsIPA<vesta::Affine>, ProvingKey<vesta::Affine>, Vec<ProofInstance>) {
+        let (params, pk) = setup().unwrap();
+        let instances = (0..n)
+            .map(|i| prove(&params, &pk, Fp::from(i as u64 + 2)).unwrap())
+            .collect();
+        (params, pk, instances)
+    }
+
+    #[test]
+    fn test_batch_verify_valid_proofs() {
+        let (params, pk, instances) = valid_instances(4);
+
+        assert!(instances.iter().all(|instance| verify_single(&params, pk.get_vk(), instance)));
+        assert!(verify_batch(&params, pk.get_vk(), &instances));
+    }
+
+    #[test]
+    fn test_batch_fails_with_one_corrupted_proof() {
+        let (params, pk, mut instances) = valid_instances(4);
+
+        let mut corrupted = prove(&params, &pk, Fp::from(7)).unwrap();
+        let middle = corrupted.proof.len() / 2;
+        corrupted.proof[middle] ^= 1;
+        instances.insert(2, corrupted);
+
+        assert!(!verify_single(&params, pk.get_vk(), &instances[2]));
+        assert!(!verify_batch(&params, pk.get_vk(), &instances));
+    }
+
+    #[test]
+    fn test_transcript_hashes_verify_with_matching_verifier() {
+        let (params, pk) = setup().unwrap();
+
+        for hash in [TranscriptHash::Blake2b, TranscriptHash::Keccak256] {
+            let (instance, ok, _) = transcript_round_trip(&params, &pk, hash, Fp::from(5)).unwrap();
+            assert!(ok, "{:?} proof failed to verify", hash);
+            assert!(!instance.proof.is_empty());
+        }
+
+        // A Keccak transcript read back with Blake2b derives other challenges
+        let keccak = prove_with::<Keccak256Write<_, _, _>>(&params, &pk, Fp::from(5)).unwrap();
+        assert!(!verify_single(&params, pk.get_vk(), &keccak));
+    }
+
+    #[test]
+    fn test_parse_transcript_hash() {
+        assert_eq!("keccak256".parse(), Ok(TranscriptHash::Keccak256));
+        assert_eq!(
+            "sha3".parse::<TranscriptHash>().unwrap_err(),
+            "unknown transcript hash \"sha3\" (expected blake2b or keccak256)"
+        );
+    }
+
+    #[test]
+    fn test_verification_breakdown_sums_to_total() {
+        let (params, pk, instances) = valid_instances(1);
+
+        let breakdown = verification_breakdown(&params, pk.get_vk(), &instances[0]).unwrap();
+        assert!(breakdown.transcript > Duration::ZERO);
+        assert!(breakdown.final_check > Duration::ZERO);
+        assert_eq!(breakdown.transcript + breakdown.final_check, breakdown.total);
+    }
+}
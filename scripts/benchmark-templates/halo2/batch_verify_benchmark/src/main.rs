@@ -0,0 +1,24 @@
+//! Command-line entry point; the benchmark itself is `batch_verify_benchmark::run`.
+
+use std::error::Error;
+
+use batch_verify_benchmark::TranscriptHash;
+use benchmark_metrics::{CountingAllocator, TemplateArgs};
+
+// Counts live allocations, so the metrics can set peak RSS against them
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = TemplateArgs::from_env();
+    let mut metrics = batch_verify_benchmark::run_repeated(args.size_or_exit(), args.repeat)?;
+    if args.has_flag("--verification-breakdown") {
+        batch_verify_benchmark::record_verification_breakdown(&mut metrics)?;
+    }
+    if args.has_flag("--transcript-hash") {
+        let hash: TranscriptHash = args.value_of("--transcript-hash").unwrap_or_default().parse()?;
+        batch_verify_benchmark::record_transcript_hash(&mut metrics, hash)?;
+    }
+    metrics.emit(&args)?;
+    Ok(())
+}
@@ -0,0 +1,366 @@
+//! Batched Range Proof Circuit using a Shared Lookup Table
+//!
+//! Proves that M private witnesses all lie in [0, 2^n) by looking every value
+//! up in a single table holding 0..2^n. The table is committed once no matter
+//! how many values are checked, so the cost of adding values is one advice
+//! cell and one lookup row each.
+//!
+//! - Lookup table size: 2^n rows (fixed by the bit width)
+//! - Rows used by the values: M
+//! - Lookup arguments: 1, adding 3 commitments and 5 evaluations to the proof
+//!
+//! This is the range-check pattern most rollup circuits spend their rows on.
+//! The batch size M is chosen by the circuit_size argument:
+//!
+//! - small:  2^8
+//! - medium: 2^12
+//! - large:  2^16
+//! - xlarge: 2^19
+//!
+//! `--k-sweep 8,10,12,14` also proves one fixed batch at each listed k,
+//! which shows how proving time grows with domain size alone, and records
+//! the results in `extra.k_sweep`.
+
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Column, Advice,
+        Selector, TableColumn, Error, ErrorFront, keygen_pk, keygen_vk},
+    poly::{Rotation, commitment::ParamsProver, ipa::commitment::ParamsIPA},
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Operation, ScalabilityMetrics, SecurityMetrics,
+    SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, prove, record_proofs};
+use pasta_curves::{pallas::Base, vesta};
+use serde_json::json;
+
+// Bit width every value is checked against
+const BITS: usize = 8;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Serialized size of a commitment and of an evaluation over Pallas
+const POINT_BYTES: usize = 32;
+const SCALAR_BYTES: usize = 32;
+
+// Every lookup argument commits the permuted input, the permuted table and
+// the grand product, and opens five evaluations of them
+const LOOKUP_COMMITMENTS: usize = 3;
+const LOOKUP_EVALUATIONS: usize = 5;
+
+// Lookup arguments registered by a circuit and the proof bytes they add
+#[derive(Debug, Clone, Copy)]
+struct LookupStats {
+    lookup_count: usize,
+    proof_bytes: usize,
+}
+
+fn lookup_stats<F: PrimeField, C: Circuit<F>>() -> LookupStats {
+    let mut cs = ConstraintSystem::<F>::default();
+    C::configure(&mut cs);
+
+    let lookup_count = cs.lookups().len();
+    LookupStats {
+        lookup_count,
+        proof_bytes: lookup_count
+            * (LOOKUP_COMMITMENTS * POINT_BYTES + LOOKUP_EVALUATIONS * SCALAR_BYTES),
+    }
+}
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct RangeConfig {
+    q_lookup: Selector,
+    value: Column<Advice>,  // values being range checked
+    table: TableColumn,     // 0..2^BITS, shared by every lookup
+}
+
+// Range check of every value against [0, 2^BITS)
+#[derive(Default, Clone)]
+struct BatchedRangeCircuit<F: PrimeField, const BITS: usize> {
+    values: Vec<Value<F>>,
+}
+
+impl<F: PrimeField, const BITS: usize> BatchedRangeCircuit<F, BITS> {
+    fn new(values: &[u64]) -> Self {
+        Self {
+            values: values.iter().map(|v| Value::known(F::from(*v))).collect(),
+        }
+    }
+
+    fn lookup_table_size() -> usize {
+        1 << BITS
+    }
+
+    fn rows_used(&self) -> usize {
+        self.values.len()
+    }
+
+    // Smallest k whose domain fits both the values and the lookup table
+    fn min_k(&self) -> u32 {
+        let rows = self.rows_used().max(Self::lookup_table_size()) + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> Circuit<F> for BatchedRangeCircuit<F, BITS> {
+    type Config = RangeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![Value::unknown(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        let table = meta.lookup_table_column();
+        let q_lookup = meta.complex_selector();
+
+        // Disabled rows look up 0, which is always in the table
+        meta.lookup("range check", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+
+            vec![(q_lookup * value, table)]
+        });
+
+        RangeConfig { q_lookup, value, table }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), ErrorFront> {
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                for i in 0..Self::lookup_table_size() {
+                    table.assign_cell(
+                        || "table value",
+                        config.table,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        layouter.assign_region(
+            || "values",
+            |mut region| {
+                for (offset, value) in self.values.iter().enumerate() {
+                    config.q_lookup.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || "value",
+                        config.value,
+                        offset,
+                        || *value,
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+// Proving time for one circuit at one domain size
+#[derive(Debug, Clone, Copy)]
+struct SweepResult {
+    k: u32,
+    domain_size: usize,
+    proving_time: Duration,
+}
+
+// Proves the same circuit at every k. Rows beyond what the circuit uses are
+// padding, so the differences between results are FFT and MSM overhead of
+// the larger domain, not extra circuit work.
+fn k_sweep<const BITS: usize>(
+    circuit: &BatchedRangeCircuit<Base, BITS>,
+    ks: &[u32],
+) -> Result<Vec<SweepResult>, Error> {
+    let mut results = Vec::with_capacity(ks.len());
+
+    for &k in ks {
+        let params = ParamsIPA::<vesta::Affine>::new(k);
+        let vk = keygen_vk(&params, circuit)?;
+        let pk = keygen_pk(&params, vk, circuit)?;
+
+        let start = Instant::now();
+        prove(&params, &pk, std::slice::from_ref(circuit), &[&[]])?;
+        let proving_time = start.elapsed();
+
+        results.push(SweepResult { k, domain_size: 1 << k, proving_time });
+    }
+
+    Ok(results)
+}
+
+/// The k values of a `--k-sweep` list such as "8,10,12,14".
+pub fn parse_k_sweep(arg: &str) -> Result<Vec<u32>, String> {
+    arg.split(',')
+        .map(|k| k.trim().parse().map_err(|_| format!("--k-sweep expects comma-separated integers, got {:?}", arg)))
+        .collect()
+}
+
+fn batch_size(size: CircuitSize) -> usize {
+    match size {
+        CircuitSize::Small => 1 << 8,
+        CircuitSize::Medium => 1 << 12,
+        CircuitSize::Large => 1 << 16,
+        CircuitSize::XLarge => 1 << 19,
+    }
+}
+
+/// Benchmarks one proof of the batch at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks range checking the batch at `size` in this process, proving
+/// it `repeat` times: time_metrics holds the median run and time_statistics
+/// the distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let batch = batch_size(size);
+    let values: Vec<u64> = (0..batch as u64).map(|v| v % (1 << BITS)).collect();
+    let circuit = BatchedRangeCircuit::<Base, BITS>::new(&values);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Base, BatchedRangeCircuit<Base, BITS>>();
+    let lookups = lookup_stats::<Base, BatchedRangeCircuit<Base, BITS>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Base::NUM_BITS,
+            // One lookup per value, all into the same table
+            constraints_count: batch as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::BatchedRange.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("bits".to_string(), BITS.into());
+    metrics.resolved_parameters.insert("batch_size".to_string(), batch.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+    let scalability = &mut metrics.scalability_metrics.extra;
+    scalability.insert("lookup_table_size".to_string(), BatchedRangeCircuit::<Base, BITS>::lookup_table_size().into());
+    scalability.insert("lookup_count".to_string(), lookups.lookup_count.into());
+    scalability.insert("lookup_proof_bytes".to_string(), lookups.proof_bytes.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[], repeat)?;
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+/// Proves one fixed batch of 256 values at each of `ks` and records the
+/// proving time per domain size in `extra.k_sweep`.
+pub fn record_k_sweep(metrics: &mut BenchmarkMetrics, ks: &[u32]) -> Result<(), Error> {
+    let values: Vec<u64> = (0..256).collect();
+    let circuit = BatchedRangeCircuit::<Base, BITS>::new(&values);
+
+    let results: Vec<_> = k_sweep(&circuit, ks)?
+        .into_iter()
+        .map(|result| {
+            json!({
+                "k": result.k,
+                "domain_size": result.domain_size,
+                "proving_time_ms": result.proving_time.as_millis() as u64,
+            })
+        })
+        .collect();
+    metrics.extra.insert("k_sweep".to_string(), results.into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    #[test]
+    fn test_batched_range_check() {
+        let values: Vec<u64> = (0..64).map(|i| (i * 37) % (1 << BITS)).collect();
+        let circuit = BatchedRangeCircuit::<Base, BITS>::new(&values);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_one_out_of_range_value() {
+        let mut values: Vec<u64> = (0..64).map(|i| (i * 37) % (1 << BITS)).collect();
+        values[41] = 1 << BITS;
+        let circuit = BatchedRangeCircuit::<Base, BITS>::new(&values);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_lookup_count_reported() {
+        let stats = lookup_stats::<Base, BatchedRangeCircuit<Base, BITS>>();
+
+        assert_eq!(stats.lookup_count, 1);
+        assert!(stats.proof_bytes > 0);
+    }
+
+    #[test]
+    fn test_k_sweep_domain_grows() {
+        let circuit = BatchedRangeCircuit::<Base, BITS>::new(&[1, 2, 3]);
+        let k = circuit.min_k();
+
+        let results = k_sweep(&circuit, &[k, k + 1]).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].k, k);
+        assert!(results[0].domain_size < results[1].domain_size);
+    }
+
+    #[test]
+    fn test_parse_k_sweep() {
+        assert_eq!(parse_k_sweep("8, 10,12"), Ok(vec![8, 10, 12]));
+        assert_eq!(parse_k_sweep("8,x").unwrap_err(), "--k-sweep expects comma-separated integers, got \"8,x\"");
+        assert!(parse_k_sweep("").is_err());
+    }
+
+    #[test]
+    fn test_batch_fits_domain() {
+        for size in CircuitSize::ALL {
+            let values = vec![0; batch_size(size)];
+            assert!(BatchedRangeCircuit::<Base, BITS>::new(&values).min_k() <= size.k(), "{} does not fit", size);
+        }
+    }
+
+    #[test]
+    fn test_table_shared_across_batch_sizes() {
+        let small = BatchedRangeCircuit::<Base, BITS>::new(&[1, 2, 3]);
+        let large = BatchedRangeCircuit::<Base, BITS>::new(&[5; 200]);
+
+        assert_eq!(BatchedRangeCircuit::<Base, BITS>::lookup_table_size(), 256);
+        assert_eq!(small.rows_used(), 3);
+        assert_eq!(large.rows_used(), 200);
+        assert_eq!(small.min_k(), large.min_k());
+    }
+}
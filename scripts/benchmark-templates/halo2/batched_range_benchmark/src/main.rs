@@ -0,0 +1,20 @@
+//! Command-line entry point; the benchmark itself is `batched_range_benchmark::run`.
+
+use std::error::Error;
+
+use benchmark_metrics::{CountingAllocator, TemplateArgs};
+
+// Counts live allocations, so the metrics can set peak RSS against them
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = TemplateArgs::from_env();
+    let mut metrics = batched_range_benchmark::run_repeated(args.size_or_exit(), args.repeat)?;
+    if args.has_flag("--k-sweep") {
+        let ks = batched_range_benchmark::parse_k_sweep(args.value_of("--k-sweep").unwrap_or_default())?;
+        batched_range_benchmark::record_k_sweep(&mut metrics, &ks)?;
+    }
+    metrics.emit(&args)?;
+    Ok(())
+}
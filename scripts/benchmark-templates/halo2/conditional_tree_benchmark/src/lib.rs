@@ -0,0 +1,312 @@
+//! Nested Conditionals (Balanced If-Else Tree)
+//!
+//! Control-flow-heavy programs nest their branches, and every level of
+//! nesting is another selection whose inputs are the results of the level
+//! below. This benchmark builds a balanced tree of depth d: 2^d leaf values
+//! and 2^d - 1 conditionals, each an independent boolean choosing between
+//! its two subtrees. The selected leaf is exposed as the public output.
+//!
+//! Every conditional is one multiplexer row (the same two constraints as
+//! the mux benchmark) whose inputs are copy-constrained to its children's
+//! outputs, so cost grows with the number of nodes, 2^d - 1.
+//!
+//! The depth d is chosen by the circuit_size argument:
+//!
+//! - small:  8
+//! - medium: 12
+//! - large:  16
+//! - xlarge: 19
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Column, Advice, Instance, Selector, Expression,
+        Error, ErrorFront},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs};
+use rand::{rngs::OsRng, Rng};
+use pasta_curves::pallas::Base;
+use serde_json::json;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct TreeConfig {
+    q_mux: Selector,
+    sel: Column<Advice>,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    out: Column<Advice>,
+    output: Column<Instance>, // the selected leaf
+}
+
+// Conditionals are stored in heap order: node 0 is the root and node i has
+// children 2i + 1 (taken when its condition is 1) and 2i + 2. Node indices
+// from 2^d - 1 upwards are the leaves.
+#[derive(Default, Clone)]
+struct ConditionalTree<F: PrimeField> {
+    depth: u32,
+    conditions: Vec<Value<F>>,
+    leaves: Vec<Value<F>>,
+}
+
+impl<F: PrimeField> ConditionalTree<F> {
+    fn new(conditions: &[bool], leaves: &[u64]) -> Self {
+        let depth = leaves.len().trailing_zeros();
+        assert_eq!(leaves.len(), 1 << depth, "leaf count must be a power of two");
+        assert_eq!(conditions.len(), leaves.len() - 1, "one condition per internal node");
+
+        Self {
+            depth,
+            conditions: conditions.iter().map(|&c| Value::known(F::from(c as u64))).collect(),
+            leaves: leaves.iter().map(|&v| Value::known(F::from(v))).collect(),
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        (1 << self.depth) - 1
+    }
+
+    // Constraints contributed by all conditionals together
+    fn constraint_count(&self) -> usize {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+        let per_node: usize = meta.gates().iter().map(|gate| gate.polynomials().len()).sum();
+        per_node * self.node_count()
+    }
+
+    // Smallest k whose domain fits one row per conditional
+    fn min_k(&self) -> u32 {
+        let rows = self.node_count() + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+// The leaf reached by following the conditions from the root
+fn selected_leaf(conditions: &[bool], leaves: &[u64]) -> u64 {
+    let mut node = 0;
+    while node < conditions.len() {
+        node = if conditions[node] { 2 * node + 1 } else { 2 * node + 2 };
+    }
+    leaves[node - conditions.len()]
+}
+
+impl<F: PrimeField> Circuit<F> for ConditionalTree<F> {
+    type Config = TreeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            depth: self.depth,
+            conditions: vec![Value::unknown(); self.conditions.len()],
+            leaves: vec![Value::unknown(); self.leaves.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_mux = meta.selector();
+        let sel = meta.advice_column();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let out = meta.advice_column();
+        let output = meta.instance_column();
+
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(out);
+        meta.enable_equality(output);
+
+        meta.create_gate("conditional", |meta| {
+            let q_mux = meta.query_selector(q_mux);
+            let sel = meta.query_advice(sel, Rotation::cur());
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                // sel is boolean
+                q_mux.clone() * sel.clone() * (one.clone() - sel.clone()),
+
+                // out = sel * a + (1 - sel) * b
+                q_mux * (out - (sel.clone() * a + (one - sel) * b)),
+            ]
+        });
+
+        TreeConfig { q_mux, sel, a, b, out, output }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), ErrorFront> {
+        let nodes = self.node_count();
+
+        let root = layouter.assign_region(
+            || "conditional tree",
+            |mut region| {
+                // Children have larger indices than their parent, so laying
+                // nodes out from the last one up assigns every child first
+                let mut outs: Vec<Option<AssignedCell<F, F>>> = vec![None; nodes];
+
+                for (offset, node) in (0..nodes).rev().enumerate() {
+                    config.q_mux.enable(&mut region, offset)?;
+                    let sel = self.conditions[node];
+                    region.assign_advice(|| "sel", config.sel, offset, || sel)?;
+
+                    let mut child_input = |child: usize, column, name: &'static str| {
+                        if child < nodes {
+                            let cell = outs[child].as_ref().expect("child laid out first");
+                            cell.copy_advice(|| name, &mut region, column, offset)
+                        } else {
+                            region.assign_advice(|| name, column, offset, || self.leaves[child - nodes])
+                        }
+                    };
+                    let a = child_input(2 * node + 1, config.a, "a")?;
+                    let b = child_input(2 * node + 2, config.b, "b")?;
+
+                    let out = sel.zip(a.value().copied()).zip(b.value().copied())
+                        .map(|((sel, a), b)| sel * a + (F::ONE - sel) * b);
+                    outs[node] = Some(region.assign_advice(|| "out", config.out, offset, || out)?);
+                }
+
+                Ok(outs[0].take().expect("tree has a root"))
+            },
+        )?;
+
+        layouter.constrain_instance(root.cell(), config.output, 0)
+    }
+}
+
+fn tree_depth(size: CircuitSize) -> u32 {
+    match size {
+        CircuitSize::Small => 8,
+        CircuitSize::Medium => 12,
+        CircuitSize::Large => 16,
+        CircuitSize::XLarge => 19,
+    }
+}
+
+fn random_tree(depth: u32) -> (Vec<bool>, Vec<u64>) {
+    let mut rng = OsRng;
+    let conditions = (0..(1 << depth) - 1).map(|_| rng.gen()).collect();
+    let leaves = (0..1 << depth).map(|_| rng.gen()).collect();
+    (conditions, leaves)
+}
+
+/// Benchmarks one proof of a random tree at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks a random balanced if-else tree at `size` in this process,
+/// proving it `repeat` times: time_metrics holds the median run and
+/// time_statistics the distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let depth = tree_depth(size);
+    let (conditions, leaves) = random_tree(depth);
+    let output = Base::from(selected_leaf(&conditions, &leaves));
+    let circuit = ConditionalTree::<Base>::new(&conditions, &leaves);
+    let k = circuit.min_k();
+    let nodes = circuit.node_count();
+    let circuit_stats = collect_circuit_stats::<Base, ConditionalTree<Base>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Base::NUM_BITS,
+            // The multiplexer gate's constraints on every conditional's row
+            constraints_count: circuit.constraint_count() as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::ConditionalTree.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("depth".to_string(), depth.into());
+    metrics.resolved_parameters.insert("conditionals".to_string(), nodes.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[&[output]], repeat)?;
+    // Median proving time spread over the conditionals
+    metrics.time_metrics.extra.insert(
+        "proving_time_per_conditional_us".to_string(),
+        json!(metrics.time_metrics.proving_time_ms as f64 * 1e3 / nodes as f64),
+    );
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    // Alternating conditions so both branches are taken along the path
+    fn sample_tree(depth: u32) -> (Vec<bool>, Vec<u64>) {
+        let conditions = (0..(1u64 << depth) - 1).map(|i| i % 3 != 0).collect();
+        let leaves = (0..1u64 << depth).map(|i| 100 + i).collect();
+        (conditions, leaves)
+    }
+
+    #[test]
+    fn test_deeper_tree_scales_and_verifies() {
+        let mut constraints = Vec::new();
+
+        for depth in [3, 5] {
+            let (conditions, leaves) = sample_tree(depth);
+            let output = Base::from(selected_leaf(&conditions, &leaves));
+            let circuit = ConditionalTree::<Base>::new(&conditions, &leaves);
+
+            let prover = MockProver::run(circuit.min_k(), &circuit, vec![vec![output]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            constraints.push(circuit.constraint_count());
+        }
+
+        // Constraints grow with the node count: 7 nodes at depth 3, 31 at depth 5
+        assert_eq!(constraints[0] * 31, constraints[1] * 7);
+    }
+
+    #[test]
+    fn test_tree_depth_fits_domain() {
+        for size in CircuitSize::ALL {
+            let (conditions, leaves) = sample_tree(tree_depth(size));
+            assert!(ConditionalTree::<Base>::new(&conditions, &leaves).min_k() <= size.k(), "{} does not fit", size);
+        }
+    }
+
+    #[test]
+    fn test_wrong_leaf_fails() {
+        let (conditions, leaves) = sample_tree(3);
+        let wrong = Base::from(selected_leaf(&conditions, &leaves) + 1);
+        let circuit = ConditionalTree::<Base>::new(&conditions, &leaves);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![vec![wrong]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,533 @@
+//! Affine vs Jacobian Point Arithmetic
+//!
+//! The ECDSA chip works in affine coordinates, where every addition and
+//! doubling needs a field inversion. In a circuit an inversion is just a
+//! witness checked by one multiplication, so it is not obvious up front that
+//! projective coordinates win. This module implements both representations
+//! on a curve native to the circuit field (y² = x³ + b, a = 0) so that their
+//! gate counts and degrees can be compared directly.
+//!
+//! - Affine:   (x, y)
+//! - Jacobian: (X, Y, Z) representing (X/Z², Y/Z³)
+//!
+//! Jacobian addition is the mixed variant (second operand affine), which is
+//! what a double-and-add scalar multiplication with a fixed base needs.
+//!
+//! The binary only reports gate costs from `configure`; laying out points
+//! is exercised by the tests.
+#![cfg_attr(not(test), allow(dead_code))]
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, ErrorFront, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use ff::PrimeField;
+
+type Cell<F> = AssignedCell<F, F>;
+
+#[derive(Clone, Debug)]
+pub(crate) struct AffinePoint<F: Field> {
+    pub(crate) x: Cell<F>,
+    pub(crate) y: Cell<F>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct JacobianPoint<F: Field> {
+    pub(crate) x: Cell<F>,
+    pub(crate) y: Cell<F>,
+    pub(crate) z: Cell<F>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CurveOpsConfig {
+    q_affine_add: Selector,
+    q_affine_double: Selector,
+    q_jacobian_add: Selector,
+    q_jacobian_double: Selector,
+    q_normalize: Selector,
+    pub(crate) p: [Column<Advice>; 3], // first operand, result on the next row
+    pub(crate) q: [Column<Advice>; 2], // affine second operand / normalized point
+    aux: [Column<Advice>; 2],          // lambda or intermediate values
+}
+
+// Number of polynomial constraints and the highest degree among them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct GateCost {
+    pub(crate) constraints: usize,
+    pub(crate) degree: usize,
+}
+
+pub(crate) const AFFINE_ADD: &str = "affine add";
+pub(crate) const AFFINE_DOUBLE: &str = "affine double";
+pub(crate) const JACOBIAN_ADD: &str = "jacobian mixed add";
+pub(crate) const JACOBIAN_DOUBLE: &str = "jacobian double";
+pub(crate) const JACOBIAN_NORMALIZE: &str = "jacobian normalize";
+
+impl CurveOpsConfig {
+    pub(crate) fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>, b: F) -> Self {
+        let p = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let q = [meta.advice_column(), meta.advice_column()];
+        let aux = [meta.advice_column(), meta.advice_column()];
+        let constants: Column<Fixed> = meta.fixed_column();
+
+        for column in p.iter().chain(q.iter()) {
+            meta.enable_equality(*column);
+        }
+        meta.enable_constant(constants);
+
+        let q_affine_add = meta.selector();
+        let q_affine_double = meta.selector();
+        let q_jacobian_add = meta.selector();
+        let q_jacobian_double = meta.selector();
+        let q_normalize = meta.selector();
+
+        let constant = |value: u64| Expression::Constant(F::from(value));
+
+        meta.create_gate(AFFINE_ADD, |meta| {
+            let q_enable = meta.query_selector(q_affine_add);
+            let x1 = meta.query_advice(p[0], Rotation::cur());
+            let y1 = meta.query_advice(p[1], Rotation::cur());
+            let x2 = meta.query_advice(q[0], Rotation::cur());
+            let y2 = meta.query_advice(q[1], Rotation::cur());
+            let lambda = meta.query_advice(aux[0], Rotation::cur());
+            let x3 = meta.query_advice(p[0], Rotation::next());
+            let y3 = meta.query_advice(p[1], Rotation::next());
+
+            vec![
+                // lambda = (y2 - y1) / (x2 - x1)
+                q_enable.clone() * (lambda.clone() * (x2.clone() - x1.clone()) - (y2 - y1.clone())),
+                q_enable.clone() * (x3.clone() - (lambda.clone() * lambda.clone() - x1.clone() - x2)),
+                q_enable * (y3 - (lambda * (x1 - x3) - y1)),
+            ]
+        });
+
+        meta.create_gate(AFFINE_DOUBLE, |meta| {
+            let q_enable = meta.query_selector(q_affine_double);
+            let x = meta.query_advice(p[0], Rotation::cur());
+            let y = meta.query_advice(p[1], Rotation::cur());
+            let lambda = meta.query_advice(aux[0], Rotation::cur());
+            let x3 = meta.query_advice(p[0], Rotation::next());
+            let y3 = meta.query_advice(p[1], Rotation::next());
+
+            vec![
+                // lambda = 3x² / 2y
+                q_enable.clone() * (lambda.clone() * constant(2) * y.clone() - constant(3) * x.clone() * x.clone()),
+                q_enable.clone() * (x3.clone() - (lambda.clone() * lambda.clone() - constant(2) * x.clone())),
+                q_enable * (y3 - (lambda * (x - x3) - y)),
+            ]
+        });
+
+        meta.create_gate(JACOBIAN_ADD, |meta| {
+            let q_enable = meta.query_selector(q_jacobian_add);
+            let x1 = meta.query_advice(p[0], Rotation::cur());
+            let y1 = meta.query_advice(p[1], Rotation::cur());
+            let z1 = meta.query_advice(p[2], Rotation::cur());
+            let x2 = meta.query_advice(q[0], Rotation::cur());
+            let y2 = meta.query_advice(q[1], Rotation::cur());
+            let h = meta.query_advice(aux[0], Rotation::cur());
+            let r = meta.query_advice(aux[1], Rotation::cur());
+            let x3 = meta.query_advice(p[0], Rotation::next());
+            let y3 = meta.query_advice(p[1], Rotation::next());
+            let z3 = meta.query_advice(p[2], Rotation::next());
+
+            let zz = z1.clone() * z1.clone();
+            let hh = h.clone() * h.clone();
+            let hhh = hh.clone() * h.clone();
+
+            vec![
+                // H = x2·Z1² - X1
+                q_enable.clone() * (h.clone() - (x2 * zz.clone() - x1.clone())),
+                // R = y2·Z1³ - Y1
+                q_enable.clone() * (r.clone() - (y2 * zz * z1.clone() - y1.clone())),
+                // X3 = R² - H³ - 2·X1·H²
+                q_enable.clone() * (x3.clone() - (r.clone() * r.clone() - hhh.clone() - constant(2) * x1.clone() * hh.clone())),
+                // Y3 = R·(X1·H² - X3) - Y1·H³
+                q_enable.clone() * (y3 - (r * (x1 * hh - x3) - y1 * hhh)),
+                // Z3 = Z1·H
+                q_enable * (z3 - z1 * h),
+            ]
+        });
+
+        meta.create_gate(JACOBIAN_DOUBLE, |meta| {
+            let q_enable = meta.query_selector(q_jacobian_double);
+            let x = meta.query_advice(p[0], Rotation::cur());
+            let y = meta.query_advice(p[1], Rotation::cur());
+            let z = meta.query_advice(p[2], Rotation::cur());
+            let a = meta.query_advice(aux[0], Rotation::cur());
+            let x3 = meta.query_advice(p[0], Rotation::next());
+            let y3 = meta.query_advice(p[1], Rotation::next());
+            let z3 = meta.query_advice(p[2], Rotation::next());
+
+            let yy = y.clone() * y.clone();
+            let d = constant(4) * x.clone() * yy.clone();
+
+            vec![
+                // A = X²
+                q_enable.clone() * (a.clone() - x.clone() * x),
+                // X3 = 9A² - 2D, with D = 4·X·Y²
+                q_enable.clone() * (x3.clone() - (constant(9) * a.clone() * a.clone() - constant(2) * d.clone())),
+                // Y3 = 3A·(D - X3) - 8Y⁴
+                q_enable.clone() * (y3 - (constant(3) * a * (d - x3) - constant(8) * yy.clone() * yy)),
+                // Z3 = 2·Y·Z
+                q_enable * (z3 - constant(2) * y * z),
+            ]
+        });
+
+        meta.create_gate(JACOBIAN_NORMALIZE, |meta| {
+            let q_enable = meta.query_selector(q_normalize);
+            let x = meta.query_advice(p[0], Rotation::cur());
+            let y = meta.query_advice(p[1], Rotation::cur());
+            let z = meta.query_advice(p[2], Rotation::cur());
+            let x_affine = meta.query_advice(q[0], Rotation::cur());
+            let y_affine = meta.query_advice(q[1], Rotation::cur());
+
+            let zz = z.clone() * z.clone();
+
+            vec![
+                q_enable.clone() * (x_affine.clone() * zz.clone() - x),
+                q_enable.clone() * (y_affine.clone() * zz.clone() * z - y),
+                // The normalized point must be on the curve
+                q_enable * (y_affine.clone() * y_affine - x_affine.clone() * x_affine.clone() * x_affine - Expression::Constant(b)),
+            ]
+        });
+
+        Self { q_affine_add, q_affine_double, q_jacobian_add, q_jacobian_double, q_normalize, p, q, aux }
+    }
+
+    fn copy_affine<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        columns: [Column<Advice>; 2],
+        offset: usize,
+        point: &AffinePoint<F>,
+    ) -> Result<AffinePoint<F>, ErrorFront> {
+        Ok(AffinePoint {
+            x: point.x.copy_advice(|| "x", region, columns[0], offset)?,
+            y: point.y.copy_advice(|| "y", region, columns[1], offset)?,
+        })
+    }
+
+    fn copy_jacobian<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        point: &JacobianPoint<F>,
+    ) -> Result<JacobianPoint<F>, ErrorFront> {
+        Ok(JacobianPoint {
+            x: point.x.copy_advice(|| "X", region, self.p[0], offset)?,
+            y: point.y.copy_advice(|| "Y", region, self.p[1], offset)?,
+            z: point.z.copy_advice(|| "Z", region, self.p[2], offset)?,
+        })
+    }
+
+    fn assign_result<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        x: Value<F>,
+        y: Value<F>,
+    ) -> Result<AffinePoint<F>, ErrorFront> {
+        Ok(AffinePoint {
+            x: region.assign_advice(|| "x3", self.p[0], offset, || x)?,
+            y: region.assign_advice(|| "y3", self.p[1], offset, || y)?,
+        })
+    }
+
+    // Uses rows `offset` and `offset + 1`
+    pub(crate) fn affine_add<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        p: &AffinePoint<F>,
+        q: &AffinePoint<F>,
+    ) -> Result<AffinePoint<F>, ErrorFront> {
+        self.q_affine_add.enable(region, offset)?;
+        let p = self.copy_affine(region, [self.p[0], self.p[1]], offset, p)?;
+        let q = self.copy_affine(region, self.q, offset, q)?;
+
+        let (x1, y1) = (p.x.value().copied(), p.y.value().copied());
+        let (x2, y2) = (q.x.value().copied(), q.y.value().copied());
+
+        let lambda = (y2 - y1) * (x2 - x1).map(|dx| dx.invert().unwrap());
+        region.assign_advice(|| "lambda", self.aux[0], offset, || lambda)?;
+
+        let x3 = lambda * lambda - x1 - x2;
+        let y3 = lambda * (x1 - x3) - y1;
+        self.assign_result(region, offset + 1, x3, y3)
+    }
+
+    // Uses rows `offset` and `offset + 1`
+    pub(crate) fn affine_double<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        p: &AffinePoint<F>,
+    ) -> Result<AffinePoint<F>, ErrorFront> {
+        self.q_affine_double.enable(region, offset)?;
+        let p = self.copy_affine(region, [self.p[0], self.p[1]], offset, p)?;
+
+        let (x, y) = (p.x.value().copied(), p.y.value().copied());
+
+        let lambda = x.map(|x| x.square() * F::from(3))
+            * y.map(|y| y.double().invert().unwrap());
+        region.assign_advice(|| "lambda", self.aux[0], offset, || lambda)?;
+
+        let x3 = lambda * lambda - x - x;
+        let y3 = lambda * (x - x3) - y;
+        self.assign_result(region, offset + 1, x3, y3)
+    }
+
+    // Lifts an affine point to Jacobian coordinates with Z = 1 on row `offset`
+    pub(crate) fn to_jacobian<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        p: &AffinePoint<F>,
+    ) -> Result<JacobianPoint<F>, ErrorFront> {
+        let p = self.copy_affine(region, [self.p[0], self.p[1]], offset, p)?;
+        let z = region.assign_advice_from_constant(|| "Z", self.p[2], offset, F::ONE)?;
+        Ok(JacobianPoint { x: p.x, y: p.y, z })
+    }
+
+    // Uses rows `offset` and `offset + 1`
+    pub(crate) fn jacobian_add<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        p: &JacobianPoint<F>,
+        q: &AffinePoint<F>,
+    ) -> Result<JacobianPoint<F>, ErrorFront> {
+        self.q_jacobian_add.enable(region, offset)?;
+        let p = self.copy_jacobian(region, offset, p)?;
+        let q = self.copy_affine(region, self.q, offset, q)?;
+
+        let (x1, y1, z1) = (p.x.value().copied(), p.y.value().copied(), p.z.value().copied());
+        let (x2, y2) = (q.x.value().copied(), q.y.value().copied());
+
+        let zz = z1 * z1;
+        let h = x2 * zz - x1;
+        let r = y2 * zz * z1 - y1;
+        region.assign_advice(|| "H", self.aux[0], offset, || h)?;
+        region.assign_advice(|| "R", self.aux[1], offset, || r)?;
+
+        let hh = h * h;
+        let hhh = hh * h;
+        let x3 = r * r - hhh - (x1 * hh).map(|v| v.double());
+        let y3 = r * (x1 * hh - x3) - y1 * hhh;
+        let z3 = z1 * h;
+
+        Ok(JacobianPoint {
+            x: region.assign_advice(|| "X3", self.p[0], offset + 1, || x3)?,
+            y: region.assign_advice(|| "Y3", self.p[1], offset + 1, || y3)?,
+            z: region.assign_advice(|| "Z3", self.p[2], offset + 1, || z3)?,
+        })
+    }
+
+    // Uses rows `offset` and `offset + 1`
+    pub(crate) fn jacobian_double<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        p: &JacobianPoint<F>,
+    ) -> Result<JacobianPoint<F>, ErrorFront> {
+        self.q_jacobian_double.enable(region, offset)?;
+        let p = self.copy_jacobian(region, offset, p)?;
+
+        let (x, y, z) = (p.x.value().copied(), p.y.value().copied(), p.z.value().copied());
+
+        let a = x * x;
+        region.assign_advice(|| "A", self.aux[0], offset, || a)?;
+
+        let yy = y * y;
+        let d = (x * yy).map(|v| v * F::from(4));
+        let x3 = (a * a).map(|v| v * F::from(9)) - d.map(|v| v.double());
+        let y3 = a.map(|v| v * F::from(3)) * (d - x3) - (yy * yy).map(|v| v * F::from(8));
+        let z3 = (y * z).map(|v| v.double());
+
+        Ok(JacobianPoint {
+            x: region.assign_advice(|| "X3", self.p[0], offset + 1, || x3)?,
+            y: region.assign_advice(|| "Y3", self.p[1], offset + 1, || y3)?,
+            z: region.assign_advice(|| "Z3", self.p[2], offset + 1, || z3)?,
+        })
+    }
+
+    // Converts back to affine on row `offset`; costs one inversion witness
+    pub(crate) fn normalize<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        p: &JacobianPoint<F>,
+    ) -> Result<AffinePoint<F>, ErrorFront> {
+        self.q_normalize.enable(region, offset)?;
+        let p = self.copy_jacobian(region, offset, p)?;
+
+        let z_inv = p.z.value().map(|z| z.invert().unwrap());
+        let zz_inv = z_inv * z_inv;
+        let x = p.x.value().copied() * zz_inv;
+        let y = p.y.value().copied() * zz_inv * z_inv;
+
+        Ok(AffinePoint {
+            x: region.assign_advice(|| "x", self.q[0], offset, || x)?,
+            y: region.assign_advice(|| "y", self.q[1], offset, || y)?,
+        })
+    }
+}
+
+pub(crate) fn gate_cost<F: Field>(meta: &ConstraintSystem<F>, name: &str) -> Option<GateCost> {
+    meta.gates()
+        .iter()
+        .find(|gate| gate.name() == name)
+        .map(|gate| GateCost {
+            constraints: gate.polynomials().len(),
+            degree: gate.polynomials().iter().map(|poly| poly.degree()).max().unwrap_or(0),
+        })
+}
+
+// Constraints for one double-and-add scalar multiplication over `bits`
+// bits, assuming half of the bits are set
+pub(crate) fn scalar_mult_constraints(bits: usize, double: GateCost, add: GateCost) -> usize {
+    bits * double.constraints + bits / 2 * add.constraints
+}
+
+// The constraint cost of each point operation gadget and of a full scalar
+// mult in both coordinate systems, as a table
+pub(crate) fn cost_comparison<F: PrimeField>(b: F) -> String {
+    let mut meta = ConstraintSystem::<F>::default();
+    CurveOpsConfig::configure(&mut meta, b);
+
+    let cost = |name| gate_cost(&meta, name).expect("gate is configured");
+    let bits = F::NUM_BITS as usize;
+
+    let mut table = format!("{:<20} {:>12} {:>8}\n", "gadget", "constraints", "degree");
+    for name in [AFFINE_ADD, AFFINE_DOUBLE, JACOBIAN_ADD, JACOBIAN_DOUBLE, JACOBIAN_NORMALIZE] {
+        let gate = cost(name);
+        table += &format!("{:<20} {:>12} {:>8}\n", name, gate.constraints, gate.degree);
+    }
+
+    let affine = scalar_mult_constraints(bits, cost(AFFINE_DOUBLE), cost(AFFINE_ADD));
+    let jacobian = scalar_mult_constraints(bits, cost(JACOBIAN_DOUBLE), cost(JACOBIAN_ADD))
+        + cost(JACOBIAN_NORMALIZE).constraints;
+    table += &format!("{}-bit scalar mult: affine {} constraints, jacobian {} constraints\n", bits, affine, jacobian);
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::Circuit,
+    };
+    use halo2_proofs::arithmetic::CurveAffine;
+    use group::{Curve, Group};
+    use pasta_curves::{vesta, Fq};
+
+    // Vesta's base field is the Pallas scalar field, so Vesta points are
+    // native to circuits over pallas::Scalar
+    struct CompareCircuit {
+        p: vesta::Affine,
+        q: vesta::Affine,
+    }
+
+    impl Circuit<Fq> for CompareCircuit {
+        type Config = CurveOpsConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { p: self.p, q: self.q }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+            CurveOpsConfig::configure(meta, vesta::Affine::b())
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fq>) -> Result<(), ErrorFront> {
+            let (p, q) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let assign = |region: &mut Region<'_, Fq>, point: &vesta::Affine, column: [Column<Advice>; 2]| {
+                        let coords = point.coordinates().unwrap();
+                        Ok::<_, ErrorFront>(AffinePoint {
+                            x: region.assign_advice(|| "x", column[0], 0, || Value::known(*coords.x()))?,
+                            y: region.assign_advice(|| "y", column[1], 0, || Value::known(*coords.y()))?,
+                        })
+                    };
+                    let p = assign(&mut region, &self.p, [config.p[0], config.p[1]])?;
+                    let q = assign(&mut region, &self.q, config.q)?;
+                    Ok((p, q))
+                },
+            )?;
+
+            let (affine_sum, affine_double) = layouter.assign_region(
+                || "affine",
+                |mut region| {
+                    let sum = config.affine_add(&mut region, 0, &p, &q)?;
+                    let double = config.affine_double(&mut region, 2, &p)?;
+                    Ok((sum, double))
+                },
+            )?;
+
+            let (jacobian_sum, jacobian_double) = layouter.assign_region(
+                || "jacobian",
+                |mut region| {
+                    let p_jacobian = config.to_jacobian(&mut region, 0, &p)?;
+                    let sum = config.jacobian_add(&mut region, 1, &p_jacobian, &q)?;
+                    let sum = config.normalize(&mut region, 3, &sum)?;
+                    let double = config.jacobian_double(&mut region, 4, &p_jacobian)?;
+                    let double = config.normalize(&mut region, 6, &double)?;
+                    Ok((sum, double))
+                },
+            )?;
+
+            layouter.assign_region(
+                || "compare",
+                |mut region| {
+                    region.constrain_equal(affine_sum.x.cell(), jacobian_sum.x.cell())?;
+                    region.constrain_equal(affine_sum.y.cell(), jacobian_sum.y.cell())?;
+                    region.constrain_equal(affine_double.x.cell(), jacobian_double.x.cell())?;
+                    region.constrain_equal(affine_double.y.cell(), jacobian_double.y.cell())
+                },
+            )
+        }
+    }
+
+    fn point(multiple: u64) -> vesta::Affine {
+        (vesta::Point::generator() * vesta::Scalar::from(multiple)).to_affine()
+    }
+
+    #[test]
+    fn test_affine_and_jacobian_agree() {
+        let circuit = CompareCircuit { p: point(7), q: point(11) };
+
+        let prover = MockProver::run(5, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_jacobian_costs_more_constraints_in_circuit() {
+        let mut meta = ConstraintSystem::<Fq>::default();
+        CurveOpsConfig::configure(&mut meta, vesta::Affine::b());
+
+        let affine_add = gate_cost(&meta, AFFINE_ADD).unwrap();
+        let affine_double = gate_cost(&meta, AFFINE_DOUBLE).unwrap();
+        let jacobian_add = gate_cost(&meta, JACOBIAN_ADD).unwrap();
+        let jacobian_double = gate_cost(&meta, JACOBIAN_DOUBLE).unwrap();
+
+        assert_eq!((affine_add.constraints, affine_double.constraints), (3, 3));
+        assert_eq!((jacobian_add.constraints, jacobian_double.constraints), (5, 4));
+        assert!(jacobian_add.degree > affine_add.degree);
+
+        // An in-circuit inversion is a single witness, so avoiding it does
+        // not pay for the extra multiplications of the Jacobian formulas
+
+        let bits = Fq::NUM_BITS as usize;
+        assert!(
+            scalar_mult_constraints(bits, jacobian_double, jacobian_add)
+                > scalar_mult_constraints(bits, affine_double, affine_add)
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Unconstrained Cell Detection
+//!
+//! A passing MockProver run only shows that the honest witness satisfies
+//! the constraints, not that the constraints pin the witness down. This
+//! module re-runs a circuit once per assigned advice cell, each time adding
+//! a random nonzero delta to that one cell. If the verification result does
+//! not change, no gate or copy constraint looks at the cell and it is
+//! reported as unconstrained.
+//!
+//! The mutation is injected by a floor planner that wraps
+//! `SimpleFloorPlanner` and intercepts advice assignments, so the circuit
+//! under test needs no changes. The same planner records every assignment,
+//! which is also how `region_rows` counts the rows each region takes.
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{layouter::SyncDeps, Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem,
+        ErrorFront, FloorPlanner, Fixed, Instance, Selector,
+    },
+};
+
+use ff::FromUniformBytes;
+use rand::rngs::OsRng;
+
+thread_local! {
+    // Index of the advice assignment to perturb on the current run
+    static TARGET: Cell<Option<usize>> = const { Cell::new(None) };
+    // Every advice assignment seen on the current run, in order
+    static ASSIGNED: RefCell<Vec<AdviceCell>> = const { RefCell::new(Vec::new()) };
+    // Every selector enabled on the current run, with its row
+    static ENABLED: RefCell<Vec<(Selector, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+// An advice assignment, identified the way a reader of the circuit would
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AdviceCell {
+    pub(crate) region: String,
+    pub(crate) name: String,
+    pub(crate) column: usize,
+    pub(crate) row: usize,
+}
+
+// Forwards to the real assignment, adding `delta` to the targeted cell
+struct MutatingAssignment<'a, F: Field, CS: Assignment<F>> {
+    inner: &'a mut CS,
+    delta: F,
+    region: String,
+    count: usize,
+}
+
+impl<F: Field, CS: Assignment<F>> Assignment<F> for MutatingAssignment<'_, F, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name: String = name_fn().into();
+        self.region = name.clone();
+        self.inner.enter_region(|| name);
+    }
+
+    fn annotate_column<A, AR>(&mut self, annotation: A, column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.annotate_column(annotation, column)
+    }
+
+    fn exit_region(&mut self) {
+        self.inner.exit_region()
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), ErrorFront>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        ENABLED.with(|enabled| enabled.borrow_mut().push((*selector, row)));
+        self.inner.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, ErrorFront> {
+        self.inner.query_instance(column, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), ErrorFront>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let name: String = annotation().into();
+        ASSIGNED.with(|cells| {
+            cells.borrow_mut().push(AdviceCell {
+                region: self.region.clone(),
+                name: name.clone(),
+                column: column.index(),
+                row,
+            })
+        });
+
+        let mutate = TARGET.with(Cell::get) == Some(self.count);
+        self.count += 1;
+
+        let delta = self.delta;
+        self.inner.assign_advice(|| name, column, row, || {
+            let value: Value<Assigned<F>> = to().map(Into::into);
+            if mutate {
+                value.map(|v| v + Assigned::from(delta))
+            } else {
+                value
+            }
+        })
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), ErrorFront>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), ErrorFront> {
+        self.inner.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), ErrorFront> {
+        self.inner.fill_from_row(column, row, to)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.inner.get_challenge(challenge)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.inner.pop_namespace(gadget_name)
+    }
+}
+
+struct MutatingFloorPlanner;
+
+impl FloorPlanner for MutatingFloorPlanner {
+    fn synthesize<F: Field, CS: Assignment<F> + SyncDeps, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), ErrorFront> {
+        ASSIGNED.with(|cells| cells.borrow_mut().clear());
+        ENABLED.with(|enabled| enabled.borrow_mut().clear());
+
+        let mut mutating = MutatingAssignment {
+            inner: cs,
+            delta: F::random(OsRng),
+            region: String::new(),
+            count: 0,
+        };
+        SimpleFloorPlanner::synthesize(&mut mutating, circuit, config, constants)
+    }
+}
+
+// Runs the wrapped circuit's synthesis under `MutatingFloorPlanner`
+struct Mutated<'a, C> {
+    circuit: &'a C,
+}
+
+impl<F: Field, C: Circuit<F>> Circuit<F> for Mutated<'_, C> {
+    type Config = C::Config;
+    type FloorPlanner = MutatingFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { circuit: self.circuit }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), ErrorFront> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+// Advice cells whose mutation leaves the MockProver result unchanged. The
+// circuit does not have to verify to begin with: a mutation that changes
+// which constraints fail, or the cell values they report, still counts as
+// the cell being constrained.
+pub(crate) fn unconstrained_cells<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>) -> Vec<AdviceCell>
+where
+    F: Field + FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    let run = |target: Option<usize>| {
+        TARGET.with(|t| t.set(target));
+        let prover = MockProver::run(k, &Mutated { circuit }, instance.clone())
+            .expect("circuit synthesis failed");
+        let cells = ASSIGNED.with(|cells| cells.borrow().clone());
+        (prover.verify(), cells)
+    };
+
+    let (baseline, cells) = run(None);
+
+    let unconstrained = (0..cells.len())
+        .filter(|&index| run(Some(index)).0 == baseline)
+        .map(|index| cells[index].clone())
+        .collect();
+
+    TARGET.with(|t| t.set(None));
+    unconstrained
+}
+
+// Every advice assignment made while synthesizing `circuit`, in order. A
+// cell appearing twice was overwritten by a later assignment.
+pub(crate) fn assigned_cells<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>) -> Vec<AdviceCell>
+where
+    F: Field + FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    TARGET.with(|t| t.set(None));
+    MockProver::run(k, &Mutated { circuit }, instance).expect("circuit synthesis failed");
+    ASSIGNED.with(|cells| cells.borrow().clone())
+}
+
+// The number of distinct rows each region assigns advice in, in the order
+// regions were first entered: where a circuit's rows go
+pub(crate) fn region_rows<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>) -> Vec<(String, usize)>
+where
+    F: Field + FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    let mut regions: Vec<(String, BTreeSet<usize>)> = Vec::new();
+    for cell in assigned_cells(k, circuit, instance) {
+        match regions.iter_mut().find(|(region, _)| *region == cell.region) {
+            Some((_, rows)) => {
+                rows.insert(cell.row);
+            }
+            None => regions.push((cell.region, BTreeSet::from([cell.row]))),
+        }
+    }
+    regions.into_iter().map(|(region, rows)| (region, rows.len())).collect()
+}
+
+// Every selector enabled while synthesizing `circuit`, with its row. Each
+// gate behind a selector is applied once per enabled row, so this is where
+// a layout's constraint count comes from.
+#[cfg(test)]
+pub(crate) fn enabled_selectors<F, C>(k: u32, circuit: &C, instance: Vec<Vec<F>>) -> Vec<(Selector, usize)>
+where
+    F: Field + FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    TARGET.with(|t| t.set(None));
+    MockProver::run(k, &Mutated { circuit }, instance).expect("circuit synthesis failed");
+    ENABLED.with(|enabled| enabled.borrow().clone())
+}
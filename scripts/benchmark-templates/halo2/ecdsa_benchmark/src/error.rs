@@ -0,0 +1,185 @@
+//! Benchmark Error Type
+//!
+//! Circuit code reports `ErrorFront` while keygen, proving and verification
+//! report the backend `plonk::Error`. `BenchError` wraps both, along with
+//! unsatisfied witnesses, unreadable dumped inputs and bad arguments, so
+//! `main` can use `?` throughout and exit with a code that says which stage
+//! failed.
+//!
+//! `ErrorFront` has no room for a cause, so a circuit whose witnesses are
+//! incomplete or invalid first reports a `WitnessError`, converted to
+//! `ErrorFront::Synthesis` only at the `Circuit` boundary. The benchmark
+//! checks witnesses before synthesis and returns the `WitnessError` itself,
+//! and a proof the verifier rejects is an error too; nothing is printed
+//! here, `main` prints whatever comes back.
+
+use std::{fmt, io};
+
+use halo2_proofs::{
+    dev::VerifyFailure,
+    plonk::{Error, ErrorFront},
+};
+
+use crate::inputs::InputsError;
+
+#[derive(Debug)]
+pub enum BenchError {
+    // Circuit layout or witness assignment failed
+    Synthesis(ErrorFront),
+    // The circuit's witnesses are incomplete or invalid
+    Witness(WitnessError),
+    // Key generation, proving or verification failed
+    Backend(Error),
+    // The witness does not satisfy the circuit
+    Unsatisfied(Vec<VerifyFailure>),
+    // Dumped inputs could not be read or written
+    Inputs(String),
+    // A command-line argument could not be parsed
+    Usage(String),
+    // The verifier rejected a proof; names which one
+    ProofRejected(&'static str),
+    // The mock prover and a real proof disagree on whether a witness
+    // satisfies the circuit
+    ProversDisagree { mock_accepts: bool, proof_verifies: bool },
+    // The metrics could not be written, or linted with --abort-on-lint
+    Output(String),
+}
+
+impl BenchError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            BenchError::Synthesis(_) | BenchError::Witness(_) => 2,
+            BenchError::Backend(_) => 3,
+            BenchError::Unsatisfied(_) => 4,
+            BenchError::Inputs(_) => 5,
+            BenchError::Usage(_) => 6,
+            BenchError::ProofRejected(_) => 7,
+            BenchError::ProversDisagree { .. } => 8,
+            BenchError::Output(_) => 9,
+        }
+    }
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BenchError::Synthesis(err) => write!(f, "circuit synthesis failed: {}", err),
+            BenchError::Witness(err) => write!(f, "cannot synthesize signature: {}", err),
+            BenchError::Backend(err) => write!(f, "proving backend failed: {}", err),
+            BenchError::Unsatisfied(failures) => {
+                write!(f, "witness does not satisfy the circuit ({} failures)", failures.len())
+            }
+            BenchError::Inputs(message) => write!(f, "{}", message),
+            BenchError::Usage(message) => write!(f, "{}", message),
+            BenchError::Output(message) => write!(f, "cannot emit metrics: {}", message),
+            BenchError::ProofRejected(proof) => write!(f, "{} did not verify", proof),
+            BenchError::ProversDisagree { mock_accepts, proof_verifies } => write!(
+                f,
+                "mock prover {} the witness but a real proof {}",
+                if *mock_accepts { "accepts" } else { "rejects" },
+                if *proof_verifies { "verifies" } else { "does not verify" },
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BenchError {}
+
+/// Why an `EcdsaCircuit`'s witnesses cannot be laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessError {
+    MissingPublicKey,
+    MissingSignature,
+    MissingMessageHash,
+    // The public key is the point at infinity or off the curve, so it has
+    // no affine coordinates to assign
+    PointNotOnCurve,
+}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessError::MissingPublicKey => write!(f, "public key missing"),
+            WitnessError::MissingSignature => write!(f, "signature missing"),
+            WitnessError::MissingMessageHash => write!(f, "message hash missing"),
+            WitnessError::PointNotOnCurve => write!(f, "public key is not an affine point on the curve"),
+        }
+    }
+}
+
+impl std::error::Error for WitnessError {}
+
+impl From<WitnessError> for ErrorFront {
+    fn from(_: WitnessError) -> Self {
+        ErrorFront::Synthesis
+    }
+}
+
+impl From<WitnessError> for BenchError {
+    fn from(err: WitnessError) -> Self {
+        BenchError::Witness(err)
+    }
+}
+
+impl From<ErrorFront> for BenchError {
+    fn from(err: ErrorFront) -> Self {
+        BenchError::Synthesis(err)
+    }
+}
+
+impl From<Error> for BenchError {
+    fn from(err: Error) -> Self {
+        BenchError::Backend(err)
+    }
+}
+
+impl From<InputsError> for BenchError {
+    fn from(err: InputsError) -> Self {
+        BenchError::Inputs(err.to_string())
+    }
+}
+
+impl From<io::Error> for BenchError {
+    fn from(err: io::Error) -> Self {
+        BenchError::Inputs(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthesis_error_maps_to_synthesis() {
+        let err = BenchError::from(ErrorFront::Synthesis);
+
+        assert!(matches!(err, BenchError::Synthesis(ErrorFront::Synthesis)));
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_witness_error_converts_to_synthesis() {
+        assert!(matches!(ErrorFront::from(WitnessError::MissingSignature), ErrorFront::Synthesis));
+        assert_eq!(WitnessError::PointNotOnCurve.to_string(), "public key is not an affine point on the curve");
+    }
+
+    #[test]
+    fn test_witness_and_rejected_proof_errors() {
+        let err = BenchError::from(WitnessError::MissingMessageHash);
+        assert!(matches!(err, BenchError::Witness(WitnessError::MissingMessageHash)));
+        assert_eq!(err.to_string(), "cannot synthesize signature: message hash missing");
+        assert_eq!(err.exit_code(), 2);
+
+        let err = BenchError::ProofRejected("batch proof");
+        assert_eq!(err.to_string(), "batch proof did not verify");
+        assert_eq!(err.exit_code(), 7);
+
+        let err = BenchError::ProversDisagree { mock_accepts: true, proof_verifies: false };
+        assert_eq!(err.to_string(), "mock prover accepts the witness but a real proof does not verify");
+        assert_eq!(err.exit_code(), 8);
+
+        let err = BenchError::Output("1 lint warning(s) with --abort-on-lint".to_string());
+        assert_eq!(err.to_string(), "cannot emit metrics: 1 lint warning(s) with --abort-on-lint");
+        assert_eq!(err.exit_code(), 9);
+    }
+}
@@ -0,0 +1,85 @@
+//! Dumped Benchmark Inputs
+//!
+//! Signatures are drawn from `OsRng`, so a run that fails verification cannot
+//! be repeated from its command line. `--dump-inputs <path>` writes the
+//! failing public key, message hash and signature as JSON, and
+//! `--inputs-from-stdin` reads that file back in place of a random signature.
+//! Field elements are stored as the hex of their canonical little-endian
+//! representation.
+
+use std::fmt;
+
+use ff::PrimeField;
+use group::prime::PrimeCurveAffine;
+use halo2_proofs::arithmetic::CurveAffine;
+use pasta_curves::pallas::{Affine, Base, Scalar};
+use serde_json::{json, Value};
+
+use crate::EcdsaCircuit;
+
+#[derive(Debug)]
+pub(crate) struct InputsError(String);
+
+impl fmt::Display for InputsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid inputs: {}", self.0)
+    }
+}
+
+fn to_hex<F: PrimeField<Repr = [u8; 32]>>(value: &F) -> String {
+    value.to_repr().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex<F: PrimeField<Repr = [u8; 32]>>(json: &Value, field: &str) -> Result<F, InputsError> {
+    let hex = json[field]
+        .as_str()
+        .ok_or_else(|| InputsError(format!("missing field {}", field)))?;
+    // Slicing below assumes one byte per character
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(InputsError(format!("{} is not 32 bytes of hex", field)));
+    }
+
+    let mut repr = [0u8; 32];
+    for (i, byte) in repr.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+            .map_err(|_| InputsError(format!("{} is not hex", field)))?;
+    }
+    Option::from(F::from_repr(repr))
+        .ok_or_else(|| InputsError(format!("{} is not a canonical field element", field)))
+}
+
+pub(crate) fn dump(circuit: &EcdsaCircuit<Affine>) -> String {
+    let public_key = circuit.public_key.unwrap().coordinates().unwrap();
+    let (r, s) = circuit.signature.unwrap();
+
+    let inputs = json!({
+        "public_key_x": to_hex(public_key.x()),
+        "public_key_y": to_hex(public_key.y()),
+        "message_hash": to_hex(&circuit.message_hash.unwrap()),
+        "r": to_hex(&r),
+        "s": to_hex(&s),
+    });
+    serde_json::to_string_pretty(&inputs).unwrap()
+}
+
+pub(crate) fn load(text: &str) -> Result<EcdsaCircuit<Affine>, InputsError> {
+    let json: Value = serde_json::from_str(text).map_err(|err| InputsError(err.to_string()))?;
+
+    let x: Base = from_hex(&json, "public_key_x")?;
+    let y: Base = from_hex(&json, "public_key_y")?;
+    let public_key: Affine = Option::from(Affine::from_xy(x, y))
+        .ok_or_else(|| InputsError("public key is not on the curve".to_string()))?;
+    // from_xy accepts (0, 0) as the identity, which has no coordinates to dump
+    if bool::from(public_key.is_identity()) {
+        return Err(InputsError("public key is the identity".to_string()));
+    }
+    let message_hash: Scalar = from_hex(&json, "message_hash")?;
+    let r: Scalar = from_hex(&json, "r")?;
+    let s: Scalar = from_hex(&json, "s")?;
+
+    Ok(EcdsaCircuit {
+        public_key: Some(public_key),
+        message_hash: Some(message_hash),
+        signature: Some((r, s)),
+    })
+}
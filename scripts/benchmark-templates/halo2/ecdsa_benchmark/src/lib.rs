@@ -0,0 +1,2958 @@
+//! ECDSA Circuit Implementation using the Pallas Curve
+//! 
+//! The Pallas curve is a prime-order curve designed for zero-knowledge proof systems.
+//! It is part of the Pasta curves family and has the following parameters:
+//! 
+//! - Base field characteristic (p): 
+//!   0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001
+//! 
+//! - Scalar field characteristic (r):
+//!   0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001
+//! 
+//! - Curve equation: y² = x³ + 5 (where a = 0, b = 5)
+//! 
+//! The curve is designed for efficient implementation and high security,
+//! providing approximately 128 bits of security.
+//!
+//! The circuit is over the base field, so point coordinates are native, and
+//! is committed with Vesta, whose scalar field that is. Scalars never become
+//! circuit values: they enter as their bits and only drive scalar mults.
+//! Rather than inverting s in the scalar field, the prover witnesses the
+//! point R = (h·s⁻¹)·G + (r·s⁻¹)·PK and the circuit checks
+//! s·R = h·G + R.x·PK. Since p is below the scalar modulus, R.x is already a
+//! canonical scalar, so the signature's r must be R.x itself. The message
+//! hash and the public key are both public inputs, so a proof says the
+//! signature is valid for that key, not merely for some key.
+
+#![allow(clippy::type_complexity)]
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    arithmetic::{Field, CurveAffine},
+    circuit::{Layouter, SimpleFloorPlanner, Value, AssignedCell, Region},
+    dev::MockProver,
+    plonk::{Circuit, ConstraintSystem, Column, Advice, Fixed, Instance, Selector, TableColumn,
+        Expression, VirtualCells, Error, ErrorFront, keygen_pk, keygen_vk},
+    poly::{Rotation, commitment::ParamsProver, ipa::commitment::ParamsIPA},
+};
+
+use ff::PrimeField;
+use group::{Curve, Group};
+use rand::{rngs::OsRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde_json::json;
+use pasta_curves::{
+    pallas::{Affine, Base, Point, Scalar},
+    vesta,
+};
+
+mod curve_ops;
+mod diagnostics;
+mod error;
+mod inputs;
+mod timings;
+
+use benchmark_metrics::{
+    collect_environment, Aggregator, AllocationGap, BenchmarkMetrics, CircuitSize, CpuSampler, GpuSampler,
+    Operation, TemplateArgs, Throughput, Timer,
+};
+pub use error::BenchError;
+use error::WitnessError;
+use halo2_common::{collect_circuit_stats, run_real_proof, CircuitStats, ProofArtifacts};
+
+// Maps a base field element (a point coordinate) to the scalar field by
+// reducing its canonical integer value mod the scalar modulus n. When the
+// base modulus exceeds n, as for Vesta, values in [n, p) wrap around
+// silently; use `base_to_scalar_reduced` where that matters.
+fn base_to_scalar<C: CurveAffine>(value: &C::Base) -> C::Scalar {
+    base_to_scalar_reduced::<C>(value).0
+}
+
+// `base_to_scalar`, plus whether the value was at least n and so had n
+// subtracted. The Pasta base moduli are below 2n, so a single flag covers
+// it: value = scalar + overflow·n, which is what an in-circuit
+// `r = R.x mod n` check has to constrain.
+fn base_to_scalar_reduced<C: CurveAffine>(value: &C::Base) -> (C::Scalar, bool) {
+    let bits = scalar_bits(value);
+    let scalar = recompose::<C::Scalar>(&bits);
+
+    // Compare against n bit by bit, most significant first
+    let modulus = modulus_bits::<C::Scalar>();
+    let width = bits.len().max(modulus.len());
+    let pad = |bits: Vec<bool>| {
+        let mut padded = vec![false; width - bits.len()];
+        padded.extend(bits);
+        padded
+    };
+    let overflow = pad(bits) >= pad(modulus);
+
+    (scalar, overflow)
+}
+
+// Width of the circuit field. A constraint over a 255-bit field does more
+// work than one over a 64-bit field, so counts are only comparable per bit.
+const FIELD_BITS: u32 = Base::NUM_BITS;
+
+// Bits of a field element, most significant first
+fn scalar_bits<F: PrimeField>(value: &F) -> Vec<bool> {
+    let mut bits: Vec<bool> = value.to_repr()
+        .as_ref()
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .take(F::NUM_BITS as usize)
+        .collect();
+    bits.reverse();
+    bits
+}
+
+// The integer with `bits`, most significant first, reduced into F
+fn recompose<F: PrimeField>(bits: &[bool]) -> F {
+    bits.iter().fold(F::ZERO, |acc, &bit| acc + acc + if bit { F::ONE } else { F::ZERO })
+}
+
+// Bits of the field modulus p, most significant first. p is an odd prime,
+// so p - 1 (representable as -1) is even and p is p - 1 with the low bit set.
+fn modulus_bits<F: PrimeField>() -> Vec<bool> {
+    let mut bits = scalar_bits(&-F::ONE);
+    *bits.last_mut().unwrap() = true;
+    bits
+}
+
+// The message hash is below r but may not be below p, so no single
+// instance value can hold it. The circuit exposes two running sums of its
+// decomposition instead, h mod p and h >> HASH_SPLIT_BITS. Together they fix
+// h: the low HASH_SPLIT_BITS bits are below p, so h mod p determines them
+// once the high bits are known.
+const HASH_SPLIT_BITS: usize = 128;
+
+// Instance rows binding the message hash
+const HASH_INSTANCE_ROWS: usize = 2;
+
+// Instance rows per signature: the message hash's, then the public key's x
+// and y coordinates
+const INSTANCE_ROWS: usize = HASH_INSTANCE_ROWS + 2;
+
+// The instance values binding `hash`: h mod p, then h >> HASH_SPLIT_BITS
+fn hash_instance<C: CurveAffine>(hash: &C::Scalar) -> [C::Base; HASH_INSTANCE_ROWS] {
+    let bits = scalar_bits(hash);
+    [recompose(&bits), recompose(&bits[..bits.len() - HASH_SPLIT_BITS])]
+}
+
+// Rows of the x and y columns used per scalar bit in `scalar_mult`: the
+// doubling, the addition and the selected accumulator
+const SCALAR_MULT_ROWS_PER_BIT: usize = 3;
+
+// Scalar bits per window of `fixed_base_mul`. Each window costs one lookup
+// and one point addition; the table grows as 2^bits per window.
+const FIXED_BASE_WINDOW_BITS: usize = 3;
+
+// Smallest circuit size that fits one verification, which is dominated by
+// its two variable-base scalar mults of about NUM_BITS *
+// SCALAR_MULT_ROWS_PER_BIT rows each. Larger circuit sizes run the same
+// verification at CircuitSize::k().
+const VERIFY_K: u32 = 11;
+
+// Windows of `window_bits` bits covering a full scalar of S, the last one
+// possibly partial
+fn fixed_base_windows<S: PrimeField>(window_bits: usize) -> usize {
+    (S::NUM_BITS as usize).div_ceil(window_bits)
+}
+
+// Affine doubling and addition outside the circuit, with the same formulas
+// as the "point double" and "point add" gates
+fn native_double<F: PrimeField>((x, y): (F, F)) -> (F, F) {
+    let lambda = (x.square() * F::from(3)) * (y + y).invert().expect("doubling a point with y = 0");
+    let x_r = lambda.square() - x - x;
+    (x_r, lambda * (x - x_r) - y)
+}
+
+fn native_add<F: PrimeField>((x1, y1): (F, F), (x2, y2): (F, F)) -> (F, F) {
+    let lambda = (y2 - y1) * (x2 - x1).invert().expect("adding points with equal x");
+    let x_r = lambda.square() - x1 - x2;
+    (x_r, lambda * (x1 - x_r) - y1)
+}
+
+// Witness of `point_add` for points given as (x, y, is identity), with the
+// identity at (0, 0): the slope, and the sum. The slope is unused when an
+// operand is the identity, and the tangent when the points share x.
+fn complete_add<F: PrimeField>((x1, y1, inf1): (F, F, bool), (x2, y2, inf2): (F, F, bool)) -> (F, (F, F, bool)) {
+    let tangent = || (x1.square() * F::from(3)) * (y1 + y1).invert().unwrap_or(F::ZERO);
+
+    if inf1 {
+        return (F::ZERO, (x2, y2, inf2));
+    }
+    if inf2 {
+        return (F::ZERO, (x1, y1, false));
+    }
+    if x1 == x2 && y1 + y2 == F::ZERO {
+        return (tangent(), (F::ZERO, F::ZERO, true));
+    }
+
+    let lambda = if x1 == x2 { tangent() } else { (y2 - y1) * (x2 - x1).invert().unwrap() };
+    let x3 = lambda.square() - x1 - x2;
+    (lambda, (x3, lambda * (x1 - x3) - y1, false))
+}
+
+// The points `fixed_base_mul` looks up, per window (least significant
+// first) and digit. With B_i = 2^(window_bits·i)·base, window i holds
+// (d + 2)·B_i rather than d·B_i: the offset keeps every entry and every
+// running sum clear of the identity, which has no affine coordinates, and
+// of each other, which incomplete addition cannot handle. The last window
+// holds d·B_i minus the sum of all the offsets, so the windows add up to
+// scalar·base, for scalars of S.
+fn fixed_base_points<S: PrimeField, F: PrimeField>(base: (F, F), window_bits: usize) -> Vec<Vec<(F, F)>> {
+    let windows = fixed_base_windows::<S>(window_bits);
+    let digits = 1 << window_bits;
+
+    let mut window_base = base;
+    let mut offsets = None;
+    let mut table = Vec::with_capacity(windows);
+    for i in 0..windows {
+        // multiples[m] = (m + 1)·B_i
+        let mut multiples = vec![window_base, native_double(window_base)];
+        while multiples.len() <= digits {
+            multiples.push(native_add(*multiples.last().unwrap(), window_base));
+        }
+
+        if i + 1 < windows {
+            table.push(multiples[1..=digits].to_vec());
+            offsets = Some(match offsets {
+                None => multiples[1],
+                Some(sum) => native_add(sum, multiples[1]),
+            });
+            for _ in 0..window_bits {
+                window_base = native_double(window_base);
+            }
+        } else {
+            let (x, y) = offsets.expect("a scalar spans at least two windows");
+            let minus_offsets = (x, -y);
+            table.push(
+                (0..digits)
+                    .map(|d| if d == 0 { minus_offsets } else { native_add(multiples[d - 1], minus_offsets) })
+                    .collect(),
+            );
+        }
+    }
+    table
+}
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct EcdsaConfig {
+    q_on_curve: Selector,
+    x: Column<Advice>,     // point x coordinate
+    y: Column<Advice>,     // point y coordinate
+    instance: Column<Instance>, // hashes and public keys, INSTANCE_ROWS per signature
+    q_decompose: Selector,
+    bit: Column<Advice>,   // scalar bits, most significant first
+    acc: Column<Advice>,   // running sum of the scalar bits
+    lt: Column<Advice>,    // bits so far are below those of the modulus
+    eq: Column<Advice>,    // bits so far equal those of the modulus
+    modulus_bit: Column<Fixed>, // modulus bits, most significant first
+    q_double: Selector,
+    q_add: Selector,
+    q_select: Selector,
+    lambda: Column<Advice>, // slope of the doubling or addition in this row
+    px: Column<Advice>,     // second operand of an addition
+    py: Column<Advice>,
+    sel_bit: Column<Advice>, // scalar bit choosing the next accumulator
+    started: Column<Advice>, // a set scalar bit has been seen
+    q_fixed_base: Selector,
+    window: Column<Fixed>,        // 1-based window index of `fixed_base_mul`
+    window_sum: Column<Advice>,   // scalar shifted right past the earlier windows
+    window_bits: usize,
+    table_window: TableColumn,    // multiples of the generator, by window
+    table_digit: TableColumn,     // and digit
+    table_x: TableColumn,
+    table_y: TableColumn,
+    q_add_complete: Selector,
+    inf: Column<Advice>,    // the point in x, y is the identity
+    pinf: Column<Advice>,   // the point in px, py is the identity
+    alpha: Column<Advice>,  // inverse of x₂ - x₁, or 0
+    beta: Column<Advice>,   // inverse of y₁ + y₂, or 0
+    finite: Column<Advice>, // neither operand is the identity
+}
+
+type Cell<F> = AssignedCell<F, F>;
+
+// Affine coordinates of a point assigned in the circuit
+type AssignedPoint<F> = (Cell<F>, Cell<F>);
+
+// A point with an explicit flag for the identity, which has no affine
+// coordinates. The identity is stored as (0, 0) with the flag set.
+#[derive(Debug, Clone)]
+struct FlaggedPoint<F: Field> {
+    x: Cell<F>,
+    y: Cell<F>,
+    infinity: Cell<F>,
+}
+
+// Chip to handle curve operations
+struct EcdsaChip<C: CurveAffine> {
+    config: EcdsaConfig,
+    _marker: std::marker::PhantomData<C>,
+}
+
+impl<C: CurveAffine> EcdsaChip<C> {
+    fn construct(config: EcdsaConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    // Each point operation below writes its result to row `offset` of the
+    // x and y columns, and its gate reads the first operand from the row
+    // above. Callers lay the operand out there and give every operation its
+    // own row.
+    fn point_double(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        point: &AssignedPoint<C::Base>,
+    ) -> Result<AssignedPoint<C::Base>, ErrorFront> {
+        let (x, y) = point;
+        
+        let x_value = x.value().copied();
+        let y_value = y.value().copied();
+        
+        let lambda = x_value.zip(y_value).map(|(x, y)| {
+            let xx = x * x;
+            let two_y = y + y;
+            let three_xx = xx + xx + xx;
+            three_xx * two_y.invert().unwrap_or(C::Base::ZERO)
+        });
+    
+        let x_r = lambda.zip(x_value).map(|(l, x)| {
+            l * l - x - x
+        });
+    
+        let y_r = lambda.zip(x_value).zip(x_r).zip(y_value)
+            .map(|(((l, x), xr), y)| {
+                l * (x - xr) - y
+            });
+
+        self.config.q_double.enable(region, offset)?;
+        region.assign_advice(|| "lambda_double", self.config.lambda, offset, || lambda)?;
+
+        let x_r_cell = region.assign_advice(
+            || "x_double",
+            self.config.x,
+            offset,
+            || x_r,
+        )?;
+
+        let y_r_cell = region.assign_advice(
+            || "y_double",
+            self.config.y,
+            offset,
+            || y_r,
+        )?;
+
+        Ok((x_r_cell, y_r_cell))
+    }
+
+    // Adds `p2`, copied into the px and py columns of row `offset`, to the
+    // point `p1` in the row above. Incomplete: the points must be distinct,
+    // not inverses and neither the identity, which holds wherever the scalar
+    // mults use it. `point_add` handles every case. Points sharing x get a
+    // zero slope that fails the gate, as a doubling of y = 0 does.
+    fn point_add_incomplete(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        p1: &AssignedPoint<C::Base>,
+        p2: &AssignedPoint<C::Base>,
+    ) -> Result<AssignedPoint<C::Base>, ErrorFront> {
+        let p2 = (
+            p2.0.copy_advice(|| "px", region, self.config.px, offset)?,
+            p2.1.copy_advice(|| "py", region, self.config.py, offset)?,
+        );
+        self.point_add_incomplete_in_place(region, offset, p1, &p2)
+    }
+
+    // `point_add_incomplete` with `p2` already assigned to the px and py
+    // columns of row `offset`
+    fn point_add_incomplete_in_place(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        p1: &AssignedPoint<C::Base>,
+        p2: &AssignedPoint<C::Base>,
+    ) -> Result<AssignedPoint<C::Base>, ErrorFront> {
+        let (x1, y1) = p1;
+        let (x2, y2) = p2;
+
+        let x1_value = x1.value().copied();
+        let y1_value = y1.value().copied();
+        let x2_value = x2.value().copied();
+        let y2_value = y2.value().copied();
+
+        let lambda = x2_value.zip(x1_value).zip(y2_value).zip(y1_value).map(
+            |(((x2, x1), y2), y1)| {
+                let dy = y2 - y1;
+                let dx = x2 - x1;
+                dy * dx.invert().unwrap_or(C::Base::ZERO)
+            }
+        );
+
+        let x_r = lambda.zip(x1_value).zip(x2_value)
+            .map(|((l, x1), x2)| l * l - x1 - x2);
+            
+        let y_r = lambda.zip(x1_value).zip(x_r).zip(y1_value)
+            .map(|(((l, x1), xr), y1)| l * (x1 - xr) - y1);
+
+        self.config.q_add.enable(region, offset)?;
+        region.assign_advice(|| "lambda_add", self.config.lambda, offset, || lambda)?;
+
+        let x_r_cell = region.assign_advice(
+            || "x_add",
+            self.config.x,
+            offset,
+            || x_r,
+        )?;
+
+        let y_r_cell = region.assign_advice(
+            || "y_add",
+            self.config.y,
+            offset,
+            || y_r,
+        )?;
+
+        Ok((x_r_cell, y_r_cell))
+    }
+
+    // Flags `point`, copied into the x and y columns of row `offset`, as
+    // finite or as the identity
+    fn flag_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        point: &AssignedPoint<C::Base>,
+        infinity: bool,
+    ) -> Result<FlaggedPoint<C::Base>, ErrorFront> {
+        let flag = if infinity { C::Base::ONE } else { C::Base::ZERO };
+        Ok(FlaggedPoint {
+            x: point.0.copy_advice(|| "x_flagged", region, self.config.x, offset)?,
+            y: point.1.copy_advice(|| "y_flagged", region, self.config.y, offset)?,
+            infinity: region.assign_advice_from_constant(|| "infinity", self.config.inf, offset, flag)?,
+        })
+    }
+
+    // Adds `p2`, copied into the px, py and pinf columns of row `offset`, to
+    // the point `p1` in the x, y and inf columns of the row above. Unlike
+    // `point_add_incomplete` this is total: an identity operand returns the
+    // other one, equal points are doubled and inverse points give the
+    // identity.
+    fn point_add(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        p1: &FlaggedPoint<C::Base>,
+        p2: &FlaggedPoint<C::Base>,
+    ) -> Result<FlaggedPoint<C::Base>, ErrorFront> {
+        let p2 = FlaggedPoint {
+            x: p2.x.copy_advice(|| "px", region, self.config.px, offset)?,
+            y: p2.y.copy_advice(|| "py", region, self.config.py, offset)?,
+            infinity: p2.infinity.copy_advice(|| "pinf", region, self.config.pinf, offset)?,
+        };
+
+        let as_native = |p: &FlaggedPoint<C::Base>| {
+            p.x.value().copied()
+                .zip(p.y.value().copied())
+                .zip(p.infinity.value().copied())
+                .map(|((x, y), infinity)| (x, y, infinity == C::Base::ONE))
+        };
+        let (p1_native, p2_native) = (as_native(p1), as_native(&p2));
+        let sum = p1_native.zip(p2_native).map(|(p1, p2)| complete_add(p1, p2));
+
+        let dx = p2_native.zip(p1_native).map(|(p2, p1)| p2.0 - p1.0);
+        let sy = p2_native.zip(p1_native).map(|(p2, p1)| p2.1 + p1.1);
+        let finite = p1_native.zip(p2_native).map(|(p1, p2)| {
+            if p1.2 || p2.2 { C::Base::ZERO } else { C::Base::ONE }
+        });
+
+        self.config.q_add_complete.enable(region, offset)?;
+        region.assign_advice(|| "lambda_add", self.config.lambda, offset, || sum.map(|(lambda, _)| lambda))?;
+        region.assign_advice(|| "dx inverse", self.config.alpha, offset, || {
+            dx.map(|dx| dx.invert().unwrap_or(C::Base::ZERO))
+        })?;
+        region.assign_advice(|| "sy inverse", self.config.beta, offset, || {
+            sy.map(|sy| sy.invert().unwrap_or(C::Base::ZERO))
+        })?;
+        region.assign_advice(|| "both finite", self.config.finite, offset, || finite)?;
+
+        let result = sum.map(|(_, result)| result);
+        Ok(FlaggedPoint {
+            x: region.assign_advice(|| "x_sum", self.config.x, offset, || result.map(|r| r.0))?,
+            y: region.assign_advice(|| "y_sum", self.config.y, offset, || result.map(|r| r.1))?,
+            infinity: region.assign_advice(|| "sum infinity", self.config.inf, offset, || {
+                result.map(|r| if r.2 { C::Base::ONE } else { C::Base::ZERO })
+            })?,
+        })
+    }
+
+    fn scalar_mult(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        scalar: &Cell<C::Base>,
+        point: &AssignedPoint<C::Base>,
+    ) -> Result<AssignedPoint<C::Base>, ErrorFront> {
+        self.scalar_mult_with_bits(region, scalar, point)
+            .map(|(result, _bits)| result)
+    }
+
+    // scalar·point for a scalar of the curve's scalar field, which is not
+    // the circuit field. The scalar is witnessed only as its bits, which
+    // must be the canonical decomposition of an integer below the scalar
+    // modulus.
+    fn curve_scalar_mult(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        scalar: Value<C::Scalar>,
+        point: &AssignedPoint<C::Base>,
+    ) -> Result<AssignedPoint<C::Base>, ErrorFront> {
+        let bits = scalar.map(|s| scalar_bits::<C::Scalar>(&s));
+        let (bit_cells, _sums) = self.decompose_bits_below(region, bits, &modulus_bits::<C::Scalar>())?;
+        self.double_and_add(region, &bit_cells, point)
+    }
+
+    // Decomposes `scalar` into NUM_BITS boolean cells, most significant bit
+    // first, whose running sum is constrained back to `scalar`
+    fn decompose_scalar(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        scalar: &Cell<C::Base>,
+    ) -> Result<Vec<Cell<C::Base>>, ErrorFront> {
+        let bits = scalar.value().map(scalar_bits::<C::Base>);
+        self.decompose_bits(region, scalar, bits)
+    }
+
+    // Lays out `bits` as the decomposition of `scalar`. Only the canonical
+    // bits of `scalar` satisfy the constraints; taking the bits as an
+    // argument lets tests try others.
+    fn decompose_bits(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        scalar: &Cell<C::Base>,
+        bits: Value<Vec<bool>>,
+    ) -> Result<Vec<Cell<C::Base>>, ErrorFront> {
+        let (bits, sums) = self.decompose_bits_below(region, bits, &modulus_bits::<C::Base>())?;
+        region.constrain_equal(sums[sums.len() - 1].cell(), scalar.cell())?;
+        Ok(bits)
+    }
+
+    // Lays out `bits` as boolean cells, most significant first, and
+    // constrains them to an integer below `modulus`, given by its bits in
+    // the same order. Also returns the running sums: entry j holds the value
+    // of the top j bits, reduced into the circuit field.
+    fn decompose_bits_below(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        bits: Value<Vec<bool>>,
+        modulus: &[bool],
+    ) -> Result<(
+        Vec<Cell<C::Base>>,
+        Vec<Cell<C::Base>>,
+    ), ErrorFront> {
+        let as_field = |bit: bool| if bit { C::Base::ONE } else { C::Base::ZERO };
+
+        let mut acc = region.assign_advice_from_constant(
+            || "decomposition start",
+            self.config.acc,
+            0,
+            C::Base::ZERO,
+        )?;
+        let mut lt = region.assign_advice_from_constant(
+            || "below modulus start",
+            self.config.lt,
+            0,
+            C::Base::ZERO,
+        )?;
+        let mut eq = region.assign_advice_from_constant(
+            || "equal to modulus start",
+            self.config.eq,
+            0,
+            C::Base::ONE,
+        )?;
+
+        let mut bit_cells = Vec::with_capacity(modulus.len());
+        let mut sums = vec![acc.clone()];
+        for (i, &m) in modulus.iter().enumerate() {
+            self.config.q_decompose.enable(region, i)?;
+
+            region.assign_fixed(
+                || format!("modulus bit {}", i),
+                self.config.modulus_bit,
+                i,
+                || Value::known(as_field(m)),
+            )?;
+
+            let bit = bits.as_ref().map(|bits| bits[i]);
+            let bit_cell = region.assign_advice(
+                || format!("bit {}", i),
+                self.config.bit,
+                i,
+                || bit.map(as_field),
+            )?;
+
+            acc = region.assign_advice(
+                || "decomposition running sum",
+                self.config.acc,
+                i + 1,
+                || acc.value().copied().zip(bit).map(|(acc, bit)| acc + acc + as_field(bit)),
+            )?;
+            lt = region.assign_advice(
+                || "below modulus",
+                self.config.lt,
+                i + 1,
+                || lt.value().copied().zip(eq.value().copied()).zip(bit)
+                    .map(|((lt, eq), bit)| if !bit && m { lt + eq } else { lt }),
+            )?;
+            eq = region.assign_advice(
+                || "equal to modulus",
+                self.config.eq,
+                i + 1,
+                || eq.value().copied().zip(bit)
+                    .map(|(eq, bit)| if bit == m { eq } else { C::Base::ZERO }),
+            )?;
+            bit_cells.push(bit_cell);
+            sums.push(acc.clone());
+        }
+
+        region.constrain_constant(lt.cell(), C::Base::ONE)?;
+        Ok((bit_cells, sums))
+    }
+
+    // One accumulator update of the double-and-add at row `offset`, whose
+    // three rows above hold the previous accumulator, its double and the
+    // double plus the base point (also in px, py of the row above). Before
+    // the first set bit (`started` = 0) the accumulator is a placeholder, so
+    // a set bit replaces it with the base point rather than taking the sum.
+    // Returns the new accumulator and `started` for the next step, which is
+    // written SCALAR_MULT_ROWS_PER_BIT rows further down.
+    fn select_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        bit: &Cell<C::Base>,
+        started: &Cell<C::Base>,
+        candidates: [&AssignedPoint<C::Base>; 4],
+    ) -> Result<(
+        AssignedPoint<C::Base>,
+        Cell<C::Base>,
+    ), ErrorFront> {
+        self.config.q_select.enable(region, offset)?;
+        let bit = bit.copy_advice(|| "select bit", region, self.config.sel_bit, offset)?;
+
+        // Index into `candidates`: [accumulator, base point, double, sum]
+        let pick = started.value().zip(bit.value()).map(|(started, bit)| {
+            match (*started == C::Base::ONE, *bit == C::Base::ONE) {
+                (false, false) => 0, // still leading zeros
+                (false, true) => 1,  // first set bit: acc = point
+                (true, false) => 2,
+                (true, true) => 3,
+            }
+        });
+        let choose = |values: [Value<C::Base>; 4]| {
+            let [a, b, c, d] = values;
+            pick.zip(a).zip(b).zip(c).zip(d)
+                .map(|((((i, a), b), c), d)| [a, b, c, d][i])
+        };
+        let x = choose(candidates.map(|p| p.0.value().copied()));
+        let y = choose(candidates.map(|p| p.1.value().copied()));
+
+        let x_cell = region.assign_advice(|| "x_select", self.config.x, offset, || x)?;
+        let y_cell = region.assign_advice(|| "y_select", self.config.y, offset, || y)?;
+
+        let started_next = region.assign_advice(
+            || "started",
+            self.config.started,
+            offset + SCALAR_MULT_ROWS_PER_BIT,
+            || started.value().copied().zip(bit.value().copied()).map(|(s, b)| s + b - s * b),
+        )?;
+
+        Ok(((x_cell, y_cell), started_next))
+    }
+
+    // Same as `scalar_mult`, but also hands back the constrained bit cells
+    // (most significant first) so callers can reuse the decomposition
+    fn scalar_mult_with_bits(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        scalar: &Cell<C::Base>,
+        point: &AssignedPoint<C::Base>,
+    ) -> Result<(
+        AssignedPoint<C::Base>,
+        Vec<Cell<C::Base>>,
+    ), ErrorFront> {
+        let bit_cells = self.decompose_scalar(region, scalar)?;
+        let result = self.double_and_add(region, &bit_cells, point)?;
+        Ok((result, bit_cells))
+    }
+
+    // The scalar with `bit_cells`, most significant first, times `point`.
+    // The scalar is unknown during keygen, so each step doubles and adds
+    // unconditionally and the bit only picks which result carries on. Until
+    // the first set bit the accumulator holds `point` as a placeholder,
+    // since the identity has no affine coordinates; a scalar without a set
+    // bit leaves `started` at 0 and the circuit unsatisfied.
+    //
+    // Row 0 holds a copy of `point` as the initial accumulator; bit i then
+    // takes rows 1 + 3i (double), 2 + 3i (add) and 3 + 3i (select).
+    fn double_and_add(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        bit_cells: &[Cell<C::Base>],
+        point: &AssignedPoint<C::Base>,
+    ) -> Result<AssignedPoint<C::Base>, ErrorFront> {
+        let mut acc = (
+            point.0.copy_advice(|| "x_start", region, self.config.x, 0)?,
+            point.1.copy_advice(|| "y_start", region, self.config.y, 0)?,
+        );
+        let mut started = region.assign_advice_from_constant(
+            || "started",
+            self.config.started,
+            SCALAR_MULT_ROWS_PER_BIT,
+            C::Base::ZERO,
+        )?;
+        for (i, bit_cell) in bit_cells.iter().enumerate() {
+            let row = 1 + i * SCALAR_MULT_ROWS_PER_BIT;
+            let doubled = self.point_double(region, row, &acc)?;
+            let added = self.point_add_incomplete(region, row + 1, &doubled, point)?;
+            (acc, started) = self.select_point(
+                region,
+                row + 2,
+                bit_cell,
+                &started,
+                [&acc, point, &doubled, &added],
+            )?;
+        }
+        region.constrain_constant(started.cell(), C::Base::ONE)?;
+
+        Ok(acc)
+    }
+
+    fn generator_coordinates() -> (C::Base, C::Base) {
+        let coordinates = C::generator().coordinates().unwrap();
+        (*coordinates.x(), *coordinates.y())
+    }
+
+    // Loads the multiples of the generator used by `fixed_base_mul`. Row 0
+    // is all zeros, which is what the lookup sees on rows where it is off;
+    // real entries start at window 1.
+    fn load_fixed_base_table(&self, layouter: &mut impl Layouter<C::Base>) -> Result<(), ErrorFront> {
+        let points = fixed_base_points::<C::Scalar, _>(Self::generator_coordinates(), self.config.window_bits);
+        let columns = [self.config.table_window, self.config.table_digit, self.config.table_x, self.config.table_y];
+
+        layouter.assign_table(
+            || "fixed base multiples",
+            |mut table| {
+                for column in columns {
+                    table.assign_cell(|| "lookup off", column, 0, || Value::known(C::Base::ZERO))?;
+                }
+
+                let entries = points.iter().enumerate().flat_map(|(window, digits)| {
+                    digits.iter().enumerate().map(move |(digit, point)| (window, digit, point))
+                });
+                for (row, (window, digit, &(x, y))) in entries.enumerate() {
+                    let values = [C::Base::from(window as u64 + 1), C::Base::from(digit as u64), x, y];
+                    for (column, value) in columns.into_iter().zip(values) {
+                        table.assign_cell(|| "fixed base entry", column, row + 1, || Value::known(value))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // scalar·G for the fixed generator G, one window of `window_bits` bits
+    // at a time. The scalar's canonical decomposition fixes a running sum
+    // per window, the shifted scalar z_i; each window's digit
+    // z_i - 2^window_bits·z_(i+1) is looked up together with its entry from
+    // `load_fixed_base_table`, and the entries are summed with one point
+    // addition per window instead of a double and an add per bit. A zero
+    // scalar is not handled: its last addition adds inverse points and
+    // leaves the circuit unsatisfied.
+    //
+    // Like `curve_scalar_mult` this takes a scalar of the curve's scalar
+    // field as bits. It also returns the running sums of its decomposition
+    // (see `decompose_bits_below`), which is how callers bind the scalar.
+    //
+    // The decomposition takes the bit, acc, lt and eq columns from row 0;
+    // window i takes row i of the window, window_sum, px/py and x/y columns.
+    fn fixed_base_mul(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        scalar: Value<C::Scalar>,
+    ) -> Result<(
+        AssignedPoint<C::Base>,
+        Vec<Cell<C::Base>>,
+    ), ErrorFront> {
+        let window_bits = self.config.window_bits;
+        let windows = fixed_base_windows::<C::Scalar>(window_bits);
+        let num_bits = C::Scalar::NUM_BITS as usize;
+        let points = fixed_base_points::<C::Scalar, _>(Self::generator_coordinates(), window_bits);
+
+        let bits = scalar.map(|s| scalar_bits::<C::Scalar>(&s));
+        let (_, sums) = self.decompose_bits_below(region, bits.clone(), &modulus_bits::<C::Scalar>())?;
+
+        // Bit p counts from the least significant end; `bits` is most
+        // significant first
+        let digit = |window: usize| bits.as_ref().map(|bits| {
+            (0..window_bits)
+                .map(|j| window * window_bits + j)
+                .filter(|&p| p < num_bits && bits[num_bits - 1 - p])
+                .map(|p| 1 << (p - window * window_bits))
+                .sum::<usize>()
+        });
+
+        let mut acc: Option<AssignedPoint<C::Base>> = None;
+        for i in 0..windows {
+            self.config.q_fixed_base.enable(region, i)?;
+            region.assign_fixed(
+                || format!("window {}", i),
+                self.config.window,
+                i,
+                || Value::known(C::Base::from(i as u64 + 1)),
+            )?;
+            sums[num_bits.saturating_sub(i * window_bits)]
+                .copy_advice(|| "window sum", region, self.config.window_sum, i)?;
+
+            let point = digit(i).map(|d| points[i][d]);
+            let entry = (
+                region.assign_advice(|| "px_window", self.config.px, i, || point.map(|p| p.0))?,
+                region.assign_advice(|| "py_window", self.config.py, i, || point.map(|p| p.1))?,
+            );
+            acc = Some(match acc {
+                None => (
+                    entry.0.copy_advice(|| "x_window", region, self.config.x, i)?,
+                    entry.1.copy_advice(|| "y_window", region, self.config.y, i)?,
+                ),
+                Some(acc) => self.point_add_incomplete_in_place(region, i, &acc, &entry)?,
+            });
+        }
+        // Nothing is left above the last window
+        sums[0].copy_advice(|| "window sum", region, self.config.window_sum, windows)?;
+
+        let acc = acc.expect("a scalar spans at least two windows");
+        Ok((acc, sums))
+    }
+}
+
+// ECDSA circuit structure
+#[derive(Default)]
+struct EcdsaCircuit<C: CurveAffine> {
+    // Public inputs
+    public_key: Option<C>,
+    message_hash: Option<C::Scalar>,
+    
+    // Private inputs (witness)
+    signature: Option<(C::Scalar, C::Scalar)>, // (r, s)
+}
+
+// Batch of ECDSA verifications in one circuit; the hash and public key of
+// the i-th signature are expected in rows INSTANCE_ROWS·i onwards of the
+// instance column. Benchmarks batch through `prove_batch` instead, so only
+// the tests lay it out.
+#[cfg(test)]
+#[derive(Default)]
+struct EcdsaBatchCircuit<C: CurveAffine> {
+    signatures: Vec<EcdsaCircuit<C>>,
+}
+
+#[cfg(test)]
+impl<C: CurveAffine> Circuit<C::Base> for EcdsaBatchCircuit<C> {
+    type Config = EcdsaConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            signatures: self.signatures
+                .iter()
+                .map(|signature| signature.without_witnesses())
+                .collect(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+        EcdsaCircuit::<C>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<C::Base>,
+    ) -> Result<(), ErrorFront> {
+        let chip: EcdsaChip<C> = EcdsaChip::construct(config);
+        chip.load_fixed_base_table(&mut layouter)?;
+
+        for (index, signature) in self.signatures.iter().enumerate() {
+            signature.assign_verification(
+                &chip,
+                &mut layouter.namespace(|| format!("signature {}", index)),
+                index,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Circuit implementation
+impl<C: CurveAffine> Circuit<C::Base> for EcdsaCircuit<C> {
+    type Config = EcdsaConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+        Self::configure_with_window(meta, FIXED_BASE_WINDOW_BITS)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<C::Base>,
+    ) -> Result<(), ErrorFront> {
+        let chip: EcdsaChip<C> = EcdsaChip::construct(config);
+        chip.load_fixed_base_table(&mut layouter)?;
+        self.assign_verification(&chip, &mut layouter, 0)
+    }
+}
+
+impl<C: CurveAffine> EcdsaCircuit<C> {
+    // `configure` with `fixed_base_mul` using windows of `window_bits` bits
+    fn configure_with_window(meta: &mut ConstraintSystem<C::Base>, window_bits: usize) -> EcdsaConfig {
+        assert!((2..=8).contains(&window_bits), "fixed-base windows take 2 to 8 bits");
+
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let instance = meta.instance_column();
+        let bit = meta.advice_column();
+        let acc = meta.advice_column();
+        let lt = meta.advice_column();
+        let eq = meta.advice_column();
+        let modulus_bit = meta.fixed_column();
+        let constants = meta.fixed_column();
+        let lambda = meta.advice_column();
+        let px = meta.advice_column();
+        let py = meta.advice_column();
+        let sel_bit = meta.advice_column();
+        let started = meta.advice_column();
+        let window = meta.fixed_column();
+        let window_sum = meta.advice_column();
+        let inf = meta.advice_column();
+        let pinf = meta.advice_column();
+        let alpha = meta.advice_column();
+        let beta = meta.advice_column();
+        let finite = meta.advice_column();
+        let table_window = meta.lookup_table_column();
+        let table_digit = meta.lookup_table_column();
+        let table_x = meta.lookup_table_column();
+        let table_y = meta.lookup_table_column();
+        let q_on_curve = meta.selector();
+        let q_decompose = meta.selector();
+        let q_double = meta.selector();
+        let q_add = meta.selector();
+        let q_select = meta.selector();
+        let q_fixed_base = meta.complex_selector();
+        let q_add_complete = meta.selector();
+
+        meta.enable_equality(x);
+        meta.enable_equality(y);
+        meta.enable_equality(instance);
+        meta.enable_equality(bit);
+        meta.enable_equality(acc);
+        meta.enable_equality(lt);
+        meta.enable_equality(eq);
+        meta.enable_equality(px);
+        meta.enable_equality(py);
+        meta.enable_equality(sel_bit);
+        meta.enable_equality(started);
+        meta.enable_equality(window_sum);
+        meta.enable_equality(inf);
+        meta.enable_equality(pinf);
+        meta.enable_constant(constants);
+
+        meta.create_gate("scalar decomposition", |meta| {
+            let q_decompose = meta.query_selector(q_decompose);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+
+            vec![
+                // Each bit is boolean
+                q_decompose.clone() * bit.clone() * (Expression::Constant(C::Base::ONE) - bit.clone()),
+
+                // acc' = 2 * acc + bit
+                q_decompose * (acc_next - (acc_cur.clone() + acc_cur + bit)),
+            ]
+        });
+
+        // NUM_BITS bits can encode values up to 2^NUM_BITS - 1 > p, so a
+        // scalar s would also decompose as s + p. Compare the bits against
+        // those of the modulus in `modulus_bit`, most significant first; the
+        // final lt must be 1.
+        meta.create_gate("canonical decomposition", |meta| {
+            let q_decompose = meta.query_selector(q_decompose);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let m = meta.query_fixed(modulus_bit, Rotation::cur());
+            let lt_cur = meta.query_advice(lt, Rotation::cur());
+            let lt_next = meta.query_advice(lt, Rotation::next());
+            let eq_cur = meta.query_advice(eq, Rotation::cur());
+            let eq_next = meta.query_advice(eq, Rotation::next());
+            let one = Expression::Constant(C::Base::ONE);
+            let two = Expression::Constant(C::Base::from(2));
+
+            let xor = bit.clone() + m.clone() - two * bit.clone() * m.clone();
+
+            vec![
+                // eq' = eq * (bit == m)
+                q_decompose.clone() * (eq_next - eq_cur.clone() * (one.clone() - xor)),
+
+                // lt' = lt + eq * (bit < m)
+                q_decompose * (lt_next - (lt_cur + eq_cur * (one - bit) * m)),
+            ]
+        });
+
+        // Affine point arithmetic on y² = x³ + b. The first operand is the
+        // point in the row above; the slope is witnessed in `lambda`.
+        meta.create_gate("point double", |meta| {
+            let q_double = meta.query_selector(q_double);
+            let x0 = meta.query_advice(x, Rotation::prev());
+            let y0 = meta.query_advice(y, Rotation::prev());
+            let x1 = meta.query_advice(x, Rotation::cur());
+            let y1 = meta.query_advice(y, Rotation::cur());
+            let lambda = meta.query_advice(lambda, Rotation::cur());
+            let three = Expression::Constant(C::Base::from(3));
+
+            vec![
+                // λ·2y₀ = 3x₀²
+                q_double.clone() * (lambda.clone() * (y0.clone() + y0.clone()) - three * x0.clone() * x0.clone()),
+                // x₁ = λ² - 2x₀
+                q_double.clone() * (x1.clone() - (lambda.clone() * lambda.clone() - x0.clone() - x0.clone())),
+                // y₁ = λ(x₀ - x₁) - y₀
+                q_double * (y1 - (lambda * (x0 - x1) - y0)),
+            ]
+        });
+
+        meta.create_gate("point add", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let x1 = meta.query_advice(x, Rotation::prev());
+            let y1 = meta.query_advice(y, Rotation::prev());
+            let x2 = meta.query_advice(px, Rotation::cur());
+            let y2 = meta.query_advice(py, Rotation::cur());
+            let x3 = meta.query_advice(x, Rotation::cur());
+            let y3 = meta.query_advice(y, Rotation::cur());
+            let lambda = meta.query_advice(lambda, Rotation::cur());
+
+            vec![
+                // λ(x₂ - x₁) = y₂ - y₁
+                q_add.clone() * (lambda.clone() * (x2.clone() - x1.clone()) - (y2 - y1.clone())),
+                // x₃ = λ² - x₁ - x₂
+                q_add.clone() * (x3.clone() - (lambda.clone() * lambda.clone() - x1.clone() - x2)),
+                // y₃ = λ(x₁ - x₃) - y₁
+                q_add * (y3 - (lambda * (x1 - x3) - y1)),
+            ]
+        });
+
+        // Total addition of flagged points: p1 = (x₁, y₁, a) in the row above,
+        // p2 = (x₂, y₂, b) in px, py, pinf and the sum (x₃, y₃, c) in this row.
+        // dx_zero and sy_zero are 1 exactly when x₂ - x₁ and y₁ + y₂ are 0,
+        // given the inverses witnessed in alpha and beta.
+        meta.create_gate("point add complete", |meta| {
+            let q = meta.query_selector(q_add_complete);
+            let x1 = meta.query_advice(x, Rotation::prev());
+            let y1 = meta.query_advice(y, Rotation::prev());
+            let a = meta.query_advice(inf, Rotation::prev());
+            let x2 = meta.query_advice(px, Rotation::cur());
+            let y2 = meta.query_advice(py, Rotation::cur());
+            let b = meta.query_advice(pinf, Rotation::cur());
+            let x3 = meta.query_advice(x, Rotation::cur());
+            let y3 = meta.query_advice(y, Rotation::cur());
+            let c = meta.query_advice(inf, Rotation::cur());
+            let lambda = meta.query_advice(lambda, Rotation::cur());
+            let alpha = meta.query_advice(alpha, Rotation::cur());
+            let beta = meta.query_advice(beta, Rotation::cur());
+            let finite = meta.query_advice(finite, Rotation::cur());
+            let one = Expression::Constant(C::Base::ONE);
+            let three = Expression::Constant(C::Base::from(3));
+
+            let dx = x2.clone() - x1.clone();
+            let dy = y2.clone() - y1.clone();
+            let sy = y1.clone() + y2.clone();
+            let dx_zero = one.clone() - dx.clone() * alpha;
+            let sy_zero = one.clone() - sy.clone() * beta;
+
+            vec![
+                q.clone() * (finite.clone() - (one.clone() - a.clone()) * (one.clone() - b.clone())),
+                q.clone() * dx.clone() * dx_zero.clone(),
+                q.clone() * sy * sy_zero.clone(),
+
+                // Distinct x: the chord, λ(x₂ - x₁) = y₂ - y₁
+                q.clone() * finite.clone() * dx.clone() * (lambda.clone() * dx - dy),
+                // Equal x: the tangent, λ·2y₁ = 3x₁². The identity at (0, 0)
+                // satisfies it with any λ.
+                q.clone() * dx_zero.clone() * (lambda.clone() * (y1.clone() + y1.clone()) - three * x1.clone() * x1.clone()),
+
+                // The sum is the identity iff both operands are, or they are
+                // finite with equal x and opposite y
+                q.clone() * (c.clone() - (a.clone() * b.clone() + finite.clone() * dx_zero * sy_zero)),
+
+                // Two finite points with a finite sum
+                q.clone() * finite.clone() * (one.clone() - c.clone())
+                    * (x3.clone() - (lambda.clone() * lambda.clone() - x1.clone() - x2.clone())),
+                q.clone() * finite * (one.clone() - c.clone()) * (y3.clone() - (lambda * (x1.clone() - x3.clone()) - y1.clone())),
+
+                // O + p2 = p2
+                q.clone() * a.clone() * (x3.clone() - x2),
+                q.clone() * a.clone() * (y3.clone() - y2),
+
+                // p1 + O = p1
+                q.clone() * (one.clone() - a.clone()) * b.clone() * (x3.clone() - x1),
+                q.clone() * (one - a) * b * (y3.clone() - y1),
+
+                // The identity sits at (0, 0)
+                q.clone() * c.clone() * x3,
+                q * c * y3,
+            ]
+        });
+
+        // Next accumulator of the double-and-add; see `select_point` for the
+        // row layout
+        meta.create_gate("point select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let bit = meta.query_advice(sel_bit, Rotation::cur());
+            let started_cur = meta.query_advice(started, Rotation::cur());
+            let started_next = meta.query_advice(started, Rotation(SCALAR_MULT_ROWS_PER_BIT as i32));
+            let one = Expression::Constant(C::Base::ONE);
+
+            let select = |column: Column<Advice>, base: Column<Advice>, meta: &mut VirtualCells<'_, C::Base>| {
+                let acc = meta.query_advice(column, Rotation(-3));
+                let doubled = meta.query_advice(column, Rotation(-2));
+                let added = meta.query_advice(column, Rotation::prev());
+                let base = meta.query_advice(base, Rotation::prev());
+                let out = meta.query_advice(column, Rotation::cur());
+
+                out - (started_cur.clone() * (bit.clone() * added + (one.clone() - bit.clone()) * doubled)
+                    + (one.clone() - started_cur.clone()) * (bit.clone() * base + (one.clone() - bit.clone()) * acc))
+            };
+            let x_select = select(x, px, meta);
+            let y_select = select(y, py, meta);
+
+            vec![
+                q_select.clone() * x_select,
+                q_select.clone() * y_select,
+                // started' = started OR bit
+                q_select * (started_next - (started_cur.clone() + bit.clone() - started_cur * bit)),
+            ]
+        });
+
+        // Window i of `fixed_base_mul`: its digit, recovered from the running
+        // sums of this row and the next, and its point in px, py must be
+        // the table entry for window i
+        meta.lookup("fixed base window", |meta| {
+            let q_fixed_base = meta.query_selector(q_fixed_base);
+            let window = meta.query_fixed(window, Rotation::cur());
+            let z = meta.query_advice(window_sum, Rotation::cur());
+            let z_next = meta.query_advice(window_sum, Rotation::next());
+            let px = meta.query_advice(px, Rotation::cur());
+            let py = meta.query_advice(py, Rotation::cur());
+            let shift = Expression::Constant(C::Base::from(1u64 << window_bits));
+
+            vec![
+                (q_fixed_base.clone() * window, table_window),
+                (q_fixed_base.clone() * (z - shift * z_next), table_digit),
+                (q_fixed_base.clone() * px, table_x),
+                (q_fixed_base * py, table_y),
+            ]
+        });
+
+        // The public key and the witnessed R are only ever used as affine
+        // points, so each is checked against y² = x³ + b once. The gate has
+        // no a·x term, so only curves with a = 0, like Pallas and Vesta, fit
+        assert!(bool::from(C::a().is_zero()), "the on-curve gate needs a curve with a = 0");
+        meta.create_gate("on curve", |meta| {
+            let q_on_curve = meta.query_selector(q_on_curve);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let b = Expression::Constant(C::b());
+
+            vec![q_on_curve * (y.clone() * y - (x.clone() * x.clone() * x + b))]
+        });
+
+        EcdsaConfig {
+            q_on_curve, x, y, instance, q_decompose, bit, acc, lt, eq, modulus_bit,
+            q_double, q_add, q_select, lambda, px, py, sel_bit, started,
+            q_fixed_base, window, window_sum, window_bits, table_window, table_digit, table_x, table_y,
+            q_add_complete, inf, pinf, alpha, beta, finite,
+        }
+    }
+
+    // The public key, signature and message hash, or None when all three
+    // are absent as in keygen. A circuit with only some of them, or with a
+    // public key that has no affine coordinates, cannot be laid out.
+    fn witnesses(&self) -> Result<Option<(C, (C::Scalar, C::Scalar), C::Scalar)>, WitnessError> {
+        match (self.public_key, self.signature, self.message_hash) {
+            (None, None, None) => Ok(None),
+            (None, _, _) => Err(WitnessError::MissingPublicKey),
+            (_, None, _) => Err(WitnessError::MissingSignature),
+            (_, _, None) => Err(WitnessError::MissingMessageHash),
+            (Some(public_key), Some(signature), Some(message_hash)) => {
+                if !bool::from(public_key.is_on_curve()) || bool::from(public_key.coordinates().is_none()) {
+                    return Err(WitnessError::PointNotOnCurve);
+                }
+                Ok(Some((public_key, signature, message_hash)))
+            }
+        }
+    }
+
+    // The public instance binding this circuit's message hash and public
+    // key, or nothing if either is missing or the key is the identity
+    fn instance(&self) -> Vec<C::Base> {
+        let (Some(message_hash), Some(public_key)) = (self.message_hash, self.public_key) else {
+            return Vec::new();
+        };
+        let coordinates = public_key.coordinates();
+        if bool::from(coordinates.is_none()) {
+            return Vec::new();
+        }
+        let coordinates = coordinates.unwrap();
+        hash_instance::<C>(&message_hash).into_iter().chain([*coordinates.x(), *coordinates.y()]).collect()
+    }
+
+    // R = (h·s⁻¹)·G + (r·s⁻¹)·PK, the point whose x coordinate a valid
+    // signature's r equals. None when s = 0 or R is the identity, neither of
+    // which a valid signature has.
+    fn signature_point(public_key: C, (r, s): (C::Scalar, C::Scalar), message_hash: C::Scalar) -> Option<C> {
+        let w = Option::<C::Scalar>::from(s.invert())?;
+        let point = (C::generator() * (message_hash * w) + public_key * (r * w)).to_affine();
+        bool::from(point.coordinates().is_some()).then_some(point)
+    }
+
+    // Lays out one signature verification, binding its message hash and
+    // public key to rows INSTANCE_ROWS·index onwards of the instance column. The
+    // chip's fixed-base table must already be loaded.
+    fn assign_verification(
+        &self,
+        chip: &EcdsaChip<C>,
+        layouter: &mut impl Layouter<C::Base>,
+        index: usize,
+    ) -> Result<(), ErrorFront> {
+        // Witnesses are absent during keygen (see `without_witnesses`), in
+        // which case every assignment below is made with an unknown value.
+        // `check_satisfied` reports which witness was wrong; here only
+        // ErrorFront::Synthesis is left.
+        let witnesses = self.witnesses()?;
+        let (public_key, r_point, s, message_hash) = match witnesses {
+            Some((public_key, signature, message_hash)) => {
+                // An invalid signature still needs some affine R to lay out;
+                // the generator fails the final check like any other guess
+                let r_point = Self::signature_point(public_key, signature, message_hash)
+                    .unwrap_or_else(C::generator);
+                (Value::known(public_key), Value::known(r_point), Value::known(signature.1), Value::known(message_hash))
+            }
+            None => (Value::unknown(), Value::unknown(), Value::unknown(), Value::unknown()),
+        };
+        let coordinates = |point: Value<C>| {
+            let coordinates = point.map(|point| point.coordinates().unwrap());
+            (coordinates.map(|c| *c.x()), coordinates.map(|c| *c.y()))
+        };
+
+        // The public key and R, both checked to be on the curve
+        let (pk_coords, r_coords) = timings::timed("ecdsa verify", || {
+            layouter.namespace(|| "main assignments")
+                .assign_region(
+                    || "ecdsa verify",
+                    |mut region| {
+                        let mut assign_point = |offset, names: [&str; 2], (x, y): (Value<C::Base>, Value<C::Base>)| {
+                            chip.config.q_on_curve.enable(&mut region, offset)?;
+                            Ok::<_, ErrorFront>((
+                                region.assign_advice(|| names[0], chip.config.x, offset, || x)?,
+                                region.assign_advice(|| names[1], chip.config.y, offset, || y)?,
+                            ))
+                        };
+                        let pk = assign_point(0, ["pk_x", "pk_y"], coordinates(public_key))?;
+                        let r = assign_point(1, ["r_x", "r_y"], coordinates(r_point))?;
+                        Ok((pk, r))
+                    },
+                )
+        })?;
+
+        // s·R = h·G + R.x·PK. The generator is fixed by the curve, so h·G
+        // uses the windowed table instead of a double-and-add.
+        let (g_mult, hash_sums) = timings::timed("g_mult", || {
+            layouter.namespace(|| "g_mult")
+                .assign_region(
+                    || "fixed base mult g",
+                    |mut region| chip.fixed_base_mul(&mut region, message_hash),
+                )
+        })?;
+        let num_bits = hash_sums.len() - 1;
+        let first_row = index * INSTANCE_ROWS;
+        for (row, sum) in [num_bits, num_bits - HASH_SPLIT_BITS].into_iter().enumerate() {
+            layouter.constrain_instance(hash_sums[sum].cell(), chip.config.instance, first_row + row)?;
+        }
+        // The key the signature is checked against is the public one, not
+        // any key the prover picks to fit s·R = h·G + R.x·PK
+        layouter.constrain_instance(pk_coords.0.cell(), chip.config.instance, first_row + HASH_INSTANCE_ROWS)?;
+        layouter.constrain_instance(pk_coords.1.cell(), chip.config.instance, first_row + HASH_INSTANCE_ROWS + 1)?;
+    
+        // R.x is below p < n, so as a scalar it is the signature's r
+        let pk_mult = timings::timed("pk_mult", || {
+            layouter.namespace(|| "pk_mult")
+                .assign_region(
+                    || "scalar mult pk",
+                    |mut region| chip.scalar_mult(&mut region, &r_coords.0, &pk_coords),
+                )
+        })?;
+
+        // Rejects s = 0, whose bits never start the double-and-add
+        let s_mult = timings::timed("s_mult", || {
+            layouter.namespace(|| "s_mult")
+                .assign_region(
+                    || "scalar mult r",
+                    |mut region| chip.curve_scalar_mult(&mut region, s, &r_coords),
+                )
+        })?;
+    
+        // Final point addition in separate namespace
+        timings::timed("final addition", || {
+            layouter.namespace(|| "final addition")
+                .assign_region(
+                    || "point addition",
+                    |mut region| {
+                        // Both products are affine points, so finite. point_add
+                        // takes its first operand from the row above.
+                        let g_mult = chip.flag_point(&mut region, 0, &g_mult, false)?;
+                        let pk_mult = chip.flag_point(&mut region, 1, &pk_mult, false)?;
+                        let sum = chip.point_add(&mut region, 2, &pk_mult, &g_mult)?;
+
+                        // The signature verifies iff the sum is s·R
+                        region.constrain_constant(sum.infinity.cell(), C::Base::ZERO)?;
+                        region.constrain_equal(sum.x.cell(), s_mult.0.cell())?;
+                        region.constrain_equal(sum.y.cell(), s_mult.1.cell())
+                    },
+                )
+        })?;
+    
+        Ok(())
+    }
+}
+
+// Key generation cost, paid once per circuit rather than per proof. Both
+// steps synthesize the circuit without witnesses.
+#[derive(Debug, Clone, Copy)]
+struct KeygenTimings {
+    vk: Duration,
+    pk: Duration,
+}
+
+fn time_keygen(k: u32) -> Result<KeygenTimings, Error> {
+    let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(k);
+    let circuit = EcdsaCircuit::<Affine>::default();
+
+    let vk_start = Instant::now();
+    let vk = keygen_vk(&params, &circuit)?;
+    let vk_time = vk_start.elapsed();
+
+    let pk_start = Instant::now();
+    keygen_pk(&params, vk, &circuit)?;
+    let pk_time = pk_start.elapsed();
+
+    Ok(KeygenTimings { vk: vk_time, pk: pk_time })
+}
+
+// What proving several verifications in one transcript cost, for setting
+// against one proof per signature
+#[derive(Debug, Clone, Copy)]
+struct BatchArtifacts {
+    proofs: usize,
+    proving: Duration,
+    verification: Duration,
+    proof_size_bytes: usize,
+    verified: bool,
+}
+
+impl BatchArtifacts {
+    // Proving time per signature in the batch
+    fn amortized_proving(&self) -> Duration {
+        self.proving / self.proofs as u32
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        json!({
+            "batch_size": self.proofs,
+            "proving_time_ms": self.proving.as_millis() as u64,
+            "amortized_proving_time_ms": self.amortized_proving().as_secs_f64() * 1000.0,
+            "verification_time_ms": self.verification.as_millis() as u64,
+            "proof_size_bytes": self.proof_size_bytes,
+            "verified": self.verified,
+        })
+    }
+}
+
+// Proves every signature in `circuits` with one set of params and keys and
+// one transcript, then verifies the batch. A batch with an invalid signature
+// is reported through `verified`.
+fn prove_batch(k: u32, circuits: &[EcdsaCircuit<Affine>]) -> Result<BatchArtifacts, Error> {
+    assert!(!circuits.is_empty(), "a batch needs at least one signature");
+    let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(k);
+    let vk = keygen_vk(&params, &EcdsaCircuit::<Affine>::default())?;
+    let pk = keygen_pk(&params, vk.clone(), &EcdsaCircuit::<Affine>::default())?;
+
+    let publics: Vec<Vec<Base>> = circuits.iter().map(EcdsaCircuit::instance).collect();
+    let columns: Vec<[&[Base]; 1]> = publics.iter().map(|public| [&public[..]]).collect();
+    let instances: Vec<&[&[Base]]> = columns.iter().map(|columns| &columns[..]).collect();
+
+    let proving_start = Instant::now();
+    let proof = halo2_common::prove(&params, &pk, circuits, &instances)?;
+    let proving = proving_start.elapsed();
+
+    let verification_start = Instant::now();
+    let verified = halo2_common::verify(&params, &vk, &proof, &instances);
+    let verification = verification_start.elapsed();
+
+    Ok(BatchArtifacts {
+        proofs: circuits.len(),
+        proving,
+        verification,
+        proof_size_bytes: proof.len(),
+        verified,
+    })
+}
+
+// A fresh key pair signing a random message hash
+fn signed_random_message(rng: &mut impl RngCore) -> EcdsaCircuit<Affine> {
+    let msg_hash = Scalar::random(&mut *rng);
+    sign(rng, msg_hash)
+}
+
+// A fresh key pair signing `msg_hash`
+fn sign(rng: &mut impl RngCore, msg_hash: Scalar) -> EcdsaCircuit<Affine> {
+    let private_key = Scalar::random(&mut *rng);
+    let public_key = (Point::generator() * private_key).to_affine();
+
+    let k = Scalar::random(&mut *rng);
+    let r_x = *(Point::generator() * k).to_affine().coordinates().unwrap().x();
+    let r = base_to_scalar::<Affine>(&r_x);
+    let s = k.invert().unwrap() * (msg_hash + (r * private_key));
+
+    EcdsaCircuit {
+        public_key: Some(public_key),
+        message_hash: Some(msg_hash),
+        signature: Some((r, s)),
+    }
+}
+
+// Checks the witness against every constraint with the mock prover,
+// after checking it is complete so a missing one is reported by name
+fn check_satisfied(k: u32, circuit: &EcdsaCircuit<Affine>) -> Result<(), BenchError> {
+    circuit.witnesses()?;
+    MockProver::run(k, circuit, vec![circuit.instance()])?
+        .verify()
+        .map_err(BenchError::Unsatisfied)
+}
+
+// Checks `circuit` with the mock prover and with a real proof, and fails
+// unless both accept it or both reject it. Returns whether it was accepted.
+fn check_prover_agreement(k: u32, circuit: &EcdsaCircuit<Affine>) -> Result<bool, BenchError> {
+    let instance = circuit.instance();
+    let mock_accepts = MockProver::run(k, circuit, vec![instance.clone()])?.verify().is_ok();
+    let proof_verifies = run_real_proof(k, EcdsaCircuit { ..*circuit }, &[&instance])?.verified;
+
+    if mock_accepts != proof_verifies {
+        return Err(BenchError::ProversDisagree { mock_accepts, proof_verifies });
+    }
+    Ok(mock_accepts)
+}
+
+// Key, message and signature randomness: a ChaCha20 stream, so a run can
+// be replayed from the seed it records
+fn benchmark_rng(seed: u64) -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
+// Proves `circuits`, which have already passed the mock prover, at `size`:
+// the batch once, then the first circuit `repeat` times (at least once),
+// whose artifacts fill the metrics. time_metrics holds the median single
+// proof and time_statistics their distribution. `seed` is the seed they
+// were generated from, or None for replayed inputs.
+fn measure(
+    size: CircuitSize,
+    circuits: Vec<EcdsaCircuit<Affine>>,
+    seed: Option<u64>,
+    repeat: usize,
+) -> Result<BenchmarkMetrics, BenchError> {
+    let k = size.k().max(VERIFY_K);
+    let signatures_checked = circuits.len();
+
+    let batch = prove_batch(k, &circuits)?;
+    if !batch.verified {
+        return Err(BenchError::ProofRejected("batch proof"));
+    }
+
+    let circuit = circuits.into_iter().next().expect("workload is at least one signature");
+    let instance = circuit.instance();
+    let circuit_stats = collect_circuit_stats::<Base, EcdsaCircuit<Affine>>();
+
+    let cpu = CpuSampler::start();
+    let gpu = GpuSampler::start();
+    let mut aggregator = Aggregator::new();
+    let mut proving_times = Vec::new();
+    let mut measured = None;
+    for _ in 0..repeat.max(1) {
+        let artifacts = run_real_proof(k, EcdsaCircuit { ..circuit }, &[&instance])?;
+        if !artifacts.verified {
+            return Err(BenchError::ProofRejected("proof"));
+        }
+        let metrics = proof_metrics(&artifacts, &circuit_stats, size, signatures_checked, seed);
+        aggregator.record(&metrics.time_metrics);
+        proving_times.push(artifacts.proving);
+        measured = Some(metrics);
+    }
+    let gpu_usage = gpu.finish();
+    let cpu_usage = cpu.finish();
+
+    let mut metrics = measured.expect("proved at least once");
+    aggregator.apply(&mut metrics);
+    if let Some(throughput) = Throughput::of(&proving_times) {
+        throughput.apply(&mut metrics);
+    }
+    metrics.performance_metrics.batch_proving_supported = true;
+    metrics.performance_metrics.batch_verification_supported = true;
+    metrics.extra.insert("batch_metrics".to_string(), batch.to_json());
+    if let Some(usage) = gpu_usage {
+        usage.apply(&mut metrics);
+    }
+    if let Some(usage) = cpu_usage {
+        usage.apply(&mut metrics);
+    }
+    if let Some(gap) = AllocationGap::measure() {
+        gap.apply(&mut metrics);
+    }
+    collect_environment(size.as_str()).apply(&mut metrics);
+
+    Ok(metrics)
+}
+
+/// How `run_with` chooses and proves its signatures.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Seed of the key, message and signature randomness. Without one a
+    /// seed is drawn from the OS; either way it is recorded in
+    /// `extra.run_metadata.seed`, so passing it back replays the run.
+    pub seed: Option<u64>,
+    /// Proofs of the single signature; 0 is taken as 1
+    pub repeat: usize,
+    /// A signature dumped by `dump_inputs`, proved instead of random ones
+    pub inputs: Option<String>,
+    /// Where to write a signature that fails the mock prover, so the
+    /// failure can be replayed through `inputs`
+    pub dump_inputs: Option<PathBuf>,
+}
+
+/// Benchmarks one proof of the signatures at `size`; see `run_with`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, BenchError> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks the signatures at `size`, proving the single one `repeat`
+/// times; see `run_with`.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, BenchError> {
+    run_with(size, &RunOptions { repeat, ..Default::default() })
+}
+
+/// Benchmarks ECDSA verification at `size` in this process: signatures,
+/// one per unit of workload, each checked with the mock prover, then proved
+/// as a batch and singly. Keygen on its own is timed into
+/// `setup_metrics.extra`. Prints nothing; a harness collects the returned
+/// metrics, and `main` reports progress from them.
+pub fn run_with(size: CircuitSize, options: &RunOptions) -> Result<BenchmarkMetrics, BenchError> {
+    let timer = Timer::start();
+    let k = size.k().max(VERIFY_K);
+
+    let (circuits, seed) = match &options.inputs {
+        Some(text) => (vec![inputs::load(text)?], None),
+        None => {
+            let seed = options.seed.unwrap_or_else(|| OsRng.next_u64());
+            let mut rng = benchmark_rng(seed);
+            ((0..size.workload()).map(|_| signed_random_message(&mut rng)).collect(), Some(seed))
+        }
+    };
+    for circuit in &circuits {
+        if let Err(err) = check_satisfied(k, circuit) {
+            if let Some(path) = &options.dump_inputs {
+                fs::write(path, inputs::dump(circuit))?;
+            }
+            return Err(err);
+        }
+    }
+
+    let mut metrics = measure(size, circuits, seed, options.repeat)?;
+    let keygen = time_keygen(k)?;
+    metrics.setup_metrics.extra.insert("keygen_vk_ms".to_string(), (keygen.vk.as_millis() as u64).into());
+    metrics.setup_metrics.extra.insert("keygen_pk_ms".to_string(), (keygen.pk.as_millis() as u64).into());
+
+    timer.finish(&mut metrics.time_metrics);
+    Ok(metrics)
+}
+
+/// A diagnostic that looks at one random signature's circuit instead of
+/// benchmarking it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// Witness cells that no constraint depends on
+    UnconstrainedCells,
+    /// Synthesis time per top-level region of one verification
+    RegionTimings,
+    /// Rows each part of one verification occupies: loading the public
+    /// key and R, the three scalar mults and the final addition
+    CostReport,
+    /// Whether the mock prover and a real proof agree on a valid signature
+    /// and on the same signature against another hash
+    ProverAgreement,
+}
+
+impl Diagnostic {
+    /// Each diagnostic with the flag that selects it.
+    pub const FLAGS: [(&'static str, Diagnostic); 4] = [
+        ("--unconstrained-cells", Diagnostic::UnconstrainedCells),
+        ("--region-timings", Diagnostic::RegionTimings),
+        ("--cost-report", Diagnostic::CostReport),
+        ("--prover-agreement", Diagnostic::ProverAgreement),
+    ];
+
+    /// The diagnostic `args` asks for, if any.
+    pub fn from_args(args: &TemplateArgs) -> Option<Self> {
+        Self::FLAGS.iter().find(|(flag, _)| args.has_flag(flag)).map(|&(_, diagnostic)| diagnostic)
+    }
+}
+
+/// Runs `diagnostic` on a signature drawn from `seed`, or from the OS
+/// without one, and returns its report, one finding per line.
+pub fn diagnose(diagnostic: Diagnostic, seed: Option<u64>) -> Result<String, BenchError> {
+    let mut rng = benchmark_rng(seed.unwrap_or_else(|| OsRng.next_u64()));
+    let circuit = signed_random_message(&mut rng);
+    let instance = vec![circuit.instance()];
+    let mut report = String::new();
+
+    match diagnostic {
+        Diagnostic::UnconstrainedCells => {
+            for cell in diagnostics::unconstrained_cells(VERIFY_K, &circuit, instance) {
+                report += &format!(
+                    "Unconstrained: {} / {} (column {}, row {})\n",
+                    cell.region, cell.name, cell.column, cell.row,
+                );
+            }
+        }
+        Diagnostic::RegionTimings => {
+            timings::take();
+            MockProver::run(VERIFY_K, &circuit, instance)?;
+
+            report += "Region timings:\n";
+            for (region, time) in timings::take() {
+                report += &format!("  {}: {:.3} ms\n", region, time.as_secs_f64() * 1000.0);
+            }
+        }
+        Diagnostic::CostReport => {
+            let regions = diagnostics::region_rows(VERIFY_K, &circuit, instance);
+            let total: usize = regions.iter().map(|(_, rows)| rows).sum();
+
+            report += &format!("Rows per region ({} in total):\n", total);
+            for (region, rows) in &regions {
+                report += &format!("  {}: {} ({:.1}%)\n", region, rows, *rows as f64 * 100.0 / total as f64);
+            }
+        }
+        Diagnostic::ProverAgreement => {
+            let wrong_hash = EcdsaCircuit { message_hash: circuit.message_hash.map(|h| h + Scalar::ONE), ..circuit };
+            for (name, circuit) in [("valid signature", &circuit), ("wrong message hash", &wrong_hash)] {
+                let accepted = check_prover_agreement(VERIFY_K, circuit)?;
+                report += &format!("{}: both provers {}\n", name, if accepted { "accept" } else { "reject" });
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Affine against Jacobian point arithmetic on Pallas, whose base field is
+/// the circuit field (y² = x³ + 5): constraints per gadget and per scalar
+/// mult, as a table.
+pub fn cost_comparison() -> String {
+    curve_ops::cost_comparison(Affine::b())
+}
+
+// Metrics for one verified-signature proof at `size`, after
+// `signatures_checked` signatures passed the mock prover. `seed` is the
+// seed the signatures were generated from, recorded as null for replayed
+// inputs.
+fn proof_metrics(
+    artifacts: &ProofArtifacts,
+    circuit_stats: &CircuitStats,
+    size: CircuitSize,
+    signatures_checked: usize,
+    seed: Option<u64>,
+) -> BenchmarkMetrics {
+    let mut metrics = BenchmarkMetrics::new("halo2", Operation::EcdsaVerify.as_str(), size.as_str());
+    metrics.resolved_parameters.insert("k".to_string(), size.k().max(VERIFY_K).into());
+    metrics.resolved_parameters.insert("signatures_checked".to_string(), signatures_checked.into());
+    metrics.setup_metrics.setup_type = "transparent".to_string();
+    metrics.setup_metrics.setup_reusable = true;
+    metrics.security_metrics.security_level_bits = 128;
+    metrics.security_metrics.assumptions = vec!["discrete_log".to_string()];
+    metrics.setup_metrics.extra.insert("vk_fingerprint".to_string(), artifacts.vk_fingerprint.clone().into());
+    metrics.time_metrics.setup_time_ms = artifacts.setup.as_millis() as u64;
+    metrics.time_metrics.proving_time_ms = artifacts.proving.as_millis() as u64;
+    metrics.time_metrics.verification_time_ms = artifacts.verification.as_millis() as u64;
+    metrics.resource_metrics.proof_size_bytes = artifacts.proof_size_bytes as u64;
+    metrics.resource_metrics.peak_memory_usage_kb = artifacts.peak_memory_kb.unwrap_or(0);
+    if let Some(verifier_peak) = artifacts.verifier_peak_memory_kb {
+        metrics.resource_metrics.extra.insert("verifier_peak_memory_kb".to_string(), verifier_peak.into());
+    }
+    if let Some(file) = &artifacts.params_file {
+        file.apply(&mut metrics);
+    }
+    if let Some(throughput) = Throughput::of(&[artifacts.proving]) {
+        throughput.apply(&mut metrics);
+    }
+    metrics.scalability_metrics.field_bits = FIELD_BITS;
+    metrics.scalability_metrics.constraints_count = circuit_stats.constraints as u64;
+    metrics.scalability_metrics.variables_count = circuit_stats.columns as u64;
+    metrics.scalability_metrics.degree = circuit_stats.degree as u32;
+    metrics.extra.insert("run_metadata".to_string(), json!({ "seed": seed }));
+    // Runs whose proof is rejected end in ProofRejected instead
+    metrics.extra.insert("verification_passed".to_string(), true.into());
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::{Group, prime::PrimeCurveAffine};
+    use rand::Rng;
+    use pasta_curves::arithmetic::Coordinates;
+    use std::cell::RefCell;
+    use pasta_curves::{pallas, vesta};
+
+    #[test]
+    fn test_ecdsa_verify() {
+        // r is R.x mapped into the scalar field, as a verifier computes it
+        let circuit = signed_random_message(&mut OsRng);
+        let (r, _) = circuit.signature.unwrap();
+        assert!(verifies_natively(&circuit));
+        assert_ne!(r, pallas::Scalar::ZERO);
+
+        let prover = MockProver::run(VERIFY_K, &circuit, vec![circuit.instance()]).unwrap();
+
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_forged_r_rejected() {
+        let circuit = signed_random_message(&mut OsRng);
+        let instance = vec![circuit.instance()];
+        let (r, s) = circuit.signature.unwrap();
+
+        let prover = MockProver::run(VERIFY_K, &circuit, instance.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // R is recomputed honestly from the forged r, so only R.x = r can
+        // fail
+        let forged = EcdsaCircuit {
+            signature: Some((r + pallas::Scalar::ONE, s)),
+            ..circuit
+        };
+        let prover = MockProver::run(VERIFY_K, &forged, instance).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_public_hash_is_bound() {
+        let circuit = signed_random_message(&mut OsRng);
+        let msg_hash = circuit.message_hash.unwrap();
+
+        let prover = MockProver::run(VERIFY_K, &circuit, vec![circuit.instance()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The witness still signs msg_hash, so only the copy from the
+        // instance column can catch the changed public input
+        let mut other = circuit.instance();
+        let other_hash = hash_instance::<pallas::Affine>(&(msg_hash + pallas::Scalar::ONE));
+        other[..HASH_INSTANCE_ROWS].copy_from_slice(&other_hash);
+        let prover = MockProver::run(VERIFY_K, &circuit, vec![other]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // A valid signature only verifies against the key that made it: the
+    // witness keeps the signer's key, so the instance copy must catch it
+    #[test]
+    fn test_public_key_is_bound() {
+        let circuit = signed_random_message(&mut OsRng);
+        let other_key = signed_random_message(&mut OsRng).public_key.unwrap().coordinates().unwrap();
+
+        let mut instance = circuit.instance();
+        assert_eq!(instance.len(), INSTANCE_ROWS);
+        instance[HASH_INSTANCE_ROWS..].copy_from_slice(&[*other_key.x(), *other_key.y()]);
+
+        let prover = MockProver::run(VERIFY_K, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // A Pallas signature whose hash is the integer p, which wraps to zero in
+    // the circuit field. The second instance row is what tells it apart
+    // from a zero hash.
+    #[test]
+    fn test_hash_above_circuit_modulus() {
+        let p_minus_one = pallas::Scalar::from_repr((-pallas::Base::ONE).to_repr()).unwrap();
+        let circuit = sign(&mut OsRng, p_minus_one + pallas::Scalar::ONE);
+        assert!(verifies_natively(&circuit));
+        assert_eq!(circuit.instance()[0], pallas::Base::ZERO);
+
+        assert!(check_satisfied(VERIFY_K, &circuit).is_ok());
+
+        let mut zero = circuit.instance();
+        zero[..HASH_INSTANCE_ROWS].copy_from_slice(&hash_instance::<pallas::Affine>(&pallas::Scalar::ZERO));
+        let prover = MockProver::run(VERIFY_K, &circuit, vec![zero]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_keygen_without_witnesses() {
+        let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(VERIFY_K);
+        let circuit = EcdsaCircuit::<pallas::Affine>::default();
+
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should succeed without witnesses");
+        keygen_pk(&params, vk, &circuit).expect("keygen_pk should succeed without witnesses");
+    }
+
+    #[test]
+    fn test_missing_witnesses_reported() {
+        let valid = signed_random_message(&mut OsRng);
+        assert!(matches!(valid.witnesses(), Ok(Some(_))));
+        assert_eq!(EcdsaCircuit::<pallas::Affine>::default().witnesses(), Ok(None));
+
+        let missing = |circuit: EcdsaCircuit<pallas::Affine>| circuit.witnesses().unwrap_err();
+        assert_eq!(missing(EcdsaCircuit { public_key: None, ..valid }), WitnessError::MissingPublicKey);
+        assert_eq!(missing(EcdsaCircuit { signature: None, ..valid }), WitnessError::MissingSignature);
+        assert_eq!(missing(EcdsaCircuit { message_hash: None, ..valid }), WitnessError::MissingMessageHash);
+        assert_eq!(
+            missing(EcdsaCircuit { public_key: Some(pallas::Affine::identity()), ..valid }),
+            WitnessError::PointNotOnCurve,
+        );
+
+        // At the Circuit boundary the cause is gone, but the benchmark
+        // checks witnesses first and returns it
+        let circuit = EcdsaCircuit { signature: None, ..valid };
+        assert!(MockProver::run(VERIFY_K, &circuit, vec![circuit.instance()]).is_err());
+        assert!(matches!(
+            check_satisfied(VERIFY_K, &circuit),
+            Err(BenchError::Witness(WitnessError::MissingSignature)),
+        ));
+    }
+
+    // Checks one pair of coordinates with the "on curve" gate
+    struct OnCurveCircuit {
+        x: pallas::Base,
+        y: pallas::Base,
+    }
+
+    impl Circuit<pallas::Base> for OnCurveCircuit {
+        type Config = EcdsaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { x: self.x, y: self.y }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            EcdsaCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), ErrorFront> {
+            layouter.assign_region(
+                || "on curve",
+                |mut region| {
+                    config.q_on_curve.enable(&mut region, 0)?;
+                    region.assign_advice(|| "x", config.x, 0, || Value::known(self.x))?;
+                    region.assign_advice(|| "y", config.y, 0, || Value::known(self.y))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_on_curve_gate_uses_curve_b() {
+        // The gate takes b from the curve: a Pallas point satisfies it, and
+        // the same x with y moved off y² = x³ + 5 does not
+        let point = (pallas::Point::generator() * pallas::Scalar::from(7u64)).to_affine();
+        let coords = point.coordinates().unwrap();
+        assert_eq!(coords.y().square(), coords.x().square() * coords.x() + pallas::Affine::b());
+
+        let on_curve = OnCurveCircuit { x: *coords.x(), y: *coords.y() };
+        assert_eq!(MockProver::run(5, &on_curve, vec![vec![]]).unwrap().verify(), Ok(()));
+
+        let off_curve = OnCurveCircuit { x: *coords.x(), y: *coords.y() + pallas::Base::ONE };
+        assert!(MockProver::run(5, &off_curve, vec![vec![]]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_keygen_over_vesta() {
+        // The circuit over the other Pasta curve lives in vesta::Base and is
+        // committed with Pallas
+        let params: ParamsIPA<pallas::Affine> = ParamsIPA::new(VERIFY_K);
+        let circuit = EcdsaCircuit::<vesta::Affine>::default();
+
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should succeed over vesta");
+        keygen_pk(&params, vk, &circuit).expect("keygen_pk should succeed over vesta");
+    }
+
+    #[test]
+    fn test_base_to_scalar_below_modulus() {
+        let value = pallas::Base::from(0xdead_beefu64);
+        assert_eq!(
+            base_to_scalar_reduced::<pallas::Affine>(&value),
+            (pallas::Scalar::from(0xdead_beefu64), false),
+        );
+    }
+
+    #[test]
+    fn test_base_to_scalar_reduces_past_modulus() {
+        // Vesta's base modulus q exceeds its scalar modulus p, so the
+        // integer p is a valid coordinate that reduces to zero
+        let p_minus_one = vesta::Base::from_repr((-vesta::Scalar::ONE).to_repr()).unwrap();
+        let p = p_minus_one + vesta::Base::ONE;
+
+        assert_eq!(base_to_scalar_reduced::<vesta::Affine>(&p_minus_one), (-vesta::Scalar::ONE, false));
+        assert_eq!(base_to_scalar_reduced::<vesta::Affine>(&p), (vesta::Scalar::ZERO, true));
+        assert_eq!(
+            base_to_scalar_reduced::<vesta::Affine>(&(p + vesta::Base::from(5))),
+            (vesta::Scalar::from(5), true),
+        );
+        assert_eq!(base_to_scalar::<vesta::Affine>(&p), vesta::Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_field_bits_pallas() {
+        assert_eq!(FIELD_BITS, 255);
+    }
+
+    #[test]
+    fn test_real_proof_verifies() {
+        let circuit = signed_random_message(&mut OsRng);
+        let instance = circuit.instance();
+
+        let artifacts = run_real_proof(VERIFY_K, circuit, &[&instance]).unwrap();
+        assert!(artifacts.verified);
+        assert!(artifacts.proof_size_bytes > 0);
+
+        let circuit_stats = collect_circuit_stats::<pallas::Base, EcdsaCircuit<pallas::Affine>>();
+        let metrics = proof_metrics(&artifacts, &circuit_stats, CircuitSize::Small, 1, Some(7));
+        assert_eq!(metrics.resource_metrics.proof_size_bytes, artifacts.proof_size_bytes as u64);
+        assert_eq!(metrics.performance_metrics.latency_ms, (artifacts.proving.as_secs_f64() * 1000.0).round() as u64);
+        assert!(metrics.performance_metrics.throughput_proofs_per_second > 0.0);
+        assert_eq!(metrics.extra["run_metadata"]["seed"], 7);
+        assert_eq!(metrics.circuit_size, "small");
+        assert_eq!(metrics.resolved_parameters["k"], VERIFY_K);
+        // Polynomial constraints, not the gates grouping them
+        assert_eq!(metrics.scalability_metrics.constraints_count, circuit_stats.constraints as u64);
+        assert_eq!(metrics.setup_metrics.extra["vk_fingerprint"], artifacts.vk_fingerprint);
+
+        #[cfg(target_os = "linux")]
+        assert!(metrics.resource_metrics.extra["verifier_peak_memory_kb"].as_u64().unwrap() > 0);
+    }
+
+    // The fingerprint depends on the circuit's layout, not its witnesses
+    #[test]
+    fn test_vk_fingerprint_tracks_circuit() {
+        let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(VERIFY_K);
+        let fingerprint = halo2_common::vk_fingerprint::<vesta::Affine>;
+
+        let empty = keygen_vk(&params, &EcdsaCircuit::<pallas::Affine>::default()).unwrap();
+        let signed = keygen_vk(&params, &signed_random_message(&mut OsRng)).unwrap();
+        assert_eq!(fingerprint(&empty), fingerprint(&signed));
+        assert_eq!(fingerprint(&empty).len(), 64);
+
+        // Same gates, different layout
+        let point = pallas::Affine::generator().coordinates().unwrap();
+        let on_curve = keygen_vk(&params, &OnCurveCircuit { x: *point.x(), y: *point.y() }).unwrap();
+        assert_ne!(fingerprint(&empty), fingerprint(&on_curve));
+
+        // Same circuit at a different size
+        let larger: ParamsIPA<vesta::Affine> = ParamsIPA::new(VERIFY_K + 1);
+        let resized = keygen_vk(&larger, &EcdsaCircuit::<pallas::Affine>::default()).unwrap();
+        assert_ne!(fingerprint(&empty), fingerprint(&resized));
+    }
+
+    // A proof written out to bytes and read back still verifies against the
+    // key it was made with, and only for the public inputs it was made for
+    #[test]
+    fn test_serialized_proof_round_trip() {
+        let circuit = signed_random_message(&mut OsRng);
+        let instance = circuit.instance();
+        let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(VERIFY_K);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+
+        let bytes = halo2_common::prove(&params, &pk, &[circuit], &[&[&instance]]).unwrap();
+
+        let mut changed = instance.clone();
+        changed[0] += pallas::Base::ONE;
+        assert!(halo2_common::verify(&params, &vk, &bytes, &[&[&instance]]));
+        assert!(!halo2_common::verify(&params, &vk, &bytes, &[&[&changed]]));
+        assert!(!halo2_common::verify(&params, &vk, &bytes[..bytes.len() / 2], &[&[&instance]]));
+    }
+
+    // A real Pallas signature, valid by the native verifier, is proved over
+    // Vesta and verifies only against its own message hash
+    #[test]
+    fn test_pallas_signature_end_to_end() {
+        let message_hash = pallas::Scalar::random(OsRng);
+        let circuit = sign(&mut OsRng, message_hash);
+        assert!(verifies_natively(&circuit));
+        assert!(check_satisfied(VERIFY_K, &circuit).is_ok());
+
+        let params: ParamsIPA<vesta::Affine> = ParamsIPA::new(VERIFY_K);
+        let vk = keygen_vk(&params, &EcdsaCircuit::<pallas::Affine>::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &EcdsaCircuit::<pallas::Affine>::default()).unwrap();
+
+        let instance = circuit.instance();
+        let proof = halo2_common::prove(&params, &pk, &[circuit], &[&[&instance]]).unwrap();
+        assert!(halo2_common::verify(&params, &vk, &proof, &[&[&instance]]));
+
+        let mut other = instance.clone();
+        let other_hash = hash_instance::<pallas::Affine>(&(message_hash + pallas::Scalar::ONE));
+        other[..HASH_INSTANCE_ROWS].copy_from_slice(&other_hash);
+        assert!(!halo2_common::verify(&params, &vk, &proof, &[&[&other]]));
+    }
+
+    // The wall clock covers every measured phase plus the work between them
+    #[test]
+    fn test_total_time_covers_phases() {
+        let timer = Timer::start();
+        let circuit = signed_random_message(&mut OsRng);
+        let instance = circuit.instance();
+        let artifacts = run_real_proof(VERIFY_K, circuit, &[&instance]).unwrap();
+        let circuit_stats = collect_circuit_stats::<pallas::Base, EcdsaCircuit<pallas::Affine>>();
+
+        let mut metrics = proof_metrics(&artifacts, &circuit_stats, CircuitSize::Small, 1, None);
+        timer.finish(&mut metrics.time_metrics);
+
+        let time = &metrics.time_metrics;
+        assert!(time.total_execution_time_ms > 0);
+        assert!(time.total_execution_time_ms >= time.setup_time_ms + time.proving_time_ms + time.verification_time_ms);
+    }
+
+    // A harness calling `run` gets the metrics without going through JSON
+    #[test]
+    fn test_run_returns_metrics() {
+        let metrics = run(CircuitSize::Small).unwrap();
+
+        assert_eq!(metrics.system, "halo2");
+        assert_eq!(metrics.operation, "ecdsa");
+        assert_eq!(metrics.circuit_size, "small");
+        assert_eq!(metrics.resolved_parameters["signatures_checked"], 1);
+        assert!(metrics.time_metrics.total_execution_time_ms >= metrics.time_metrics.proving_time_ms);
+        assert!(metrics.setup_metrics.extra.contains_key("keygen_vk_ms"));
+        assert!(metrics.setup_metrics.setup_size_bytes > 0);
+        assert_eq!(metrics.system_requirements.disk_space_gb, 1);
+
+        // Without a seed one is drawn, and recorded so the run can be replayed
+        assert!(metrics.extra["run_metadata"]["seed"].is_u64());
+    }
+
+    // A diagnostic reports on the signature its seed picks, nothing else
+    #[test]
+    fn test_seeded_diagnostic_repeats() {
+        let report = diagnose(Diagnostic::CostReport, Some(42)).unwrap();
+        assert!(report.starts_with("Rows per region ("));
+        assert_eq!(diagnose(Diagnostic::CostReport, Some(42)).unwrap(), report);
+
+        let args = TemplateArgs::parse(["bench", "small", "--cost-report"].map(String::from)).unwrap();
+        assert_eq!(Diagnostic::from_args(&args), Some(Diagnostic::CostReport));
+    }
+
+    // Each repeat is a fresh proof; the metrics hold the median of them
+    #[test]
+    fn test_run_repeated_aggregates() {
+        let metrics = run_repeated(CircuitSize::Small, 3).unwrap();
+
+        let statistics = metrics.time_statistics.expect("repeated runs are summarized");
+        let proving = statistics["proving_time_ms"];
+        assert_eq!(proving.runs, 3);
+        assert!(proving.min <= proving.median && proving.median <= proving.max);
+        assert_eq!(metrics.time_metrics.proving_time_ms, proving.median.round() as u64);
+        assert!(metrics.performance_metrics.throughput_proofs_per_second > 0.0);
+    }
+
+    // Every named size fits a verification and still passes the mock prover
+    #[test]
+    fn test_circuit_size_k_fits_verification() {
+        for size in CircuitSize::ALL {
+            assert!(size.k() >= VERIFY_K, "{} maps to k = {}", size, size.k());
+        }
+
+        let circuit = signed_random_message(&mut OsRng);
+        assert!(check_satisfied(CircuitSize::Medium.k(), &circuit).is_ok());
+    }
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let first = signed_random_message(&mut benchmark_rng(42));
+        let second = signed_random_message(&mut benchmark_rng(42));
+        let other = signed_random_message(&mut benchmark_rng(43));
+
+        assert_eq!(inputs::dump(&first), inputs::dump(&second));
+        assert_ne!(inputs::dump(&first), inputs::dump(&other));
+    }
+
+    #[test]
+    fn test_circuit_stats() {
+        let stats = collect_circuit_stats::<pallas::Base, EcdsaCircuit<pallas::Affine>>();
+
+        // y² = x³ + b is cubic
+        assert!(stats.degree >= 3);
+        assert!(stats.constraints >= stats.gates);
+        assert!(stats.gates > 0 && stats.columns > 0);
+    }
+
+    #[test]
+    fn test_keygen_timings_populated() {
+        let timings = time_keygen(VERIFY_K).unwrap();
+
+        assert!(timings.vk > Duration::ZERO);
+        assert!(timings.pk > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let mut rng = OsRng;
+
+        // Corrupt a valid signature by a known delta instead of drawing a
+        // random s, which is only almost-surely invalid
+        let circuit = with_corrupted_s(signed_random_message(&mut rng), pallas::Scalar::ONE);
+        assert!(!verifies_natively(&circuit));
+
+        let prover = MockProver::run(
+            VERIFY_K,
+            &circuit,
+            vec![circuit.instance()],
+        ).unwrap();
+
+        assert!(prover.verify().is_err());
+    }
+
+    // s = 0 has no inverse; the circuit must reject it rather than panic
+    // while computing R
+    #[test]
+    fn test_zero_s_rejected() {
+        let mut rng = OsRng;
+        let valid = signed_random_message(&mut rng);
+        let (r, _) = valid.signature.unwrap();
+        let circuit = EcdsaCircuit {
+            signature: Some((r, pallas::Scalar::ZERO)),
+            ..valid
+        };
+        assert!(!verifies_natively(&circuit));
+
+        let prover = MockProver::run(
+            VERIFY_K,
+            &circuit,
+            vec![circuit.instance()],
+        ).unwrap();
+        assert!(prover.verify().is_err());
+
+        assert!(matches!(
+            check_satisfied(VERIFY_K, &circuit),
+            Err(BenchError::Unsatisfied(_)),
+        ));
+    }
+
+    // Returns `circuit` with `delta` added to s. Any nonzero delta yields a
+    // different s, and for a fixed (r, hash, key) only one s verifies, so the
+    // result is guaranteed invalid.
+    fn with_corrupted_s(
+        circuit: EcdsaCircuit<pallas::Affine>,
+        delta: pallas::Scalar,
+    ) -> EcdsaCircuit<pallas::Affine> {
+        assert!(!bool::from(delta.is_zero()), "delta must be nonzero to corrupt the signature");
+        let (r, s) = circuit.signature.unwrap();
+
+        EcdsaCircuit {
+            signature: Some((r, s + delta)),
+            ..circuit
+        }
+    }
+
+    // Out-of-circuit ECDSA verification: R = (h·s⁻¹)·G + (r·s⁻¹)·PK, R.x == r
+    fn verifies_natively(circuit: &EcdsaCircuit<pallas::Affine>) -> bool {
+        let (r, s) = circuit.signature.unwrap();
+        let s_inv = match Option::<pallas::Scalar>::from(s.invert()) {
+            Some(s_inv) => s_inv,
+            None => return false,
+        };
+        let u1 = circuit.message_hash.unwrap() * s_inv;
+        let u2 = r * s_inv;
+
+        let point = (pallas::Point::generator() * u1 + circuit.public_key.unwrap() * u2).to_affine();
+        let coords = point.coordinates();
+        if bool::from(coords.is_none()) {
+            return false;
+        }
+        base_to_scalar::<pallas::Affine>(coords.unwrap().x()) == r
+    }
+
+    #[test]
+    fn test_corrupted_signature_is_invalid() {
+        let mut rng = OsRng;
+        let valid = signed_random_message(&mut rng);
+        assert!(verifies_natively(&valid));
+
+        for delta in [1u64, 2, 1 << 32] {
+            let corrupted = with_corrupted_s(
+                EcdsaCircuit { ..valid },
+                pallas::Scalar::from(delta),
+            );
+            assert!(!verifies_natively(&corrupted));
+        }
+    }
+
+    #[test]
+    fn test_dumped_failure_replays() {
+        let circuit = with_corrupted_s(signed_random_message(&mut OsRng), pallas::Scalar::ONE);
+        let failures = match check_satisfied(VERIFY_K, &circuit) {
+            Err(BenchError::Unsatisfied(failures)) => failures,
+            other => panic!("expected unsatisfied witness, got {:?}", other),
+        };
+
+        let replayed = inputs::load(&inputs::dump(&circuit)).unwrap();
+        match check_satisfied(VERIFY_K, &replayed) {
+            Err(BenchError::Unsatisfied(replayed_failures)) => assert_eq!(replayed_failures, failures),
+            other => panic!("expected unsatisfied witness, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cost_report_attributes_rows_to_scalar_mults() {
+        let circuit = signed_random_message(&mut OsRng);
+        let regions = diagnostics::region_rows(VERIFY_K, &circuit, vec![circuit.instance()]);
+
+        let names: Vec<&str> = regions.iter().map(|(region, _)| region.as_str()).collect();
+        assert_eq!(names, ["ecdsa verify", "fixed base mult g", "scalar mult pk", "scalar mult r", "point addition"]);
+        assert_eq!(regions[0].1, 2);
+
+        let total: usize = regions.iter().map(|(_, rows)| rows).sum();
+        let scalar_mults: usize = regions[1..4].iter().map(|(_, rows)| rows).sum();
+        assert!(scalar_mults * 2 > total, "scalar mults use {} of {} rows", scalar_mults, total);
+    }
+
+    #[test]
+    fn test_mock_and_real_provers_agree() {
+        let valid = signed_random_message(&mut OsRng);
+        assert!(matches!(check_prover_agreement(VERIFY_K, &valid), Ok(true)));
+
+        let invalid = with_corrupted_s(EcdsaCircuit { ..valid }, pallas::Scalar::ONE);
+        assert!(matches!(check_prover_agreement(VERIFY_K, &invalid), Ok(false)));
+    }
+
+    #[test]
+    fn test_load_rejects_non_ascii_hex() {
+        let dumped = inputs::dump(&signed_random_message(&mut OsRng));
+        let mut json: serde_json::Value = serde_json::from_str(&dumped).unwrap();
+
+        // 64 bytes, but "é" is two of them and straddles a hex pair
+        json["r"] = serde_json::Value::from(format!("{}é{}", "0".repeat(31), "0".repeat(31)));
+        match inputs::load(&json.to_string()) {
+            Err(err) => assert_eq!(err.to_string(), "invalid inputs: r is not 32 bytes of hex"),
+            Ok(_) => panic!("non-ASCII hex was accepted"),
+        }
+    }
+
+    #[test]
+    fn test_load_rejects_identity_public_key() {
+        let dumped = inputs::dump(&signed_random_message(&mut OsRng));
+        let mut json: serde_json::Value = serde_json::from_str(&dumped).unwrap();
+
+        json["public_key_x"] = serde_json::Value::from("0".repeat(64));
+        json["public_key_y"] = serde_json::Value::from("0".repeat(64));
+        match inputs::load(&json.to_string()) {
+            Err(err) => assert_eq!(err.to_string(), "invalid inputs: public key is the identity"),
+            Ok(_) => panic!("identity public key was accepted"),
+        }
+    }
+
+    // Property test: --inputs-from-stdin reads whatever the user pipes in, so
+    // any edit of a dumped file must load cleanly or be rejected, and anything
+    // that loads must dump back to the same file
+    #[test]
+    fn test_load_mutated_dumps_never_panics() {
+        const HEX_AND_JSON: &[u8] = b"0123456789abcdefABCDEFxz{}[]\",: -";
+
+        let mut rng = ChaCha20Rng::seed_from_u64(464);
+        for _ in 0..200 {
+            let mut dumped = inputs::dump(&signed_random_message(&mut rng)).into_bytes();
+            for _ in 0..1 + rng.gen_range(0..4) {
+                let at = rng.gen_range(0..dumped.len());
+                match rng.gen_range(0..4) {
+                    0 => dumped[at] = HEX_AND_JSON[rng.gen_range(0..HEX_AND_JSON.len())],
+                    1 => dumped[at] = rng.gen(),
+                    2 => {
+                        dumped.remove(at);
+                    }
+                    _ => dumped.truncate(at),
+                }
+            }
+
+            let text = String::from_utf8_lossy(&dumped);
+            if let Ok(loaded) = inputs::load(&text) {
+                let redumped = inputs::dump(&loaded);
+                assert_eq!(inputs::dump(&inputs::load(&redumped).unwrap()), redumped);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_cells_reported() {
+        let circuit = signed_random_message(&mut OsRng);
+        let instance = vec![circuit.instance()];
+
+        let names: Vec<String> = diagnostics::unconstrained_cells(VERIFY_K, &circuit, instance)
+            .into_iter()
+            .map(|cell| cell.name)
+            .collect();
+
+        // The public key and R are on-curve checked and feed the scalar
+        // mults, the fixed-base entries are pinned by the table lookup, and
+        // every point of the scalar mults feeds the next one through the
+        // point gates down to the final s·R check
+        for name in ["pk_x", "pk_y", "r_x", "r_y", "bit 0", "px_window", "x_window", "x_double", "y_double", "x_add", "x_select"] {
+            assert!(!names.iter().any(|n| n == name), "{} should be constrained", name);
+        }
+    }
+
+    #[test]
+    fn test_scalar_mults_dominate_synthesis() {
+        let circuit = signed_random_message(&mut OsRng);
+        let instance = vec![circuit.instance()];
+
+        timings::take();
+        MockProver::run(VERIFY_K, &circuit, instance).unwrap();
+        let timings = timings::take();
+
+        let time = |name: &str| timings
+            .iter()
+            .find(|(region, _)| *region == name)
+            .map(|(_, time)| *time)
+            .unwrap_or_else(|| panic!("no timing for {}", name));
+
+        assert!(time("g_mult") + time("pk_mult") + time("s_mult") > time("ecdsa verify") + time("final addition"));
+    }
+
+    #[test]
+    fn test_ecdsa_batch_verify() {
+        let mut rng = OsRng;
+        let first = signed_random_message(&mut rng);
+        let second = signed_random_message(&mut rng);
+        let publics = [first.instance(), second.instance()].concat();
+        let swapped = [second.instance(), first.instance()].concat();
+
+        let circuit = EcdsaBatchCircuit {
+            signatures: vec![first, second],
+        };
+
+        let prover = MockProver::run(VERIFY_K + 1, &circuit, vec![publics]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Each signature must be bound to its own rows of the instance column
+        let prover = MockProver::run(VERIFY_K + 1, &circuit, vec![swapped]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Two signatures proved in one transcript verify together; corrupting
+    // either one fails the whole batch
+    #[test]
+    fn test_prove_batch() {
+        let mut rng = OsRng;
+        let circuits = [signed_random_message(&mut rng), signed_random_message(&mut rng)];
+
+        let batch = prove_batch(VERIFY_K, &circuits).unwrap();
+        assert!(batch.verified);
+        assert_eq!(batch.proofs, 2);
+        assert_eq!(batch.amortized_proving(), batch.proving / 2);
+        assert_eq!(batch.to_json()["batch_size"], 2);
+
+        let [first, second] = circuits;
+        let flipped = [first, with_corrupted_s(second, pallas::Scalar::ONE)];
+        assert!(!prove_batch(VERIFY_K, &flipped).unwrap().verified);
+    }
+
+    // Multiplies the generator by a fixed scalar and records the values of
+    // the decomposition cells returned by the chip
+    struct ScalarBitsCircuit {
+        scalar: pallas::Base,
+        bits: RefCell<Vec<pallas::Base>>,
+    }
+
+    impl Circuit<pallas::Base> for ScalarBitsCircuit {
+        type Config = EcdsaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { scalar: self.scalar, bits: RefCell::new(vec![]) }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            EcdsaCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), ErrorFront> {
+            let chip: EcdsaChip<pallas::Affine> = EcdsaChip::construct(config);
+            let generator = pallas::Affine::generator();
+            let coords = generator.coordinates().unwrap();
+
+            let (scalar, g) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let scalar = region.assign_advice(|| "scalar", chip.config.x, 0, || Value::known(self.scalar))?;
+                    let g_x = region.assign_advice(|| "g_x", chip.config.x, 1, || Value::known(*coords.x()))?;
+                    let g_y = region.assign_advice(|| "g_y", chip.config.y, 1, || Value::known(*coords.y()))?;
+                    Ok((scalar, (g_x, g_y)))
+                },
+            )?;
+
+            let (_, bits) = layouter.assign_region(
+                || "scalar mult",
+                |mut region| chip.scalar_mult_with_bits(&mut region, &scalar, &g),
+            )?;
+
+            let mut recorded = self.bits.borrow_mut();
+            recorded.clear();
+            for bit in bits.iter() {
+                bit.value().map(|b| recorded.push(*b));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_scalar_mult_bits_recompose() {
+        let scalar = pallas::Base::from(0b1011_0110u64) + pallas::Base::from(u64::MAX);
+        let circuit = ScalarBitsCircuit { scalar, bits: RefCell::new(vec![]) };
+
+        let prover = MockProver::run(10, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let bits = circuit.bits.borrow();
+        assert_eq!(bits.len(), pallas::Base::NUM_BITS as usize);
+        assert!(bits.iter().all(|b| *b == pallas::Base::ZERO || *b == pallas::Base::ONE));
+
+        let recomposed = bits.iter().fold(pallas::Base::ZERO, |acc, b| acc + acc + b);
+        assert_eq!(recomposed, scalar);
+    }
+
+    #[test]
+    fn test_scalar_near_modulus_decomposes() {
+        let scalar = -pallas::Base::ONE;
+        let circuit = ScalarBitsCircuit { scalar, bits: RefCell::new(vec![]) };
+
+        let prover = MockProver::run(10, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let recomposed = circuit.bits.borrow().iter().fold(pallas::Base::ZERO, |acc, b| acc + acc + b);
+        assert_eq!(recomposed, scalar);
+    }
+
+    // Multiplies `point` by `scalar` in-circuit and records the affine
+    // coordinates of the result. Pallas's base field is the circuit field,
+    // so its points can be used without conversion.
+    struct ScalarMultCircuit {
+        scalar: pallas::Base,
+        point: pallas::Affine,
+        result: RefCell<Option<(pallas::Base, pallas::Base)>>,
+    }
+
+    impl Circuit<pallas::Base> for ScalarMultCircuit {
+        type Config = EcdsaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { scalar: self.scalar, point: self.point, result: RefCell::new(None) }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            EcdsaCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), ErrorFront> {
+            let chip: EcdsaChip<pallas::Affine> = EcdsaChip::construct(config);
+            let coords = self.point.coordinates().unwrap();
+
+            let (scalar, point) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let scalar = region.assign_advice(|| "scalar", chip.config.x, 0, || Value::known(self.scalar))?;
+                    let x = region.assign_advice(|| "p_x", chip.config.x, 1, || Value::known(*coords.x()))?;
+                    let y = region.assign_advice(|| "p_y", chip.config.y, 1, || Value::known(*coords.y()))?;
+                    Ok((scalar, (x, y)))
+                },
+            )?;
+
+            let (x, y) = layouter.assign_region(
+                || "scalar mult",
+                |mut region| chip.scalar_mult(&mut region, &scalar, &point),
+            )?;
+
+            x.value().zip(y.value()).map(|(x, y)| *self.result.borrow_mut() = Some((*x, *y)));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_scalar_mult_matches_native() {
+        let generator = pallas::Affine::generator();
+
+        for k in [1u64, 2, 3, 0b1011, 0xdead_beef] {
+            let circuit = ScalarMultCircuit {
+                scalar: pallas::Base::from(k),
+                point: generator,
+                result: RefCell::new(None),
+            };
+            let prover = MockProver::run(10, &circuit, vec![vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            let expected = (pallas::Point::generator() * pallas::Scalar::from(k)).to_affine();
+            let expected = expected.coordinates().unwrap();
+            assert_eq!(
+                circuit.result.borrow().unwrap(),
+                (*expected.x(), *expected.y()),
+                "{}·G",
+                k,
+            );
+        }
+    }
+
+    // The gadget on the curve the benchmark proves over: random Pallas
+    // points and full-width scalars, checked against pasta_curves
+    #[test]
+    fn test_scalar_mult_on_random_pallas_points() {
+        for _ in 0..3 {
+            let point = pallas::Point::random(OsRng).to_affine();
+            let scalar = pallas::Base::random(OsRng);
+            let circuit = ScalarMultCircuit { scalar, point, result: RefCell::new(None) };
+            let prover = MockProver::run(10, &circuit, vec![vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The scalar's integer value is below p < n, so it maps to the
+            // scalar field unchanged
+            let expected = (point * base_to_scalar::<pallas::Affine>(&scalar)).to_affine();
+            let expected = expected.coordinates().unwrap();
+            assert_eq!(circuit.result.borrow().unwrap(), (*expected.x(), *expected.y()));
+        }
+
+        // A zero scalar has no affine result and is rejected
+        let circuit = ScalarMultCircuit {
+            scalar: pallas::Base::ZERO,
+            point: pallas::Point::random(OsRng).to_affine(),
+            result: RefCell::new(None),
+        };
+        assert!(MockProver::run(10, &circuit, vec![vec![]]).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn test_point_ops_occupy_distinct_rows() {
+        let circuit = ScalarMultCircuit {
+            scalar: pallas::Base::from(0b1011u64),
+            point: pallas::Affine::generator(),
+            result: RefCell::new(None),
+        };
+        let cells = diagnostics::assigned_cells(10, &circuit, vec![vec![]]);
+
+        // No advice cell is assigned twice, so every intermediate point of
+        // the double-and-add survives in the witness
+        let mut positions: Vec<(usize, usize)> = cells.iter().map(|cell| (cell.column, cell.row)).collect();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), cells.len());
+
+        let bits = pallas::Base::NUM_BITS as usize;
+        for name in ["x_double", "y_double", "x_add", "y_add", "x_select", "y_select"] {
+            assert_eq!(cells.iter().filter(|cell| cell.name == name).count(), bits, "{}", name);
+        }
+    }
+
+    // The table windows add back up to scalar·base, checked natively on
+    // Pallas
+    #[test]
+    fn test_fixed_base_points_sum_to_multiple() {
+        let generator = pallas::Affine::generator();
+        let coords = generator.coordinates().unwrap();
+
+        for window_bits in [2, 3, 5] {
+            let points = fixed_base_points::<pallas::Scalar, _>((*coords.x(), *coords.y()), window_bits);
+            assert_eq!(points.len(), (pallas::Scalar::NUM_BITS as usize).div_ceil(window_bits));
+            assert!(points.iter().all(|digits| digits.len() == 1 << window_bits));
+
+            for k in [1u64, 0b1011, 0xdead_beef] {
+                let digit = |i: usize| (k.checked_shr((i * window_bits) as u32).unwrap_or(0) & ((1 << window_bits) - 1)) as usize;
+                let sum = (1..points.len()).fold(points[0][digit(0)], |acc, i| native_add(acc, points[i][digit(i)]));
+
+                let expected = (pallas::Point::generator() * pallas::Scalar::from(k)).to_affine();
+                let expected = expected.coordinates().unwrap();
+                assert_eq!(sum, (*expected.x(), *expected.y()), "{}·G with {}-bit windows", k, window_bits);
+            }
+        }
+    }
+
+    // Multiplies the generator by `scalar`, with `fixed_base_mul` over
+    // W-bit windows or with the double-and-add of `curve_scalar_mult`, and
+    // records the result
+    struct GeneratorMultCircuit<const W: usize> {
+        scalar: pallas::Scalar,
+        fixed_base: bool,
+        result: RefCell<Option<(pallas::Base, pallas::Base)>>,
+    }
+
+    impl<const W: usize> GeneratorMultCircuit<W> {
+        fn new(scalar: pallas::Scalar, fixed_base: bool) -> Self {
+            Self { scalar, fixed_base, result: RefCell::new(None) }
+        }
+    }
+
+    impl<const W: usize> Circuit<pallas::Base> for GeneratorMultCircuit<W> {
+        type Config = EcdsaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::new(self.scalar, self.fixed_base)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            EcdsaCircuit::<pallas::Affine>::configure_with_window(meta, W)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), ErrorFront> {
+            let chip: EcdsaChip<pallas::Affine> = EcdsaChip::construct(config);
+            let (g_x, g_y) = EcdsaChip::<pallas::Affine>::generator_coordinates();
+
+            let g = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let g_x = region.assign_advice_from_constant(|| "g_x", chip.config.x, 0, g_x)?;
+                    let g_y = region.assign_advice_from_constant(|| "g_y", chip.config.y, 0, g_y)?;
+                    Ok((g_x, g_y))
+                },
+            )?;
+
+            let scalar = Value::known(self.scalar);
+            let (x, y) = if self.fixed_base {
+                chip.load_fixed_base_table(&mut layouter)?;
+                layouter.assign_region(
+                    || "fixed base mult",
+                    |mut region| chip.fixed_base_mul(&mut region, scalar),
+                )?.0
+            } else {
+                layouter.assign_region(
+                    || "scalar mult",
+                    |mut region| chip.curve_scalar_mult(&mut region, scalar, &g),
+                )?
+            };
+
+            x.value().zip(y.value()).map(|(x, y)| *self.result.borrow_mut() = Some((*x, *y)));
+            Ok(())
+        }
+    }
+
+    fn generator_mult<const W: usize>(scalar: pallas::Scalar, fixed_base: bool) -> (pallas::Base, pallas::Base) {
+        let circuit = GeneratorMultCircuit::<W>::new(scalar, fixed_base);
+        let prover = MockProver::run(VERIFY_K, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        let result = circuit.result.borrow().expect("result recorded");
+        result
+    }
+
+    #[test]
+    fn test_fixed_base_mul_matches_scalar_mult() {
+        let scalars = [
+            pallas::Scalar::from(1u64),
+            pallas::Scalar::from(8u64),
+            pallas::Scalar::from(0xdead_beefu64),
+            // -1 itself would add G to -G in the last, unconditional step
+            // of the double-and-add
+            -pallas::Scalar::from(2u64),
+            pallas::Scalar::random(OsRng),
+        ];
+        for scalar in scalars {
+            let expected = generator_mult::<FIXED_BASE_WINDOW_BITS>(scalar, false);
+            let native = (pallas::Point::generator() * scalar).to_affine().coordinates().unwrap();
+            assert_eq!(expected, (*native.x(), *native.y()), "{:?}", scalar);
+            assert_eq!(generator_mult::<FIXED_BASE_WINDOW_BITS>(scalar, true), expected, "{:?}", scalar);
+        }
+
+        let scalar = pallas::Scalar::random(OsRng);
+        let expected = generator_mult::<FIXED_BASE_WINDOW_BITS>(scalar, false);
+        assert_eq!(generator_mult::<2>(scalar, true), expected);
+        assert_eq!(generator_mult::<4>(scalar, true), expected);
+    }
+
+    // Polynomial constraints applied by one generator mult: every gate
+    // once per row its selector is enabled on, plus one lookup per window
+    fn applied_constraints<const W: usize>(fixed_base: bool) -> usize {
+        let mut meta = ConstraintSystem::<pallas::Base>::default();
+        let config = EcdsaCircuit::<pallas::Affine>::configure_with_window(&mut meta, W);
+        let gate = |name| curve_ops::gate_cost(&meta, name).expect("gate is configured").constraints;
+        let per_row = [
+            (config.q_decompose, gate("scalar decomposition") + gate("canonical decomposition")),
+            (config.q_double, gate("point double")),
+            (config.q_add, gate("point add")),
+            (config.q_select, gate("point select")),
+            (config.q_fixed_base, 1),
+        ];
+
+        let circuit = GeneratorMultCircuit::<W>::new(pallas::Scalar::from(0xdead_beefu64), fixed_base);
+        diagnostics::enabled_selectors(VERIFY_K, &circuit, vec![vec![]])
+            .iter()
+            .map(|(selector, _)| per_row.iter().find(|(s, _)| s == selector).map_or(0, |(_, cost)| *cost))
+            .sum()
+    }
+
+    #[test]
+    fn test_fixed_base_mul_applies_fewer_constraints() {
+        let naive = applied_constraints::<FIXED_BASE_WINDOW_BITS>(false);
+        let windowed = [applied_constraints::<2>(true), applied_constraints::<3>(true), applied_constraints::<4>(true)];
+
+        // Both decompose the scalar; the windows replace a double, an add
+        // and a select per bit with a lookup and an add per window
+        assert!(windowed[1] * 2 < naive, "windowed {} vs naive {}", windowed[1], naive);
+        assert!(windowed[0] > windowed[1] && windowed[1] > windowed[2], "{:?}", windowed);
+    }
+
+    // Adds two Pallas points, either of which may be the identity, with the
+    // total `point_add` and records the flagged sum
+    struct CompleteAddCircuit {
+        p: pallas::Affine,
+        q: pallas::Affine,
+        sum: RefCell<Option<(pallas::Base, pallas::Base, pallas::Base)>>,
+    }
+
+    impl CompleteAddCircuit {
+        fn new(p: pallas::Affine, q: pallas::Affine) -> Self {
+            Self { p, q, sum: RefCell::new(None) }
+        }
+    }
+
+    impl Circuit<pallas::Base> for CompleteAddCircuit {
+        type Config = EcdsaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::new(self.p, self.q)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            EcdsaCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), ErrorFront> {
+            let chip: EcdsaChip<pallas::Affine> = EcdsaChip::construct(config);
+
+            let sum = layouter.assign_region(
+                || "complete add",
+                |mut region| {
+                    // Coordinates go in the px and py columns, then get
+                    // flagged into rows 0 (q) and 1 (p); the sum is in row 2
+                    let mut flagged = Vec::new();
+                    for (row, point) in [self.q, self.p].into_iter().enumerate() {
+                        let infinity = bool::from(point.is_identity());
+                        let (x, y) = if infinity {
+                            (pallas::Base::ZERO, pallas::Base::ZERO)
+                        } else {
+                            let coords = point.coordinates().unwrap();
+                            (*coords.x(), *coords.y())
+                        };
+                        let coords = (
+                            region.assign_advice(|| "x", chip.config.px, row, || Value::known(x))?,
+                            region.assign_advice(|| "y", chip.config.py, row, || Value::known(y))?,
+                        );
+                        flagged.push(chip.flag_point(&mut region, row, &coords, infinity)?);
+                    }
+                    chip.point_add(&mut region, 2, &flagged[1], &flagged[0])
+                },
+            )?;
+
+            sum.x.value()
+                .zip(sum.y.value())
+                .zip(sum.infinity.value())
+                .map(|((x, y), infinity)| *self.sum.borrow_mut() = Some((*x, *y, *infinity)));
+            Ok(())
+        }
+    }
+
+    // The flagged sum p + q computed in-circuit, checked by the mock prover
+    fn complete_add_in_circuit(p: pallas::Affine, q: pallas::Affine) -> (pallas::Base, pallas::Base, pallas::Base) {
+        let circuit = CompleteAddCircuit::new(p, q);
+        let prover = MockProver::run(5, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        let sum = circuit.sum.borrow().expect("sum recorded");
+        sum
+    }
+
+    fn flagged(point: pallas::Point) -> (pallas::Base, pallas::Base, pallas::Base) {
+        match Option::<Coordinates<pallas::Affine>>::from(point.to_affine().coordinates()) {
+            Some(coords) => (*coords.x(), *coords.y(), pallas::Base::ZERO),
+            None => (pallas::Base::ZERO, pallas::Base::ZERO, pallas::Base::ONE),
+        }
+    }
+
+    #[test]
+    fn test_point_add_doubles_equal_points() {
+        let p = pallas::Point::generator() * pallas::Scalar::from(5u64);
+
+        assert_eq!(complete_add_in_circuit(p.to_affine(), p.to_affine()), flagged(p.double()));
+    }
+
+    #[test]
+    fn test_point_add_of_inverses_is_identity() {
+        let p = pallas::Point::generator() * pallas::Scalar::from(5u64);
+
+        let sum = complete_add_in_circuit(p.to_affine(), (-p).to_affine());
+        assert_eq!(sum, (pallas::Base::ZERO, pallas::Base::ZERO, pallas::Base::ONE));
+    }
+
+    #[test]
+    fn test_point_add_with_identity() {
+        let p = pallas::Point::generator() * pallas::Scalar::from(5u64);
+        let identity = pallas::Affine::identity();
+
+        assert_eq!(complete_add_in_circuit(p.to_affine(), identity), flagged(p));
+        assert_eq!(complete_add_in_circuit(identity, p.to_affine()), flagged(p));
+        assert_eq!(complete_add_in_circuit(identity, identity), flagged(pallas::Point::identity()));
+    }
+
+    #[test]
+    fn test_point_add_distinct_points() {
+        let p = pallas::Point::generator() * pallas::Scalar::from(5u64);
+        let q = pallas::Point::generator() * pallas::Scalar::from(9u64);
+
+        assert_eq!(complete_add_in_circuit(p.to_affine(), q.to_affine()), flagged(p + q));
+    }
+
+    // The witness side picks the case natively
+    #[test]
+    fn test_complete_add_native_cases() {
+        let p = pallas::Point::generator() * pallas::Scalar::from(5u64);
+        let coords = p.to_affine().coordinates().unwrap();
+        let (x, y) = (*coords.x(), *coords.y());
+
+        assert_eq!(complete_add((x, y, false), (x, -y, false)).1, (pallas::Base::ZERO, pallas::Base::ZERO, true));
+        assert_eq!(complete_add((x, y, false), (x, y, false)).1, {
+            let (x2, y2, _) = flagged(p.double());
+            (x2, y2, false)
+        });
+        assert_eq!(complete_add((pallas::Base::ZERO, pallas::Base::ZERO, true), (x, y, false)).1, (x, y, false));
+    }
+
+    // Decomposes `scalar` using the given bits instead of its canonical ones
+    struct ForgedBitsCircuit {
+        scalar: pallas::Base,
+        bits: Vec<bool>,
+    }
+
+    impl Circuit<pallas::Base> for ForgedBitsCircuit {
+        type Config = EcdsaConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { scalar: self.scalar, bits: self.bits.clone() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            EcdsaCircuit::<pallas::Affine>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), ErrorFront> {
+            let chip: EcdsaChip<pallas::Affine> = EcdsaChip::construct(config);
+
+            let scalar = layouter.assign_region(
+                || "inputs",
+                |mut region| region.assign_advice(|| "scalar", chip.config.x, 0, || Value::known(self.scalar)),
+            )?;
+
+            layouter.assign_region(
+                || "decomposition",
+                |mut region| chip.decompose_bits(&mut region, &scalar, Value::known(self.bits.clone())),
+            )?;
+            Ok(())
+        }
+    }
+
+    // Adds a small integer to a most-significant-first bit string
+    fn add_to_bits(bits: &[bool], mut n: u64) -> Vec<bool> {
+        let mut sum = bits.to_vec();
+        for bit in sum.iter_mut().rev() {
+            let total = *bit as u64 + (n & 1);
+            *bit = total & 1 == 1;
+            n = (n >> 1) + (total >> 1);
+        }
+        assert_eq!(n, 0, "overflow");
+        sum
+    }
+
+    #[test]
+    fn test_non_canonical_decomposition_rejected() {
+        let scalar = pallas::Base::from(5);
+
+        // The bits of 5 and of p + 5 both recompose to 5 in the field
+        let canonical = ForgedBitsCircuit { scalar, bits: scalar_bits(&scalar) };
+        let prover = MockProver::run(10, &canonical, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let forged = ForgedBitsCircuit {
+            scalar,
+            bits: add_to_bits(&modulus_bits::<pallas::Base>(), 5),
+        };
+        let prover = MockProver::run(10, &forged, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_scalar_bits_are_constrained_boolean() {
+        let mut meta = ConstraintSystem::<pallas::Base>::default();
+        EcdsaCircuit::<pallas::Affine>::configure(&mut meta);
+
+        let gate = meta.gates()
+            .iter()
+            .find(|gate| gate.name() == "scalar decomposition")
+            .expect("decomposition gate is configured");
+        assert_eq!(gate.polynomials().len(), 2);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+//! Per-Region Synthesis Timing
+//!
+//! Witness generation happens inside `assign_region` closures, so the time
+//! spent in each named region is where synthesis time goes. `timed` wraps a
+//! region assignment and adds its wall-clock time to a per-thread total for
+//! that name; `take` returns the totals and resets them. Timing is always
+//! on, since an `Instant` pair costs nothing next to a region assignment.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static REGION_TIMINGS: RefCell<Vec<(&'static str, Duration)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) fn timed<T, E>(name: &'static str, assign: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let start = Instant::now();
+    let result = assign();
+    let elapsed = start.elapsed();
+
+    REGION_TIMINGS.with(|timings| {
+        let mut timings = timings.borrow_mut();
+        match timings.iter_mut().find(|(region, _)| *region == name) {
+            Some((_, total)) => *total += elapsed,
+            None => timings.push((name, elapsed)),
+        }
+    });
+    result
+}
+
+// Totals per region, in the order regions were first entered
+pub(crate) fn take() -> Vec<(&'static str, Duration)> {
+    REGION_TIMINGS.with(|timings| std::mem::take(&mut *timings.borrow_mut()))
+}
@@ -0,0 +1,20 @@
+// The benchmark as the runner sees it: a child process whose stdout must
+// parse as metrics even though it reports progress as it goes
+
+use std::path::Path;
+
+use benchmark_runner::{run_template, ReportEntry};
+
+#[test]
+fn test_runner_parses_ecdsa_output() {
+    let entry = run_template(Path::new(env!("CARGO_BIN_EXE_ecdsa_benchmark")), "small", true);
+
+    let metrics = match &entry {
+        ReportEntry::Metrics(metrics) => metrics,
+        ReportEntry::Error { error, .. } => panic!("runner rejected the ECDSA output: {}", error),
+    };
+    assert_eq!(metrics.system, "halo2");
+    assert_eq!(metrics.operation, "ecdsa");
+    assert_eq!(metrics.circuit_size, "small");
+    assert!(metrics.resource_metrics.proof_size_bytes > 0);
+}
@@ -0,0 +1,619 @@
+//! EdDSA Verification Circuit
+//!
+//! Proves that private EdDSA signatures over Baby Jubjub (see `native`) are
+//! valid for public messages. Each message is one row of the instance
+//! column; the public key, R and S stay in the witness. Per signature the
+//! circuit:
+//!
+//! - checks that A and R are on the curve,
+//! - hashes h = Poseidon(R.x, R.y, A.x, A.y, M) with halo2_gadgets' Pow5
+//!   chip,
+//! - range checks S < l, so (R, S + l) is not a second valid signature,
+//! - computes [S]B and R + [h]A with double-and-add ladders, one row per
+//!   scalar bit, using the complete Edwards formulas,
+//! - and constrains [8][S]B = [8](R + [h]A).
+//!
+//! h is decomposed into 254 bits without checking that they are its
+//! canonical encoding. A non-canonical decomposition differs by the field
+//! modulus, which the prover cannot choose, so it buys nothing beyond a
+//! different multiple of A.
+//!
+//! The number of signatures is chosen by the circuit_size argument:
+//!
+//! - small:  1 signature
+//! - medium: 4 signatures
+//! - large:  16 signatures
+
+use halo2_gadgets::poseidon::{primitives::ConstantLength, Hash, Pow5Chip, Pow5Config};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront, Expression, Instance,
+        Selector},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::{Field, PrimeField};
+use halo2_common::{collect_circuit_stats, record_proofs};
+use native::{Keypair, Point, PoseidonBn254, Signature, RATE, SUBGROUP_BITS, WIDTH};
+use rand::rngs::OsRng;
+
+mod native;
+
+// h can be any field element
+const CHALLENGE_BITS: usize = 254;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Upper bound on the rows one signature takes: the inputs, three Pow5
+// permutations, the range check and the two ladders (one row per bit plus
+// the result), and seven additions of two rows each
+const ROWS_PER_SIGNATURE: usize = 3 + 3 * 42 + (SUBGROUP_BITS + 1) * 2 + CHALLENGE_BITS + 1 + 7 * 2;
+
+fn signatures_for(circuit_size: &str) -> usize {
+    match circuit_size {
+        "medium" => 4,
+        "large" => 16,
+        _ => 1,
+    }
+}
+
+// A message with its signer's public key and signature
+#[derive(Debug, Clone)]
+struct SignedMessage {
+    public_key: Point,
+    message: Fr,
+    signature: Signature,
+}
+
+impl SignedMessage {
+    // A fresh key signing a random message
+    fn random() -> Self {
+        let key = Keypair::random(&mut OsRng);
+        let message = Fr::random(OsRng);
+        let signature = key.sign(message, &mut OsRng);
+        Self { public_key: key.public, message, signature }
+    }
+}
+
+// The witness for one signature; its message is public
+#[derive(Debug, Clone)]
+struct SignatureWitness {
+    public_key: Value<Point>,
+    r: Value<Point>,
+    s: Value<Fr>,
+}
+
+#[derive(Debug, Clone)]
+struct AssignedPoint {
+    x: AssignedCell<Fr, Fr>,
+    y: AssignedCell<Fr, Fr>,
+}
+
+impl AssignedPoint {
+    fn value(&self) -> Value<Point> {
+        self.x.value().copied().zip(self.y.value().copied())
+    }
+}
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct EddsaConfig {
+    // Running point and scalar accumulator
+    x: Column<Advice>,
+    y: Column<Advice>,
+    z: Column<Advice>,
+    bit: Column<Advice>,
+    // The running point doubled
+    dx: Column<Advice>,
+    dy: Column<Advice>,
+    // The operand, or the identity when the bit is 0
+    sx: Column<Advice>,
+    sy: Column<Advice>,
+    // The operand
+    px: Column<Advice>,
+    py: Column<Advice>,
+    message: Column<Instance>,
+    q_on_curve: Selector,
+    q_add: Selector,
+    q_ladder: Selector,
+    q_offset: Selector,
+    q_bits: Selector,
+    poseidon: Pow5Config<Fr, WIDTH, RATE>,
+}
+
+// (x3, y3) = (x1, y1) + (x2, y2), with the divisions multiplied out
+fn edwards_add(
+    (x1, y1): (Expression<Fr>, Expression<Fr>),
+    (x2, y2): (Expression<Fr>, Expression<Fr>),
+    (x3, y3): (Expression<Fr>, Expression<Fr>),
+) -> [Expression<Fr>; 2] {
+    let one = Expression::Constant(Fr::ONE);
+    let a = Expression::Constant(native::coeff_a());
+    let t = Expression::Constant(native::coeff_d()) * x1.clone() * x2.clone() * y1.clone() * y2.clone();
+    [
+        x3 * (one.clone() + t.clone()) - (x1.clone() * y2.clone() + y1.clone() * x2.clone()),
+        y3 * (one - t) - (y1 * y2 - a * x1 * x2),
+    ]
+}
+
+impl EddsaConfig {
+    // One double-and-add step from `point`: its double, the operand or the
+    // identity, and their sum
+    fn ladder_step(point: Point, operand: Point, bit: bool) -> (Point, Point, Point) {
+        let doubled = native::add(point, point);
+        let selected = if bit { operand } else { native::IDENTITY };
+        (doubled, selected, native::add(doubled, selected))
+    }
+
+    fn witness_inputs(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        index: usize,
+        signature: &SignatureWitness,
+    ) -> Result<(AssignedPoint, AssignedPoint, AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>), ErrorFront> {
+        layouter.assign_region(
+            || format!("inputs {}", index),
+            |mut region| {
+                let mut point = |row: usize, value: Value<Point>| {
+                    self.q_on_curve.enable(&mut region, row)?;
+                    Ok::<_, ErrorFront>(AssignedPoint {
+                        x: region.assign_advice(|| "x", self.x, row, || value.map(|p| p.0))?,
+                        y: region.assign_advice(|| "y", self.y, row, || value.map(|p| p.1))?,
+                    })
+                };
+                let public_key = point(0, signature.public_key)?;
+                let r = point(1, signature.r)?;
+
+                let message = region.assign_advice_from_instance(|| "message", self.message, index, self.x, 2)?;
+                let s = region.assign_advice(|| "s", self.z, 2, || signature.s)?;
+                Ok((public_key, r, message, s))
+            },
+        )
+    }
+
+    fn constant_point(&self, layouter: &mut impl Layouter<Fr>, point: Point) -> Result<AssignedPoint, ErrorFront> {
+        layouter.assign_region(
+            || "base point",
+            |mut region| {
+                Ok(AssignedPoint {
+                    x: region.assign_advice_from_constant(|| "x", self.x, 0, point.0)?,
+                    y: region.assign_advice_from_constant(|| "y", self.y, 0, point.1)?,
+                })
+            },
+        )
+    }
+
+    // S + 2^251 - l, decomposed least significant bit first into a running
+    // sum that has to reach 0 after 251 bits
+    fn check_below_order(&self, layouter: &mut impl Layouter<Fr>, s: &AssignedCell<Fr, Fr>) -> Result<(), ErrorFront> {
+        layouter.assign_region(
+            || "S < l",
+            |mut region| {
+                self.q_offset.enable(&mut region, 0)?;
+                s.copy_advice(|| "s", &mut region, self.x, 0)?;
+
+                let shifted = s.value().map(|s| native::to_biguint(&(*s + native::range_offset())));
+                let mut z = region.assign_advice(|| "z", self.z, 0, || shifted.as_ref().map(native::to_field))?;
+                for i in 0..SUBGROUP_BITS {
+                    self.q_bits.enable(&mut region, i)?;
+                    let bit = shifted.as_ref().map(|t| Fr::from(t.bit(i as u64) as u64));
+                    region.assign_advice(|| "bit", self.bit, i, || bit)?;
+                    let next = shifted.as_ref().map(|t| native::to_field(&(t >> (i + 1))));
+                    z = region.assign_advice(|| "z", self.z, i + 1, || next)?;
+                }
+                region.constrain_constant(z.cell(), Fr::ZERO)
+            },
+        )
+    }
+
+    // [scalar]point from the low `num_bits` bits of `scalar`, most
+    // significant first; the accumulated bits must equal `scalar`
+    fn scalar_mul(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        scalar: &AssignedCell<Fr, Fr>,
+        point: &AssignedPoint,
+        num_bits: usize,
+    ) -> Result<AssignedPoint, ErrorFront> {
+        layouter.assign_region(
+            || "scalar mul",
+            |mut region| {
+                let bits = scalar.value().map(|s| native::to_bits(s, num_bits));
+                let operand = point.value();
+
+                let mut acc = Value::known(native::IDENTITY);
+                let mut z = Value::known(Fr::ZERO);
+                let mut x = region.assign_advice_from_constant(|| "x", self.x, 0, Fr::ZERO)?;
+                let mut y = region.assign_advice_from_constant(|| "y", self.y, 0, Fr::ONE)?;
+                let mut z_cell = region.assign_advice_from_constant(|| "z", self.z, 0, Fr::ZERO)?;
+
+                for row in 0..num_bits {
+                    self.q_ladder.enable(&mut region, row)?;
+                    point.x.copy_advice(|| "px", &mut region, self.px, row)?;
+                    point.y.copy_advice(|| "py", &mut region, self.py, row)?;
+
+                    let bit = bits.as_ref().map(|bits| bits[row]);
+                    let step = acc.zip(operand).zip(bit).map(|((acc, operand), bit)| Self::ladder_step(acc, operand, bit));
+                    let (operands, next) = step.map(|(d, s, n)| ((d, s), n)).unzip();
+                    let (doubled, selected) = operands.unzip();
+
+                    region.assign_advice(|| "bit", self.bit, row, || bit.map(|b| Fr::from(b as u64)))?;
+                    region.assign_advice(|| "dx", self.dx, row, || doubled.map(|p| p.0))?;
+                    region.assign_advice(|| "dy", self.dy, row, || doubled.map(|p| p.1))?;
+                    region.assign_advice(|| "sx", self.sx, row, || selected.map(|p| p.0))?;
+                    region.assign_advice(|| "sy", self.sy, row, || selected.map(|p| p.1))?;
+
+                    acc = next;
+                    z = z.zip(bit).map(|(z, bit)| z.double() + Fr::from(bit as u64));
+                    x = region.assign_advice(|| "x", self.x, row + 1, || acc.map(|p| p.0))?;
+                    y = region.assign_advice(|| "y", self.y, row + 1, || acc.map(|p| p.1))?;
+                    z_cell = region.assign_advice(|| "z", self.z, row + 1, || z)?;
+                }
+
+                region.constrain_equal(z_cell.cell(), scalar.cell())?;
+                Ok(AssignedPoint { x, y })
+            },
+        )
+    }
+
+    fn add(&self, layouter: &mut impl Layouter<Fr>, p: &AssignedPoint, q: &AssignedPoint) -> Result<AssignedPoint, ErrorFront> {
+        layouter.assign_region(
+            || "add",
+            |mut region| {
+                self.q_add.enable(&mut region, 0)?;
+                p.x.copy_advice(|| "x", &mut region, self.x, 0)?;
+                p.y.copy_advice(|| "y", &mut region, self.y, 0)?;
+                q.x.copy_advice(|| "px", &mut region, self.px, 0)?;
+                q.y.copy_advice(|| "py", &mut region, self.py, 0)?;
+
+                let sum = p.value().zip(q.value()).map(|(p, q)| native::add(p, q));
+                Ok(AssignedPoint {
+                    x: region.assign_advice(|| "x", self.x, 1, || sum.map(|p| p.0))?,
+                    y: region.assign_advice(|| "y", self.y, 1, || sum.map(|p| p.1))?,
+                })
+            },
+        )
+    }
+
+    fn mul_by_cofactor(&self, layouter: &mut impl Layouter<Fr>, point: AssignedPoint) -> Result<AssignedPoint, ErrorFront> {
+        (0..3).try_fold(point, |acc, _| self.add(layouter, &acc, &acc))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EddsaCircuit {
+    signatures: Vec<SignatureWitness>,
+}
+
+impl EddsaCircuit {
+    fn new(signed: &[SignedMessage]) -> Self {
+        Self {
+            signatures: signed
+                .iter()
+                .map(|signed| SignatureWitness {
+                    public_key: Value::known(signed.public_key),
+                    r: Value::known(signed.signature.r),
+                    s: Value::known(signed.signature.s),
+                })
+                .collect(),
+        }
+    }
+
+    fn min_k(&self) -> u32 {
+        let rows = self.signatures.len() * ROWS_PER_SIGNATURE + 1 + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl Circuit<Fr> for EddsaCircuit {
+    type Config = EddsaConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        let unknown = SignatureWitness { public_key: Value::unknown(), r: Value::unknown(), s: Value::unknown() };
+        Self { signatures: vec![unknown; self.signatures.len()] }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+        let [x, y, z, bit, dx, dy, sx, sy, px, py] = [(); 10].map(|_| meta.advice_column());
+        let message = meta.instance_column();
+        let q_on_curve = meta.selector();
+        let q_add = meta.selector();
+        let q_ladder = meta.selector();
+        let q_offset = meta.selector();
+        let q_bits = meta.selector();
+
+        // Poseidon's initial capacity element, the base point and the
+        // ladders' starting values are constants
+        meta.enable_constant(rc_b[0]);
+        for column in [x, y, z, px, py] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(message);
+
+        let one = || Expression::Constant(Fr::ONE);
+
+        // a·x² + y² = 1 + d·x²·y²
+        meta.create_gate("on curve", |meta| {
+            let q = meta.query_selector(q_on_curve);
+            let xx = meta.query_advice(x, Rotation::cur()).square();
+            let yy = meta.query_advice(y, Rotation::cur()).square();
+            let a = Expression::Constant(native::coeff_a());
+            let d = Expression::Constant(native::coeff_d());
+
+            vec![q * (a * xx.clone() + yy.clone() - one() - d * xx * yy)]
+        });
+
+        // (x, y) + (px, py) on the next row
+        meta.create_gate("point add", |meta| {
+            let q = meta.query_selector(q_add);
+            let p = (meta.query_advice(x, Rotation::cur()), meta.query_advice(y, Rotation::cur()));
+            let operand = (meta.query_advice(px, Rotation::cur()), meta.query_advice(py, Rotation::cur()));
+            let sum = (meta.query_advice(x, Rotation::next()), meta.query_advice(y, Rotation::next()));
+
+            edwards_add(p, operand, sum).map(|constraint| q.clone() * constraint)
+        });
+
+        // One double-and-add step: the next running point is (x, y) doubled
+        // into (dx, dy), plus (px, py) if the bit is set and the identity
+        // otherwise, selected into (sx, sy). The bit is shifted into z.
+        meta.create_gate("ladder step", |meta| {
+            let q = meta.query_selector(q_ladder);
+            let p = (meta.query_advice(x, Rotation::cur()), meta.query_advice(y, Rotation::cur()));
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let doubled = (meta.query_advice(dx, Rotation::cur()), meta.query_advice(dy, Rotation::cur()));
+            let selected = (meta.query_advice(sx, Rotation::cur()), meta.query_advice(sy, Rotation::cur()));
+            let operand = (meta.query_advice(px, Rotation::cur()), meta.query_advice(py, Rotation::cur()));
+            let next = (meta.query_advice(x, Rotation::next()), meta.query_advice(y, Rotation::next()));
+            let z_next = meta.query_advice(z, Rotation::next());
+
+            let [double_x, double_y] = edwards_add(p.clone(), p, doubled.clone());
+            let [add_x, add_y] = edwards_add(doubled, selected.clone(), next);
+
+            vec![
+                q.clone() * bit.clone() * (one() - bit.clone()),
+                q.clone() * (z_next - (z_cur.clone() + z_cur + bit.clone())),
+                q.clone() * (selected.0 - bit.clone() * operand.0),
+                q.clone() * (selected.1 - (bit.clone() * operand.1 + one() - bit)),
+                q.clone() * double_x,
+                q.clone() * double_y,
+                q.clone() * add_x,
+                q * add_y,
+            ]
+        });
+
+        // z = s + 2^251 - l
+        meta.create_gate("range offset", |meta| {
+            let q = meta.query_selector(q_offset);
+            let s = meta.query_advice(x, Rotation::cur());
+            let shifted = meta.query_advice(z, Rotation::cur());
+
+            vec![q * (shifted - s - Expression::Constant(native::range_offset()))]
+        });
+
+        // z = 2·z_next + bit, least significant bit first
+        meta.create_gate("running sum", |meta| {
+            let q = meta.query_selector(q_bits);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+
+            vec![
+                q.clone() * bit.clone() * (one() - bit.clone()),
+                q * (z_cur - (z_next.clone() + z_next + bit)),
+            ]
+        });
+
+        EddsaConfig {
+            x,
+            y,
+            z,
+            bit,
+            dx,
+            dy,
+            sx,
+            sy,
+            px,
+            py,
+            message,
+            q_on_curve,
+            q_add,
+            q_ladder,
+            q_offset,
+            q_bits,
+            poseidon: Pow5Chip::configure::<PoseidonBn254>(meta, state, partial_sbox, rc_a, rc_b),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), ErrorFront> {
+        let chip = Pow5Chip::construct(config.poseidon.clone());
+        let base = config.constant_point(&mut layouter, native::base_point())?;
+
+        for (index, signature) in self.signatures.iter().enumerate() {
+            let (public_key, r, message, s) = config.witness_inputs(&mut layouter, index, signature)?;
+
+            let hasher = Hash::<_, _, PoseidonBn254, ConstantLength<5>, WIDTH, RATE>::init(
+                chip.clone(),
+                layouter.namespace(|| format!("init {}", index)),
+            )?;
+            let h = hasher.hash(
+                layouter.namespace(|| format!("challenge {}", index)),
+                [r.x.clone(), r.y.clone(), public_key.x.clone(), public_key.y.clone(), message],
+            )?;
+
+            config.check_below_order(&mut layouter, &s)?;
+            let lhs = config.scalar_mul(&mut layouter, &s, &base, SUBGROUP_BITS)?;
+            let h_a = config.scalar_mul(&mut layouter, &h, &public_key, CHALLENGE_BITS)?;
+            let rhs = config.add(&mut layouter, &r, &h_a)?;
+
+            let lhs = config.mul_by_cofactor(&mut layouter, lhs)?;
+            let rhs = config.mul_by_cofactor(&mut layouter, rhs)?;
+            layouter.assign_region(
+                || format!("verify {}", index),
+                |mut region| {
+                    region.constrain_equal(lhs.x.cell(), rhs.x.cell())?;
+                    region.constrain_equal(lhs.y.cell(), rhs.y.cell())
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Benchmarks one proof of the signatures at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks verification of random signatures at `size` in this process, proving it
+/// `repeat` times: time_metrics holds the median run and time_statistics the
+/// distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let circuit_size = size.as_str();
+
+    let signed: Vec<SignedMessage> = (0..signatures_for(circuit_size)).map(|_| SignedMessage::random()).collect();
+    let messages: Vec<Fr> = signed.iter().map(|signed| signed.message).collect();
+    let circuit = EddsaCircuit::new(&signed);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Fr, EddsaCircuit>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fr::NUM_BITS,
+            constraints_count: circuit_stats.constraints as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::EddsaVerify.as_str(), circuit_size)
+    };
+    metrics.resolved_parameters.insert("curve".to_string(), "baby_jubjub".into());
+    metrics.resolved_parameters.insert("signatures".to_string(), signed.len().into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[&messages], repeat)?;
+
+    collect_environment(circuit_size).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    fn verify(signed: &[SignedMessage], messages: Vec<Fr>) -> bool {
+        let circuit = EddsaCircuit::new(signed);
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![messages]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    fn messages(signed: &[SignedMessage]) -> Vec<Fr> {
+        signed.iter().map(|signed| signed.message).collect()
+    }
+
+    #[test]
+    fn test_native_sign_and_verify() {
+        let signed = SignedMessage::random();
+        assert!(native::is_on_curve(native::base_point()));
+        assert!(native::is_on_curve(signed.public_key));
+        assert!(native::verify(signed.public_key, signed.message, &signed.signature));
+        assert!(!native::verify(signed.public_key, signed.message + Fr::ONE, &signed.signature));
+
+        // [l]B is the identity
+        assert_eq!(native::mul(native::base_point(), &native::subgroup_order()), native::IDENTITY);
+    }
+
+    #[test]
+    fn test_valid_signatures_accepted() {
+        let signed = vec![SignedMessage::random(), SignedMessage::random()];
+        assert!(verify(&signed, messages(&signed)));
+    }
+
+    #[test]
+    fn test_tampered_signature_rejected() {
+        let signed = vec![SignedMessage::random()];
+
+        let mut wrong_s = signed.clone();
+        wrong_s[0].signature.s += Fr::ONE;
+        assert!(!verify(&wrong_s, messages(&signed)));
+
+        let mut wrong_r = signed.clone();
+        wrong_r[0].signature.r = native::add(wrong_r[0].signature.r, native::base_point());
+        assert!(!verify(&wrong_r, messages(&signed)));
+
+        let mut wrong_key = signed.clone();
+        wrong_key[0].public_key = Keypair::random(&mut OsRng).public;
+        assert!(!verify(&wrong_key, messages(&signed)));
+    }
+
+    #[test]
+    fn test_wrong_message_rejected() {
+        let signed = vec![SignedMessage::random()];
+        assert!(!verify(&signed, vec![signed[0].message + Fr::ONE]));
+    }
+
+    // S + l satisfies the verification equation, since [l]B is the
+    // identity; only the range check rules it out
+    #[test]
+    fn test_malleated_s_rejected() {
+        let signed = vec![SignedMessage::random()];
+        let s = native::to_biguint(&signed[0].signature.s) + native::subgroup_order();
+
+        let mut malleated = signed.clone();
+        malleated[0].signature.s = native::to_field(&s);
+        assert!(!verify(&malleated, messages(&signed)));
+    }
+
+    #[test]
+    fn test_signature_count_mapping() {
+        assert_eq!(signatures_for("small"), 1);
+        assert_eq!(signatures_for("medium"), 4);
+        assert_eq!(signatures_for("large"), 16);
+
+        let one = EddsaCircuit::new(&[SignedMessage::random()]);
+        let many = EddsaCircuit::new(&vec![SignedMessage::random(); 16]);
+        assert!(many.min_k() > one.min_k());
+    }
+
+    #[test]
+    fn test_real_proof_verifies() {
+        let signed = vec![SignedMessage::random()];
+        let circuit = EddsaCircuit::new(&signed);
+
+        let artifacts = halo2_common::run_real_proof(circuit.min_k(), circuit, &[&messages(&signed)]).unwrap();
+
+        assert!(artifacts.verified);
+    }
+}
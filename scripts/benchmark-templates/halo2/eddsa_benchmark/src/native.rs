@@ -0,0 +1,189 @@
+//! Baby Jubjub EdDSA Outside the Circuit
+//!
+//! Baby Jubjub is the twisted Edwards curve a·x² + y² = 1 + d·x²·y² over
+//! BN254's scalar field, with a = 168700 and d = 168696. Since a is a square
+//! and d is not, the addition law below is complete: it has no exceptional
+//! cases, including doubling and the identity (0, 1). Signatures are over the
+//! prime-order subgroup generated by `base_point`, of order l (251 bits);
+//! the full curve has cofactor 8.
+//!
+//! Signing is EdDSA with a Poseidon challenge: for a secret a with public
+//! key A = [a]B, a random r gives R = [r]B, h = Poseidon(R.x, R.y, A.x,
+//! A.y, M) and S = r + h·a mod l. A signature (R, S) verifies when S < l
+//! and [8][S]B = [8](R + [h]A).
+
+use halo2_gadgets::poseidon::primitives::{self as poseidon, ConstantLength, Mds, Spec};
+use halo2_proofs::halo2curves::bn256::Fr;
+
+use ff::{Field, PrimeField};
+use num_bigint::BigUint;
+use rand::RngCore;
+
+pub(crate) const WIDTH: usize = 3;
+pub(crate) const RATE: usize = 2;
+
+pub(crate) const COEFF_A: u64 = 168700;
+pub(crate) const COEFF_D: u64 = 168696;
+
+// Bits of a scalar below l
+pub(crate) const SUBGROUP_BITS: usize = 251;
+
+const SUBGROUP_ORDER: &str =
+    "2736030358979909402780800718157159386076813972158567259200215660948447373041";
+
+// The generator of the order-l subgroup (eight times the curve generator)
+const BASE_X: &str = "5299619240641551281634865583518297030282874472190772894086521144482721001553";
+const BASE_Y: &str = "16950150798460657717958625567821834550301663161624707787222815936182638968203";
+
+// Affine (x, y)
+pub(crate) type Point = (Fr, Fr);
+
+pub(crate) const IDENTITY: Point = (Fr::ZERO, Fr::ONE);
+
+// Poseidon over BN254's scalar field: width 3, x^5, 8 full rounds. 57
+// partial rounds give 128-bit security; Pow5Chip computes them two per row,
+// so the count is rounded up to 58.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PoseidonBn254;
+
+impl Spec<Fr, WIDTH, RATE> for PoseidonBn254 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        58
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fr; WIDTH]>, Mds<Fr, WIDTH>, Mds<Fr, WIDTH>) {
+        poseidon::generate_constants::<_, Self, WIDTH, RATE>()
+    }
+}
+
+pub(crate) fn coeff_a() -> Fr {
+    Fr::from(COEFF_A)
+}
+
+pub(crate) fn coeff_d() -> Fr {
+    Fr::from(COEFF_D)
+}
+
+pub(crate) fn subgroup_order() -> BigUint {
+    SUBGROUP_ORDER.parse().expect("valid decimal")
+}
+
+pub(crate) fn base_point() -> Point {
+    (
+        Fr::from_str_vartime(BASE_X).expect("valid decimal"),
+        Fr::from_str_vartime(BASE_Y).expect("valid decimal"),
+    )
+}
+
+// 2^251 - l: S < l exactly when S + 2^251 - l fits in 251 bits
+pub(crate) fn range_offset() -> Fr {
+    to_field(&((BigUint::from(1u8) << SUBGROUP_BITS) - subgroup_order()))
+}
+
+pub(crate) fn to_biguint(value: &Fr) -> BigUint {
+    BigUint::from_bytes_le(value.to_repr().as_ref())
+}
+
+// `value` must be below the field modulus
+pub(crate) fn to_field(value: &BigUint) -> Fr {
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    let bytes = value.to_bytes_le();
+    repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+    Fr::from_repr(repr).expect("value below the modulus")
+}
+
+// The low `num_bits` bits of `value`, most significant first
+pub(crate) fn to_bits(value: &Fr, num_bits: usize) -> Vec<bool> {
+    let value = to_biguint(value);
+    (0..num_bits as u64).rev().map(|i| value.bit(i)).collect()
+}
+
+pub(crate) fn is_on_curve((x, y): Point) -> bool {
+    let (xx, yy) = (x.square(), y.square());
+    coeff_a() * xx + yy == Fr::ONE + coeff_d() * xx * yy
+}
+
+pub(crate) fn add((x1, y1): Point, (x2, y2): Point) -> Point {
+    let t = coeff_d() * x1 * x2 * y1 * y2;
+    let x3 = (x1 * y2 + y1 * x2) * (Fr::ONE + t).invert().unwrap();
+    let y3 = (y1 * y2 - coeff_a() * x1 * x2) * (Fr::ONE - t).invert().unwrap();
+    (x3, y3)
+}
+
+// [scalar]point, by the same double-and-add the circuit uses
+pub(crate) fn mul(point: Point, scalar: &BigUint) -> Point {
+    (0..scalar.bits()).rev().fold(IDENTITY, |acc, i| {
+        let doubled = add(acc, acc);
+        if scalar.bit(i) {
+            add(doubled, point)
+        } else {
+            doubled
+        }
+    })
+}
+
+fn mul_by_cofactor(point: Point) -> Point {
+    (0..3).fold(point, |acc, _| add(acc, acc))
+}
+
+pub(crate) fn challenge(r: Point, public_key: Point, message: Fr) -> Fr {
+    poseidon::Hash::<_, PoseidonBn254, ConstantLength<5>, WIDTH, RATE>::init()
+        .hash([r.0, r.1, public_key.0, public_key.1, message])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Signature {
+    pub(crate) r: Point,
+    pub(crate) s: Fr,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Keypair {
+    secret: BigUint,
+    pub(crate) public: Point,
+}
+
+// Uniform below l, up to a negligible bias from reducing 512 bits
+fn random_scalar(rng: &mut impl RngCore) -> BigUint {
+    let mut bytes = [0u8; 64];
+    rng.fill_bytes(&mut bytes);
+    BigUint::from_bytes_le(&bytes) % subgroup_order()
+}
+
+impl Keypair {
+    pub(crate) fn random(rng: &mut impl RngCore) -> Self {
+        let secret = random_scalar(rng);
+        let public = mul(base_point(), &secret);
+        Self { secret, public }
+    }
+
+    pub(crate) fn sign(&self, message: Fr, rng: &mut impl RngCore) -> Signature {
+        let nonce = random_scalar(rng);
+        let r = mul(base_point(), &nonce);
+        let h = to_biguint(&challenge(r, self.public, message));
+        let s = (nonce + h * &self.secret) % subgroup_order();
+        Signature { r, s: to_field(&s) }
+    }
+}
+
+pub(crate) fn verify(public_key: Point, message: Fr, signature: &Signature) -> bool {
+    if !is_on_curve(public_key) || !is_on_curve(signature.r) || to_biguint(&signature.s) >= subgroup_order() {
+        return false;
+    }
+    let h = to_biguint(&challenge(signature.r, public_key, message));
+    let lhs = mul(base_point(), &to_biguint(&signature.s));
+    let rhs = add(signature.r, mul(public_key, &h));
+    mul_by_cofactor(lhs) == mul_by_cofactor(rhs)
+}
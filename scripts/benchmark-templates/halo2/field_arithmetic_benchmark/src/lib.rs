@@ -0,0 +1,277 @@
+//! Field Arithmetic Microbenchmark
+//!
+//! A chain of N multiply-adds, s_{i+1} = s_i * m_i + a_i, with one row per
+//! step and a single custom gate holding every step. There are no lookups,
+//! no hashing and no curve operations, so proving cost is just the cost of
+//! N degree-3 constraints over the Pasta base field; divide proving time by
+//! constraints_count for a per-constraint figure. The chain's input s_0 and
+//! output s_N are public.
+//!
+//! N fills the 2^k rows circuit_size asks for, less the rows halo2
+//! reserves, so it roughly doubles with each step of k: 2040 steps at small
+//! (k = 11) up to about a million at xlarge (k = 20).
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront, Instance, Selector},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs};
+use pasta_curves::Fp;
+use serde_json::json;
+
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Rows of the instance column: the chain's input, then its output
+const INPUT_ROW: usize = 0;
+const OUTPUT_ROW: usize = 1;
+
+#[derive(Debug, Clone)]
+struct FieldArithmeticConfig {
+    q_step: Selector,
+    state: Column<Advice>,
+    mul: Column<Advice>,
+    add: Column<Advice>,
+    instance: Column<Instance>,
+}
+
+impl FieldArithmeticConfig {
+    fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_step = meta.selector();
+        let state = meta.advice_column();
+        let mul = meta.advice_column();
+        let add = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(state);
+        meta.enable_equality(instance);
+
+        meta.create_gate("multiply-add", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let state_cur = meta.query_advice(state, Rotation::cur());
+            let state_next = meta.query_advice(state, Rotation::next());
+            let mul = meta.query_advice(mul, Rotation::cur());
+            let add = meta.query_advice(add, Rotation::cur());
+
+            vec![q_step * (state_next - (state_cur * mul + add))]
+        });
+
+        Self { q_step, state, mul, add, instance }
+    }
+
+    // Copies s_0 from the instance column and assigns one step per row,
+    // returning the cell holding s_N
+    fn assign_chain<F: PrimeField>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        muls: &[Value<F>],
+        adds: &[Value<F>],
+    ) -> Result<AssignedCell<F, F>, ErrorFront> {
+        layouter.assign_region(
+            || "multiply-add chain",
+            |mut region| {
+                let mut state =
+                    region.assign_advice_from_instance(|| "s_0", self.instance, INPUT_ROW, self.state, 0)?;
+                for (offset, (mul, add)) in muls.iter().zip(adds).enumerate() {
+                    self.q_step.enable(&mut region, offset)?;
+                    region.assign_advice(|| "m", self.mul, offset, || *mul)?;
+                    region.assign_advice(|| "a", self.add, offset, || *add)?;
+                    let next = state.value().copied() * *mul + *add;
+                    state = region.assign_advice(|| "s", self.state, offset + 1, || next)?;
+                }
+                Ok(state)
+            },
+        )
+    }
+}
+
+#[derive(Clone)]
+struct FieldArithmeticCircuit<F: PrimeField> {
+    muls: Vec<Value<F>>,
+    adds: Vec<Value<F>>,
+}
+
+impl<F: PrimeField> FieldArithmeticCircuit<F> {
+    fn new(muls: &[F], adds: &[F]) -> Self {
+        assert_eq!(muls.len(), adds.len(), "one addend per multiplier");
+        Self {
+            muls: muls.iter().copied().map(Value::known).collect(),
+            adds: adds.iter().copied().map(Value::known).collect(),
+        }
+    }
+
+    // Smallest k whose domain fits the chain's N + 1 state rows
+    fn min_k(&self) -> u32 {
+        let rows = self.muls.len() + 1 + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for FieldArithmeticCircuit<F> {
+    type Config = FieldArithmeticConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { muls: vec![Value::unknown(); self.muls.len()], adds: vec![Value::unknown(); self.adds.len()] }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        FieldArithmeticConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), ErrorFront> {
+        let output = config.assign_chain(layouter.namespace(|| "chain"), &self.muls, &self.adds)?;
+        layouter.constrain_instance(output.cell(), config.instance, OUTPUT_ROW)
+    }
+}
+
+// Steps that fit in 2^k rows alongside the final state and blinding rows
+fn chain_length(k: u32) -> usize {
+    (1 << k) - BLINDING_ROWS - 2
+}
+
+// The chain's operands: m_i = i + 2 and a_i = i + 1, so no step multiplies
+// by 0 or 1 and the state never settles
+fn operands<F: PrimeField>(steps: usize) -> (Vec<F>, Vec<F>) {
+    (0..steps as u64).map(|i| (F::from(i + 2), F::from(i + 1))).unzip()
+}
+
+// s_N computed natively, what the circuit's output must equal
+fn chain_output<F: PrimeField>(input: F, muls: &[F], adds: &[F]) -> F {
+    muls.iter().zip(adds).fold(input, |state, (mul, add)| state * mul + add)
+}
+
+/// Benchmarks one multiply-add chain proof at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks the multiply-add chain at `size` in this process, proving it
+/// `repeat` times: time_metrics holds the median run and time_statistics
+/// the distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let steps = chain_length(size.k());
+    let (muls, adds) = operands::<Fp>(steps);
+    let input = Fp::from(3);
+    let output = chain_output(input, &muls, &adds);
+    let circuit = FieldArithmeticCircuit::new(&muls, &adds);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Fp, FieldArithmeticCircuit<Fp>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            // One multiply-add constraint per step; the single gate is
+            // applied on every row of the chain
+            constraints_count: steps as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::FieldArithmetic.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("steps".to_string(), steps.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[&[input, output]], repeat)?;
+    // Median proving time spread over the chain, the figure this benchmark
+    // exists for
+    metrics.time_metrics.extra.insert(
+        "proving_time_per_constraint_ns".to_string(),
+        json!(metrics.time_metrics.proving_time_ms as f64 * 1e6 / steps as f64),
+    );
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+    use halo2_proofs::dev::MockProver;
+
+    fn verify(circuit: &FieldArithmeticCircuit<Fp>, input: Fp, output: Fp) -> bool {
+        let prover = MockProver::run(circuit.min_k(), circuit, vec![vec![input, output]]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    // 1 * 2 + 1 = 3, 3 * 3 + 2 = 11, 11 * 4 + 3 = 47
+    #[test]
+    fn test_output_cell_equals_expected_value() {
+        let (muls, adds) = operands::<Fp>(3);
+        let expected = Fp::from(47);
+        assert_eq!(chain_output(Fp::ONE, &muls, &adds), expected);
+
+        let circuit = FieldArithmeticCircuit::new(&muls, &adds);
+        assert!(verify(&circuit, Fp::ONE, expected));
+        assert!(!verify(&circuit, Fp::ONE, expected + Fp::ONE));
+        assert!(!verify(&circuit, Fp::from(2), expected));
+    }
+
+    // The state wraps around the modulus without the circuit noticing
+    #[test]
+    fn test_output_reduced_mod_p() {
+        let muls = [-Fp::ONE, Fp::from(2)];
+        let adds = [Fp::ZERO, Fp::from(5)];
+        let output = chain_output(Fp::from(7), &muls, &adds);
+        assert_eq!(output, -Fp::from(9));
+
+        assert!(verify(&FieldArithmeticCircuit::new(&muls, &adds), Fp::from(7), output));
+    }
+
+    #[test]
+    fn test_chain_fills_domain() {
+        assert_eq!(CircuitSize::ALL.map(|size| chain_length(size.k())), [2040, 16376, 131064, 1048568]);
+
+        let steps = chain_length(CircuitSize::Small.k());
+        let (muls, adds) = operands::<Fp>(steps);
+        let circuit = FieldArithmeticCircuit::new(&muls, &adds);
+        assert_eq!(circuit.min_k(), CircuitSize::Small.k());
+        assert!(verify(&circuit, Fp::from(3), chain_output(Fp::from(3), &muls, &adds)));
+    }
+
+    #[test]
+    fn test_constraint_counts() {
+        let stats = collect_circuit_stats::<Fp, FieldArithmeticCircuit<Fp>>();
+
+        assert_eq!(stats.gates, 1);
+        assert_eq!(stats.lookups, 0);
+        // Three advice columns and the instance column
+        assert_eq!(stats.columns, 4);
+    }
+
+    #[test]
+    fn test_real_proof_verifies() {
+        let (muls, adds) = operands::<Fp>(5);
+        let output = chain_output(Fp::ONE, &muls, &adds);
+        let circuit = FieldArithmeticCircuit::new(&muls, &adds);
+
+        let artifacts = halo2_common::run_real_proof(circuit.min_k(), circuit, &[&[Fp::ONE, output]]).unwrap();
+
+        assert!(artifacts.verified);
+    }
+}
@@ -0,0 +1,89 @@
+//! Shared halo2 Helpers
+//!
+//! Code every halo2 benchmark would otherwise carry its own copy of. A
+//! halo2 proof is the byte stream its transcript wrote: `serialize_proof`
+//! takes those bytes out of a finished Blake2b transcript, and
+//! `deserialize_proof` wraps bytes back into a transcript that
+//! `verify_proof` reads, so a proof can be measured, stored and verified
+//! somewhere other than where it was created. Verifying needs the same
+//! verifying key and public inputs as proving; the bytes carry neither.
+//!
+//! `run_real_proof` builds on these to time the full IPA pipeline, over
+//! the curve `IpaField` gives the circuit's field, and writes the
+//! parameters to disk with `write_params`, `record_proofs` repeats it into
+//! a benchmark's metrics, and `collect_circuit_stats` reads a circuit's
+//! shape off its configuration. `vk_fingerprint` names a verifying key, so
+//! results from two runs can show whether they proved the same circuit;
+//! `record_proofs` stores it as `setup_metrics.extra.vk_fingerprint`.
+//!
+//! `record_proofs` reports the gap between peak RSS and peak live
+//! allocation when the benchmark binary installs `benchmark_metrics`'
+//! `CountingAllocator` as its global allocator; this crate leaves the
+//! choice of allocator to the binary.
+
+use blake2b_simd::Params as Blake2bParams;
+use ff::FromUniformBytes;
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    halo2curves::serde::SerdeObject,
+    plonk::VerifyingKey,
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+
+mod params;
+mod proof;
+mod stats;
+
+pub use params::{artifacts_dir, write_params, ParamsFile};
+pub use proof::{prove, record_proofs, run_real_proof, verify, IpaField, ProofArtifacts};
+pub use stats::{collect_circuit_stats, CircuitStats};
+
+/// Transcript `create_proof` writes an IPA proof into.
+pub type ProofWriter<C> = Blake2bWrite<Vec<u8>, C, Challenge255<C>>;
+
+/// Transcript `verify_proof` reads a serialized proof from.
+pub type ProofReader<'a, C> = Blake2bRead<&'a [u8], C, Challenge255<C>>;
+
+/// An empty transcript to pass to `create_proof`.
+pub fn proof_writer<C: CurveAffine>() -> ProofWriter<C>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    Blake2bWrite::init(Vec::new())
+}
+
+/// The proof bytes `create_proof` wrote to `transcript`.
+pub fn serialize_proof<C: CurveAffine>(transcript: ProofWriter<C>) -> Vec<u8>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    transcript.finalize()
+}
+
+/// A transcript over `proof`, as produced by `serialize_proof`, ready for
+/// `verify_proof`.
+pub fn deserialize_proof<C: CurveAffine>(proof: &[u8]) -> ProofReader<'_, C>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    Blake2bRead::init(proof)
+}
+
+/// Hex Blake2b-256 of `vk` as halo2 serializes it with
+/// `SerdeFormat::RawBytes`: its domain size, fixed commitments and
+/// permutation commitments. The bytes come from halo2's own encoding of the
+/// key, so two fingerprints are comparable when both runs used the same
+/// halo2 build.
+pub fn vk_fingerprint<C>(vk: &VerifyingKey<C>) -> String
+where
+    C: CurveAffine + SerdeObject,
+    C::Scalar: FromUniformBytes<64> + SerdeObject,
+{
+    let mut bytes = Vec::new();
+    vk.write(&mut bytes, SerdeFormat::RawBytes).expect("writing to a Vec cannot fail");
+    let hash = Blake2bParams::new().hash_length(32).personal(b"zkbench-vk-print").hash(&bytes);
+    hash.as_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
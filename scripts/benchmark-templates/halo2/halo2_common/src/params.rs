@@ -0,0 +1,105 @@
+//! Setup Parameters on Disk
+//!
+//! IPA parameters need no trusted setup, but a deployment still ships and
+//! stores them, and their size grows with 2^k. `write_params` serializes
+//! the parameters a run generated to a file under `artifacts_dir`, so
+//! `setup_size_bytes` and `disk_space_gb` are the size of that file rather
+//! than a guess. The file is written after the timed setup, so writing it
+//! does not count towards setup time.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, CurveExt},
+    poly::{commitment::Params, ipa::commitment::ParamsIPA},
+};
+
+use benchmark_metrics::BenchmarkMetrics;
+
+const BYTES_PER_GB: u64 = 1 << 30;
+
+/// Parameters written to disk and the space they take there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsFile {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+impl ParamsFile {
+    /// Sets `setup_size_bytes` to the file's size and `disk_space_gb` to
+    /// that size rounded up to a whole GB.
+    pub fn apply(&self, metrics: &mut BenchmarkMetrics) {
+        metrics.setup_metrics.setup_size_bytes = self.size_bytes;
+        metrics.system_requirements.disk_space_gb = self.size_bytes.div_ceil(BYTES_PER_GB);
+    }
+}
+
+/// Where benchmarks write setup parameters: `BENCHMARK_ARTIFACTS_DIR`, or
+/// a directory under the system temp dir.
+pub fn artifacts_dir() -> PathBuf {
+    std::env::var_os("BENCHMARK_ARTIFACTS_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("zk-benchmark-artifacts"))
+}
+
+/// Writes `params` to `dir` as `ipa-<curve>-k<k>.params`, e.g.
+/// `ipa-vesta-k11.params`, and measures the file. The file is replaced if
+/// it exists.
+pub fn write_params<C: CurveAffine>(dir: &Path, params: &ParamsIPA<C>) -> io::Result<ParamsFile> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("ipa-{}-k{}.params", C::CurveExt::CURVE_ID, params.k()));
+
+    let mut writer = BufWriter::new(File::create(&path)?);
+    params.write(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+
+    let size_bytes = fs::metadata(&path)?.len();
+    Ok(ParamsFile { path, size_bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::G1Affine;
+    use pasta_curves::vesta::Affine;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("halo2_common_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_params_size_matches_file_on_disk() {
+        let dir = scratch_dir("params");
+        let params = ParamsIPA::<Affine>::new(4);
+
+        let file = write_params(&dir, &params).unwrap();
+
+        assert_eq!(file.path, dir.join("ipa-vesta-k4.params"));
+        assert!(file.size_bytes > 0);
+        assert_eq!(file.size_bytes, fs::metadata(&file.path).unwrap().len());
+        let read = ParamsIPA::<Affine>::read(&mut File::open(&file.path).unwrap()).unwrap();
+        assert_eq!(read.k(), 4);
+
+        let mut metrics = BenchmarkMetrics::new("halo2", "ecdsa", "small");
+        file.apply(&mut metrics);
+        assert_eq!(metrics.setup_metrics.setup_size_bytes, file.size_bytes);
+        assert_eq!(metrics.system_requirements.disk_space_gb, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_params_file_named_by_curve() {
+        let dir = scratch_dir("params_bn256");
+
+        let file = write_params(&dir, &ParamsIPA::<G1Affine>::new(4)).unwrap();
+
+        assert_eq!(file.path, dir.join("ipa-bn256_g1-k4.params"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
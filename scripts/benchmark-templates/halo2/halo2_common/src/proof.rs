@@ -0,0 +1,220 @@
+//! Real Proving Path
+//!
+//! MockProver only checks a witness; it produces no proof and says nothing
+//! about proving or verification cost. `run_real_proof` goes through the
+//! full IPA pipeline over the curve `IpaField` pairs with the circuit's
+//! field (Vesta for Pasta's Fp, BN254's G1 for its Fr): parameters, keys,
+//! `create_proof` with a Blake2b transcript, then `verify_proof`, timing
+//! each stage and keeping
+//! the size of the serialized proof and the peak memory of proving and of
+//! verifying. The proof is verified from its serialized bytes, the same way
+//! a proof produced elsewhere would be, and only once the proving key has
+//! been dropped, since a verifier never holds one. The parameters file is
+//! only measured, so it is removed before `run_real_proof` returns, or
+//! unwinds, unless the benchmark was run with `--keep-artifacts`.
+
+use std::time::{Duration, Instant};
+
+use ff::{FromUniformBytes, WithSmallOrderMulGroup};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    halo2curves::{bn256, serde::SerdeObject},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+    },
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy,
+        },
+    },
+};
+
+use crate::params::{artifacts_dir, write_params, ParamsFile};
+use crate::{deserialize_proof, proof_writer, serialize_proof, vk_fingerprint};
+use benchmark_metrics::{
+    peak_rss_kb, Aggregator, AllocationGap, BenchmarkMetrics, MemoryProbe, TempArtifact, Throughput,
+};
+use pasta_curves::{vesta, Fp};
+use rand::rngs::OsRng;
+
+/// A circuit field with the curve whose IPA commitments prove circuits over
+/// it: the curve's scalar field is this field.
+pub trait IpaField: WithSmallOrderMulGroup<3> + FromUniformBytes<64> + SerdeObject {
+    type Curve: CurveAffine<ScalarExt = Self> + SerdeObject;
+}
+
+impl IpaField for Fp {
+    type Curve = vesta::Affine;
+}
+
+impl IpaField for bn256::Fr {
+    type Curve = bn256::G1Affine;
+}
+
+/// What one proof cost to produce and check.
+#[derive(Debug, Clone)]
+pub struct ProofArtifacts {
+    /// Parameters plus verifying and proving keys
+    pub setup: Duration,
+    pub proving: Duration,
+    pub verification: Duration,
+    pub proof_size_bytes: usize,
+    pub verified: bool,
+    /// Process peak RSS once the proof is created, if the platform reports it
+    pub peak_memory_kb: Option<u64>,
+    /// Resident memory verification needed on top of what the process
+    /// already held, if the platform can measure one phase
+    pub verifier_peak_memory_kb: Option<u64>,
+    /// `vk_fingerprint` of the verifying key
+    pub vk_fingerprint: String,
+    /// The parameters as written to `artifacts_dir`, if they could be; the
+    /// file itself is gone by the time this is returned unless
+    /// `--keep-artifacts` was passed
+    pub params_file: Option<ParamsFile>,
+}
+
+/// Proves all of `circuits` in one transcript, circuit i against
+/// `instances[i]`, the contents of each of its instance columns, and
+/// returns the serialized proof. Circuits proven together share the final
+/// opening argument, so the proof is smaller than one per circuit.
+pub fn prove<F: IpaField, C: Circuit<F>>(
+    params: &ParamsIPA<F::Curve>,
+    pk: &ProvingKey<F::Curve>,
+    circuits: &[C],
+    instances: &[&[&[F]]],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = proof_writer();
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        params,
+        pk,
+        circuits,
+        instances,
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(serialize_proof(transcript))
+}
+
+/// Whether a serialized proof from `prove` verifies against `vk` and
+/// `instances`; one bad circuit fails the whole proof.
+pub fn verify<F: IpaField>(
+    params: &ParamsIPA<F::Curve>,
+    vk: &VerifyingKey<F::Curve>,
+    proof: &[u8],
+    instances: &[&[&[F]]],
+) -> bool {
+    let verifier_params = params.verifier_params();
+    let mut transcript = deserialize_proof(proof);
+    verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+        verifier_params,
+        vk,
+        SingleStrategy::new(verifier_params),
+        instances,
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+/// Proves `circuit` at size 2^k against `instances`, the contents of each
+/// of its instance columns, and verifies the result. A proof that fails to
+/// verify is reported through `verified`; only setup and proving errors are
+/// returned as `Err`.
+pub fn run_real_proof<F: IpaField, C: Circuit<F>>(
+    k: u32,
+    circuit: C,
+    instances: &[&[F]],
+) -> Result<ProofArtifacts, Error> {
+    let setup_start = Instant::now();
+    let params: ParamsIPA<F::Curve> = ParamsIPA::new(k);
+    let vk = keygen_vk(&params, &circuit)?;
+    let pk = keygen_pk(&params, vk.clone(), &circuit)?;
+    let setup = setup_start.elapsed();
+    let params_file = match write_params(&artifacts_dir(), &params) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!("warning: cannot write setup parameters: {}", err);
+            None
+        }
+    };
+    let _params_artifact = params_file.as_ref().map(|file| TempArtifact::new(file.path.clone()));
+
+    let proving_start = Instant::now();
+    let proof = prove(&params, &pk, &[circuit], &[instances])?;
+    let proving = proving_start.elapsed();
+    let peak_memory_kb = peak_rss_kb();
+    drop(pk);
+
+    let memory = MemoryProbe::start();
+    let verification_start = Instant::now();
+    let verified = verify(&params, &vk, &proof, &[instances]);
+    let verification = verification_start.elapsed();
+    let verifier_peak_memory_kb = memory.and_then(MemoryProbe::finish);
+
+    Ok(ProofArtifacts {
+        setup,
+        proving,
+        verification,
+        proof_size_bytes: proof.len(),
+        verified,
+        peak_memory_kb,
+        verifier_peak_memory_kb,
+        vk_fingerprint: vk_fingerprint(&vk),
+        params_file,
+    })
+}
+
+/// Proves `circuit` `repeat` times with `run_real_proof` and records the
+/// runs in `metrics`: time_metrics holds the median run and time_statistics
+/// the distribution, alongside the proof size, peak memory and proving
+/// throughput, the verifier's own peak memory in
+/// `resource_metrics.extra.verifier_peak_memory_kb`, the size of the setup
+/// parameters on disk, the verifying key's fingerprint in
+/// `setup_metrics.extra.vk_fingerprint`, and the
+/// `AllocationGap` between peak RSS and peak live allocation. Whether every
+/// proof verified goes in `extra.verification_passed`, and a proof that
+/// fails is also reported on stderr; only setup and proving errors are
+/// returned as `Err`.
+pub fn record_proofs<F: IpaField, C: Circuit<F> + Clone>(
+    metrics: &mut BenchmarkMetrics,
+    k: u32,
+    circuit: &C,
+    instances: &[&[F]],
+    repeat: usize,
+) -> Result<(), Error> {
+    let mut aggregator = Aggregator::new();
+    let mut verified = true;
+    let mut proving_times = Vec::new();
+    for _ in 0..repeat {
+        let artifacts = run_real_proof(k, circuit.clone(), instances)?;
+        if !artifacts.verified {
+            eprintln!("warning: proof did not verify");
+        }
+        verified &= artifacts.verified;
+        metrics.time_metrics.setup_time_ms = artifacts.setup.as_millis() as u64;
+        metrics.time_metrics.proving_time_ms = artifacts.proving.as_millis() as u64;
+        metrics.time_metrics.verification_time_ms = artifacts.verification.as_millis() as u64;
+        metrics.resource_metrics.proof_size_bytes = artifacts.proof_size_bytes as u64;
+        metrics.resource_metrics.peak_memory_usage_kb = artifacts.peak_memory_kb.unwrap_or(0);
+        if let Some(verifier_peak) = artifacts.verifier_peak_memory_kb {
+            metrics.resource_metrics.extra.insert("verifier_peak_memory_kb".to_string(), verifier_peak.into());
+        }
+        if let Some(file) = &artifacts.params_file {
+            file.apply(metrics);
+        }
+        metrics.setup_metrics.extra.insert("vk_fingerprint".to_string(), artifacts.vk_fingerprint.into());
+        aggregator.record(&metrics.time_metrics);
+        proving_times.push(artifacts.proving);
+    }
+    aggregator.apply(metrics);
+    metrics.extra.insert("verification_passed".to_string(), verified.into());
+    if let Some(throughput) = Throughput::of(&proving_times) {
+        throughput.apply(metrics);
+    }
+    if let Some(gap) = AllocationGap::measure() {
+        gap.apply(metrics);
+    }
+    Ok(())
+}
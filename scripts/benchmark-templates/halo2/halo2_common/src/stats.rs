@@ -0,0 +1,38 @@
+//! Circuit Shape Statistics
+//!
+//! Once `configure` has run, the `ConstraintSystem` knows every gate,
+//! lookup and column the circuit declared and the maximum degree of its
+//! constraints. `collect_circuit_stats` runs configuration on its own, with
+//! no synthesis, and reads those numbers off for scalability_metrics.
+
+use halo2_proofs::{arithmetic::Field, plonk::{Circuit, ConstraintSystem}};
+
+/// The shape of a circuit, as declared by its `configure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// Named gates; a gate may hold several polynomial constraints
+    pub gates: usize,
+    /// Polynomial constraints across all gates
+    pub constraints: usize,
+    /// Lookup arguments
+    pub lookups: usize,
+    /// Advice, instance and fixed columns (selectors not included)
+    pub columns: usize,
+    /// Maximum degree of any constraint, including the lookup and
+    /// permutation arguments
+    pub degree: usize,
+}
+
+/// Configures `C` and reports its shape.
+pub fn collect_circuit_stats<F: Field, C: Circuit<F>>() -> CircuitStats {
+    let mut meta = ConstraintSystem::<F>::default();
+    C::configure(&mut meta);
+
+    CircuitStats {
+        gates: meta.gates().len(),
+        constraints: meta.gates().iter().map(|gate| gate.polynomials().len()).sum(),
+        lookups: meta.lookups().len(),
+        columns: meta.num_advice_columns() + meta.num_instance_columns() + meta.num_fixed_columns(),
+        degree: meta.degree(),
+    }
+}
@@ -0,0 +1,244 @@
+//! Public vs Private Input Cost
+//!
+//! The halo2 verifier has to commit to every instance column itself, so
+//! its work grows with the number of public inputs while the prover's
+//! barely changes. This benchmark fixes N witnesses and exposes the first
+//! P of them as public instances, keeping the rest private, then reports
+//! verification time and proof size for each P.
+//!
+//! - Witnesses: N (fixed by circuit_size: 2^8, 2^12, 2^16 or 2^19)
+//! - Public:    P in {0, N/4, N/2, N}
+//!
+//! time_metrics are those of the fully public circuit, P = N; the sweep
+//! over P goes in `extra.public_input_sweep`.
+
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront,
+        Instance,
+    },
+    poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA},
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Operation, ScalabilityMetrics, SecurityMetrics,
+    SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, prove, record_proofs, verify};
+use pasta_curves::{vesta, Fp};
+use serde_json::json;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct InstanceConfig {
+    witness: Column<Advice>,
+    public: Column<Instance>,
+}
+
+// N witnesses, the first `public` of which are copied to the instance column
+#[derive(Default, Clone)]
+struct InstanceCostCircuit {
+    witnesses: Vec<Value<Fp>>,
+    public: usize,
+}
+
+impl InstanceCostCircuit {
+    fn new(n: usize, public: usize) -> Self {
+        Self {
+            witnesses: (0..n as u64).map(|i| Value::known(Fp::from(i + 1))).collect(),
+            public,
+        }
+    }
+
+    fn public_inputs(&self) -> Vec<Fp> {
+        (0..self.public as u64).map(|i| Fp::from(i + 1)).collect()
+    }
+
+    fn min_k(&self) -> u32 {
+        let rows = self.witnesses.len() + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl Circuit<Fp> for InstanceCostCircuit {
+    type Config = InstanceConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            witnesses: vec![Value::unknown(); self.witnesses.len()],
+            public: self.public,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let witness = meta.advice_column();
+        let public = meta.instance_column();
+
+        meta.enable_equality(witness);
+        meta.enable_equality(public);
+
+        InstanceConfig { witness, public }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), ErrorFront> {
+        let cells = layouter.assign_region(
+            || "witnesses",
+            |mut region| {
+                self.witnesses
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, value)| {
+                        region.assign_advice(|| "witness", config.witness, offset, || *value)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        for (row, cell) in cells.iter().take(self.public).enumerate() {
+            layouter.constrain_instance(cell.cell(), config.public, row)?;
+        }
+        Ok(())
+    }
+}
+
+// Verification cost and proof size for one public/private split
+#[derive(Debug, Clone, Copy)]
+struct InstanceCost {
+    public: usize,
+    verification_time: Duration,
+    proof_size: usize,
+}
+
+// Best of `runs` verifications, to keep scheduler noise out of the comparison
+fn measure(circuit: &InstanceCostCircuit, runs: usize) -> Result<InstanceCost, Error> {
+    let params = ParamsIPA::<vesta::Affine>::new(circuit.min_k());
+    let vk = keygen_vk(&params, circuit)?;
+    let pk = keygen_pk(&params, vk, circuit)?;
+    let public = circuit.public_inputs();
+
+    let proof = prove(&params, &pk, std::slice::from_ref(circuit), &[&[&public]])?;
+
+    let mut verification_time = Duration::MAX;
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        if !verify(&params, pk.get_vk(), &proof, &[&[&public]]) {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        verification_time = verification_time.min(start.elapsed());
+    }
+
+    Ok(InstanceCost {
+        public: circuit.public,
+        verification_time,
+        proof_size: proof.len(),
+    })
+}
+
+fn witness_count(size: CircuitSize) -> usize {
+    match size {
+        CircuitSize::Small => 1 << 8,
+        CircuitSize::Medium => 1 << 12,
+        CircuitSize::Large => 1 << 16,
+        CircuitSize::XLarge => 1 << 19,
+    }
+}
+
+/// Benchmarks the witnesses at `size` made fully public, proved once; see
+/// `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks the witnesses at `size` in this process: the fully public
+/// circuit proved `repeat` times, time_metrics holding the median run and
+/// time_statistics the distribution, then the verification time and proof
+/// size of each public/private split.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let n = witness_count(size);
+    let circuit = InstanceCostCircuit::new(n, n);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Fp, InstanceCostCircuit>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            // One copy constraint per public input; there are no gates
+            constraints_count: n as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::InstanceCost.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("witnesses".to_string(), n.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[&circuit.public_inputs()], repeat)?;
+
+    let mut sweep = Vec::new();
+    for public in [0, n / 4, n / 2, n] {
+        let cost = measure(&InstanceCostCircuit::new(n, public), 5)?;
+        sweep.push(json!({
+            "public": cost.public,
+            "private": n - cost.public,
+            "verification_time_ms": cost.verification_time.as_secs_f64() * 1000.0,
+            "proof_size_bytes": cost.proof_size,
+        }));
+    }
+    metrics.extra.insert("public_input_sweep".to_string(), sweep.into());
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    #[test]
+    fn test_public_inputs_bound_to_witnesses() {
+        let circuit = InstanceCostCircuit::new(32, 8);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![circuit.public_inputs()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut wrong = circuit.public_inputs();
+        wrong[3] += Fp::from(1);
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_more_public_inputs_cost_more_to_verify() {
+        let n = 1 << 10;
+        let few = measure(&InstanceCostCircuit::new(n, 1), 5).unwrap();
+        let many = measure(&InstanceCostCircuit::new(n, n), 5).unwrap();
+
+        assert!(many.verification_time > few.verification_time);
+    }
+}
@@ -0,0 +1,16 @@
+//! Command-line entry point; the benchmark itself is `instance_cost_benchmark::run`.
+
+use std::error::Error;
+
+use benchmark_metrics::{CountingAllocator, TemplateArgs};
+
+// Counts live allocations, so the metrics can set peak RSS against them
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = TemplateArgs::from_env();
+    let metrics = instance_cost_benchmark::run_repeated(args.size_or_exit(), args.repeat)?;
+    metrics.emit(&args)?;
+    Ok(())
+}
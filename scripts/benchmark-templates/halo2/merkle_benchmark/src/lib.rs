@@ -0,0 +1,413 @@
+//! Merkle Inclusion Circuit
+//!
+//! Proves that a private leaf is in a Poseidon Merkle tree whose root is the
+//! public input. The path is private too: one sibling per level and the
+//! leaf's index, whose bit at each level says whether the running node is
+//! the left (0) or the right (1) child. A swap gate orders the node and its
+//! sibling by that bit, and halo2_gadgets' Pow5 Poseidon (P128Pow5T3, width
+//! 3, rate 2) hashes the pair into the parent.
+//!
+//! The tree depth is chosen by the circuit_size argument:
+//!
+//! - small:  8 levels
+//! - medium: 16 levels
+//! - large:  32 levels
+//!
+//! `--operation-args depth=N` overrides it with any depth from 1 to 63.
+
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, ConstantLength, P128Pow5T3},
+    Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront, Expression, Instance,
+        Selector},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::{Field, PrimeField};
+use halo2_common::{collect_circuit_stats, record_proofs};
+use pasta_curves::Fp;
+use rand::{rngs::OsRng, RngCore};
+
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Upper bound on the rows one level takes: a Pow5 permutation (8 full
+// rounds, 56 partial rounds two per row, plus the initial state) and the
+// swap row
+const ROWS_PER_LEVEL: usize = 42;
+
+// The index is a u64 with one bit per level
+const MAX_DEPTH: usize = 63;
+
+/// The keys this benchmark takes in `--operation-args`.
+pub const OPERATION_ARGS: [&str; 1] = ["depth"];
+
+fn depth_for(circuit_size: &str) -> usize {
+    match circuit_size {
+        "medium" => 16,
+        "large" => 32,
+        _ => 8,
+    }
+}
+
+/// The tree depth of an `--operation-args depth=N` value.
+pub fn parse_depth(arg: &str) -> Result<usize, String> {
+    arg.parse()
+        .ok()
+        .filter(|depth| (1..=MAX_DEPTH).contains(depth))
+        .ok_or_else(|| format!("depth expects an integer from 1 to {}, got {:?}", MAX_DEPTH, arg))
+}
+
+// Parent of two nodes, outside the circuit
+fn hash_pair(left: Fp, right: Fp) -> Fp {
+    poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, WIDTH, RATE>::init().hash([left, right])
+}
+
+// A leaf with its authentication path, bottom level first
+#[derive(Debug, Clone)]
+struct MerklePath {
+    leaf: Fp,
+    siblings: Vec<Fp>,
+    index: u64,
+}
+
+impl MerklePath {
+    // Random leaf and siblings at a random index; depth is below 64
+    fn random(depth: usize) -> Self {
+        Self {
+            leaf: Fp::random(OsRng),
+            siblings: (0..depth).map(|_| Fp::random(OsRng)).collect(),
+            index: OsRng.next_u64() % (1u64 << depth),
+        }
+    }
+
+    // Bit `level` of the index: true when the node at that level is a right child
+    fn is_right(&self, level: usize) -> bool {
+        (self.index >> level) & 1 == 1
+    }
+
+    fn root(&self) -> Fp {
+        self.siblings.iter().enumerate().fold(self.leaf, |node, (level, &sibling)| {
+            if self.is_right(level) {
+                hash_pair(sibling, node)
+            } else {
+                hash_pair(node, sibling)
+            }
+        })
+    }
+}
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct MerkleConfig {
+    node: Column<Advice>,
+    sibling: Column<Advice>,
+    bit: Column<Advice>,
+    left: Column<Advice>,
+    right: Column<Advice>,
+    root: Column<Instance>,
+    q_swap: Selector,
+    poseidon: Pow5Config<Fp, WIDTH, RATE>,
+}
+
+#[derive(Clone)]
+struct MerkleCircuit {
+    leaf: Value<Fp>,
+    siblings: Vec<Value<Fp>>,
+    index_bits: Vec<Value<bool>>,
+}
+
+impl MerkleCircuit {
+    fn new(path: &MerklePath) -> Self {
+        Self {
+            leaf: Value::known(path.leaf),
+            siblings: path.siblings.iter().map(|s| Value::known(*s)).collect(),
+            index_bits: (0..path.siblings.len()).map(|level| Value::known(path.is_right(level))).collect(),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    fn min_k(&self) -> u32 {
+        let rows = self.depth() * ROWS_PER_LEVEL + 1 + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl Circuit<Fp> for MerkleCircuit {
+    type Config = MerkleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            leaf: Value::unknown(),
+            siblings: vec![Value::unknown(); self.depth()],
+            index_bits: vec![Value::unknown(); self.depth()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+        let node = meta.advice_column();
+        let sibling = meta.advice_column();
+        let bit = meta.advice_column();
+        let left = meta.advice_column();
+        let right = meta.advice_column();
+        let root = meta.instance_column();
+        let q_swap = meta.selector();
+
+        // The initial capacity element is a constant
+        meta.enable_constant(rc_b[0]);
+        meta.enable_equality(node);
+        meta.enable_equality(left);
+        meta.enable_equality(right);
+        meta.enable_equality(root);
+
+        // bit = 0: (left, right) = (node, sibling)
+        // bit = 1: (left, right) = (sibling, node)
+        meta.create_gate("swap", |meta| {
+            let q_swap = meta.query_selector(q_swap);
+            let node = meta.query_advice(node, Rotation::cur());
+            let sibling = meta.query_advice(sibling, Rotation::cur());
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let left = meta.query_advice(left, Rotation::cur());
+            let right = meta.query_advice(right, Rotation::cur());
+            let one = Expression::Constant(Fp::ONE);
+
+            vec![
+                q_swap.clone() * bit.clone() * (one - bit.clone()),
+                q_swap.clone() * (left - (node.clone() + bit.clone() * (sibling.clone() - node.clone()))),
+                q_swap * (right - (sibling.clone() + bit * (node - sibling))),
+            ]
+        });
+
+        MerkleConfig {
+            node,
+            sibling,
+            bit,
+            left,
+            right,
+            root,
+            q_swap,
+            poseidon: Pow5Chip::configure::<P128Pow5T3>(meta, state, partial_sbox, rc_a, rc_b),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), ErrorFront> {
+        let mut node = layouter.assign_region(
+            || "leaf",
+            |mut region| region.assign_advice(|| "leaf", config.node, 0, || self.leaf),
+        )?;
+
+        for (level, (sibling, bit)) in self.siblings.iter().zip(&self.index_bits).enumerate() {
+            let (left, right) = layouter.assign_region(
+                || format!("swap {}", level),
+                |mut region| {
+                    config.q_swap.enable(&mut region, 0)?;
+                    let node = node.copy_advice(|| "node", &mut region, config.node, 0)?;
+                    region.assign_advice(|| "sibling", config.sibling, 0, || *sibling)?;
+                    region.assign_advice(|| "bit", config.bit, 0, || bit.map(|b| Fp::from(b as u64)))?;
+
+                    let (left, right) = node
+                        .value()
+                        .copied()
+                        .zip(*sibling)
+                        .zip(*bit)
+                        .map(|((node, sibling), bit)| if bit { (sibling, node) } else { (node, sibling) })
+                        .unzip();
+                    let left = region.assign_advice(|| "left", config.left, 0, || left)?;
+                    let right = region.assign_advice(|| "right", config.right, 0, || right)?;
+                    Ok((left, right))
+                },
+            )?;
+
+            let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<2>, WIDTH, RATE>::init(
+                Pow5Chip::construct(config.poseidon.clone()),
+                layouter.namespace(|| format!("init {}", level)),
+            )?;
+            node = hasher.hash(layouter.namespace(|| format!("hash {}", level)), [left, right])?;
+        }
+
+        layouter.constrain_instance(node.cell(), config.root, 0)
+    }
+}
+
+/// Benchmarks one proof of inclusion at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks inclusion in a random tree at `size` in this process, proving it
+/// `repeat` times: time_metrics holds the median run and time_statistics the
+/// distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    run_at_depth(size, depth_for(size.as_str()), repeat)
+}
+
+/// As `run_repeated`, but with a tree of `depth` levels instead of the one
+/// `size` picks; `size` is still the size the metrics report.
+pub fn run_at_depth(size: CircuitSize, depth: usize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let circuit_size = size.as_str();
+
+    let path = MerklePath::random(depth);
+    let root = path.root();
+    let circuit = MerkleCircuit::new(&path);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Fp, MerkleCircuit>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            constraints_count: circuit_stats.constraints as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::MerkleInclusion.as_str(), circuit_size)
+    };
+    metrics.resolved_parameters.insert("depth".to_string(), circuit.depth().into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[&[root]], repeat)?;
+
+    collect_environment(circuit_size).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    fn verify(circuit: &MerkleCircuit, root: Fp) -> bool {
+        let prover = MockProver::run(circuit.min_k(), circuit, vec![vec![root]]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    // Every leaf of a full depth-3 tree, built level by level, so the index
+    // bits are checked against actual left and right children
+    #[test]
+    fn test_every_leaf_of_small_tree() {
+        const DEPTH: usize = 3;
+        let leaves: Vec<Fp> = (0..1u64 << DEPTH).map(|i| Fp::from(i * 11 + 5)).collect();
+
+        let mut levels = vec![leaves.clone()];
+        while levels.last().unwrap().len() > 1 {
+            let parents = levels.last().unwrap().chunks(2).map(|pair| hash_pair(pair[0], pair[1])).collect();
+            levels.push(parents);
+        }
+        let root = levels[DEPTH][0];
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let siblings = (0..DEPTH).map(|level| levels[level][(index >> level) ^ 1]).collect();
+            let path = MerklePath { leaf: *leaf, siblings, index: index as u64 };
+
+            assert_eq!(path.root(), root);
+            assert!(verify(&MerkleCircuit::new(&path), root), "leaf {}", index);
+        }
+    }
+
+    #[test]
+    fn test_flipped_index_bit_rejected() {
+        // 0b0110: right child at levels 1 and 2, left at 0 and 3
+        let path = MerklePath {
+            leaf: Fp::from(7u64),
+            siblings: (1..=4u64).map(Fp::from).collect(),
+            index: 0b0110,
+        };
+        let root = path.root();
+        assert!(verify(&MerkleCircuit::new(&path), root));
+
+        for level in 0..4 {
+            let flipped = MerklePath { index: path.index ^ (1 << level), ..path.clone() };
+            assert!(!verify(&MerkleCircuit::new(&flipped), root), "bit {}", level);
+        }
+    }
+
+    #[test]
+    fn test_wrong_sibling_rejected() {
+        let path = MerklePath::random(8);
+        let root = path.root();
+
+        let mut wrong = path.clone();
+        wrong.siblings[5] += Fp::ONE;
+
+        assert!(verify(&MerkleCircuit::new(&path), root));
+        assert!(!verify(&MerkleCircuit::new(&wrong), root));
+    }
+
+    #[test]
+    fn test_depth_mapping() {
+        assert_eq!(depth_for("small"), 8);
+        assert_eq!(depth_for("medium"), 16);
+        assert_eq!(depth_for("large"), 32);
+
+        let shallow = MerkleCircuit::new(&MerklePath::random(8));
+        let deep = MerkleCircuit::new(&MerklePath::random(32));
+        assert!(deep.min_k() > shallow.min_k());
+    }
+
+    #[test]
+    fn test_parse_depth() {
+        assert_eq!(parse_depth("20"), Ok(20));
+        assert_eq!(parse_depth("63"), Ok(63));
+        assert_eq!(parse_depth("0").unwrap_err(), "depth expects an integer from 1 to 63, got \"0\"");
+        assert!(parse_depth("64").is_err());
+        assert!(parse_depth("deep").is_err());
+    }
+
+    #[test]
+    fn test_depth_overrides_size() {
+        let metrics = run_at_depth(CircuitSize::Small, 3, 1).unwrap();
+
+        assert_eq!(metrics.circuit_size, "small");
+        assert_eq!(metrics.resolved_parameters["depth"], 3);
+        assert_eq!(metrics.extra["verification_passed"], true);
+    }
+
+    #[test]
+    fn test_real_proof_verifies() {
+        let path = MerklePath::random(4);
+        let circuit = MerkleCircuit::new(&path);
+
+        let artifacts = halo2_common::run_real_proof(circuit.min_k(), circuit, &[&[path.root()]]).unwrap();
+
+        assert!(artifacts.verified);
+    }
+}
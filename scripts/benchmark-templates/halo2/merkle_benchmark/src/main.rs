@@ -0,0 +1,21 @@
+//! Command-line entry point; the benchmark itself is `merkle_benchmark::run`.
+
+use std::error::Error;
+
+use benchmark_metrics::{CountingAllocator, TemplateArgs};
+
+// Counts live allocations, so the metrics can set peak RSS against them
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = TemplateArgs::from_env();
+    let operation_args = args.operation_args(&merkle_benchmark::OPERATION_ARGS)?;
+    let size = args.size_or_exit();
+    let metrics = match operation_args.get("depth") {
+        Some(depth) => merkle_benchmark::run_at_depth(size, merkle_benchmark::parse_depth(depth)?, args.repeat)?,
+        None => merkle_benchmark::run_repeated(size, args.repeat)?,
+    };
+    metrics.emit(&args)?;
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! Multi-Scalar Multiplication (MSM) Benchmark
+//!
+//! Computes sum(s_i * P_i) over N Pallas points with halo2's `best_multiexp`,
+//! the same routine the prover uses for every polynomial commitment. MSM
+//! dominates proving time in most KZG/IPA systems, so timing it in isolation
+//! separates the cost of the MSM backend (CPU, GPU) from circuit shape.
+//!
+//! The number of points N is chosen by the circuit_size argument:
+//!
+//! - small:  2^12
+//! - medium: 2^16
+//! - large:  2^20
+//! - xlarge: 2^22
+//!
+//! No proof is made, so proving_time_ms stays 0; the MSM time goes in
+//! `time_metrics.extra.msm_time_ms` and its distribution over `--repeat`
+//! runs in `time_statistics.msm_time_ms`.
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::time::Instant;
+
+use halo2_proofs::arithmetic::best_multiexp;
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Operation, ScalabilityMetrics, SecurityMetrics,
+    Summary,
+};
+use ff::{Field, PrimeField};
+use group::{Curve, Group};
+use rand::rngs::OsRng;
+use pasta_curves::pallas;
+
+fn msm_size(size: CircuitSize) -> usize {
+    match size {
+        CircuitSize::Small => 1 << 12,
+        CircuitSize::Medium => 1 << 16,
+        CircuitSize::Large => 1 << 20,
+        CircuitSize::XLarge => 1 << 22,
+    }
+}
+
+fn random_instance(n: usize) -> (Vec<pallas::Scalar>, Vec<pallas::Affine>) {
+    let mut rng = OsRng;
+
+    let scalars: Vec<pallas::Scalar> = (0..n).map(|_| pallas::Scalar::random(&mut rng)).collect();
+    let points: Vec<pallas::Point> = (0..n).map(|_| pallas::Point::random(&mut rng)).collect();
+
+    let mut bases = vec![pallas::Affine::default(); n];
+    pallas::Point::batch_normalize(&points, &mut bases);
+
+    (scalars, bases)
+}
+
+fn msm(scalars: &[pallas::Scalar], bases: &[pallas::Affine]) -> pallas::Point {
+    best_multiexp(scalars, bases)
+}
+
+/// Benchmarks one MSM at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> BenchmarkMetrics {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks an MSM over N random Pallas points at `size`, repeated
+/// `repeat` times over the same instance: `time_metrics.extra.msm_time_ms`
+/// holds the median run and `time_statistics.msm_time_ms` the distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> BenchmarkMetrics {
+    let n = msm_size(size);
+
+    let mut metrics = BenchmarkMetrics {
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: pallas::Scalar::NUM_BITS,
+            // Pippenger's bucket method
+            proving_complexity_class: "O(n / log n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::Msm.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("msm_size".to_string(), n.into());
+
+    let (scalars, bases) = random_instance(n);
+
+    let mut millis = Vec::with_capacity(repeat);
+    for _ in 0..repeat {
+        let start = Instant::now();
+        // Keeps the MSM from being optimized away without checking its result
+        black_box(msm(&scalars, &bases));
+        let elapsed = start.elapsed();
+
+        millis.push(elapsed.as_secs_f64() * 1000.0);
+    }
+
+    if let Some(summary) = Summary::of(&millis) {
+        metrics.time_metrics.extra.insert("msm_time_ms".to_string(), (summary.median.round() as u64).into());
+        metrics.performance_metrics.extra.insert(
+            "points_per_second".to_string(),
+            (n as f64 * 1000.0 / summary.median).round().into(),
+        );
+        metrics.time_statistics = Some(BTreeMap::from([("msm_time_ms".to_string(), summary)]));
+    }
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Textbook double-and-add over the scalar's bits, most significant first
+    fn double_and_add(base: &pallas::Affine, scalar: &pallas::Scalar) -> pallas::Point {
+        let mut acc = pallas::Point::identity();
+        for byte in scalar.to_repr().as_ref().iter().rev() {
+            for i in (0..8).rev() {
+                acc = acc.double();
+                if (byte >> i) & 1 == 1 {
+                    acc += base;
+                }
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn test_msm_matches_naive_sum() {
+        let (scalars, bases) = random_instance(64);
+
+        let expected = scalars
+            .iter()
+            .zip(bases.iter())
+            .fold(pallas::Point::identity(), |acc, (scalar, base)| acc + double_and_add(base, scalar));
+
+        assert_eq!(msm(&scalars, &bases).to_affine(), expected.to_affine());
+    }
+
+    #[test]
+    fn test_msm_size_scales_with_circuit_size() {
+        for pair in CircuitSize::ALL.windows(2) {
+            assert!(msm_size(pair[0]) < msm_size(pair[1]));
+        }
+    }
+
+    #[test]
+    fn test_run_repeated_records_msm_time() {
+        let metrics = run_repeated(CircuitSize::Small, 2);
+
+        assert_eq!(metrics.operation, "msm");
+        assert_eq!(metrics.resolved_parameters["msm_size"], 1 << 12);
+        assert_eq!(metrics.time_statistics.unwrap()["msm_time_ms"].runs, 2);
+        assert!(metrics.time_metrics.extra["msm_time_ms"].is_u64());
+    }
+}
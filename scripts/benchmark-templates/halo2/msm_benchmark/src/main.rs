@@ -0,0 +1,12 @@
+//! Command-line entry point; the benchmark itself is `msm_benchmark::run`.
+
+use std::error::Error;
+
+use benchmark_metrics::TemplateArgs;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = TemplateArgs::from_env();
+    let metrics = msm_benchmark::run_repeated(args.size_or_exit(), args.repeat);
+    metrics.emit(&args)?;
+    Ok(())
+}
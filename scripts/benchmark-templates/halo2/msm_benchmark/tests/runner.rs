@@ -0,0 +1,32 @@
+// The benchmark as the runner sees it: a child process whose stdout must
+// parse as metrics, and which rejects a size it does not know as a usage
+// error rather than quietly running another one
+
+use std::path::Path;
+use std::process::Command;
+
+use benchmark_runner::{run_template, ReportEntry};
+
+#[test]
+fn test_runner_parses_msm_output() {
+    let entry = run_template(Path::new(env!("CARGO_BIN_EXE_msm_benchmark")), "small", true);
+
+    let metrics = match &entry {
+        ReportEntry::Metrics(metrics) => metrics,
+        ReportEntry::Error { error, .. } => panic!("runner rejected the MSM output: {}", error),
+    };
+    assert_eq!(metrics.system, "halo2");
+    assert_eq!(metrics.operation, "msm");
+    assert_eq!(metrics.circuit_size, "small");
+    assert!(metrics.time_metrics.extra.contains_key("msm_time_ms"));
+}
+
+#[test]
+fn test_unknown_size_is_usage_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_msm_benchmark")).arg("huge").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: unknown circuit size \"huge\""), "unexpected stderr: {}", stderr);
+}
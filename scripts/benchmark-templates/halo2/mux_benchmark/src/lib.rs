@@ -0,0 +1,267 @@
+//! Conditional Selection (Multiplexer) Circuit
+//!
+//! Branching in a zkVM is arithmetized as selection: both sides are
+//! computed and a boolean picks one. Each multiplexer here is one row with
+//! two constraints:
+//!
+//! - sel · (1 - sel) = 0                  (sel is boolean)
+//! - out = sel · a + (1 - sel) · b        (out = sel ? a : b)
+//!
+//! The number of multiplexers N is chosen by the circuit_size argument:
+//!
+//! - small:  2^8
+//! - medium: 2^12
+//! - large:  2^16
+//! - xlarge: 2^19
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Column, Advice, Selector, Expression, Error, ErrorFront},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs};
+use pasta_curves::pallas::Base;
+use serde_json::json;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Constraints contributed by every multiplexer row
+const CONSTRAINTS_PER_MUX: usize = 2;
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct MuxConfig {
+    q_mux: Selector,
+    sel: Column<Advice>,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    out: Column<Advice>,
+}
+
+// One selection: out = sel ? a : b
+#[derive(Debug, Clone, Copy)]
+struct Mux<F: PrimeField> {
+    sel: Value<F>,
+    a: Value<F>,
+    b: Value<F>,
+}
+
+impl<F: PrimeField> Mux<F> {
+    fn new(sel: u64, a: u64, b: u64) -> Self {
+        Self {
+            sel: Value::known(F::from(sel)),
+            a: Value::known(F::from(a)),
+            b: Value::known(F::from(b)),
+        }
+    }
+
+    fn out(&self) -> Value<F> {
+        self.sel.zip(self.a).zip(self.b)
+            .map(|((sel, a), b)| sel * a + (F::ONE - sel) * b)
+    }
+}
+
+// N independent multiplexers, one per row
+#[derive(Default, Clone)]
+struct MuxCircuit<F: PrimeField> {
+    muxes: Vec<Mux<F>>,
+}
+
+impl<F: PrimeField> MuxCircuit<F> {
+    fn rows_used(&self) -> usize {
+        self.muxes.len()
+    }
+
+    // Smallest k whose domain fits every multiplexer row
+    fn min_k(&self) -> u32 {
+        let rows = self.rows_used() + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for MuxCircuit<F> {
+    type Config = MuxConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        let unknown = Mux { sel: Value::unknown(), a: Value::unknown(), b: Value::unknown() };
+        Self {
+            muxes: vec![unknown; self.muxes.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_mux = meta.selector();
+        let sel = meta.advice_column();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let out = meta.advice_column();
+
+        meta.create_gate("mux", |meta| {
+            let q_mux = meta.query_selector(q_mux);
+            let sel = meta.query_advice(sel, Rotation::cur());
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                // sel is boolean
+                q_mux.clone() * sel.clone() * (one.clone() - sel.clone()),
+
+                // out = sel * a + (1 - sel) * b
+                q_mux * (out - (sel.clone() * a + (one - sel) * b)),
+            ]
+        });
+
+        MuxConfig { q_mux, sel, a, b, out }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), ErrorFront> {
+        layouter.assign_region(
+            || "muxes",
+            |mut region| {
+                for (offset, mux) in self.muxes.iter().enumerate() {
+                    config.q_mux.enable(&mut region, offset)?;
+                    region.assign_advice(|| "sel", config.sel, offset, || mux.sel)?;
+                    region.assign_advice(|| "a", config.a, offset, || mux.a)?;
+                    region.assign_advice(|| "b", config.b, offset, || mux.b)?;
+                    region.assign_advice(|| "out", config.out, offset, || mux.out())?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+fn mux_count(size: CircuitSize) -> usize {
+    match size {
+        CircuitSize::Small => 1 << 8,
+        CircuitSize::Medium => 1 << 12,
+        CircuitSize::Large => 1 << 16,
+        CircuitSize::XLarge => 1 << 19,
+    }
+}
+
+// Alternating selections so both branches are exercised
+fn sample_circuit(n: usize) -> MuxCircuit<Base> {
+    MuxCircuit {
+        muxes: (0..n as u64).map(|i| Mux::new(i % 2, i, i + 1)).collect(),
+    }
+}
+
+/// Benchmarks one proof of the multiplexers at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks the multiplexers at `size` in this process, proving them
+/// `repeat` times: time_metrics holds the median run and time_statistics
+/// the distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let n = mux_count(size);
+    let circuit = sample_circuit(n);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Base, MuxCircuit<Base>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Base::NUM_BITS,
+            // Both constraints of the gate apply on every multiplexer row
+            constraints_count: (n * CONSTRAINTS_PER_MUX) as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::Mux.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("multiplexers".to_string(), n.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[], repeat)?;
+    // Median proving time spread over the selections, what a branch costs
+    metrics.time_metrics.extra.insert(
+        "proving_time_per_mux_us".to_string(),
+        json!(metrics.time_metrics.proving_time_ms as f64 * 1e3 / n as f64),
+    );
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    #[test]
+    fn test_mux_selects_branch() {
+        let circuit = sample_circuit(16);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_mux_output_follows_selector() {
+        let taken = Mux::<Base>::new(1, 7, 9);
+        let skipped = Mux::<Base>::new(0, 7, 9);
+
+        taken.out().assert_if_known(|out| *out == Base::from(7));
+        skipped.out().assert_if_known(|out| *out == Base::from(9));
+    }
+
+    #[test]
+    fn test_constraints_per_mux() {
+        let stats = collect_circuit_stats::<Base, MuxCircuit<Base>>();
+
+        assert_eq!(stats.constraints, CONSTRAINTS_PER_MUX);
+        assert_eq!(stats.gates, 1);
+        assert_eq!(stats.columns, 4);
+    }
+
+    #[test]
+    fn test_mux_count_fits_domain() {
+        for size in CircuitSize::ALL {
+            assert!(sample_circuit(mux_count(size)).min_k() <= size.k(), "{} does not fit", size);
+        }
+    }
+
+    #[test]
+    fn test_non_boolean_selector_fails() {
+        // sel = 2 gives out = 2a - b, which the output gate alone would
+        // accept; only the boolean constraint rejects it
+        let mut circuit = sample_circuit(16);
+        circuit.muxes[5] = Mux::new(2, 7, 9);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -1,108 +1,116 @@
 use std::time::Instant;
-use std::env;
 use serde_json::json;
-use chrono;
+use benchmark_metrics::{
+    extra_fields, BenchmarkMetrics, Features, Operation, PerformanceMetrics,
+    ScalabilityMetrics, SecurityMetrics, SetupMetrics, TemplateArgs,
+};
 
 fn main() {
-    // Parse command line arguments
-    let args: Vec<String> = env::args().collect();
-    let circuit_size = args.get(1).map(String::as_str).unwrap_or("small");
-    
-    // Metrics collection
-    let mut metrics = json!({
-        "operation": "operation_name",
-        "system": "halo2",
-        "circuit_size": circuit_size,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        
-        // Time measurements
-        "time_metrics": {
-            "setup_time_ms": 0,            // Time for initial setup
-            "proving_time_ms": 0,          // Time to generate proof
-            "verification_time_ms": 0,      // Time to verify proof
-            "total_execution_time_ms": 0    // Total execution time
-        },
-        
-        // Resource usage
-        "resource_metrics": {
-            "peak_memory_usage_kb": 0,     // Peak memory during execution
-            "proof_size_bytes": 0,         // Size of the generated proof
-            "cpu_utilization_percent": 0,   // CPU usage during proving
-            "gpu_utilization_percent": 0    // GPU usage if applicable
-        },
-        
+    // Parse command line arguments (circuit size, --format, --out)
+    let args = TemplateArgs::from_env();
+    let circuit_size = args.circuit_size.as_str();
+
+    // Metrics collection; time, resource and requirement metrics start at
+    // zero and are filled in below
+    let mut metrics = BenchmarkMetrics {
         // Setup characteristics
-        "setup_metrics": {
-            "setup_type": "trusted/transparent",
-            "setup_size_bytes": 0,
-            "setup_reusable": true/false
+        setup_metrics: SetupMetrics {
+            setup_type: "trusted/transparent".to_string(),
+            setup_reusable: true,  // true/false
+            ..Default::default()
         },
-        
+
         // Feature support
-        "features": {
-            "recursive_proofs": false,
-            "universal_circuits": false,
-            "parallel_proving": false,
-            "parallel_verification": false,
-            "custom_gates": false
+        features: Features {
+            recursive_proofs: false,
+            universal_circuits: false,
+            parallel_proving: false,
+            parallel_verification: false,
+            custom_gates: false,
+            ..Default::default()
         },
-        
+
         // Security characteristics
-        "security_metrics": {
-            "post_quantum_resistant": false,
-            "security_level_bits": 0,
-            "assumptions": ["discrete_log", "etc"]
+        security_metrics: SecurityMetrics {
+            post_quantum_resistant: false,
+            security_level_bits: 0,
+            assumptions: vec!["discrete_log".to_string(), "etc".to_string()],
+            ..Default::default()
         },
-        
+
         // Scalability metrics
-        "scalability_metrics": {
-            "constraints_count": 0,
-            "variables_count": 0,
-            "degree": 0,
-            "proving_complexity_class": "O(n log n)",  // Theoretical complexity
-            "verification_complexity_class": "O(n)"
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: 255,  // Pallas base field
+            proving_complexity_class: "O(n log n)".to_string(),  // Theoretical complexity
+            verification_complexity_class: "O(n)".to_string(),
+            extra: extra_fields(json!({
+                "lookup_table_size": 0  // halo2 specific: rows of the largest lookup table
+            })),
+            ..Default::default()
         },
-        
+
         // Additional performance metrics
-        "performance_metrics": {
-            "throughput_proofs_per_second": 0.0,
-            "latency_ms": 0,
-            "batch_proving_supported": false,
-            "batch_verification_supported": false
+        performance_metrics: PerformanceMetrics {
+            batch_proving_supported: false,
+            batch_verification_supported: true,
+            ..Default::default()
         },
-        
-        // System requirements
-        "system_requirements": {
-            "minimum_memory_gb": 0,
-            "recommended_cpu_cores": 0,
-            "gpu_required": false,
-            "disk_space_gb": 0
-        }
-    });
+
+        // The operation this benchmark proves, from benchmark_metrics::Operation
+        ..BenchmarkMetrics::new("halo2", Operation::EcdsaVerify.as_str(), circuit_size)
+    };
 
     // Your implementation and benchmarking code goes here
     /*
     // Example structure:
-    // 1. Setup phase
-    let setup_start = Instant::now();
-    let circuit = your_implementation::setup(circuit_size);
-    metrics["time_metrics"]["setup_time_ms"] = setup_start.elapsed().as_millis();
-    
-    // 2. Proving phase
-    let proving_start = Instant::now();
-    let (proof, proving_key) = your_implementation::prove(&circuit);
-    metrics["time_metrics"]["proving_time_ms"] = proving_start.elapsed().as_millis();
-    
-    // 3. Verification phase
-    let verify_start = Instant::now();
-    let verified = your_implementation::verify(&proof, &proving_key);
-    metrics["time_metrics"]["verification_time_ms"] = verify_start.elapsed().as_millis();
-    
-    // 4. Collect additional metrics
-    metrics["resource_metrics"]["proof_size_bytes"] = proof.serialized_size();
-    metrics["scalability_metrics"]["constraints_count"] = circuit.constraint_count();
+    // --repeat <n> runs the measured phases n times; time_metrics ends up
+    // with the median run and time_statistics with the distribution
+    let mut aggregator = benchmark_metrics::Aggregator::new();
+    let mut proving_times = Vec::new();
+    for _ in 0..args.repeat {
+        // 1. Setup phase; record what circuit_size expanded to (k, iterations)
+        let setup_start = Instant::now();
+        let circuit = your_implementation::setup(circuit_size);
+        metrics.resolved_parameters.insert("k".to_string(), circuit.k().into());
+        metrics.time_metrics.setup_time_ms = setup_start.elapsed().as_millis() as u64;
+
+        // 2. Proving phase
+        let proving_start = Instant::now();
+        let (proof, proving_key) = {
+            // Writes cpu_utilization_percent when it goes out of scope
+            let _cpu = benchmark_metrics::CpuSampler::start_into(&mut metrics.resource_metrics);
+            your_implementation::prove(&circuit)
+        };
+        let proving_time = proving_start.elapsed();
+        proving_times.push(proving_time);
+        metrics.time_metrics.proving_time_ms = proving_time.as_millis() as u64;
+
+        // Peak memory so far, which proving usually dominates
+        metrics.resource_metrics.peak_memory_usage_kb = benchmark_metrics::peak_rss_kb().unwrap_or(0);
+
+        // 3. Verification phase
+        let verify_start = Instant::now();
+        let verified = your_implementation::verify(&proof, &proving_key);
+        metrics.time_metrics.verification_time_ms = verify_start.elapsed().as_millis() as u64;
+
+        // 4. Collect additional metrics
+        metrics.resource_metrics.proof_size_bytes = proof.serialized_size() as u64;
+        metrics.scalability_metrics.constraints_count = circuit.constraint_count() as u64;
+        metrics.scalability_metrics.extra["lookup_table_size"] = json!(circuit.lookup_table_size());
+
+        aggregator.record(&metrics.time_metrics);
+    }
+    aggregator.apply(&mut metrics);
+    // Throughput over the runs and the median single-proof latency
+    if let Some(throughput) = benchmark_metrics::Throughput::of(&proving_times) {
+        throughput.apply(&mut metrics);
+    }
     */
 
-    // Output metrics in JSON format
-    println!("{}", serde_json::to_string_pretty(&metrics).unwrap());
+    // Machine, toolchain and revision, so results can be compared across runs
+    benchmark_metrics::collect_environment(circuit_size).apply(&mut metrics);
+
+    // `--format json-compact` prints a single line for log ingestion;
+    // `--out <path>` writes the metrics there instead of stdout
+    metrics.emit(&args).expect("failed to write metrics");
 }
\ No newline at end of file
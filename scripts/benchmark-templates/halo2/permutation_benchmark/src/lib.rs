@@ -0,0 +1,221 @@
+//! Permutation (Copy-Constraint) Circuit
+//!
+//! PLONK-style systems tie cells together with a permutation argument, and
+//! every wire between gates is one copy constraint. This benchmark proves
+//! that an output column is a rearrangement of an input column of N
+//! elements: output row i is copy-constrained to input row σ(i). There are
+//! no custom gates, so the proving cost is the permutation argument alone.
+//!
+//! σ is part of the circuit and fixed at keygen. Here it is the permutation
+//! that sorts the sampled inputs, so the output is the sorted input; the
+//! ordering itself is not constrained, only that nothing was duplicated or
+//! dropped.
+//!
+//! The number of elements N is chosen by the circuit_size argument:
+//!
+//! - small:  2^8
+//! - medium: 2^12
+//! - large:  2^16
+//! - xlarge: 2^19
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Column, Advice, Error, ErrorFront},
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Operation, ScalabilityMetrics, SecurityMetrics,
+    SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs};
+use rand::{rngs::OsRng, RngCore};
+use pasta_curves::pallas::Base;
+use serde_json::json;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct PermutationConfig {
+    input: Column<Advice>,
+    output: Column<Advice>,
+}
+
+// output[i] = input[sigma[i]], enforced by copy constraints
+#[derive(Default, Clone)]
+struct PermutationCircuit<F: PrimeField> {
+    input: Vec<Value<F>>,
+    output: Vec<Value<F>>,
+    sigma: Vec<usize>,
+}
+
+impl<F: PrimeField> PermutationCircuit<F> {
+    // Sorts `values` and records where each sorted element came from
+    fn sorting(values: &[u64]) -> Self {
+        let mut sigma: Vec<usize> = (0..values.len()).collect();
+        sigma.sort_by_key(|&i| values[i]);
+
+        Self {
+            input: values.iter().map(|&v| Value::known(F::from(v))).collect(),
+            output: sigma.iter().map(|&i| Value::known(F::from(values[i]))).collect(),
+            sigma,
+        }
+    }
+
+    fn min_k(&self) -> u32 {
+        let rows = self.input.len() + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for PermutationCircuit<F> {
+    type Config = PermutationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            input: vec![Value::unknown(); self.input.len()],
+            output: vec![Value::unknown(); self.output.len()],
+            sigma: self.sigma.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let input = meta.advice_column();
+        let output = meta.advice_column();
+
+        meta.enable_equality(input);
+        meta.enable_equality(output);
+
+        PermutationConfig { input, output }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), ErrorFront> {
+        layouter.assign_region(
+            || "permutation",
+            |mut region| {
+                let inputs = self.input.iter().enumerate()
+                    .map(|(row, value)| region.assign_advice(|| "input", config.input, row, || *value))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for (row, (value, &source)) in self.output.iter().zip(&self.sigma).enumerate() {
+                    let cell = region.assign_advice(|| "output", config.output, row, || *value)?;
+                    region.constrain_equal(cell.cell(), inputs[source].cell())?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+fn element_count(size: CircuitSize) -> usize {
+    match size {
+        CircuitSize::Small => 1 << 8,
+        CircuitSize::Medium => 1 << 12,
+        CircuitSize::Large => 1 << 16,
+        CircuitSize::XLarge => 1 << 19,
+    }
+}
+
+/// Benchmarks one proof of a random permutation at `size`; see
+/// `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks sorting random elements at `size` in this process, proving
+/// the permutation `repeat` times: time_metrics holds the median run and
+/// time_statistics the distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let n = element_count(size);
+    let values: Vec<u64> = (0..n).map(|_| OsRng.next_u64()).collect();
+    let circuit = PermutationCircuit::<Base>::sorting(&values);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Base, PermutationCircuit<Base>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Base::NUM_BITS,
+            // One copy constraint per element; there are no gates
+            constraints_count: n as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::Permutation.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("elements".to_string(), n.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[], repeat)?;
+    // Median proving time spread over the copy constraints
+    metrics.time_metrics.extra.insert(
+        "proving_time_per_element_us".to_string(),
+        json!(metrics.time_metrics.proving_time_ms as f64 * 1e3 / n as f64),
+    );
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    const VALUES: [u64; 8] = [42, 7, 19, 3, 88, 61, 25, 14];
+
+    #[test]
+    fn test_correct_permutation_passes() {
+        let circuit = PermutationCircuit::<Base>::sorting(&VALUES);
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_no_gates() {
+        let stats = collect_circuit_stats::<Base, PermutationCircuit<Base>>();
+
+        assert_eq!((stats.gates, stats.constraints, stats.lookups), (0, 0, 0));
+        assert_eq!(stats.columns, 2);
+    }
+
+    #[test]
+    fn test_duplicated_element_fails() {
+        let mut circuit = PermutationCircuit::<Base>::sorting(&VALUES);
+        circuit.output[1] = circuit.output[0];
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_missing_element_fails() {
+        let mut circuit = PermutationCircuit::<Base>::sorting(&VALUES);
+        // 100 is not among the inputs, so some input no longer appears
+        circuit.output[3] = Value::known(Base::from(100));
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
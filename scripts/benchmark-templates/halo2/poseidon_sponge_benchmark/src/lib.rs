@@ -0,0 +1,306 @@
+//! Streaming Poseidon Sponge Absorption
+//!
+//! One-shot hashing hands the whole message to the hasher at once.
+//! Streaming workloads (long messages, data arriving in pieces) instead
+//! absorb it chunk by chunk into a sponge that is kept open between
+//! chunks. Both produce the same digest. This benchmark proves the streamed
+//! form with halo2_gadgets' Pow5 Poseidon (P128Pow5T3, width 3, rate 2) and
+//! reports the proving cost per chunk absorbed.
+//!
+//! The message length L is chosen by the circuit_size argument:
+//!
+//! - small:  8 field elements
+//! - medium: 64 field elements
+//! - large:  512 field elements
+//! - xlarge: 4096 field elements
+//!
+//! time_metrics are those of the streamed form; the one-shot proving time
+//! goes in `time_metrics.extra.one_shot_proving_time_ms`.
+
+use halo2_gadgets::poseidon::{
+    primitives::{self as poseidon, Absorbing, ConstantLength, Domain, P128Pow5T3},
+    Hash, PaddedWord, Pow5Chip, Pow5Config, Sponge,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront, Instance},
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs, run_real_proof};
+use pasta_curves::Fp;
+use serde_json::json;
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Upper bound on the rows one Pow5 permutation takes (8 full rounds, 56
+// partial rounds two per row, plus the initial state)
+const ROWS_PER_PERMUTATION: usize = 40;
+
+type StreamingSponge<const L: usize> = Sponge<
+    Fp,
+    Pow5Chip<Fp, WIDTH, RATE>,
+    P128Pow5T3,
+    Absorbing<PaddedWord<Fp>, RATE>,
+    ConstantLength<L>,
+    WIDTH,
+    RATE,
+>;
+
+// How the message is fed to the sponge
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Absorb {
+    // The whole message through `Hash::hash`
+    OneShot,
+    // `chunk` elements at a time into a sponge kept open between chunks
+    Streamed { chunk: usize },
+}
+
+// Circuit configuration
+#[derive(Debug, Clone)]
+struct SpongeConfig {
+    message: Column<Advice>,
+    digest: Column<Instance>,
+    poseidon: Pow5Config<Fp, WIDTH, RATE>,
+}
+
+// Hashes an L-element message and exposes the digest as the public input
+#[derive(Clone)]
+struct SpongeCircuit<const L: usize> {
+    message: [Value<Fp>; L],
+    mode: Absorb,
+}
+
+impl<const L: usize> SpongeCircuit<L> {
+    fn new(message: [Fp; L], mode: Absorb) -> Self {
+        Self {
+            message: message.map(Value::known),
+            mode,
+        }
+    }
+
+    // Permutations run while absorbing the message and its padding
+    fn permutations() -> usize {
+        L.div_ceil(RATE)
+    }
+
+    fn min_k() -> u32 {
+        let rows = L.max(Self::permutations() * ROWS_PER_PERMUTATION) + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl<const L: usize> Circuit<Fp> for SpongeCircuit<L> {
+    type Config = SpongeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            message: [Value::unknown(); L],
+            mode: self.mode,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+        let message = meta.advice_column();
+        let digest = meta.instance_column();
+
+        // Padding words and the initial capacity element are constants
+        meta.enable_constant(rc_b[0]);
+        meta.enable_equality(message);
+        meta.enable_equality(digest);
+
+        SpongeConfig {
+            message,
+            digest,
+            poseidon: Pow5Chip::configure::<P128Pow5T3>(meta, state, partial_sbox, rc_a, rc_b),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), ErrorFront> {
+        let chip = Pow5Chip::construct(config.poseidon.clone());
+
+        let message = layouter.assign_region(
+            || "load message",
+            |mut region| {
+                self.message
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        region.assign_advice(|| format!("message {}", i), config.message, i, || *value)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let digest = match self.mode {
+            Absorb::OneShot => {
+                let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init(
+                    chip,
+                    layouter.namespace(|| "init"),
+                )?;
+                hasher.hash(layouter.namespace(|| "hash"), message.try_into().unwrap())?
+            }
+            Absorb::Streamed { chunk } => {
+                let mut sponge = StreamingSponge::<L>::new(chip, layouter.namespace(|| "init"))?;
+
+                for (i, words) in message.chunks(chunk).enumerate() {
+                    let mut layouter = layouter.namespace(|| format!("chunk {}", i));
+                    for word in words {
+                        sponge.absorb(layouter.namespace(|| "absorb"), PaddedWord::Message(word.clone()))?;
+                    }
+                }
+                for padding in <ConstantLength<L> as Domain<Fp, RATE>>::padding(L) {
+                    sponge.absorb(layouter.namespace(|| "padding"), PaddedWord::Padding(padding))?;
+                }
+
+                sponge
+                    .finish_absorbing(layouter.namespace(|| "finish absorbing"))?
+                    .squeeze(layouter.namespace(|| "squeeze"))?
+            }
+        };
+
+        layouter.constrain_instance(digest.cell(), config.digest, 0)
+    }
+}
+
+// Native digest the circuit output is checked against
+fn reference_digest<const L: usize>(message: [Fp; L]) -> Fp {
+    poseidon::Hash::<_, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init().hash(message)
+}
+
+/// Benchmarks one proof of the streamed sponge at `size`; see
+/// `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks streaming the message at `size` into a Poseidon sponge,
+/// proving it `repeat` times: time_metrics holds the median run and
+/// time_statistics the distribution. The one-shot form is proved once for
+/// comparison.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    match size {
+        CircuitSize::Small => measure::<8>(size, repeat),
+        CircuitSize::Medium => measure::<64>(size, repeat),
+        CircuitSize::Large => measure::<512>(size, repeat),
+        CircuitSize::XLarge => measure::<4096>(size, repeat),
+    }
+}
+
+fn measure<const L: usize>(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let message: [Fp; L] = core::array::from_fn(|i| Fp::from(i as u64));
+    let digest = reference_digest(message);
+    let k = SpongeCircuit::<L>::min_k();
+    let permutations = SpongeCircuit::<L>::permutations();
+    let circuit_stats = collect_circuit_stats::<Fp, SpongeCircuit<L>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            // Upper bound: every Pow5 constraint on every permutation row
+            constraints_count: (permutations * ROWS_PER_PERMUTATION * circuit_stats.constraints) as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::PoseidonSponge.as_str(), size.as_str())
+    };
+    metrics.resolved_parameters.insert("message_length".to_string(), L.into());
+    // The message as 32-byte field elements, what `finalize` divides
+    // proving time by for bytes hashed per second
+    metrics.resolved_parameters.insert("input_bytes".to_string(), (L * 32).into());
+    metrics.resolved_parameters.insert("chunk".to_string(), RATE.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    let streamed = SpongeCircuit::new(message, Absorb::Streamed { chunk: RATE });
+    record_proofs(&mut metrics, k, &streamed, &[&[digest]], repeat)?;
+    metrics.finalize();
+
+    // Median proving time spread over the chunks, what absorbing one costs
+    metrics.time_metrics.extra.insert(
+        "proving_time_per_chunk_us".to_string(),
+        json!(metrics.time_metrics.proving_time_ms as f64 * 1e3 / permutations as f64),
+    );
+
+    let one_shot = run_real_proof(k, SpongeCircuit::new(message, Absorb::OneShot), &[&[digest]])?;
+    metrics.time_metrics.extra.insert(
+        "one_shot_proving_time_ms".to_string(),
+        json!(one_shot.proving.as_millis() as u64),
+    );
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+    use ff::Field;
+
+    const L: usize = 6;
+
+    fn message() -> [Fp; L] {
+        core::array::from_fn(|i| Fp::from(i as u64 * 7 + 1))
+    }
+
+    #[test]
+    fn test_streamed_digest_matches_one_shot() {
+        let digest = reference_digest(message());
+
+        // A chunk size that is not a multiple of the rate, so chunk and
+        // permutation boundaries do not line up
+        for mode in [Absorb::OneShot, Absorb::Streamed { chunk: 3 }] {
+            let circuit = SpongeCircuit::new(message(), mode);
+            let prover = MockProver::run(SpongeCircuit::<L>::min_k(), &circuit, vec![vec![digest]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_xlarge_message_fits_domain() {
+        assert!(SpongeCircuit::<4096>::min_k() <= CircuitSize::XLarge.k());
+    }
+
+    #[test]
+    fn test_streamed_digest_rejects_wrong_digest() {
+        let wrong = reference_digest(message()) + Fp::ONE;
+        let circuit = SpongeCircuit::new(message(), Absorb::Streamed { chunk: 3 });
+
+        let prover = MockProver::run(SpongeCircuit::<L>::min_k(), &circuit, vec![vec![wrong]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//! Lookup-Based Range Check Chip
+//!
+//! `RangeCheckChip<F, BITS>` proves values lie in [0, 2^BITS) by looking
+//! each one up in a fixed table of 0..2^BITS. It is written as a reusable
+//! chip: a circuit configures it on one of its advice columns, loads the
+//! table once, and hands it the cells to check.
+//!
+//! The bit width, and with it the table size, is chosen by the
+//! circuit_size argument; the number of values checked stays the same so
+//! the results isolate the cost of a wider table:
+//!
+//! - small:  8 bits (256-row table)
+//! - medium: 12 bits (4096-row table)
+//! - large:  16 bits (65536-row table)
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs};
+use pasta_curves::Fp;
+use serde_json::json;
+
+
+// Rows halo2 reserves at the bottom of every column for blinding factors
+const BLINDING_ROWS: usize = 6;
+
+// Values range checked per proof, whatever the bit width
+const VALUES_CHECKED: usize = 1024;
+
+#[derive(Debug, Clone)]
+struct RangeCheckConfig {
+    q_lookup: Selector,
+    value: Column<Advice>,
+    table: TableColumn,
+}
+
+struct RangeCheckChip<F: PrimeField, const BITS: usize> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const BITS: usize> Chip<F> for RangeCheckChip<F, BITS> {
+    type Config = RangeCheckConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> RangeCheckChip<F, BITS> {
+    fn construct(config: RangeCheckConfig) -> Self {
+        Self { config, _marker: PhantomData }
+    }
+
+    fn lookup_table_size() -> usize {
+        1 << BITS
+    }
+
+    // Range checks `value` wherever q_lookup is enabled
+    fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> RangeCheckConfig {
+        let table = meta.lookup_table_column();
+        let q_lookup = meta.complex_selector();
+        meta.enable_equality(value);
+
+        // Disabled rows look up 0, which is always in the table
+        meta.lookup("range check", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+
+            vec![(q_lookup * value, table)]
+        });
+
+        RangeCheckConfig { q_lookup, value, table }
+    }
+
+    fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), ErrorFront> {
+        layouter.assign_table(
+            || format!("{}-bit range table", BITS),
+            |mut table| {
+                for i in 0..Self::lookup_table_size() {
+                    let value = Value::known(F::from(i as u64));
+                    table.assign_cell(|| "table value", self.config.table, i, || value)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // Witnesses `values` one per row and range checks each
+    fn check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, ErrorFront> {
+        layouter.assign_region(
+            || "range checked values",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, value)| {
+                        self.config.q_lookup.enable(&mut region, offset)?;
+                        region.assign_advice(|| "value", self.config.value, offset, || *value)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[derive(Clone)]
+struct RangeCheckCircuit<F: PrimeField, const BITS: usize> {
+    values: Vec<Value<F>>,
+}
+
+impl<F: PrimeField, const BITS: usize> RangeCheckCircuit<F, BITS> {
+    fn new(values: &[u64]) -> Self {
+        Self { values: values.iter().map(|&v| Value::known(F::from(v))).collect() }
+    }
+
+    // Smallest k whose domain fits both the values and the table
+    fn min_k(&self) -> u32 {
+        let table_size = RangeCheckChip::<F, BITS>::lookup_table_size();
+        let rows = self.values.len().max(table_size) + BLINDING_ROWS + 1;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+impl<F: PrimeField, const BITS: usize> Circuit<F> for RangeCheckCircuit<F, BITS> {
+    type Config = RangeCheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { values: vec![Value::unknown(); self.values.len()] }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckChip::<F, BITS>::configure(meta, value)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), ErrorFront> {
+        let chip = RangeCheckChip::<F, BITS>::construct(config);
+        chip.load_table(&mut layouter)?;
+        chip.check(layouter.namespace(|| "check"), &self.values)?;
+        Ok(())
+    }
+}
+
+fn bench<const BITS: usize>(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let values: Vec<u64> = (0..VALUES_CHECKED as u64).map(|i| (i * 7919) % (1 << BITS)).collect();
+    let circuit = RangeCheckCircuit::<Fp, BITS>::new(&values);
+    let k = circuit.min_k();
+    let circuit_stats = collect_circuit_stats::<Fp, RangeCheckCircuit<Fp, BITS>>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            constraints_count: (circuit_stats.constraints + circuit_stats.lookups) as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::RangeCheck.as_str(), size.as_str())
+    };
+    metrics.scalability_metrics.extra.insert(
+        "lookup_table_size".to_string(),
+        json!(RangeCheckChip::<Fp, BITS>::lookup_table_size()),
+    );
+    metrics.resolved_parameters.insert("bits".to_string(), BITS.into());
+    metrics.resolved_parameters.insert("values_checked".to_string(), VALUES_CHECKED.into());
+    metrics.resolved_parameters.insert("k".to_string(), k.into());
+
+    record_proofs(&mut metrics, k, &circuit, &[], repeat)?;
+
+    collect_environment(size.as_str()).apply(&mut metrics);
+    Ok(metrics)
+}
+
+/// Benchmarks one range check proof at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks range checks at `size` in this process, proving them `repeat`
+/// times: time_metrics holds the median run and time_statistics the
+/// distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    match size.as_str() {
+        "medium" => bench::<12>(size, repeat),
+        "large" => bench::<16>(size, repeat),
+        _ => bench::<8>(size, repeat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    const BITS: usize = 8;
+
+    fn verify<const N: usize>(values: &[u64]) -> bool {
+        let circuit = RangeCheckCircuit::<Fp, N>::new(values);
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_in_range_values_pass() {
+        assert!(verify::<BITS>(&[0, 1, 17, 200, 255]));
+    }
+
+    #[test]
+    fn test_out_of_range_value_fails() {
+        assert!(!verify::<BITS>(&[0, 1, 256, 3]));
+        assert!(!verify::<BITS>(&[u64::MAX]));
+    }
+
+    // The table holds exactly 0..2^BITS: the top value passes, one past it fails
+    #[test]
+    fn test_table_sized_for_bit_width() {
+        assert_eq!(RangeCheckChip::<Fp, 8>::lookup_table_size(), 256);
+        assert_eq!(RangeCheckChip::<Fp, 12>::lookup_table_size(), 4096);
+
+        assert!(verify::<10>(&[(1 << 10) - 1]));
+        assert!(!verify::<10>(&[1 << 10]));
+        assert!(verify::<12>(&[1 << 10]));
+
+        assert_eq!(RangeCheckCircuit::<Fp, 12>::new(&[1]).min_k(), 13);
+    }
+
+    #[test]
+    fn test_constraint_counts() {
+        let stats = collect_circuit_stats::<Fp, RangeCheckCircuit<Fp, BITS>>();
+
+        assert_eq!(stats.gates, 0);
+        assert_eq!(stats.lookups, 1);
+    }
+
+    #[test]
+    fn test_real_proof_verifies() {
+        let circuit = RangeCheckCircuit::<Fp, BITS>::new(&[3, 250, 128]);
+
+        let artifacts = halo2_common::run_real_proof(circuit.min_k(), circuit, &[]).unwrap();
+
+        assert!(artifacts.verified);
+    }
+
+    #[test]
+    fn test_metrics_carry_vk_fingerprint_and_verifier_memory() {
+        let metrics = run(CircuitSize::Small).unwrap();
+
+        let fingerprint = metrics.setup_metrics.extra["vk_fingerprint"].as_str().unwrap();
+        assert_eq!(fingerprint.len(), 64);
+        #[cfg(target_os = "linux")]
+        assert!(metrics.resource_metrics.extra["verifier_peak_memory_kb"].as_u64().unwrap() > 0);
+    }
+}
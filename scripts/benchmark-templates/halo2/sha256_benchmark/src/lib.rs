@@ -0,0 +1,578 @@
+//! SHA-256 Compression Circuit
+//!
+//! Proves one SHA-256 compression of a fixed-length preimage of at most 55
+//! bytes, the longest that pads to a single block, and exposes the 256-bit
+//! digest as eight 32-bit words in the instance column.
+//!
+//! Every 32-bit word the rounds touch is decomposed into 32 boolean
+//! columns next to its packed value. Rotations and shifts are then free
+//! rewirings of bits, XOR, Ch and Maj are low-degree bit polynomials, and
+//! each addition mod 2^32 is a packed sum with a small carry. One row holds
+//! the a and e words a round produces; the round gate reads b, c, d and f,
+//! g, h from the three rows above.
+//!
+//! - Rows 0..4: initial state, fixed to H(0)
+//! - Rows 4..68: rounds 0..63, with the message schedule word W_i
+//! - Rows 68..76: digest H(1) = H(0) + final state, copied to the instance
+//!
+//! The preimage length picks which padding bytes are constants; those are
+//! constrained too, so the proof covers exactly an L-byte preimage. The
+//! circuit_size argument sets L:
+//!
+//! - small:  3 bytes
+//! - medium: 32 bytes
+//! - large:  55 bytes
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, ErrorFront, Expression, Fixed,
+        Instance, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use benchmark_metrics::{
+    collect_environment, BenchmarkMetrics, CircuitSize, Features, Operation, ScalabilityMetrics,
+    SecurityMetrics, SetupMetrics,
+};
+use ff::PrimeField;
+use halo2_common::{collect_circuit_stats, record_proofs};
+use pasta_curves::Fp;
+use rand::{rngs::OsRng, RngCore};
+
+mod native;
+
+use native::{IV, IV_ROWS, MAX_MESSAGE_BYTES, ROUND_CONSTANTS};
+
+const ROUNDS: usize = 64;
+const MESSAGE_WORDS: usize = 16;
+
+// First of the eight digest rows, after the initial state and the rounds
+const DIGEST_ROW: usize = IV_ROWS + ROUNDS;
+
+// 2^7 rows leave room for the 76 used plus the blinding rows
+const SHA256_K: u32 = 7;
+
+type Bits<F> = [Expression<F>; 32];
+
+#[derive(Debug, Clone)]
+struct Sha256Config {
+    // a and e words (the digest and the final state in the digest rows)
+    a: Column<Advice>,
+    a_bits: [Column<Advice>; 32],
+    e: Column<Advice>,
+    e_bits: [Column<Advice>; 32],
+    // Message schedule word of the round on this row
+    w: Column<Advice>,
+    w_bits: [Column<Advice>; 32],
+    // Little-endian bits of the carries out of bit 32
+    carry_a: [Column<Advice>; 3],
+    carry_e: [Column<Advice>; 3],
+    carry_w: [Column<Advice>; 2],
+    // H(0) words in the initial-state and digest rows
+    iv_a: Column<Fixed>,
+    iv_e: Column<Fixed>,
+    round_constant: Column<Fixed>,
+    // Padding bits of the message words, in the rows they constrain
+    padding: Column<Fixed>,
+    digest: Column<Instance>,
+    q_bits: Selector,
+    q_iv: Selector,
+    q_round: Selector,
+    q_schedule: Selector,
+    q_pad_word: Selector,
+    // Padding in the low 8, 16 or 24 bits of a word shared with the message
+    q_pad_low: [Selector; 3],
+    q_digest: Selector,
+}
+
+// Bits of `columns` at `rotation`, least significant first
+fn query_bits<F: PrimeField>(
+    meta: &mut VirtualCells<F>,
+    columns: &[Column<Advice>; 32],
+    rotation: i32,
+) -> Bits<F> {
+    columns.map(|column| meta.query_advice(column, Rotation(rotation)))
+}
+
+fn constant<F: PrimeField>(value: u64) -> Expression<F> {
+    Expression::Constant(F::from(value))
+}
+
+fn pack<F: PrimeField>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter().rev().fold(constant(0), |acc, bit| acc * constant(2) + bit.clone())
+}
+
+fn pack_carry<F: PrimeField>(meta: &mut VirtualCells<F>, columns: &[Column<Advice>]) -> Expression<F> {
+    let bits: Vec<Expression<F>> = columns.iter().map(|c| meta.query_advice(*c, Rotation::cur())).collect();
+    pack(&bits)
+}
+
+fn boolean<F: PrimeField>(bit: Expression<F>) -> Expression<F> {
+    bit.clone() * (constant(1) - bit)
+}
+
+fn xor<F: PrimeField>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - constant(2) * x * y
+}
+
+// Bit i of ROTR^n
+fn rotr<F: PrimeField>(bits: &Bits<F>, n: usize, i: usize) -> Expression<F> {
+    bits[(i + n) % 32].clone()
+}
+
+// Bit i of SHR^n
+fn shr<F: PrimeField>(bits: &Bits<F>, n: usize, i: usize) -> Expression<F> {
+    bits.get(i + n).cloned().unwrap_or_else(|| constant(0))
+}
+
+// Σ0 / Σ1: XOR of three rotations, packed
+fn big_sigma<F: PrimeField>(bits: &Bits<F>, [r1, r2, r3]: [usize; 3]) -> Expression<F> {
+    let out: Vec<Expression<F>> = (0..32)
+        .map(|i| xor(xor(rotr(bits, r1, i), rotr(bits, r2, i)), rotr(bits, r3, i)))
+        .collect();
+    pack(&out)
+}
+
+// σ0 / σ1: XOR of two rotations and a shift, packed
+fn small_sigma<F: PrimeField>(bits: &Bits<F>, [r1, r2]: [usize; 2], s: usize) -> Expression<F> {
+    let out: Vec<Expression<F>> = (0..32)
+        .map(|i| xor(xor(rotr(bits, r1, i), rotr(bits, r2, i)), shr(bits, s, i)))
+        .collect();
+    pack(&out)
+}
+
+impl Sha256Config {
+    fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> Self {
+        let mut advice = || meta.advice_column();
+        let a = advice();
+        let a_bits = [(); 32].map(|_| advice());
+        let e = advice();
+        let e_bits = [(); 32].map(|_| advice());
+        let w = advice();
+        let w_bits = [(); 32].map(|_| advice());
+        let carry_a = [(); 3].map(|_| advice());
+        let carry_e = [(); 3].map(|_| advice());
+        let carry_w = [(); 2].map(|_| advice());
+        let iv_a = meta.fixed_column();
+        let iv_e = meta.fixed_column();
+        let round_constant = meta.fixed_column();
+        let padding = meta.fixed_column();
+        let digest = meta.instance_column();
+        let q_bits = meta.selector();
+        let q_iv = meta.selector();
+        let q_round = meta.selector();
+        let q_schedule = meta.selector();
+        let q_pad_word = meta.selector();
+        let q_pad_low = [(); 3].map(|_| meta.selector());
+        let q_digest = meta.selector();
+
+        meta.enable_equality(a);
+        meta.enable_equality(e);
+        meta.enable_equality(digest);
+
+        // Every assigned word is 32 boolean bits, packed little-endian
+        meta.create_gate("word decomposition", |meta| {
+            let q_bits = meta.query_selector(q_bits);
+            let mut constraints = Vec::new();
+            for (word, bits) in [(a, &a_bits), (e, &e_bits), (w, &w_bits)] {
+                let bits = query_bits(meta, bits, 0);
+                let word = meta.query_advice(word, Rotation::cur());
+                constraints.push(q_bits.clone() * (word - pack(&bits)));
+                constraints.extend(bits.into_iter().map(|bit| q_bits.clone() * boolean(bit)));
+            }
+            constraints
+        });
+
+        meta.create_gate("initial state", |meta| {
+            let q_iv = meta.query_selector(q_iv);
+            let a = meta.query_advice(a, Rotation::cur());
+            let e = meta.query_advice(e, Rotation::cur());
+            let iv_a = meta.query_fixed(iv_a, Rotation::cur());
+            let iv_e = meta.query_fixed(iv_e, Rotation::cur());
+
+            vec![q_iv.clone() * (a - iv_a), q_iv * (e - iv_e)]
+        });
+
+        // The row above holds a and e of the previous round, the rows above
+        // that b, c, d and f, g, h:
+        //   T1 = h + Σ1(e) + Ch(e, f, g) + K_i + W_i
+        //   T2 = Σ0(a) + Maj(a, b, c)
+        //   a' = T1 + T2 mod 2^32, e' = d + T1 mod 2^32
+        meta.create_gate("round", |meta| {
+            let q_round = meta.query_selector(q_round);
+            let a_prev = query_bits(meta, &a_bits, -1);
+            let b = query_bits(meta, &a_bits, -2);
+            let c = query_bits(meta, &a_bits, -3);
+            let d = meta.query_advice(a, Rotation(-4));
+            let e_prev = query_bits(meta, &e_bits, -1);
+            let f = query_bits(meta, &e_bits, -2);
+            let g = query_bits(meta, &e_bits, -3);
+            let h = meta.query_advice(e, Rotation(-4));
+            let k = meta.query_fixed(round_constant, Rotation::cur());
+            let w = meta.query_advice(w, Rotation::cur());
+            let a_next = meta.query_advice(a, Rotation::cur());
+            let e_next = meta.query_advice(e, Rotation::cur());
+
+            let ch: Vec<Expression<F>> = (0..32)
+                .map(|i| e_prev[i].clone() * f[i].clone() + (constant(1) - e_prev[i].clone()) * g[i].clone())
+                .collect();
+            let maj: Vec<Expression<F>> = (0..32)
+                .map(|i| {
+                    let (x, y, z) = (a_prev[i].clone(), b[i].clone(), c[i].clone());
+                    x.clone() * y.clone() + x.clone() * z.clone() + y.clone() * z.clone()
+                        - constant(2) * x * y * z
+                })
+                .collect();
+
+            let t1 = h + big_sigma(&e_prev, [6, 11, 25]) + pack(&ch) + k + w;
+            let t2 = big_sigma(&a_prev, [2, 13, 22]) + pack(&maj);
+            let two_32 = constant(1 << 32);
+
+            let mut constraints = vec![
+                q_round.clone() * (a_next + two_32.clone() * pack_carry(meta, &carry_a) - (t1.clone() + t2)),
+                q_round.clone() * (e_next + two_32 * pack_carry(meta, &carry_e) - (d + t1)),
+            ];
+            for carry in carry_a.iter().chain(&carry_e) {
+                constraints.push(q_round.clone() * boolean(meta.query_advice(*carry, Rotation::cur())));
+            }
+            constraints
+        });
+
+        // W_i = σ1(W_{i-2}) + W_{i-7} + σ0(W_{i-15}) + W_{i-16} mod 2^32
+        meta.create_gate("message schedule", |meta| {
+            let q_schedule = meta.query_selector(q_schedule);
+            let w_2 = query_bits(meta, &w_bits, -2);
+            let w_7 = meta.query_advice(w, Rotation(-7));
+            let w_15 = query_bits(meta, &w_bits, -15);
+            let w_16 = meta.query_advice(w, Rotation(-16));
+            let w = meta.query_advice(w, Rotation::cur());
+
+            let sum = small_sigma(&w_2, [17, 19], 10) + w_7 + small_sigma(&w_15, [7, 18], 3) + w_16;
+            let carry = pack_carry(meta, &carry_w);
+            let mut constraints = vec![q_schedule.clone() * (w + constant(1 << 32) * carry - sum)];
+            for carry in &carry_w {
+                constraints.push(q_schedule.clone() * boolean(meta.query_advice(*carry, Rotation::cur())));
+            }
+            constraints
+        });
+
+        // Message words past the preimage are constants, and so are the low
+        // bytes of the word the preimage ends in
+        meta.create_gate("padding", |meta| {
+            let q_pad_word = meta.query_selector(q_pad_word);
+            let w_bits = query_bits(meta, &w_bits, 0);
+            let w = meta.query_advice(w, Rotation::cur());
+            let padding = meta.query_fixed(padding, Rotation::cur());
+
+            let mut constraints = vec![q_pad_word * (w - padding.clone())];
+            for (bytes, q_pad_low) in q_pad_low.iter().enumerate() {
+                let q_pad_low = meta.query_selector(*q_pad_low);
+                constraints.push(q_pad_low * (pack(&w_bits[..8 * (bytes + 1)]) - padding.clone()));
+            }
+            constraints
+        });
+
+        // digest = H(0) word + state word mod 2^32; the state word sits in e
+        meta.create_gate("digest", |meta| {
+            let q_digest = meta.query_selector(q_digest);
+            let digest = meta.query_advice(a, Rotation::cur());
+            let state = meta.query_advice(e, Rotation::cur());
+            let iv = meta.query_fixed(iv_a, Rotation::cur());
+            let carry = meta.query_advice(carry_a[0], Rotation::cur());
+
+            vec![
+                q_digest.clone() * (digest + constant(1 << 32) * carry.clone() - (state + iv)),
+                q_digest * boolean(carry),
+            ]
+        });
+
+        Sha256Config {
+            a, a_bits, e, e_bits, w, w_bits, carry_a, carry_e, carry_w,
+            iv_a, iv_e, round_constant, padding, digest,
+            q_bits, q_iv, q_round, q_schedule, q_pad_word, q_pad_low, q_digest,
+        }
+    }
+
+    // Assigns `value` and its bits to `word` and `bits` at `offset`
+    fn assign_word<F: PrimeField>(
+        region: &mut Region<'_, F>,
+        word: Column<Advice>,
+        bits: &[Column<Advice>; 32],
+        offset: usize,
+        value: Value<u32>,
+    ) -> Result<AssignedCell<F, F>, ErrorFront> {
+        for (i, column) in bits.iter().enumerate() {
+            region.assign_advice(|| "bit", *column, offset, || value.map(|v| F::from(((v >> i) & 1) as u64)))?;
+        }
+        region.assign_advice(|| "word", word, offset, || value.map(|v| F::from(v as u64)))
+    }
+
+    fn assign_carry<F: PrimeField>(
+        region: &mut Region<'_, F>,
+        columns: &[Column<Advice>],
+        offset: usize,
+        value: Value<u64>,
+    ) -> Result<(), ErrorFront> {
+        for (i, column) in columns.iter().enumerate() {
+            region.assign_advice(|| "carry bit", *column, offset, || value.map(|v| F::from((v >> i) & 1)))?;
+        }
+        Ok(())
+    }
+}
+
+// One compression of a `len`-byte preimage
+#[derive(Clone)]
+struct Sha256Circuit {
+    len: usize,
+    preimage: Value<Vec<u8>>,
+}
+
+impl Sha256Circuit {
+    fn new(preimage: Vec<u8>) -> Self {
+        assert!(preimage.len() <= MAX_MESSAGE_BYTES, "preimage longer than one block");
+        Self { len: preimage.len(), preimage: Value::known(preimage) }
+    }
+
+    // The padding of message word i, as (selector to enable, constant):
+    // None for a word of preimage bytes only
+    fn padding_constraint(&self, config: &Sha256Config, i: usize) -> Option<(Selector, u32)> {
+        let block = native::pad_message(&vec![0; self.len]);
+        let message_bytes = self.len.saturating_sub(4 * i).min(4);
+        match message_bytes {
+            4 => None,
+            0 => Some((config.q_pad_word, block[i])),
+            n => {
+                let padding_bytes = 4 - n;
+                let mask = (1u32 << (8 * padding_bytes)) - 1;
+                Some((config.q_pad_low[padding_bytes - 1], block[i] & mask))
+            }
+        }
+    }
+}
+
+impl Circuit<Fp> for Sha256Circuit {
+    type Config = Sha256Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { len: self.len, preimage: Value::unknown() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        Sha256Config::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), ErrorFront> {
+        let trace = self.preimage.as_ref().map(|preimage| native::compress(&native::pad_message(preimage)));
+
+        let digest = layouter.assign_region(
+            || "sha256 compression",
+            |mut region| {
+                let mut a_cells = Vec::with_capacity(DIGEST_ROW);
+                let mut e_cells = Vec::with_capacity(DIGEST_ROW);
+
+                for row in 0..DIGEST_ROW {
+                    config.q_bits.enable(&mut region, row)?;
+                    let a = trace.as_ref().map(|t| t.a[row]);
+                    let e = trace.as_ref().map(|t| t.e[row]);
+                    a_cells.push(Sha256Config::assign_word(&mut region, config.a, &config.a_bits, row, a)?);
+                    e_cells.push(Sha256Config::assign_word(&mut region, config.e, &config.e_bits, row, e)?);
+
+                    if row < IV_ROWS {
+                        config.q_iv.enable(&mut region, row)?;
+                        let (iv_a, iv_e) = (IV[IV_ROWS - 1 - row], IV[2 * IV_ROWS - 1 - row]);
+                        region.assign_fixed(|| "iv a", config.iv_a, row, || Value::known(Fp::from(iv_a as u64)))?;
+                        region.assign_fixed(|| "iv e", config.iv_e, row, || Value::known(Fp::from(iv_e as u64)))?;
+                        Sha256Config::assign_word(&mut region, config.w, &config.w_bits, row, Value::known(0))?;
+                        continue;
+                    }
+
+                    let i = row - IV_ROWS;
+                    config.q_round.enable(&mut region, row)?;
+                    let k = Fp::from(ROUND_CONSTANTS[i] as u64);
+                    region.assign_fixed(|| "K", config.round_constant, row, || Value::known(k))?;
+                    let w = trace.as_ref().map(|t| t.w[i]);
+                    Sha256Config::assign_word(&mut region, config.w, &config.w_bits, row, w)?;
+                    let carries = [
+                        (&config.carry_a[..], trace.as_ref().map(|t| t.carry_a[i])),
+                        (&config.carry_e[..], trace.as_ref().map(|t| t.carry_e[i])),
+                        (&config.carry_w[..], trace.as_ref().map(|t| t.carry_w[i])),
+                    ];
+                    for (columns, carry) in carries {
+                        Sha256Config::assign_carry(&mut region, columns, row, carry)?;
+                    }
+
+                    if i >= MESSAGE_WORDS {
+                        config.q_schedule.enable(&mut region, row)?;
+                    } else if let Some((selector, padding)) = self.padding_constraint(&config, i) {
+                        selector.enable(&mut region, row)?;
+                        let padding = Value::known(Fp::from(padding as u64));
+                        region.assign_fixed(|| "padding", config.padding, row, || padding)?;
+                    }
+                }
+
+                // Final state a, b, c, d, e, f, g, h: the last four rows' a
+                // and e words, newest first
+                let last = DIGEST_ROW - 1;
+                let state_cells: Vec<&AssignedCell<Fp, Fp>> = (0..4)
+                    .map(|j| &a_cells[last - j])
+                    .chain((0..4).map(|j| &e_cells[last - j]))
+                    .collect();
+
+                let mut digest = Vec::with_capacity(8);
+                for (j, state_cell) in state_cells.into_iter().enumerate() {
+                    let row = DIGEST_ROW + j;
+                    config.q_bits.enable(&mut region, row)?;
+                    config.q_digest.enable(&mut region, row)?;
+                    region.assign_fixed(|| "iv", config.iv_a, row, || Value::known(Fp::from(IV[j] as u64)))?;
+
+                    let word = trace.as_ref().map(|t| t.digest[j]);
+                    digest.push(Sha256Config::assign_word(&mut region, config.a, &config.a_bits, row, word)?);
+                    let state = trace.as_ref().map(|t| t.state[j]);
+                    let state_copy = Sha256Config::assign_word(&mut region, config.e, &config.e_bits, row, state)?;
+                    region.constrain_equal(state_cell.cell(), state_copy.cell())?;
+                    Sha256Config::assign_word(&mut region, config.w, &config.w_bits, row, Value::known(0))?;
+                    let carry = trace.as_ref().map(|t| t.digest_carry[j]);
+                    Sha256Config::assign_carry(&mut region, &config.carry_a[..1], row, carry)?;
+                }
+                Ok(digest)
+            },
+        )?;
+
+        for (j, word) in digest.iter().enumerate() {
+            layouter.constrain_instance(word.cell(), config.digest, j)?;
+        }
+        Ok(())
+    }
+}
+
+// The digest words as the circuit's public inputs
+fn digest_instance(digest: &[u32; 8]) -> Vec<Fp> {
+    digest.iter().map(|&word| Fp::from(word as u64)).collect()
+}
+
+fn preimage_len(circuit_size: &str) -> usize {
+    match circuit_size {
+        "small" => 3,
+        "medium" => 32,
+        "large" => MAX_MESSAGE_BYTES,
+        other => match other.parse::<usize>() {
+            Ok(len) if len <= MAX_MESSAGE_BYTES => len,
+            _ => panic!(
+                "circuit size must be small, medium, large or a length up to {} bytes",
+                MAX_MESSAGE_BYTES,
+            ),
+        },
+    }
+}
+
+/// Benchmarks one SHA-256 proof at `size`; see `run_repeated`.
+pub fn run(size: CircuitSize) -> Result<BenchmarkMetrics, Error> {
+    run_repeated(size, 1)
+}
+
+/// Benchmarks SHA-256 of a random preimage at `size` in this process, proving it
+/// `repeat` times: time_metrics holds the median run and time_statistics the
+/// distribution.
+pub fn run_repeated(size: CircuitSize, repeat: usize) -> Result<BenchmarkMetrics, Error> {
+    let circuit_size = size.as_str();
+
+    let mut preimage = vec![0u8; preimage_len(circuit_size)];
+    OsRng.fill_bytes(&mut preimage);
+    let public_inputs = digest_instance(&native::sha256_words(&preimage));
+    let circuit = Sha256Circuit::new(preimage);
+    let circuit_stats = collect_circuit_stats::<Fp, Sha256Circuit>();
+
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
+        },
+        features: Features {
+            custom_gates: true,
+            ..Default::default()
+        },
+        security_metrics: SecurityMetrics {
+            security_level_bits: 128,
+            assumptions: vec!["discrete_log".to_string()],
+            ..Default::default()
+        },
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: Fp::NUM_BITS,
+            constraints_count: circuit_stats.constraints as u64,
+            variables_count: circuit_stats.columns as u64,
+            degree: circuit_stats.degree as u32,
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(n)".to_string(),
+            ..Default::default()
+        },
+        ..BenchmarkMetrics::new("halo2", Operation::Sha256.as_str(), circuit_size)
+    };
+    metrics.resolved_parameters.insert("k".to_string(), SHA256_K.into());
+    metrics.resolved_parameters.insert("preimage_bytes".to_string(), circuit.len.into());
+    // What `finalize` divides proving time by for bytes hashed per second
+    metrics.resolved_parameters.insert("input_bytes".to_string(), circuit.len.into());
+
+    record_proofs(&mut metrics, SHA256_K, &circuit, &[&public_inputs], repeat)?;
+    metrics.finalize();
+
+    collect_environment(circuit_size).apply(&mut metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    fn prove(preimage: &[u8], digest: [u32; 8]) -> Result<(), Vec<halo2_proofs::dev::VerifyFailure>> {
+        let circuit = Sha256Circuit::new(preimage.to_vec());
+        let prover = MockProver::run(SHA256_K, &circuit, vec![digest_instance(&digest)]).unwrap();
+        prover.verify()
+    }
+
+    #[test]
+    fn test_empty_string_nist_vector() {
+        let digest = [
+            0xe3b0c442, 0x98fc1c14, 0x9afbf4c8, 0x996fb924, 0x27ae41e4, 0x649b934c, 0xa495991b, 0x7852b855,
+        ];
+        assert_eq!(prove(b"", digest), Ok(()));
+    }
+
+    #[test]
+    fn test_unaligned_preimages() {
+        // Lengths ending in each position of a word, up to the one-block limit
+        for len in [1, 2, 3, 5, 31, 54, MAX_MESSAGE_BYTES] {
+            let preimage: Vec<u8> = (0..len as u8).collect();
+            assert_eq!(prove(&preimage, native::sha256_words(&preimage)), Ok(()), "{}-byte preimage", len);
+        }
+    }
+
+    #[test]
+    fn test_wrong_digest_rejected() {
+        let mut digest = native::sha256_words(b"abc");
+        digest[7] ^= 1;
+        assert!(prove(b"abc", digest).is_err());
+    }
+
+    #[test]
+    fn test_circuit_stats() {
+        let stats = collect_circuit_stats::<Fp, Sha256Circuit>();
+
+        assert_eq!(stats.gates, 6);
+        assert!(stats.degree >= 4);
+    }
+
+    #[test]
+    fn test_real_proof_verifies() {
+        let preimage = b"abc".to_vec();
+        let public_inputs = digest_instance(&native::sha256_words(&preimage));
+        let circuit = Sha256Circuit::new(preimage);
+
+        let artifacts = halo2_common::run_real_proof(SHA256_K, circuit, &[&public_inputs]).unwrap();
+
+        assert!(artifacts.verified);
+        assert!(artifacts.proof_size_bytes > 0);
+    }
+}
@@ -0,0 +1,173 @@
+//! Native SHA-256
+//!
+//! Padding and the compression function outside the circuit (FIPS 180-4).
+//! `compress` keeps every intermediate value the circuit assigns: the a and
+//! e words after each round, the message schedule and the carries of the
+//! modular additions, laid out by circuit row.
+
+// Initial hash value H(0)
+pub(crate) const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+pub(crate) const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Longest message that pads to a single 64-byte block: one 0x80 byte and
+// the 8-byte length must still fit
+pub(crate) const MAX_MESSAGE_BYTES: usize = 55;
+
+// Rows holding the initial a and e words before the first round
+pub(crate) const IV_ROWS: usize = 4;
+
+/// Pads `message` to one block: the message, 0x80, zeros, then the bit
+/// length as a big-endian u64. Panics past `MAX_MESSAGE_BYTES`.
+pub(crate) fn pad_message(message: &[u8]) -> [u32; 16] {
+    assert!(
+        message.len() <= MAX_MESSAGE_BYTES,
+        "a {}-byte message does not fit a single block",
+        message.len(),
+    );
+
+    let mut block = [0u8; 64];
+    block[..message.len()].copy_from_slice(message);
+    block[message.len()] = 0x80;
+    block[56..].copy_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    words
+}
+
+fn big_sigma0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+fn big_sigma1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+fn small_sigma0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn small_sigma1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+// Every value the circuit assigns for one compression, by row
+#[derive(Debug, Clone)]
+pub(crate) struct CompressionTrace {
+    // a and e words: rows 0..4 hold the initial state (d, c, b, a and
+    // h, g, f, e), row 4 + i the words produced by round i
+    pub(crate) a: [u32; IV_ROWS + 64],
+    pub(crate) e: [u32; IV_ROWS + 64],
+    // Message schedule W_0..W_63
+    pub(crate) w: [u32; 64],
+    // Carries out of bit 32 of each round's two sums and each schedule sum
+    pub(crate) carry_a: [u64; 64],
+    pub(crate) carry_e: [u64; 64],
+    pub(crate) carry_w: [u64; 64],
+    // Final state, H(1) = H(0) + state, and the carries of those sums
+    pub(crate) state: [u32; 8],
+    pub(crate) digest: [u32; 8],
+    pub(crate) digest_carry: [u64; 8],
+}
+
+pub(crate) fn compress(block: &[u32; 16]) -> CompressionTrace {
+    let mut w = [0u32; 64];
+    let mut carry_w = [0u64; 64];
+    w[..16].copy_from_slice(block);
+    for i in 16..64 {
+        let sum = small_sigma1(w[i - 2]) as u64
+            + w[i - 7] as u64
+            + small_sigma0(w[i - 15]) as u64
+            + w[i - 16] as u64;
+        w[i] = sum as u32;
+        carry_w[i] = sum >> 32;
+    }
+
+    let mut a = [0u32; IV_ROWS + 64];
+    let mut e = [0u32; IV_ROWS + 64];
+    for row in 0..IV_ROWS {
+        a[row] = IV[IV_ROWS - 1 - row];
+        e[row] = IV[2 * IV_ROWS - 1 - row];
+    }
+
+    let mut carry_a = [0u64; 64];
+    let mut carry_e = [0u64; 64];
+    for i in 0..64 {
+        let row = IV_ROWS + i;
+        let (a_prev, b, c, d) = (a[row - 1], a[row - 2], a[row - 3], a[row - 4]);
+        let (e_prev, f, g, h) = (e[row - 1], e[row - 2], e[row - 3], e[row - 4]);
+
+        let ch = (e_prev & f) ^ (!e_prev & g);
+        let maj = (a_prev & b) ^ (a_prev & c) ^ (b & c);
+        let t1 = h as u64 + big_sigma1(e_prev) as u64 + ch as u64 + ROUND_CONSTANTS[i] as u64 + w[i] as u64;
+        let t2 = big_sigma0(a_prev) as u64 + maj as u64;
+
+        a[row] = (t1 + t2) as u32;
+        carry_a[i] = (t1 + t2) >> 32;
+        e[row] = (d as u64 + t1) as u32;
+        carry_e[i] = (d as u64 + t1) >> 32;
+    }
+
+    let last = IV_ROWS + 63;
+    let state = [a[last], a[last - 1], a[last - 2], a[last - 3], e[last], e[last - 1], e[last - 2], e[last - 3]];
+    let mut digest = [0u32; 8];
+    let mut digest_carry = [0u64; 8];
+    for j in 0..8 {
+        let sum = IV[j] as u64 + state[j] as u64;
+        digest[j] = sum as u32;
+        digest_carry[j] = sum >> 32;
+    }
+
+    CompressionTrace { a, e, w, carry_a, carry_e, carry_w, state, digest, digest_carry }
+}
+
+/// SHA-256 of a single-block message, as eight big-endian words.
+pub(crate) fn sha256_words(message: &[u8]) -> [u32; 8] {
+    compress(&pad_message(message)).digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nist_vectors() {
+        // FIPS 180-4 examples: the empty string and "abc"
+        assert_eq!(
+            sha256_words(b""),
+            [0xe3b0c442, 0x98fc1c14, 0x9afbf4c8, 0x996fb924, 0x27ae41e4, 0x649b934c, 0xa495991b, 0x7852b855],
+        );
+        assert_eq!(
+            sha256_words(b"abc"),
+            [0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61, 0xf20015ad],
+        );
+    }
+
+    #[test]
+    fn test_padding_of_partial_word() {
+        // Two message bytes, then 0x80 in the same word
+        let block = pad_message(b"ab");
+        assert_eq!(block[0], 0x61628000);
+        assert!(block[1..15].iter().all(|&word| word == 0));
+        assert_eq!(block[15], 16);
+
+        let longest = pad_message(&[0xff; MAX_MESSAGE_BYTES]);
+        assert_eq!(longest[13], 0xffffff80);
+        assert_eq!(longest[15], MAX_MESSAGE_BYTES as u32 * 8);
+    }
+}
@@ -1,95 +1,133 @@
 use std::time::Instant;
-use std::env;
 use serde_json::json;
-use chrono;
+use benchmark_metrics::{
+    extra_fields, BenchmarkMetrics, Features, Operation, PerformanceMetrics,
+    ScalabilityMetrics, SecurityMetrics, SetupMetrics, TemplateArgs, TimeMetrics,
+};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let circuit_size = args.get(1).map(String::as_str).unwrap_or("small");
-    
-    let mut metrics = json!({
-        "operation": "operation_name",
-        "system": "nexus",
-        "circuit_size": circuit_size,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        
-        "time_metrics": {
-            "setup_time_ms": 0,
-            "proving_time_ms": 0,
-            "verification_time_ms": 0,
-            "total_execution_time_ms": 0
-        },
-        
-        "resource_metrics": {
-            "peak_memory_usage_kb": 0,
-            "proof_size_bytes": 0,
-            "cpu_utilization_percent": 0,
-            "gpu_utilization_percent": 0
+    // Circuit size, --format and --out; see benchmark_metrics::TemplateArgs
+    let args = TemplateArgs::from_env();
+    let circuit_size = args.circuit_size.as_str();
+
+    // resolved_parameters: what circuit_size expanded to (steps, iterations)
+    let mut metrics = BenchmarkMetrics {
+        time_metrics: TimeMetrics {
+            extra: extra_fields(json!({
+                "step_proving_time_ms": 0,      // Nexus-specific: avg time per folding step
+                "final_proving_time_ms": 0      // Nexus-specific: final compression proof
+            })),
+            ..Default::default()
         },
-        
-        "setup_metrics": {
-            "setup_type": "transparent",
-            "setup_size_bytes": 0,
-            "setup_reusable": true
+
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
         },
-        
-        "features": {
-            "recursive_proofs": true,
-            "universal_circuits": true,
-            "parallel_proving": true,
-            "parallel_verification": true,
-            "custom_gates": true,
-            "zero_knowledge": true,
-            "native_lookups": true  // Nexus-specific feature
+
+        features: Features {
+            recursive_proofs: true,
+            universal_circuits: true,
+            parallel_proving: true,
+            parallel_verification: true,
+            custom_gates: true,
+            extra: extra_fields(json!({
+                "zero_knowledge": true,
+                "native_lookups": true  // Nexus-specific feature
+            })),
         },
-        
-        "security_metrics": {
-            "post_quantum_resistant": true,
-            "security_level_bits": 128,
-            "assumptions": ["collision_resistant_hash", "discrete_log"]
+
+        security_metrics: SecurityMetrics {
+            post_quantum_resistant: true,
+            security_level_bits: 128,
+            assumptions: vec!["collision_resistant_hash".to_string(), "discrete_log".to_string()],
+            extra: extra_fields(json!({
+                "grinding_bits": 0,           // Nexus-specific: proof-of-work bits
+                "blowup_factor": 0,           // Nexus-specific: FRI/LDE blowup
+                "num_queries": 0              // Nexus-specific: FRI query count
+            })),
         },
-        
-        "scalability_metrics": {
-            "constraints_count": 0,
-            "variables_count": 0,
-            "degree": 0,
-            "proving_complexity_class": "O(n log n)",
-            "verification_complexity_class": "O(1)",
-            "lookup_table_size": 0  // Nexus-specific metric
+
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: 254,  // BN254 scalar field
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(1)".to_string(),
+            extra: extra_fields(json!({
+                "lookup_table_size": 0  // Nexus-specific metric
+            })),
+            ..Default::default()
         },
-        
-        "performance_metrics": {
-            "throughput_proofs_per_second": 0.0,
-            "latency_ms": 0,
-            "batch_proving_supported": true,
-            "batch_verification_supported": true
+
+        performance_metrics: PerformanceMetrics {
+            batch_proving_supported: true,
+            batch_verification_supported: true,
+            ..Default::default()
         },
-        
-        "system_requirements": {
-            "minimum_memory_gb": 0,
-            "recommended_cpu_cores": 0,
-            "gpu_required": false,
-            "disk_space_gb": 0
-        }
-    });
+
+        // The operation this benchmark proves, from benchmark_metrics::Operation
+        ..BenchmarkMetrics::new("nexus", Operation::EcdsaVerify.as_str(), circuit_size)
+    };
 
     // Implementation placeholder
     /*
-    // 1. Setup phase
-    let setup_start = Instant::now();
-    let circuit = your_implementation::setup(circuit_size);
-    metrics["time_metrics"]["setup_time_ms"] = setup_start.elapsed().as_millis();
-    
-    // 2. Proving phase
-    let proving_start = Instant::now();
-    let proof = your_implementation::prove(&circuit);
-    metrics["time_metrics"]["proving_time_ms"] = proving_start.elapsed().as_millis();
-    
-    // 3. Verification phase
-    let verify_start = Instant::now();
-    let verified = your_implementation::verify(&proof);
-    metrics["time_metrics"]["verification_time_ms"] = verify_start.elapsed().as_millis();
+    // --repeat <n> runs the measured phases n times; time_metrics ends up
+    // with the median run and time_statistics with the distribution
+    let mut aggregator = benchmark_metrics::Aggregator::new();
+    let mut proving_times = Vec::new();
+    for _ in 0..args.repeat {
+        // 1. Setup phase
+        let setup_start = Instant::now();
+        let circuit = your_implementation::setup(circuit_size);
+        metrics.time_metrics.setup_time_ms = setup_start.elapsed().as_millis() as u64;
+
+        // Record the parameters backing security_level_bits
+        let options = your_implementation::proof_options(circuit_size);
+        metrics.security_metrics.extra["grinding_bits"] = json!(options.grinding_factor());
+        metrics.security_metrics.extra["blowup_factor"] = json!(options.blowup_factor());
+        metrics.security_metrics.extra["num_queries"] = json!(options.num_queries());
+
+        // 2. Proving phase (incremental folding steps, then final compression)
+        let proving_start = Instant::now();
+        // Measures CPU utilization over both, until dropped below
+        let cpu = benchmark_metrics::CpuSampler::start_into(&mut metrics.resource_metrics);
+        let mut state = your_implementation::init_recursion(&circuit);
+        let steps = your_implementation::num_steps(&circuit);
+        for _ in 0..steps {
+            state = your_implementation::prove_step(state);
+        }
+        let step_proving_time = proving_start.elapsed().as_millis();
+        metrics.time_metrics.extra["step_proving_time_ms"] = json!(step_proving_time / steps.max(1) as u128);
+
+        let final_start = Instant::now();
+        let proof = your_implementation::compress(state);
+        metrics.time_metrics.extra["final_proving_time_ms"] = json!(final_start.elapsed().as_millis());
+        drop(cpu);
+        let proving_time = proving_start.elapsed();
+        proving_times.push(proving_time);
+        metrics.time_metrics.proving_time_ms = proving_time.as_millis() as u64;
+
+        // Peak memory so far, which proving usually dominates
+        metrics.resource_metrics.peak_memory_usage_kb = benchmark_metrics::peak_rss_kb().unwrap_or(0);
+
+        // 3. Verification phase
+        let verify_start = Instant::now();
+        let verified = your_implementation::verify(&proof);
+        metrics.time_metrics.verification_time_ms = verify_start.elapsed().as_millis() as u64;
+
+        aggregator.record(&metrics.time_metrics);
+    }
+    aggregator.apply(&mut metrics);
+    // Throughput over the runs and the median single-proof latency
+    if let Some(throughput) = benchmark_metrics::Throughput::of(&proving_times) {
+        throughput.apply(&mut metrics);
+    }
     */
 
-    println!("{}", serde_json::to_string_pretty(&metrics).unwrap());
+    // Machine, toolchain and revision, so results can be compared across runs
+    benchmark_metrics::collect_environment(circuit_size).apply(&mut metrics);
+
+    // `--format json-compact` prints a single line for log ingestion;
+    // `--out <path>` writes the metrics there instead of stdout
+    metrics.emit(&args).expect("failed to write metrics");
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+// Plonky2 template with a working Fibonacci example; replace the circuit
+// with your own. Dependencies: plonky2 = "0.2", serde_json and
+// benchmark_metrics.
+use std::time::Instant;
+use serde_json::json;
+use benchmark_metrics::{
+    extra_fields, BenchmarkMetrics, Features, Operation, PerformanceMetrics,
+    ScalabilityMetrics, SecurityMetrics, SetupMetrics, TemplateArgs,
+};
+use plonky2::field::types::Field;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+// Extension degree: challenges are drawn from the quadratic extension of
+// Goldilocks
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+// Fibonacci steps per circuit size
+fn steps_for(circuit_size: &str) -> usize {
+    match circuit_size {
+        "medium" => 1 << 14,
+        "large" => 1 << 17,
+        "xlarge" => 1 << 20,
+        _ => 1 << 11,
+    }
+}
+
+// A circuit proving that F(steps) is the public output, starting from the
+// public inputs 0 and 1. Returns the circuit and its witness.
+fn fibonacci_circuit(steps: usize) -> (CircuitData<F, C, D>, PartialWitness<F>) {
+    let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+    let initial_a = builder.add_virtual_target();
+    let initial_b = builder.add_virtual_target();
+    let (mut prev, mut cur) = (initial_a, initial_b);
+    for _ in 0..steps {
+        let next = builder.add(prev, cur);
+        prev = cur;
+        cur = next;
+    }
+    builder.register_public_input(initial_a);
+    builder.register_public_input(initial_b);
+    builder.register_public_input(cur);
+
+    let mut witness = PartialWitness::new();
+    witness.set_target(initial_a, F::ZERO);
+    witness.set_target(initial_b, F::ONE);
+    (builder.build::<C>(), witness)
+}
+
+// Wraps `inner` in a proof that it verifies, keeping its public inputs
+fn recursive_proof(
+    inner: &ProofWithPublicInputs<F, C, D>,
+    inner_data: &CircuitData<F, C, D>,
+) -> (ProofWithPublicInputs<F, C, D>, CircuitData<F, C, D>) {
+    let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+    let proof_target = builder.add_virtual_proof_with_pis(&inner_data.common);
+    let verifier_target = builder.add_virtual_verifier_data(inner_data.common.config.fri_config.cap_height);
+    builder.verify_proof::<C>(&proof_target, &verifier_target, &inner_data.common);
+    builder.register_public_inputs(&proof_target.public_inputs);
+    let data = builder.build::<C>();
+
+    let mut witness = PartialWitness::new();
+    witness.set_proof_with_pis_target(&proof_target, inner);
+    witness.set_verifier_data_target(&verifier_target, &inner_data.verifier_only);
+    let proof = data.prove(witness).expect("recursive proving failed");
+    (proof, data)
+}
+
+fn main() {
+    // Circuit size, --format and --out; see benchmark_metrics::TemplateArgs
+    let args = TemplateArgs::from_env();
+    let circuit_size = args.circuit_size.as_str();
+    // `--recursion-depth <n>` wraps the proof in n layers of recursive
+    // verification; the outermost one is verified and measured
+    let recursion_depth: usize = args.value_of("--recursion-depth")
+        .and_then(|depth| depth.parse().ok())
+        .unwrap_or(0);
+    let steps = steps_for(circuit_size);
+
+    // resolved_parameters: what circuit_size expanded to (steps, degree bits)
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),  // FRI needs no trusted setup
+            setup_reusable: true,
+            ..Default::default()
+        },
+
+        features: Features {
+            recursive_proofs: true,     // Plonky2 is built for fast recursion
+            universal_circuits: false,
+            parallel_proving: true,
+            parallel_verification: false,
+            custom_gates: true,
+            ..Default::default()
+        },
+
+        security_metrics: SecurityMetrics {
+            post_quantum_resistant: true,
+            security_level_bits: 0,  // From the circuit config, below
+            assumptions: vec!["collision_resistant_hash".to_string(), "FRI_soundness".to_string()],
+            extra: extra_fields(json!({
+                "blowup_factor": 0,      // Plonky2-specific: FRI rate
+                "num_queries": 0,        // Plonky2-specific: FRI query rounds
+                "grinding_bits": 0       // Plonky2-specific: proof-of-work bits
+            })),
+        },
+
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: 64,  // Goldilocks
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(log² n)".to_string(),
+            extra: extra_fields(json!({
+                "field": "goldilocks",
+                "extension_degree": D
+            })),
+            ..Default::default()
+        },
+
+        performance_metrics: PerformanceMetrics {
+            batch_proving_supported: false,
+            batch_verification_supported: false,
+            ..Default::default()
+        },
+
+        ..BenchmarkMetrics::new("plonky2", Operation::Fibonacci.as_str(), circuit_size)
+    };
+    metrics.resolved_parameters.insert("steps".to_string(), steps.into());
+    metrics.resolved_parameters.insert("recursion_depth".to_string(), recursion_depth.into());
+
+    // --repeat <n> runs the measured phases n times; time_metrics ends up
+    // with the median run and time_statistics with the distribution
+    let mut aggregator = benchmark_metrics::Aggregator::new();
+    let mut proving_times = Vec::new();
+    for _ in 0..args.repeat {
+        // 1. Setup phase: building the circuit computes its constants and
+        // the verifier's commitment to them
+        let setup_start = Instant::now();
+        let (data, witness) = fibonacci_circuit(steps);
+        metrics.time_metrics.setup_time_ms = setup_start.elapsed().as_millis() as u64;
+
+        let config = &data.common.config;
+        metrics.security_metrics.security_level_bits = config.security_bits as u32;
+        metrics.security_metrics.extra["blowup_factor"] = json!(1u32 << config.fri_config.rate_bits);
+        metrics.security_metrics.extra["num_queries"] = json!(config.fri_config.num_query_rounds);
+        metrics.security_metrics.extra["grinding_bits"] = json!(config.fri_config.proof_of_work_bits);
+        metrics.resolved_parameters.insert("degree_bits".to_string(), data.common.degree_bits().into());
+        metrics.scalability_metrics.constraints_count = data.common.degree() as u64;
+        metrics.scalability_metrics.degree = data.common.quotient_degree_factor as u32;
+
+        // 2. Proving phase
+        let proving_start = Instant::now();
+        let proof = {
+            // Writes cpu_utilization_percent when it goes out of scope
+            let _cpu = benchmark_metrics::CpuSampler::start_into(&mut metrics.resource_metrics);
+            data.prove(witness).expect("proving failed")
+        };
+        let proving_time = proving_start.elapsed();
+        proving_times.push(proving_time);
+        metrics.time_metrics.proving_time_ms = proving_time.as_millis() as u64;
+
+        // Recursive layers, each proving the previous proof verifies
+        let recursion_start = Instant::now();
+        let (proof, data) = (0..recursion_depth).fold((proof, data), |(proof, data), _| {
+            recursive_proof(&proof, &data)
+        });
+        metrics.time_metrics.extra.insert(
+            "recursion_time_ms".to_string(),
+            json!(recursion_start.elapsed().as_millis() as u64),
+        );
+
+        // Peak memory so far, which proving usually dominates
+        metrics.resource_metrics.peak_memory_usage_kb = benchmark_metrics::peak_rss_kb().unwrap_or(0);
+
+        // 3. Verification phase
+        metrics.resource_metrics.proof_size_bytes = proof.to_bytes().len() as u64;
+        let verify_start = Instant::now();
+        let verified = data.verify(proof).is_ok();
+        metrics.time_metrics.verification_time_ms = verify_start.elapsed().as_millis() as u64;
+        if !verified {
+            eprintln!("warning: proof did not verify");
+        }
+
+        aggregator.record(&metrics.time_metrics);
+    }
+    aggregator.apply(&mut metrics);
+    // Throughput over the runs and the median single-proof latency
+    if let Some(throughput) = benchmark_metrics::Throughput::of(&proving_times) {
+        throughput.apply(&mut metrics);
+    }
+
+    // Machine, toolchain and revision, so results can be compared across runs
+    benchmark_metrics::collect_environment(circuit_size).apply(&mut metrics);
+
+    // `--format json-compact` prints a single line for log ingestion;
+    // `--out <path>` writes the metrics there instead of stdout
+    metrics.emit(&args).expect("failed to write metrics");
+}
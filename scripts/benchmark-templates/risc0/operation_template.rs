@@ -1,94 +1,192 @@
+// RISC Zero template proving SHA-256 of an input in a guest program.
+//
+// Real proving is behind the `prove` feature, since the prover is heavy:
+//
+//     [features]
+//     prove = ["dep:risc0-zkvm", "dep:bincode", "dep:methods"]
+//
+// with risc0-zkvm, bincode and a `methods` crate as optional dependencies.
+// `methods` is the usual risc0-build crate; its guest `sha256` is:
+//
+//     use risc0_zkvm::guest::env;
+//     use risc0_zkvm::sha::{Impl, Sha256};
+//
+//     fn main() {
+//         let input: Vec<u8> = env::read();
+//         env::commit(&Impl::hash_bytes(&input).as_bytes());
+//     }
+//
+// Without the feature the metrics are emitted with their defaults.
 use std::time::Instant;
-use std::env;
 use serde_json::json;
-use chrono;
+use benchmark_metrics::{
+    extra_fields, BenchmarkMetrics, Features, Operation, PerformanceMetrics,
+    ResourceMetrics, ScalabilityMetrics, SecurityMetrics, SetupMetrics, TemplateArgs,
+};
+
+// Bytes hashed by the guest per circuit size
+fn input_len_for(circuit_size: &str) -> usize {
+    match circuit_size {
+        "medium" => 16 * 1024,
+        "large" => 128 * 1024,
+        "xlarge" => 1024 * 1024,
+        _ => 1024,
+    }
+}
+
+#[cfg(feature = "prove")]
+mod guest {
+    use methods::{SHA256_ELF, SHA256_ID};
+    use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+
+    // What proving one execution of the guest produced
+    pub(crate) struct Proved {
+        pub(crate) receipt: Receipt,
+        pub(crate) user_cycles: u64,
+        pub(crate) total_cycles: u64,
+        pub(crate) segments: usize,
+    }
+
+    pub(crate) fn prove(input: &[u8]) -> Proved {
+        let env = ExecutorEnv::builder()
+            .write(&input.to_vec())
+            .expect("input serializes")
+            .build()
+            .expect("executor environment builds");
+        let info = default_prover().prove(env, SHA256_ELF).expect("proving failed");
+        Proved {
+            receipt: info.receipt,
+            user_cycles: info.stats.user_cycles,
+            total_cycles: info.stats.total_cycles,
+            segments: info.stats.segments,
+        }
+    }
+
+    pub(crate) fn verify(receipt: &Receipt) -> bool {
+        receipt.verify(SHA256_ID).is_ok()
+    }
+
+    // The receipt as it would be stored or sent to a verifier
+    pub(crate) fn receipt_size_bytes(receipt: &Receipt) -> usize {
+        bincode::serialize(receipt).expect("receipt serializes").len()
+    }
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let circuit_size = args.get(1).map(String::as_str).unwrap_or("small");
-    
-    let mut metrics = json!({
-        "operation": "operation_name",
-        "system": "risc0",
-        "circuit_size": circuit_size,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        
-        "time_metrics": {
-            "setup_time_ms": 0,
-            "proving_time_ms": 0,
-            "verification_time_ms": 0,
-            "total_execution_time_ms": 0
-        },
-        
-        "resource_metrics": {
-            "peak_memory_usage_kb": 0,
-            "proof_size_bytes": 0,
-            "cpu_utilization_percent": 0,
-            "gpu_utilization_percent": 0
+    // Circuit size, --format and --out; see benchmark_metrics::TemplateArgs
+    let args = TemplateArgs::from_env();
+    let circuit_size = args.circuit_size.as_str();
+    let input_len = input_len_for(circuit_size);
+
+    // resolved_parameters: what circuit_size expanded to (input bytes)
+    let mut metrics = BenchmarkMetrics {
+        setup_metrics: SetupMetrics {
+            setup_type: "transparent".to_string(),
+            setup_reusable: true,
+            ..Default::default()
         },
-        
-        "setup_metrics": {
-            "setup_type": "transparent",
-            "setup_size_bytes": 0,
-            "setup_reusable": true
+
+        features: Features {
+            recursive_proofs: true,
+            universal_circuits: true,
+            parallel_proving: true,
+            parallel_verification: true,
+            custom_gates: false,
+            extra: extra_fields(json!({
+                "risc_v_native": true  // RISC0-specific feature
+            })),
         },
-        
-        "features": {
-            "recursive_proofs": true,
-            "universal_circuits": true,
-            "parallel_proving": true,
-            "parallel_verification": true,
-            "custom_gates": false,
-            "risc_v_native": true  // RISC0-specific feature
+
+        security_metrics: SecurityMetrics {
+            post_quantum_resistant: true,
+            security_level_bits: 128,
+            assumptions: vec!["collision_resistant_hash".to_string(), "risc_v_soundness".to_string()],
+            ..Default::default()
         },
-        
-        "security_metrics": {
-            "post_quantum_resistant": true,
-            "security_level_bits": 128,
-            "assumptions": ["collision_resistant_hash", "risc_v_soundness"]
+
+        scalability_metrics: ScalabilityMetrics {
+            field_bits: 31,  // BabyBear
+            proving_complexity_class: "O(n log n)".to_string(),
+            verification_complexity_class: "O(1)".to_string(),
+            extra: extra_fields(json!({
+                "cycle_count": 0,   // RISC0-specific: guest cycles
+                "segment_count": 0  // RISC0-specific: continuation segments
+            })),
+            ..Default::default()
         },
-        
-        "scalability_metrics": {
-            "constraints_count": 0,
-            "variables_count": 0,
-            "degree": 0,
-            "proving_complexity_class": "O(n log n)",
-            "verification_complexity_class": "O(1)",
-            "cycle_count": 0  // RISC0-specific metric
+
+        performance_metrics: PerformanceMetrics {
+            batch_proving_supported: true,
+            batch_verification_supported: true,
+            ..Default::default()
         },
-        
-        "performance_metrics": {
-            "throughput_proofs_per_second": 0.0,
-            "latency_ms": 0,
-            "batch_proving_supported": true,
-            "batch_verification_supported": true
+
+        resource_metrics: ResourceMetrics {
+            extra: extra_fields(json!({
+                "receipt_size_bytes": 0  // RISC0-specific: serialized receipt
+            })),
+            ..Default::default()
         },
-        
-        "system_requirements": {
-            "minimum_memory_gb": 0,
-            "recommended_cpu_cores": 0,
-            "gpu_required": false,
-            "disk_space_gb": 0
+
+        ..BenchmarkMetrics::new("risc0", Operation::Sha256.as_str(), circuit_size)
+    };
+    metrics.resolved_parameters.insert("input_bytes".to_string(), input_len.into());
+
+    #[cfg(feature = "prove")]
+    {
+        // --repeat <n> runs the measured phases n times; time_metrics ends
+        // up with the median run and time_statistics with the distribution
+        let mut aggregator = benchmark_metrics::Aggregator::new();
+        let mut proving_times = Vec::new();
+        for _ in 0..args.repeat {
+            // 1. Setup phase: there is no per-program setup, only the input
+            let setup_start = Instant::now();
+            let input: Vec<u8> = (0..input_len).map(|i| (i * 31 + 7) as u8).collect();
+            metrics.time_metrics.setup_time_ms = setup_start.elapsed().as_millis() as u64;
+
+            // 2. Proving phase: execution and proving of every segment
+            let proving_start = Instant::now();
+            let proved = {
+                // Writes cpu_utilization_percent when it goes out of scope
+                let _cpu = benchmark_metrics::CpuSampler::start_into(&mut metrics.resource_metrics);
+                guest::prove(&input)
+            };
+            let proving_time = proving_start.elapsed();
+            proving_times.push(proving_time);
+            metrics.time_metrics.proving_time_ms = proving_time.as_millis() as u64;
+            metrics.scalability_metrics.extra["cycle_count"] = json!(proved.user_cycles);
+            metrics.scalability_metrics.extra["total_cycles"] = json!(proved.total_cycles);
+            metrics.scalability_metrics.extra["segment_count"] = json!(proved.segments);
+
+            // Peak memory so far, which proving usually dominates
+            metrics.resource_metrics.peak_memory_usage_kb = benchmark_metrics::peak_rss_kb().unwrap_or(0);
+
+            // 3. Verification phase
+            let receipt_size = guest::receipt_size_bytes(&proved.receipt) as u64;
+            metrics.resource_metrics.proof_size_bytes = receipt_size;
+            metrics.resource_metrics.extra["receipt_size_bytes"] = json!(receipt_size);
+            let verify_start = Instant::now();
+            let verified = guest::verify(&proved.receipt);
+            metrics.time_metrics.verification_time_ms = verify_start.elapsed().as_millis() as u64;
+            if !verified {
+                eprintln!("warning: receipt did not verify");
+            }
+
+            aggregator.record(&metrics.time_metrics);
+        }
+        aggregator.apply(&mut metrics);
+        // Throughput over the runs and the median single-proof latency
+        if let Some(throughput) = benchmark_metrics::Throughput::of(&proving_times) {
+            throughput.apply(&mut metrics);
         }
-    });
-
-    // Implementation placeholder
-    /*
-    // 1. Setup phase
-    let setup_start = Instant::now();
-    let program = your_implementation::compile(circuit_size);
-    metrics["time_metrics"]["setup_time_ms"] = setup_start.elapsed().as_millis();
-    
-    // 2. Proving phase
-    let proving_start = Instant::now();
-    let proof = your_implementation::prove(&program);
-    metrics["time_metrics"]["proving_time_ms"] = proving_start.elapsed().as_millis();
-    
-    // 3. Verification phase
-    let verify_start = Instant::now();
-    let verified = your_implementation::verify(&proof);
-    metrics["time_metrics"]["verification_time_ms"] = verify_start.elapsed().as_millis();
-    */
-
-    println!("{}", serde_json::to_string_pretty(&metrics).unwrap());
+    }
+    #[cfg(not(feature = "prove"))]
+    eprintln!("warning: built without the `prove` feature; nothing was proved");
+
+    // Machine, toolchain and revision, so results can be compared across runs
+    benchmark_metrics::collect_environment(circuit_size).apply(&mut metrics);
+
+    // `--format json-compact` prints a single line for log ingestion;
+    // `--out <path>` writes the metrics there instead of stdout
+    metrics.emit(&args).expect("failed to write metrics");
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 #!/usr/bin/env python3
 
 import argparse
+import base64
 import json
 import os
 from datetime import datetime
@@ -77,6 +78,19 @@ class BenchmarkReportGenerator:
         
         return plots
     
+    def _embed_plots(self, plots):
+        """Inline plot images as data URIs so the report is a single file"""
+        embedded = {}
+        for metric, plot_path in plots.items():
+            with open(plot_path, 'rb') as f:
+                encoded = base64.b64encode(f.read()).decode('ascii')
+            embedded[metric] = f'data:image/png;base64,{encoded}'
+        return embedded
+    
+    def _raw_results_json(self):
+        # Escape closing tags so the JSON cannot terminate its <script> block
+        return json.dumps(self.results, indent=2).replace('</', '<\\/')
+    
     def generate_tables(self):
         """Generate summary tables"""
         tables = {}
@@ -99,7 +113,7 @@ class BenchmarkReportGenerator:
     
     def generate_html_report(self):
         """Generate final HTML report"""
-        plots = self.generate_plots()
+        plots = self._embed_plots(self.generate_plots())
         tables = self.generate_tables()
         
         template = Template('''
@@ -135,6 +149,10 @@ class BenchmarkReportGenerator:
             <div class="table">
                 {{ tables.detailed }}
             </div>
+            
+            <script type="application/json" id="raw-results">
+{{ raw_results }}
+            </script>
         </body>
         </html>
         ''')
@@ -142,7 +160,8 @@ class BenchmarkReportGenerator:
         html = template.render(
             timestamp=self.timestamp,
             plots=plots,
-            tables=tables
+            tables=tables,
+            raw_results=self._raw_results_json()
         )
         
         report_path = self.results_dir / f'report_{self.timestamp}.html'
@@ -8,16 +8,33 @@
 set -euo pipefail
 IFS=$'\n\t'
 
+# Checkout the suite runs from; overridable so tests can use a scratch tree
+: "${BENCHMARK_ROOT:=/zkvm-benchmarking}"
+
+# The Rust tooling next to this script, shared with the benchmark templates
+readonly RUNNER_MANIFEST="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)/benchmark-templates/benchmark_runner/Cargo.toml"
+
 # Constants
-readonly LOG_DIR="/zkvm-benchmarking/logs"
-readonly RESULTS_DIR="/zkvm-benchmarking/results"
+readonly LOG_DIR="${BENCHMARK_ROOT}/logs"
+readonly RESULTS_DIR="${BENCHMARK_ROOT}/results"
 readonly TIMESTAMP=$(date +%Y%m%d_%H%M%S)
 readonly LOG_FILE="${LOG_DIR}/benchmark_${TIMESTAMP}.log"
+readonly HISTORY_FILE="${RESULTS_DIR}/history.jsonl"
 
 # Default settings (can be overridden via env vars)
 : "${BENCHMARK_ITERATIONS:=100}"
+# Circuit size every benchmark runs at; the templates take it as their
+# first argument and reject a size they do not know
+: "${BENCHMARK_CIRCUIT_SIZE:=small}"
 : "${BENCHMARK_WARMUP_ITERATIONS:=10}"
 : "${COLLECT_MEMORY_STATS:=true}"
+: "${BENCHMARK_MIN_TIME:=0}"
+: "${BENCHMARK_MAX_DURATION:=3600}"
+: "${BENCHMARK_PUSH_URL:=}"
+: "${BENCHMARK_THREADS:=}"
+: "${BENCHMARK_MAX_LOAD:=}"
+: "${BENCHMARK_LOAD_TIMEOUT:=300}"
+: "${BENCHMARK_LOAD_POLL_INTERVAL:=5}"
 
 # Setup
 mkdir -p "$LOG_DIR" "$RESULTS_DIR"
@@ -35,17 +52,129 @@ fail() {
     exit 1
 }
 
+# Tuned defaults per kind of machine, selected with --env-preset. Sets the
+# iteration counts, prover thread count and per-iteration timeout; explicit
+# flags are applied afterwards and take precedence.
+apply_env_preset() {
+    case "$1" in
+        laptop)
+            BENCHMARK_ITERATIONS=10
+            BENCHMARK_WARMUP_ITERATIONS=2
+            BENCHMARK_THREADS=4
+            BENCHMARK_MAX_DURATION=600
+            ;;
+        ci)
+            BENCHMARK_ITERATIONS=3
+            BENCHMARK_WARMUP_ITERATIONS=1
+            BENCHMARK_THREADS=2
+            BENCHMARK_MAX_DURATION=900
+            ;;
+        server)
+            BENCHMARK_ITERATIONS=100
+            BENCHMARK_WARMUP_ITERATIONS=10
+            BENCHMARK_THREADS=$(nproc)
+            BENCHMARK_MAX_DURATION=3600
+            ;;
+        *)
+            fail "Unknown --env-preset: $1 (expected laptop, ci or server)"
+            ;;
+    esac
+}
+
+# Resident set size in KB of process $1 and all of its descendants, so a
+# benchmark that runs its prover in a child process is counted in full
 get_memory_usage() {
-    ps -o rss= -p "$1" 2>/dev/null || echo 0
+    local rss child
+    rss=$(ps -o rss= -p "$1" 2>/dev/null) || rss=0
+    for child in $(ps -o pid= --ppid "$1" 2>/dev/null | tr -d ' '); do
+        rss=$((rss + $(get_memory_usage "$child")))
+    done
+    echo $((rss))
+}
+
+# Samples the memory of process $1 every 0.1s until killed, keeping the
+# highest value seen in file $2
+track_peak_memory() {
+    local pid=$1 peak_file=$2 peak=0 current
+    echo 0 > "$peak_file"
+    while sleep 0.1; do
+        current=$(get_memory_usage "$pid")
+        if (( current > peak )); then
+            peak=$current
+            echo "$peak" > "$peak_file"
+        fi
+    done
+}
+
+# Seconds since boot, to the hundredth. Unlike the wall clock this never
+# steps back or jumps when NTP adjusts the time, so durations between two
+# readings are sound.
+monotonic_time() {
+    cut -d' ' -f1 /proc/uptime
+}
+
+# 1-minute load average
+read_load_average() {
+    cut -d' ' -f1 /proc/loadavg
+}
+
+# Wait until the load average drops to BENCHMARK_MAX_LOAD so other work on a
+# shared machine does not leak into the measurements. Gives up after
+# BENCHMARK_LOAD_TIMEOUT seconds and measures anyway, with a warning.
+wait_for_quiescence() {
+    [[ -n "$BENCHMARK_MAX_LOAD" ]] || return 0
+    
+    local waited=0 load
+    load=$(read_load_average)
+    while (( $(echo "$load > $BENCHMARK_MAX_LOAD" | bc) )); do
+        if (( waited >= BENCHMARK_LOAD_TIMEOUT )); then
+            log "WARNING: load $load still above $BENCHMARK_MAX_LOAD after ${waited}s; measuring anyway"
+            return 0
+        fi
+        log "Load $load above $BENCHMARK_MAX_LOAD, waiting..."
+        sleep "$BENCHMARK_LOAD_POLL_INTERVAL"
+        waited=$((waited + BENCHMARK_LOAD_POLL_INTERVAL))
+        load=$(read_load_average)
+    done
+    (( waited == 0 )) || log "Load $load, proceeding after ${waited}s"
 }
 
 check_dependencies() {
-    local deps=("jq" "bc" "python3")
+    local deps=("jq" "bc" "python3" "cargo")
     for dep in "${deps[@]}"; do
         command -v "$dep" >/dev/null 2>&1 || fail "Required dependency not found: $dep"
     done
 }
 
+# POST a result file to BENCHMARK_PUSH_URL, if set. The local file stays the
+# source of truth, so a collector outage only logs a warning.
+push_results() {
+    local result_file=$1
+    [[ -n "$BENCHMARK_PUSH_URL" ]] || return 0
+    
+    if curl --silent --show-error --fail \
+            --retry 3 --retry-delay 2 --max-time 30 \
+            -H "Content-Type: application/json" \
+            --data-binary @"$result_file" \
+            "$BENCHMARK_PUSH_URL" >/dev/null; then
+        log "Pushed $(basename "$result_file") to $BENCHMARK_PUSH_URL"
+    else
+        log "WARNING: failed to push $(basename "$result_file") to $BENCHMARK_PUSH_URL"
+    fi
+}
+
+# Runs benchmark-runner, building it first if needed
+benchmark_runner() {
+    cargo run --quiet --release --manifest-path "$RUNNER_MANIFEST" -- "$@"
+}
+
+# Commit of the benchmark tree and whether tracked files were modified, as
+# JSON; null when the tree is not a git checkout. The templates record the
+# same through benchmark_metrics, so both come from one implementation.
+get_source_commit() {
+    benchmark_runner source-commit "$BENCHMARK_ROOT" || echo null
+}
+
 get_system_info() {
     local info="{"
     
@@ -85,7 +214,7 @@ monitor_resources() {
 run_single_benchmark() {
     local system=$1
     local op=$2
-    local bench_path="/zkvm-benchmarking/${system}/target/release/bench"
+    local bench_path="${BENCHMARK_ROOT}/${system}/target/release/bench"
     local result_file="${RESULTS_DIR}/${system}_${op}_${TIMESTAMP}.json"
     
     # Sanity checks
@@ -95,62 +224,102 @@ run_single_benchmark() {
     
     # System info
     local sys_info
-    sys_info=$(cat /proc/cpuinfo | grep "model name" | head -n1 || echo "Unknown CPU")
+    sys_info=$(grep -m1 "model name" /proc/cpuinfo | cut -d: -f2 | xargs) || true
+    : "${sys_info:=Unknown CPU}"
     
     # Initialize results file
     cat > "$result_file" << EOF
 {
     "system": "$system",
     "operation": "$op",
+    "circuit_size": "$BENCHMARK_CIRCUIT_SIZE",
     "timestamp": "$TIMESTAMP",
     "system_info": "$sys_info",
+    "source_commit": $(get_source_commit),
     "measurements": []
 }
 EOF
     
+    wait_for_quiescence
+    
     # Warmup phase
     log "Warming up..."
     for i in $(seq "$BENCHMARK_WARMUP_ITERATIONS"); do
-        "$bench_path" "$op" >/dev/null 2>&1 || true
+        timeout "$BENCHMARK_MAX_DURATION" "$bench_path" "$BENCHMARK_CIRCUIT_SIZE" >/dev/null 2>&1 || true
     done
     
-    # Main benchmark loop
+    # Main benchmark loop: run at least BENCHMARK_ITERATIONS, then keep going
+    # until the cumulative measured time reaches BENCHMARK_MIN_TIME seconds
     log "Running measurements..."
-    for i in $(seq "$BENCHMARK_ITERATIONS"); do
+    local i=0 retries=0 measured_time=0 output_file error_file peak_file
+    output_file=$(mktemp)
+    error_file=$(mktemp)
+    peak_file=$(mktemp)
+    while (( i < BENCHMARK_ITERATIONS )) || (( $(echo "$measured_time < $BENCHMARK_MIN_TIME" | bc) )); do
         local start_time peak_mem output end_time
+        i=$((i + 1))
         
-        start_time=$(date +%s.%N)
+        start_time=$(monotonic_time)
         peak_mem=0
         
-        # Memory monitoring
+        # Run benchmark in the background so its own memory, not this
+        # shell's, can be sampled while it runs, and kill it once it runs
+        # past BENCHMARK_MAX_DURATION
+        timeout "$BENCHMARK_MAX_DURATION" "$bench_path" "$BENCHMARK_CIRCUIT_SIZE" > "$output_file" 2> "$error_file" &
+        local bench_pid=$! mem_pid="" status=0
         if [[ "$COLLECT_MEMORY_STATS" == "true" ]]; then
-            while sleep 0.1; do
-                local current_mem
-                current_mem=$(get_memory_usage "$$")
-                (( current_mem > peak_mem )) && peak_mem=$current_mem
-            done & local mem_pid=$!
+            track_peak_memory "$bench_pid" "$peak_file" &
+            mem_pid=$!
         fi
-        
-        # Run benchmark
-        output=$("$bench_path" "$op" 2>&1)
-        end_time=$(date +%s.%N)
+        wait "$bench_pid" || status=$?
+        end_time=$(monotonic_time)
+        output=$(< "$output_file")
         
         # Cleanup memory monitor
-        [[ "$COLLECT_MEMORY_STATS" == "true" ]] && kill $mem_pid 2>/dev/null || true
+        if [[ -n "$mem_pid" ]]; then
+            kill "$mem_pid" 2>/dev/null || true
+            wait "$mem_pid" 2>/dev/null || true
+            peak_mem=$(< "$peak_file")
+        fi
+        (( status != 124 )) || fail "Benchmark $system ($op) timed out after ${BENCHMARK_MAX_DURATION}s in iteration $i"
+        (( status == 0 )) || fail "Benchmark $system ($op) exited with status $status in iteration $i: $(tail -n1 "$error_file")"
         
-        # Parse metrics
-        local prove_time verify_time proof_size
-        prove_time=$(echo "$output" | grep -oP 'Proving time: \K[\d.]+' || echo 0)
-        verify_time=$(echo "$output" | grep -oP 'Verification time: \K[\d.]+' || echo 0)
-        proof_size=$(echo "$output" | grep -oP 'Proof size: \K[\d.]+' || echo 0)
+        # Parse metrics from the BenchmarkMetrics JSON the benchmark prints;
+        # input_bytes and its throughput are only reported by hash benchmarks
+        local metrics prove_time verify_time proof_size input_size input_throughput
+        metrics=$(jq -r '[.time_metrics.proving_time_ms, .time_metrics.verification_time_ms,
+                          .resource_metrics.proof_size_bytes, .resolved_parameters.input_bytes // 0,
+                          .performance_metrics.input_bytes_per_proving_second // 0]
+                         | if any(. == null) then error("missing metrics") else @tsv end' \
+            <<< "$output" 2>/dev/null) \
+            || fail "Benchmark $system ($op) printed no metrics JSON in iteration $i"
+        read -r prove_time verify_time proof_size input_size input_throughput <<< "$metrics"
         
         # Record results
         local duration
         duration=$(echo "$end_time - $start_time" | bc)
         
+        # The clock counts time suspended, so a suspend/resume mid-iteration
+        # yields an absurd duration; never record one. Rerun the iteration a
+        # few times, then drop it and move on.
+        if (( $(echo "$duration < 0 || $duration > $BENCHMARK_MAX_DURATION" | bc) )); then
+            if (( retries < 3 )); then
+                retries=$((retries + 1))
+                log "WARNING: dropped implausible duration ${duration}s in iteration $i of $system ($op); retrying"
+                i=$((i - 1))
+            else
+                retries=0
+                log "WARNING: dropped implausible duration ${duration}s in iteration $i of $system ($op); skipping it"
+            fi
+            continue
+        fi
+        retries=0
+        
         jq --arg pt "$prove_time" \
            --arg vt "$verify_time" \
            --arg ps "$proof_size" \
+           --arg is "$input_size" \
+           --arg it "$input_throughput" \
            --arg pm "$peak_mem" \
            --arg d "$duration" \
            '.measurements += [{
@@ -158,31 +327,119 @@ EOF
                "prove_time": $pt,
                "verify_time": $vt,
                "proof_size": $ps,
+               "input_size": $is,
+               "input_bytes_per_proving_second": $it,
                "peak_memory_kb": $pm,
                "duration": $d
            }]' "$result_file" > "${result_file}.tmp" && mv "${result_file}.tmp" "$result_file"
         
-        log "Iteration $i/$BENCHMARK_ITERATIONS complete"
+        measured_time=$(echo "$measured_time + $duration" | bc)
+        log "Iteration $i complete (${measured_time}s measured)"
     done
     
+    rm -f "$output_file" "$error_file" "$peak_file"
+    log "Completed $i iterations in ${measured_time}s"
+    
     # Calculate summary stats
     jq '
         .summary = {
+            "iterations": (.measurements | length),
             "avg_prove_time": (.measurements | map(.prove_time | tonumber) | add / length),
             "avg_verify_time": (.measurements | map(.verify_time | tonumber) | add / length),
             "avg_proof_size": (.measurements | map(.proof_size | tonumber) | add / length),
+            # Constant-size proofs trend to 0, linear ones stay flat; null
+            # when the benchmark does not report its input size
+            "proof_bytes_per_input_byte": (
+                (.measurements | map(.input_size | tonumber) | add / length) as $input
+                | if $input > 0
+                  then (.measurements | map(.proof_size | tonumber) | add / length) / $input
+                  else null end),
+            # Input bytes hashed per second of proving, as the hash
+            # benchmarks compute it; null for the others
+            "input_bytes_per_proving_second": (
+                (.measurements | map(.input_bytes_per_proving_second | tonumber) | add / length) as $throughput
+                | if $throughput > 0 then $throughput else null end),
             "peak_memory": (.measurements | map(.peak_memory_kb | tonumber) | max),
             "min_prove_time": (.measurements | map(.prove_time | tonumber) | min),
             "max_prove_time": (.measurements | map(.prove_time | tonumber) | max)
         }
     ' "$result_file" > "${result_file}.tmp" && mv "${result_file}.tmp" "$result_file"
+    
+    # Append the key metrics to the cross-run history, which
+    # utils/summarize.py --trend shows across commits
+    jq -c '{commit: (.source_commit.commit // "unknown"), timestamp, system, operation, summary}' \
+        "$result_file" >> "$HISTORY_FILE"
+    
+    push_results "$result_file"
 }
 
 #
 # Main
 #
+# Parses the command line into the BENCHMARK_* settings. The preset is
+# applied first so explicit flags override it whatever their order.
+parse_args() {
+    local preset="" iterations="" threads=""
+    while [[ $# -gt 0 ]]; do
+        case "$1" in
+            --env-preset)
+                [[ $# -ge 2 ]] || fail "--env-preset requires laptop, ci or server"
+                preset=$2
+                shift 2
+                ;;
+            --iterations)
+                [[ $# -ge 2 ]] || fail "--iterations requires a count"
+                iterations=$2
+                shift 2
+                ;;
+            --threads)
+                [[ $# -ge 2 ]] || fail "--threads requires a count"
+                threads=$2
+                shift 2
+                ;;
+            --size)
+                [[ $# -ge 2 ]] || fail "--size requires a circuit size"
+                BENCHMARK_CIRCUIT_SIZE=$2
+                shift 2
+                ;;
+            --min-time)
+                [[ $# -ge 2 ]] || fail "--min-time requires a value in seconds"
+                BENCHMARK_MIN_TIME=$2
+                shift 2
+                ;;
+            --max-load)
+                [[ $# -ge 2 ]] || fail "--max-load requires a load average"
+                BENCHMARK_MAX_LOAD=$2
+                shift 2
+                ;;
+            --push-url)
+                [[ $# -ge 2 ]] || fail "--push-url requires a URL"
+                BENCHMARK_PUSH_URL=$2
+                shift 2
+                ;;
+            *)
+                fail "Unknown argument: $1"
+                ;;
+        esac
+    done
+    
+    BENCHMARK_PRESET=$preset
+    [[ -z "$preset" ]] || apply_env_preset "$preset"
+    [[ -z "$iterations" ]] || BENCHMARK_ITERATIONS=$iterations
+    [[ -z "$threads" ]] || BENCHMARK_THREADS=$threads
+}
+
 main() {
+    parse_args "$@"
+    
+    # The provers parallelize with rayon
+    [[ -z "$BENCHMARK_THREADS" ]] || export RAYON_NUM_THREADS=$BENCHMARK_THREADS
+    
+    [[ -z "$BENCHMARK_PUSH_URL" ]] || command -v curl >/dev/null 2>&1 \
+        || fail "Required dependency not found: curl (needed for --push-url)"
+    
     log "Starting ZK Proving Systems Benchmark Suite"
+    [[ -z "$BENCHMARK_PRESET" ]] || log "Preset $BENCHMARK_PRESET: ${BENCHMARK_ITERATIONS} iterations, ${BENCHMARK_WARMUP_ITERATIONS} warmup, ${BENCHMARK_THREADS:-all} threads, ${BENCHMARK_MAX_DURATION}s timeout"
     
     local -a systems=("halo2" "plonky3" "miden" "risc0" "jolt" "nexus" "aleo")
     local -a ops=("ecdsa")
@@ -194,11 +451,13 @@ main() {
     done
     
     log "Generating report..."
-    python3 /zkvm-benchmarking/scripts/generate_report.py \
+    python3 "${BENCHMARK_ROOT}/scripts/generate_report.py" \
         --results-dir "$RESULTS_DIR" \
         --timestamp "$TIMESTAMP" \
         --output "${RESULTS_DIR}/report_${TIMESTAMP}.html"
     
+    python3 "${BENCHMARK_ROOT}/scripts/utils/summarize.py" "$HISTORY_FILE" --trend --last 10 | tee -a "$LOG_FILE"
+    
     log "Benchmark suite completed"
 }
 
@@ -0,0 +1,6 @@
+import sys
+from pathlib import Path
+
+# The utilities are scripts, not a package; import them by module name
+sys.path.insert(0, str(Path(__file__).resolve().parents[1] / 'utils'))
+sys.path.insert(0, str(Path(__file__).resolve().parents[1]))
@@ -0,0 +1,336 @@
+#!/usr/bin/env bash
+#
+# Tests for run_all_benchmarks.sh
+# Sources the suite against a scratch BENCHMARK_ROOT and runs each test_*
+# function in its own subshell, so a test can override suite functions
+# (read_load_average, the benchmark binary, ...) without affecting the next.
+#
+# Usage: scripts/tests/run_all_benchmarks_test.sh [test_name...]
+#
+
+set -uo pipefail
+
+readonly TEST_DIR=$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)
+SCRATCH=$(mktemp -d)
+trap 'rm -rf "$SCRATCH"' EXIT
+export BENCHMARK_ROOT="$SCRATCH"
+
+# shellcheck source=../run_all_benchmarks.sh
+source "$TEST_DIR/../run_all_benchmarks.sh"
+set +e
+
+#
+# Assertions; each ends the test's subshell on failure
+#
+assert_eq() {
+    [[ "$1" == "$2" ]] && return
+    echo "expected: $2"
+    echo "actual:   $1"
+    exit 1
+}
+
+assert_true() {
+    eval "$1" && return
+    echo "assertion failed: $1${2:+ ($2)}"
+    exit 1
+}
+
+# A process holding about 64 MB of written, so resident, memory for 2s
+hold_memory() {
+    python3 -c 'import time; held = b"\x01" * (64 << 20); time.sleep(2)'
+}
+
+# Installs a benchmark binary for halo2 running the bash snippet $1
+fake_benchmark() {
+    local bench="$BENCHMARK_ROOT/halo2/target/release/bench"
+    mkdir -p "$(dirname "$bench")"
+    printf '#!/usr/bin/env bash\n%s\n' "$1" > "$bench"
+    chmod +x "$bench"
+}
+
+# BenchmarkMetrics JSON with the given proving time, verification time and
+# proof size, and if $4 is given input bytes and the throughput over them
+# that BenchmarkMetrics::finalize adds
+metrics_json() {
+    jq -cn --argjson p "$1" --argjson v "$2" --argjson s "$3" --argjson i "${4:-null}" '{
+        time_metrics: {proving_time_ms: $p, verification_time_ms: $v},
+        resource_metrics: {proof_size_bytes: $s},
+        resolved_parameters: (if $i == null then {} else {input_bytes: $i} end),
+        performance_metrics: (if $i == null then {} else {input_bytes_per_proving_second: ($i / ($p / 1000))} end)
+    }'
+}
+
+# Replaces monotonic_time with a clock returning the given readings in turn
+fake_clock() {
+    printf '%s\n' "$@" > "$SCRATCH/clock"
+    monotonic_time() {
+        head -n1 "$SCRATCH/clock"
+        sed -i 1d "$SCRATCH/clock"
+    }
+}
+
+# Runs the halo2 ecdsa benchmark without warmup or memory sampling and
+# prints the path of its result file
+run_fake_benchmark() {
+    BENCHMARK_WARMUP_ITERATIONS=0
+    COLLECT_MEMORY_STATS=false
+    run_single_benchmark halo2 ecdsa > /dev/null
+    echo "$RESULTS_DIR/halo2_ecdsa_${TIMESTAMP}.json"
+}
+
+#
+# Tests
+#
+
+# The sampled process is the benchmark, not the shell running the suite
+test_memory_usage_samples_the_benchmark() {
+    hold_memory &
+    local pid=$!
+    sleep 1
+    local rss
+    rss=$(get_memory_usage "$pid")
+    kill "$pid"
+    assert_true "(( rss > 60000 ))" "benchmark RSS ${rss} KB"
+}
+
+# A benchmark whose prover runs in a child process is counted in full
+test_peak_memory_includes_children() {
+    local peak_file="$SCRATCH/peak"
+    bash -c "$(declare -f hold_memory); hold_memory; true" &
+    local pid=$!
+    track_peak_memory "$pid" "$peak_file" &
+    local mem_pid=$!
+    wait "$pid"
+    kill "$mem_pid"
+    wait "$mem_pid" 2>/dev/null
+    local peak
+    peak=$(< "$peak_file")
+    assert_true "(( peak > 60000 ))" "peak ${peak} KB"
+}
+
+# A reading that goes backwards is dropped and the iteration rerun, rather
+# than recorded or ending the suite
+test_negative_duration_is_retried() {
+    fake_benchmark "echo '$(metrics_json 5 1 100)'"
+    fake_clock 100 40 200 201.5
+    BENCHMARK_ITERATIONS=1
+    local result
+    result=$(run_fake_benchmark)
+    assert_eq "$(jq '.measurements | length' "$result")" 1
+    assert_eq "$(jq -r '.measurements[0].duration' "$result")" 1.5
+    assert_true "grep -q 'dropped implausible duration -60s' '$LOG_FILE'"
+}
+
+# Iterations continue past BENCHMARK_ITERATIONS until BENCHMARK_MIN_TIME
+# seconds are measured, and stop as soon as they are
+test_min_time_extends_iterations() {
+    fake_benchmark "echo '$(metrics_json 5 1 100)'"
+    fake_clock 0 1 1 2 2 3 3 4
+    BENCHMARK_ITERATIONS=1 BENCHMARK_MIN_TIME=3
+    local result
+    result=$(run_fake_benchmark)
+    assert_eq "$(jq '.measurements | length' "$result")" 3
+    assert_eq "$(jq '.summary.iterations' "$result")" 3
+}
+
+# Explicit flags override the preset whether they come before or after it,
+# and leave the rest of the preset in place
+test_flags_override_preset() {
+    parse_args --iterations 7 --env-preset ci --threads 5
+    assert_eq "$BENCHMARK_ITERATIONS" 7
+    assert_eq "$BENCHMARK_THREADS" 5
+    assert_eq "$BENCHMARK_WARMUP_ITERATIONS" 1
+    assert_eq "$BENCHMARK_MAX_DURATION" 900
+
+    parse_args --env-preset laptop --iterations 3
+    assert_eq "$BENCHMARK_ITERATIONS" 3
+    assert_eq "$BENCHMARK_THREADS" 4
+}
+
+# An iteration running past BENCHMARK_MAX_DURATION is killed and ends the
+# suite with an error instead of hanging it
+test_iteration_times_out() {
+    fake_benchmark 'exec sleep 30'
+    BENCHMARK_ITERATIONS=1 BENCHMARK_MAX_DURATION=1
+    local output status=0
+    output=$( (run_fake_benchmark) ) || status=$?
+    assert_eq "$status" 1
+    assert_true "grep -q 'timed out after 1s in iteration 1' '$LOG_FILE'"
+}
+
+# Serves one request on localhost, answering with status $1, and writes the
+# request's content type and body to $2; prints the port once listening
+serve_once() {
+    python3 - "$1" "$2" <<'PY' &
+import http.server, sys
+
+status, capture = int(sys.argv[1]), sys.argv[2]
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_POST(self):
+        body = self.rfile.read(int(self.headers["Content-Length"]))
+        with open(capture, "w") as f:
+            f.write(self.headers["Content-Type"] + "\n" + body.decode())
+        self.send_response(status)
+        self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+server = http.server.HTTPServer(("127.0.0.1", 0), Handler)
+print(server.server_port, flush=True)
+server.handle_request()
+PY
+}
+
+# The result file is POSTed as JSON to BENCHMARK_PUSH_URL
+test_push_results_posts_the_file() {
+    local result="$SCRATCH/result.json" capture="$SCRATCH/request" port output
+    echo '{"system": "halo2"}' > "$result"
+    exec 3< <(serve_once 200 "$capture")
+    read -r port <&3
+    BENCHMARK_PUSH_URL="http://127.0.0.1:$port/results"
+    output=$(push_results "$result")
+    assert_eq "$(head -n1 "$capture")" "application/json"
+    assert_eq "$(tail -n +2 "$capture")" '{"system": "halo2"}'
+    assert_true "grep -q 'Pushed result.json' <<< \"\$output\""
+}
+
+# A collector that rejects the result only costs a warning
+test_push_results_failure_is_a_warning() {
+    local result="$SCRATCH/result.json" port output status=0
+    echo '{}' > "$result"
+    exec 3< <(serve_once 404 "$SCRATCH/request")
+    read -r port <&3
+    BENCHMARK_PUSH_URL="http://127.0.0.1:$port/results"
+    output=$(push_results "$result") || status=$?
+    assert_eq "$status" 0
+    assert_true "grep -q 'WARNING: failed to push result.json' <<< \"\$output\""
+}
+
+# The result's source_commit is HEAD of BENCHMARK_ROOT and whether tracked
+# files are modified, or null outside a git checkout
+test_source_commit_in_git_fixture() {
+    assert_eq "$(get_source_commit)" null
+
+    local git=(git -C "$BENCHMARK_ROOT" -c user.name=bench -c user.email=bench@example.com)
+    "${git[@]}" init --quiet
+    echo a > "$BENCHMARK_ROOT/tracked"
+    "${git[@]}" add tracked
+    "${git[@]}" commit --quiet -m fixture
+    local head
+    head=$("${git[@]}" rev-parse HEAD)
+
+    assert_eq "$(get_source_commit)" "{\"commit\":\"$head\",\"dirty\":false}"
+    echo b > "$BENCHMARK_ROOT/tracked"
+    assert_eq "$(get_source_commit)" "{\"commit\":\"$head\",\"dirty\":true}"
+}
+
+# The summary averages the measurements and derives the size and
+# throughput ratios from them
+test_summary_of_measurements() {
+    # Proves in 100 ms, then 400 ms
+    echo 1 > "$SCRATCH/run"
+    fake_benchmark "$(declare -f metrics_json)
+        n=\$(< '$SCRATCH/run'); echo \$((n + 3)) > '$SCRATCH/run'
+        metrics_json \$((n * 100)) 4 1024 64"
+    fake_clock 0 1 1 2
+    BENCHMARK_ITERATIONS=2
+    local result
+    result=$(run_fake_benchmark)
+    assert_eq "$(jq -c '.summary | del(.peak_memory)' "$result")" \
+        '{"iterations":2,"avg_prove_time":250,"avg_verify_time":4,"avg_proof_size":1024,"proof_bytes_per_input_byte":16,"input_bytes_per_proving_second":400,"min_prove_time":100,"max_prove_time":400}'
+}
+
+# Without an input size the ratios over it are null, not infinite
+test_summary_without_input_size() {
+    fake_benchmark "echo '$(metrics_json 50 2 512)'"
+    fake_clock 0 1
+    BENCHMARK_ITERATIONS=1
+    local result
+    result=$(run_fake_benchmark)
+    assert_eq "$(jq '.summary.avg_prove_time' "$result")" 50
+    assert_eq "$(jq '.summary.proof_bytes_per_input_byte' "$result")" null
+    assert_eq "$(jq '.summary.input_bytes_per_proving_second' "$result")" null
+}
+
+# Output that is not BenchmarkMetrics JSON ends the suite instead of being
+# recorded as zeros
+test_text_output_is_rejected() {
+    fake_benchmark 'echo "Proving time: 5"'
+    fake_clock 0 1
+    BENCHMARK_ITERATIONS=1
+    local status=0
+    (run_fake_benchmark) > /dev/null || status=$?
+    assert_eq "$status" 1
+    assert_true "grep -q 'printed no metrics JSON in iteration 1' '$LOG_FILE'"
+}
+
+# The benchmark gets the circuit size as its first argument, which the
+# templates parse and reject with status 2 if unknown, as this one does
+test_benchmark_gets_circuit_size() {
+    fake_benchmark "case \$1 in
+        small|medium|large|xlarge) echo '$(metrics_json 50 2 512)' ;;
+        *) echo \"error: unknown circuit size \\\"\$1\\\"\" >&2; exit 2 ;;
+    esac"
+    fake_clock 0 1
+    BENCHMARK_ITERATIONS=1
+    local result
+    result=$(run_fake_benchmark)
+    assert_eq "$(jq -r '.circuit_size' "$result")" small
+    assert_eq "$(jq '.measurements | length' "$result")" 1
+
+    parse_args --size huge
+    local status=0
+    (run_fake_benchmark) > /dev/null || status=$?
+    assert_eq "$status" 1
+    assert_true "grep -q 'exited with status 2 in iteration 1: error: unknown circuit size \"huge\"' '$LOG_FILE'"
+}
+
+# Replaces read_load_average with one returning the given loads in turn,
+# repeating the last, and sleep with a no-op
+fake_load() {
+    printf '%s\n' "$@" > "$SCRATCH/load"
+    read_load_average() {
+        head -n1 "$SCRATCH/load"
+        (( $(wc -l < "$SCRATCH/load") == 1 )) || sed -i 1d "$SCRATCH/load"
+    }
+    sleep() { :; }
+}
+
+# Measuring waits until the load drops to BENCHMARK_MAX_LOAD
+test_quiescence_waits_for_load_to_drop() {
+    fake_load 3.5 2.0 0.5
+    BENCHMARK_MAX_LOAD=1 BENCHMARK_LOAD_POLL_INTERVAL=5 BENCHMARK_LOAD_TIMEOUT=300
+    local output
+    output=$(wait_for_quiescence)
+    assert_true "grep -q 'Load 0.5, proceeding after 10s' <<< \"\$output\""
+    assert_true "! grep -q WARNING <<< \"\$output\""
+}
+
+# A load that never drops delays measuring by BENCHMARK_LOAD_TIMEOUT, not
+# forever
+test_quiescence_gives_up_after_timeout() {
+    fake_load 3.5
+    BENCHMARK_MAX_LOAD=1 BENCHMARK_LOAD_POLL_INTERVAL=5 BENCHMARK_LOAD_TIMEOUT=15
+    local output
+    output=$(wait_for_quiescence)
+    assert_true "grep -q 'WARNING: load 3.5 still above 1 after 15s' <<< \"\$output\""
+}
+
+#
+# Runner
+#
+failed=0
+tests=("$@")
+(( ${#tests[@]} )) || mapfile -t tests < <(declare -F | awk '$3 ~ /^test_/ {print $3}')
+for test in "${tests[@]}"; do
+    if ( "$test" ) > "$SCRATCH/$test.log" 2>&1; then
+        echo "ok   $test"
+    else
+        echo "FAIL $test"
+        sed 's/^/     /' "$SCRATCH/$test.log"
+        failed=1
+    fi
+done
+exit "$failed"
@@ -0,0 +1,44 @@
+import json
+import re
+import sys
+
+import export_influx
+from export_influx import InfluxExporter
+
+def result(timestamp='20260101_000000'):
+    return {
+        'system': 'halo 2',
+        'operation': 'ecdsa',
+        'circuit_size': 'small',
+        'timestamp': timestamp,
+        'summary': {'avg_prove_time': 200, 'iterations': 3, 'proof_bytes_per_input_byte': None},
+    }
+
+def test_tags_and_fields_are_separate():
+    line = InfluxExporter().line(result())
+
+    # Identity goes in the tags, measured values in the fields; a null
+    # summary value is left out rather than written as a string
+    assert re.split(r'(?<!\\) ', line) == [
+        'zk_benchmark,system=halo\\ 2,operation=ecdsa,circuit_size=small',
+        'avg_prove_time=200.0,iterations=3.0',
+        '1767225600000000000',
+    ]
+
+def test_result_without_circuit_size_has_no_empty_tag():
+    shell_result = result()
+    del shell_result['circuit_size']
+
+    assert InfluxExporter().line(shell_result).startswith('zk_benchmark,system=halo\\ 2,operation=ecdsa ')
+
+def test_unexpected_timestamp_is_skipped(tmp_path, monkeypatch, capsys):
+    good, bad = tmp_path / 'good.json', tmp_path / 'bad.json'
+    good.write_text(json.dumps(result()))
+    bad.write_text(json.dumps(result('2026-01-01T00:00:00Z')))
+    monkeypatch.setattr(sys, 'argv', ['export_influx.py', str(bad), str(good)])
+
+    export_influx.main()
+
+    captured = capsys.readouterr()
+    assert captured.out.splitlines() == [InfluxExporter().line(result())]
+    assert captured.err == f"skipping {bad}: timestamp '2026-01-01T00:00:00Z' is not YYYYmmdd_HHMMSS\n"
@@ -0,0 +1,43 @@
+import json
+import re
+
+import yaml
+
+from generate_report import BenchmarkReportGenerator
+
+TIMESTAMP = '20260101_000000'
+
+def write_result(results_dir, system, system_info='Test CPU'):
+    result = {
+        'system': system,
+        'operation': 'ecdsa',
+        'timestamp': TIMESTAMP,
+        'system_info': system_info,
+        'measurements': [{'iteration': 1, 'prove_time': '5', 'duration': '1.5'}],
+        'summary': {'iterations': 1, 'avg_prove_time': 5},
+    }
+    (results_dir / f'{system}_ecdsa_{TIMESTAMP}.json').write_text(json.dumps(result))
+    return result
+
+def test_report_names_systems_and_embeds_results(tmp_path):
+    results_dir = tmp_path / 'results'
+    results_dir.mkdir()
+    written = [
+        write_result(results_dir, 'halo2'),
+        # Must not be able to close the <script> block it is embedded in
+        write_result(results_dir, 'risc0', system_info='</script><b>CPU</b>'),
+    ]
+    config = tmp_path / 'config.yaml'
+    config.write_text(yaml.safe_dump({'reporting': {
+        'plots': [],
+        'tables': [{'name': 'summary', 'metrics': []}, {'name': 'detailed', 'metrics': ['all']}],
+    }}))
+
+    report = BenchmarkReportGenerator(results_dir, config, TIMESTAMP).generate_html_report()
+
+    html = report.read_text()
+    tables, script = html.split('<script type="application/json" id="raw-results">')
+    assert 'halo2' in tables and 'risc0' in tables
+    raw = re.fullmatch(r'(.*?)</script>.*', script, re.S).group(1)
+    embedded = json.loads(raw)
+    assert sorted(embedded, key=lambda result: result['system']) == written
@@ -0,0 +1,52 @@
+import json
+import sys
+
+import merge_results
+from merge_results import ResultMerger
+
+def shell_result(system, timestamp):
+    """A result as older run_all_benchmarks.sh versions wrote it: no run_id or circuit_size"""
+    return {'system': system, 'operation': 'ecdsa', 'timestamp': timestamp, 'measurements': [], 'summary': {}}
+
+def write_shard(path, records):
+    path.write_text(json.dumps(records))
+    return path
+
+def test_overlapping_shards_keep_newest(tmp_path):
+    first = write_shard(tmp_path / 'a.json', [
+        shell_result('halo2', '20260101_000000'),
+        shell_result('risc0', '20260101_000000'),
+    ])
+    second = write_shard(tmp_path / 'b.json', shell_result('halo2', '20260102_000000'))
+
+    merged = ResultMerger([first, second]).merge()
+
+    assert sorted((r['system'], r['timestamp']) for r in merged) == [
+        ('halo2', '20260102_000000'),
+        ('risc0', '20260101_000000'),
+    ]
+    # Order of the shards does not matter
+    merged = ResultMerger([second, first]).merge()
+    assert sorted((r['system'], r['timestamp']) for r in merged)[0] == ('halo2', '20260102_000000')
+
+def test_sizes_and_run_ids_are_kept_apart(tmp_path):
+    records = [
+        {'system': 'halo2', 'operation': 'msm', 'circuit_size': 'small', 'timestamp': '1'},
+        {'system': 'halo2', 'operation': 'msm', 'circuit_size': 'medium', 'timestamp': '1'},
+        {'system': 'halo2', 'operation': 'msm', 'circuit_size': 'small', 'timestamp': '2', 'run_id': 'x'},
+        {'system': 'halo2', 'operation': 'msm', 'circuit_size': 'small', 'timestamp': '3', 'run_id': 'y'},
+    ]
+    shard = write_shard(tmp_path / 'shard.json', records)
+
+    assert len(ResultMerger([shard]).merge()) == 4
+
+def test_main_writes_merged_file(tmp_path, monkeypatch, capsys):
+    first = write_shard(tmp_path / 'a.json', shell_result('halo2', '20260101_000000'))
+    second = write_shard(tmp_path / 'b.json', shell_result('halo2', '20260102_000000'))
+    output = tmp_path / 'merged.json'
+    monkeypatch.setattr(sys, 'argv', ['merge_results.py', str(first), str(second), '-o', str(output)])
+
+    merge_results.main()
+
+    assert [r['timestamp'] for r in json.loads(output.read_text())] == ['20260102_000000']
+    assert capsys.readouterr().out.startswith('Merged 1 results')
@@ -0,0 +1,48 @@
+import json
+
+import summarize
+
+def append_run(history, commit, prove_time):
+    """One line as run_all_benchmarks.sh appends it after a run"""
+    entry = {
+        'commit': commit,
+        'timestamp': '20260101_000000',
+        'system': 'halo2',
+        'operation': 'ecdsa',
+        'summary': {'avg_prove_time': prove_time, 'proof_bytes_per_input_byte': None},
+    }
+    with history.open('a') as f:
+        f.write(json.dumps(entry) + '\n')
+
+def test_trend_of_three_runs(tmp_path, capsys):
+    history = tmp_path / 'history.jsonl'
+    for commit, prove_time in [('a' * 40, 100), ('b' * 40, 150), ('c' * 40, 200)]:
+        append_run(history, commit, prove_time)
+
+    summarize.main([str(history), '--trend'])
+
+    assert capsys.readouterr().out.splitlines() == [
+        'halo2 (ecdsa)',
+        '  avg_prove_time       ▁▄█  latest=200.000 (cccccccc), n=3',
+    ]
+
+def test_trend_keeps_last_runs(tmp_path, capsys):
+    history = tmp_path / 'history.jsonl'
+    for commit, prove_time in [('a' * 40, 100), ('b' * 40, 150), ('c' * 40, 200)]:
+        append_run(history, commit, prove_time)
+
+    summarize.main([str(history), '--trend', '--last', '2'])
+
+    assert 'n=2' in capsys.readouterr().out
+
+def test_summary_shows_latest_run(tmp_path, capsys):
+    history = tmp_path / 'history.jsonl'
+    append_run(history, 'a' * 40, 100)
+    append_run(history, 'b' * 40, 150)
+
+    summarize.main([str(history)])
+
+    assert capsys.readouterr().out.splitlines() == [
+        'halo2 (ecdsa)',
+        '  avg_prove_time       150.000 (bbbbbbbb)',
+    ]
@@ -0,0 +1,36 @@
+import json
+import shutil
+from pathlib import Path
+
+from verify_results import ResultVerifier
+
+CORPUS = Path(__file__).resolve().parents[1] / 'benchmark-templates' / 'benchmark_metrics' / 'tests' / 'corpus'
+
+def suite_result(duration):
+    """A one-iteration result as run_all_benchmarks.sh writes it"""
+    measurement = {'prove_time': '5', 'verify_time': '1', 'proof_size': '100', 'peak_memory_kb': '0',
+                   'duration': duration}
+    return {'system': 'halo2', 'operation': 'ecdsa', 'timestamp': '20260101_000000',
+            'measurements': [measurement], 'summary': {'iterations': 1}}
+
+def test_directory_of_mixed_files(tmp_path, capsys):
+    (tmp_path / 'suite_ok.json').write_text(json.dumps(suite_result('1.5')))
+    (tmp_path / 'suite_negative.json').write_text(json.dumps(suite_result('-2')))
+    shutil.copy(CORPUS / 'halo2_field_arithmetic_small.json', tmp_path / 'metrics_ok.json')
+    placeholder = json.loads((CORPUS / 'halo2_field_arithmetic_small.json').read_text())
+    placeholder['operation'] = 'operation_name'
+    (tmp_path / 'metrics_placeholder.json').write_text(json.dumps(placeholder))
+    (tmp_path / 'truncated.json').write_text('{"system": ')
+    (tmp_path / 'history.jsonl').write_text('{}\n')
+
+    invalid = ResultVerifier([tmp_path]).run()
+
+    assert invalid == 3
+    lines = capsys.readouterr().out.splitlines()
+    assert lines[-1] == '2 valid, 3 invalid, 5 total'
+    assert f'ok      {tmp_path / "metrics_ok.json"}' in lines
+    assert f'ok      {tmp_path / "suite_ok.json"}' in lines
+    assert f'INVALID {tmp_path / "metrics_placeholder.json"}' in lines
+    assert any(line.startswith('  - operation:') for line in lines)
+    assert '  - measurement 1: negative duration' in lines
+    assert any(line.startswith('  - unreadable:') for line in lines)
@@ -0,0 +1,78 @@
+#!/usr/bin/env python3
+
+import argparse
+import json
+import sys
+from datetime import datetime, timezone
+from pathlib import Path
+
+MEASUREMENT = 'zk_benchmark'
+TAG_FIELDS = ('system', 'operation', 'circuit_size')
+
+class InfluxExporter:
+    """Converts result summaries to InfluxDB line protocol:
+
+        measurement,tag=v,... field=n,... timestamp_ns
+    """
+
+    @staticmethod
+    def _escape_tag(value):
+        return str(value).replace('\\', '\\\\').replace(',', '\\,').replace('=', '\\=').replace(' ', '\\ ')
+
+    @staticmethod
+    def _field_value(value):
+        if isinstance(value, bool):
+            return 'true' if value else 'false'
+        # jq prints whole-number averages without a decimal point; writing
+        # every number as a float keeps each field's type stable across runs
+        return repr(float(value))
+
+    @staticmethod
+    def _timestamp_ns(result):
+        # run_all_benchmarks.sh stamps runs as YYYYmmdd_HHMMSS (UTC in the container)
+        timestamp = result.get('timestamp')
+        try:
+            run_time = datetime.strptime(str(timestamp), '%Y%m%d_%H%M%S').replace(tzinfo=timezone.utc)
+        except ValueError:
+            raise ValueError(f'timestamp {timestamp!r} is not YYYYmmdd_HHMMSS') from None
+        return int(run_time.timestamp()) * 1_000_000_000
+
+    def line(self, result):
+        """The result's line, or None if its summary has no numbers; raises
+        ValueError if its timestamp is not one run_all_benchmarks.sh writes"""
+        tags = ''.join(
+            f',{tag}={self._escape_tag(result[tag])}'
+            for tag in TAG_FIELDS
+            if result.get(tag) not in (None, '')
+        )
+        fields = ','.join(
+            f'{name}={self._field_value(value)}'
+            for name, value in sorted((result.get('summary') or {}).items())
+            if isinstance(value, (int, float))
+        )
+        if not fields:
+            return None
+        return f'{MEASUREMENT}{tags} {fields} {self._timestamp_ns(result)}'
+
+def main():
+    parser = argparse.ArgumentParser(description='Export benchmark results as InfluxDB line protocol')
+    parser.add_argument('results', nargs='+', help='Result files written by run_all_benchmarks.sh')
+
+    args = parser.parse_args()
+
+    exporter = InfluxExporter()
+    for path in args.results:
+        with Path(path).open() as f:
+            result = json.load(f)
+        try:
+            line = exporter.line(result)
+        except ValueError as e:
+            print(f'skipping {path}: {e}', file=sys.stderr)
+            continue
+        if line is None:
+            print(f'skipping {path}: no numeric summary', file=sys.stderr)
+        else:
+            print(line)
+
+if __name__ == '__main__':
+    main()
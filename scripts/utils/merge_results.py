@@ -0,0 +1,50 @@
+#!/usr/bin/env python3
+
+import argparse
+import json
+from pathlib import Path
+
+class ResultMerger:
+    def __init__(self, inputs):
+        self.inputs = [Path(path) for path in inputs]
+
+    @staticmethod
+    def _records(path):
+        """A shard holds one result object or a list of them"""
+        with path.open() as f:
+            data = json.load(f)
+        return data if isinstance(data, list) else [data]
+
+    @staticmethod
+    def key(record):
+        # Without a run_id, a newer result for the same system, operation
+        # and size replaces an older one. Older results written by
+        # run_all_benchmarks.sh have no circuit_size, so theirs is None
+        key = (record.get('system'), record.get('operation'), record.get('circuit_size'))
+        return key + (record['run_id'],) if 'run_id' in record else key
+
+    def merge(self):
+        merged = {}
+        for path in self.inputs:
+            for record in self._records(path):
+                key = self.key(record)
+                current = merged.get(key)
+                # Later shards win ties, so re-running a shard replaces it
+                if current is None or str(record.get('timestamp', '')) >= str(current.get('timestamp', '')):
+                    merged[key] = record
+        return list(merged.values())
+
+def main():
+    parser = argparse.ArgumentParser(description='Merge partial benchmark result files from sharded runs')
+    parser.add_argument('inputs', nargs='+', help='Result files to merge')
+    parser.add_argument('-o', '--output', required=True, help='Path of the merged result file')
+
+    args = parser.parse_args()
+
+    records = ResultMerger(args.inputs).merge()
+    with open(args.output, 'w') as f:
+        json.dump(records, f, indent=2)
+    print(f"Merged {len(records)} results into {args.output}")
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,23 @@
+#!/usr/bin/env python3
+
+import argparse
+
+from trend import TrendReport
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(description='Summarize the benchmark history')
+    parser.add_argument('history', help='Path to history.jsonl written by run_all_benchmarks.sh')
+    parser.add_argument('--trend', action='store_true',
+                      help='Show a sparkline of the most recent runs per metric, not just the latest value')
+    parser.add_argument('--last', type=int, default=20,
+                      help='Number of most recent runs --trend shows per metric')
+
+    args = parser.parse_args(argv)
+
+    if args.trend:
+        print(TrendReport(args.history, args.last).render())
+    else:
+        print(TrendReport(args.history, 1).render_latest())
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,63 @@
+import json
+from collections import defaultdict
+from pathlib import Path
+
+SPARK_CHARS = "▁▂▃▄▅▆▇█"
+
+class TrendReport:
+    def __init__(self, history_file, last):
+        self.history_file = Path(history_file)
+        self.last = last
+        self.entries = self._load_history()
+
+    def _load_history(self):
+        entries = []
+        with self.history_file.open() as f:
+            for line in f:
+                line = line.strip()
+                if line:
+                    entries.append(json.loads(line))
+        return entries
+
+    def series(self):
+        """Group the last N values of every summary metric by (system, operation)"""
+        grouped = defaultdict(lambda: defaultdict(list))
+        for entry in self.entries:
+            key = (entry['system'], entry['operation'])
+            for metric, value in (entry.get('summary') or {}).items():
+                if isinstance(value, (int, float)):
+                    grouped[key][metric].append((entry['commit'], value))
+
+        return {
+            key: {metric: points[-self.last:] for metric, points in metrics.items()}
+            for key, metrics in grouped.items()
+        }
+
+    @staticmethod
+    def sparkline(values):
+        low, high = min(values), max(values)
+        if high == low:
+            return SPARK_CHARS[0] * len(values)
+        scale = (len(SPARK_CHARS) - 1) / (high - low)
+        return ''.join(SPARK_CHARS[int((v - low) * scale)] for v in values)
+
+    def render(self):
+        lines = []
+        for (system, operation), metrics in sorted(self.series().items()):
+            lines.append(f"{system} ({operation})")
+            for metric, points in sorted(metrics.items()):
+                values = [value for _, value in points]
+                lines.append(
+                    f"  {metric:<20} {self.sparkline(values)}  "
+                    f"latest={values[-1]:.3f} ({points[-1][0][:8]}), n={len(values)}"
+                )
+        return '\n'.join(lines)
+
+    def render_latest(self):
+        lines = []
+        for (system, operation), metrics in sorted(self.series().items()):
+            lines.append(f"{system} ({operation})")
+            for metric, points in sorted(metrics.items()):
+                commit, value = points[-1]
+                lines.append(f"  {metric:<20} {value:.3f} ({commit[:8]})")
+        return '\n'.join(lines)
@@ -0,0 +1,119 @@
+#!/usr/bin/env python3
+
+import argparse
+import json
+import subprocess
+import sys
+from pathlib import Path
+
+REQUIRED_FIELDS = ('system', 'operation', 'timestamp', 'measurements', 'summary')
+MEASUREMENT_FIELDS = ('prove_time', 'verify_time', 'proof_size', 'peak_memory_kb', 'duration')
+RUNNER_MANIFEST = Path(__file__).resolve().parents[1] / 'benchmark-templates' / 'benchmark_runner' / 'Cargo.toml'
+
+class ResultVerifier:
+    def __init__(self, paths):
+        self.files = self._collect(paths)
+
+    @staticmethod
+    def _collect(paths):
+        files = []
+        for path in map(Path, paths):
+            if path.is_dir():
+                # history.jsonl and reports live next to the results
+                files.extend(sorted(path.glob('*.json')))
+            else:
+                files.append(path)
+        return files
+
+    @staticmethod
+    def _is_number(value):
+        try:
+            float(value)
+        except (TypeError, ValueError):
+            return False
+        return True
+
+    @staticmethod
+    def validate_metrics(paths):
+        """Problems with each template metrics file, from benchmark_metrics'
+        validate_metrics through benchmark-runner, so both check one schema"""
+        command = ['cargo', 'run', '--quiet', '--release', '--manifest-path', str(RUNNER_MANIFEST),
+                   '--', 'validate', *map(str, paths)]
+        result = subprocess.run(command, capture_output=True, text=True)
+        # 1 only means some file is invalid
+        if result.returncode not in (0, 1):
+            raise RuntimeError(f'benchmark-runner validate failed: {result.stderr.strip()}')
+        problems = json.loads(result.stdout)
+        return {path: problems[str(path)] for path in paths}
+
+    def check_all(self):
+        """Reasons each file is invalid, empty for a valid one. Results of
+        run_all_benchmarks.sh are checked here, anything else as template metrics"""
+        reasons, metrics_files = {}, []
+        for path in self.files:
+            try:
+                with path.open() as f:
+                    result = json.load(f)
+            except (OSError, json.JSONDecodeError) as e:
+                reasons[path] = [f'unreadable: {e}']
+                continue
+            if isinstance(result, dict) and 'measurements' in result:
+                reasons[path] = self.check(result)
+            else:
+                metrics_files.append(path)
+        if metrics_files:
+            reasons.update(self.validate_metrics(metrics_files))
+        return reasons
+
+    def check(self, result):
+        """Reasons a parsed run_all_benchmarks.sh result is not valid, if any"""
+        reasons = [f'missing field: {field}' for field in REQUIRED_FIELDS if field not in result]
+
+        measurements = result.get('measurements')
+        if not isinstance(measurements, list) or not measurements:
+            reasons.append('no measurements')
+        else:
+            for i, measurement in enumerate(measurements, 1):
+                for field in MEASUREMENT_FIELDS:
+                    if not self._is_number((measurement or {}).get(field)):
+                        reasons.append(f'measurement {i}: {field} is not a number')
+                if self._is_number((measurement or {}).get('duration')) and float(measurement['duration']) < 0:
+                    reasons.append(f'measurement {i}: negative duration')
+
+        summary = result.get('summary')
+        if isinstance(summary, dict) and isinstance(measurements, list):
+            if summary.get('iterations') not in (None, len(measurements)):
+                reasons.append(
+                    f"summary.iterations is {summary['iterations']} "
+                    f"but there are {len(measurements)} measurements"
+                )
+
+        return reasons
+
+    def run(self):
+        invalid = 0
+        all_reasons = self.check_all()
+        for path in self.files:
+            reasons = all_reasons[path]
+            if reasons:
+                invalid += 1
+                print(f'INVALID {path}')
+                for reason in reasons:
+                    print(f'  - {reason}')
+            else:
+                print(f'ok      {path}')
+
+        print(f'\n{len(self.files) - invalid} valid, {invalid} invalid, {len(self.files)} total')
+        return invalid
+
+def main():
+    parser = argparse.ArgumentParser(description='Check a batch of benchmark result files before ingesting them')
+    parser.add_argument('paths', nargs='+', help='Result files or directories of *.json results')
+
+    args = parser.parse_args()
+
+    verifier = ResultVerifier(args.paths)
+    sys.exit(1 if verifier.run() else 0)
+
+if __name__ == '__main__':
+    main()